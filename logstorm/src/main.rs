@@ -208,7 +208,7 @@ impl LogGenerator {
 
     fn generate_complex_message(&self, is_error: bool, rng: &mut impl Rng) -> (String, String) {
         if is_error {
-            let error_types = vec![
+            let error_types = [
                 ("NullPointerException", self.generate_stack_trace(rng)),
                 ("SQLException", format!("Query failed: {}", self.generate_sql_query(rng))),
                 ("TimeoutException", format!("Timeout after {}ms connecting to {}", 
@@ -221,7 +221,7 @@ impl LogGenerator {
             let (msg, extra) = &error_types[rng.gen_range(0..error_types.len())];
             (msg.to_string(), extra.clone())
         } else {
-            let info_types = vec![
+            let info_types = [
                 ("Request completed", format!(" endpoint={}", self.generate_url(rng))),
                 ("Database query executed", format!(" query={}", self.generate_sql_query(rng))),
                 ("API call successful", format!(" url={}", self.generate_url(rng))),
@@ -267,9 +267,9 @@ impl LogGenerator {
     }
 
     fn generate_cache_key(&self, rng: &mut impl Rng) -> String {
-        format!("cache:user:{}:session:{}", 
+        format!("cache:user:{}:session:{:016x}", 
             rng.gen_range(1000..9999), 
-            format!("{:016x}", rng.gen::<u64>()))
+            rng.gen::<u64>())
     }
 
     fn generate_user_agent(&self, rng: &mut impl Rng) -> String {
@@ -293,10 +293,10 @@ impl LogGenerator {
                 }
             }
             "long" => {
-                format!(" additional_context={} trace_id={} span_id={}", 
+                format!(" additional_context={} trace_id={:032x} span_id={:016x}", 
                     "x".repeat(rng.gen_range(100..200)),
-                    format!("{:032x}", rng.gen::<u128>()),
-                    format!("{:016x}", rng.gen::<u64>()))
+                    rng.gen::<u128>(),
+                    rng.gen::<u64>())
             }
             "xl" => {
                 let json_data = format!("{{\"nested\":{{\"data\":\"{}\"}},\"array\":[{}],\"timestamp\":{}}}",
@@ -0,0 +1,57 @@
+use super::AlertHandler;
+use anyhow::Result;
+use async_trait::async_trait;
+use notify_rust::Notification;
+
+/// Shows alerts as native desktop notifications, for running tinywatcher on a workstation
+pub struct DesktopAlert {
+    name: String,
+}
+
+impl DesktopAlert {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+
+    fn notification(identity: &str, rule_name: &str, message: &str) -> Notification {
+        let mut notification = Notification::new();
+        notification
+            .appname("TinyWatcher")
+            .summary(&format!("TinyWatcher: {}", rule_name))
+            .body(&format!("{}\n{}", identity, message));
+        notification
+    }
+}
+
+#[async_trait]
+impl AlertHandler for DesktopAlert {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        let notification = Self::notification(identity, rule_name, message);
+
+        // Showing a notification blocks on the platform's notification service
+        let shown = tokio::task::spawn_blocking(move || notification.show().map(|_| ())).await?;
+
+        // A headless session has no notification daemon; that's not worth retrying or reporting as a failure
+        if let Err(e) = shown {
+            tracing::warn!("Desktop alert '{}' could not be shown: {}", self.name, e);
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_content() {
+        let notification = DesktopAlert::notification("laptop", "errors", "ERROR boom");
+        assert_eq!(notification.appname, "TinyWatcher");
+        assert_eq!(notification.summary, "TinyWatcher: errors");
+        assert_eq!(notification.body, "laptop\nERROR boom");
+    }
+}
@@ -3,26 +3,100 @@ use async_trait::async_trait;
 use anyhow::Result;
 use serde_json::json;
 
+/// Who an alert pings, parsed from `@here`, `@everyone`, `role:<id>` or `user:<id>`
+#[derive(Debug, Clone, PartialEq)]
+enum Mention {
+    Here,
+    Everyone,
+    Role(String),
+    User(String),
+}
+
+impl std::str::FromStr for Mention {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let id = |id: &str| -> Result<String> {
+            if id.is_empty() || !id.chars().all(|c| c.is_ascii_digit()) {
+                anyhow::bail!("Invalid Discord mention '{}': IDs must be numeric", s);
+            }
+            Ok(id.to_string())
+        };
+
+        match s.trim() {
+            "@here" | "here" => Ok(Mention::Here),
+            "@everyone" | "everyone" => Ok(Mention::Everyone),
+            other => match other.split_once(':') {
+                Some(("role", role)) => Ok(Mention::Role(id(role)?)),
+                Some(("user", user)) => Ok(Mention::User(id(user)?)),
+                _ => anyhow::bail!(
+                    "Invalid Discord mention '{}': expected @here, @everyone, role:<id> or user:<id>",
+                    s
+                ),
+            },
+        }
+    }
+}
+
+impl Mention {
+    fn content(&self) -> String {
+        match self {
+            Mention::Here => "@here".to_string(),
+            Mention::Everyone => "@everyone".to_string(),
+            Mention::Role(id) => format!("<@&{}>", id),
+            Mention::User(id) => format!("<@{}>", id),
+        }
+    }
+}
+
 pub struct DiscordAlert {
     name: String,
     webhook_url: String,
+    thread_id: Option<String>,
+    mentions: Vec<Mention>,
     client: reqwest::Client,
 }
 
 impl DiscordAlert {
-    pub fn new(name: String, webhook_url: String) -> Self {
-        Self {
+    pub fn new(
+        name: String,
+        webhook_url: String,
+        thread_id: Option<String>,
+        mentions: &[String],
+        client: reqwest::Client,
+    ) -> Result<Self> {
+        Ok(Self {
             name,
             webhook_url,
-            client: reqwest::Client::new(),
+            thread_id,
+            mentions: mentions.iter().map(|m| m.parse()).collect::<Result<_>>()?,
+            client,
+        })
+    }
+
+    /// Only the configured mentions may ping; `@everyone` in a log line stays inert
+    fn allowed_mentions(&self) -> serde_json::Value {
+        let mut parse = Vec::new();
+        let mut roles = Vec::new();
+        let mut users = Vec::new();
+        for mention in &self.mentions {
+            match mention {
+                // Discord covers @here under the "everyone" mention type
+                Mention::Here | Mention::Everyone => {
+                    if !parse.contains(&"everyone") {
+                        parse.push("everyone");
+                    }
+                }
+                Mention::Role(id) => roles.push(id.as_str()),
+                Mention::User(id) => users.push(id.as_str()),
+            }
         }
+
+        json!({ "parse": parse, "roles": roles, "users": users })
     }
-}
 
-#[async_trait]
-impl AlertHandler for DiscordAlert {
-    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
-        let payload = json!({
+    fn build_payload(&self, identity: &str, rule_name: &str, message: &str) -> serde_json::Value {
+        let mut payload = json!({
             "embeds": [{
                 "title": format!("🚨 Alert: {}", rule_name),
                 "description": message,
@@ -37,14 +111,36 @@ impl AlertHandler for DiscordAlert {
                 "footer": {
                     "text": "TinyWatcher"
                 },
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }]
+                "timestamp": crate::timestamp::now_rfc3339()
+            }],
+            "allowed_mentions": self.allowed_mentions()
         });
 
+        if !self.mentions.is_empty() {
+            let content: Vec<String> = self.mentions.iter().map(Mention::content).collect();
+            payload["content"] = json!(content.join(" "));
+        }
+
+        payload
+    }
+
+    fn build_request(&self, payload: &serde_json::Value) -> Result<reqwest::Request> {
+        let mut request = self.client.post(&self.webhook_url).json(payload);
+        if let Some(thread_id) = &self.thread_id {
+            request = request.query(&[("thread_id", thread_id)]);
+        }
+        Ok(request.build()?)
+    }
+}
+
+#[async_trait]
+impl AlertHandler for DiscordAlert {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        let payload = self.build_payload(identity, rule_name, message);
+        let request = self.build_request(&payload)?;
+
         self.client
-            .post(&self.webhook_url)
-            .json(&payload)
-            .send()
+            .execute(request)
             .await?
             .error_for_status()?;
         
@@ -56,3 +152,54 @@ impl AlertHandler for DiscordAlert {
         &self.name
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://discord.com/api/webhooks/123/token";
+
+    fn discord(thread_id: Option<&str>, mentions: &[&str]) -> DiscordAlert {
+        let mentions: Vec<String> = mentions.iter().map(|m| m.to_string()).collect();
+        DiscordAlert::new("discord".to_string(), URL.to_string(), thread_id.map(String::from), &mentions, reqwest::Client::new()).unwrap()
+    }
+
+    #[test]
+    fn test_payload_with_mentions() {
+        let payload = discord(None, &["@here", "role:111", "user:222"]).build_payload("server-1", "errors", "boom");
+
+        assert_eq!(payload["content"], "@here <@&111> <@222>");
+        assert_eq!(
+            payload["allowed_mentions"],
+            json!({ "parse": ["everyone"], "roles": ["111"], "users": ["222"] })
+        );
+        assert_eq!(payload["embeds"][0]["title"], "🚨 Alert: errors");
+    }
+
+    #[test]
+    fn test_payload_without_mentions_pings_nobody() {
+        let payload = discord(None, &[]).build_payload("server-1", "errors", "@everyone boom");
+
+        assert!(payload.get("content").is_none());
+        assert_eq!(payload["allowed_mentions"], json!({ "parse": [], "roles": [], "users": [] }));
+    }
+
+    #[test]
+    fn test_thread_routing() {
+        let alert = discord(Some("987654321"), &[]);
+        let request = alert.build_request(&json!({})).unwrap();
+        assert_eq!(request.url().as_str(), format!("{}?thread_id=987654321", URL));
+
+        let request = discord(None, &[]).build_request(&json!({})).unwrap();
+        assert_eq!(request.url().as_str(), URL);
+    }
+
+    #[test]
+    fn test_invalid_mentions() {
+        let mentions = vec!["role:abc".to_string()];
+        assert!(DiscordAlert::new("discord".to_string(), URL.to_string(), None, &mentions, reqwest::Client::new()).is_err());
+
+        let mentions = vec!["admins".to_string()];
+        assert!(DiscordAlert::new("discord".to_string(), URL.to_string(), None, &mentions, reqwest::Client::new()).is_err());
+    }
+}
@@ -69,7 +69,7 @@ impl AlertHandler for EmailAlert {
              {}\n",
             identity,
             rule_name,
-            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            crate::timestamp::now(),
             message
         );
 
@@ -92,10 +92,10 @@ impl AlertHandler for EmailAlert {
                 let sender = SendmailTransport::new();
                 match sender.send(&email) {
                     Ok(_) => {
-                        tracing::info!("✅ Successfully sent email alert '{}' to {} for rule: {}", self.name, recipient, rule_name);
+                        tracing::info!("{}Successfully sent email alert '{}' to {} for rule: {}", crate::emoji::icon("✅ "), self.name, recipient, rule_name);
                     }
                     Err(e) => {
-                        tracing::error!("❌ Failed to send email via sendmail to {}: {}", recipient, e);
+                        tracing::error!("{}Failed to send email via sendmail to {}: {}", crate::emoji::icon("❌ "), recipient, e);
                         return Err(anyhow::anyhow!("Failed to send email via sendmail to {}: {}", recipient, e));
                     }
                 }
@@ -114,10 +114,10 @@ impl AlertHandler for EmailAlert {
                 
                 match sender.send(&email) {
                     Ok(_) => {
-                        tracing::info!("✅ Successfully sent email alert '{}' to {} for rule: {}", self.name, recipient, rule_name);
+                        tracing::info!("{}Successfully sent email alert '{}' to {} for rule: {}", crate::emoji::icon("✅ "), self.name, recipient, rule_name);
                     }
                     Err(e) => {
-                        tracing::error!("❌ Failed to send email via SMTP to {}: {}", recipient, e);
+                        tracing::error!("{}Failed to send email via SMTP to {}: {}", crate::emoji::icon("❌ "), recipient, e);
                         return Err(anyhow::anyhow!("Failed to send email via SMTP to {}: {}", recipient, e));
                     }
                 }
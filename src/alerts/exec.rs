@@ -0,0 +1,180 @@
+use super::{AlertContext, AlertHandler};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+/// How long a command gets to finish when no `timeout` is configured
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest stretch of stderr included in a failure message
+const MAX_STDERR_CHARS: usize = 200;
+
+/// Runs a command for each alert, passing the alert in `TW_*` environment variables and on stdin
+pub struct ExecAlert {
+    name: String,
+    command: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl ExecAlert {
+    pub fn new(name: String, command: String, args: Vec<String>, timeout: Option<Duration>) -> Self {
+        Self {
+            name,
+            command,
+            args,
+            timeout: timeout.unwrap_or(DEFAULT_TIMEOUT),
+        }
+    }
+
+    fn build_command(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(&self.command);
+        command
+            .args(&self.args)
+            .env("TW_RULE", rule_name)
+            .env("TW_IDENTITY", identity)
+            .env("TW_MESSAGE", message)
+            .env("TW_SEVERITY", context.severity.as_str())
+            .env("TW_ALERT", &self.name)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        for (key, value) in &context.fields {
+            command.env(format!("TW_FIELD_{}", key.to_uppercase()), value);
+        }
+        command
+    }
+
+    async fn run(&self, mut command: tokio::process::Command, message: &str) -> Result<()> {
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("Failed to run '{}'", self.command))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // A command that doesn't read stdin may exit before the message is written
+            let _ = stdin.write_all(message.as_bytes()).await;
+        }
+
+        let output = match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
+            Ok(output) => output.with_context(|| format!("Failed to run '{}'", self.command))?,
+            Err(_) => anyhow::bail!("Command '{}' timed out after {:?}", self.command, self.timeout),
+        };
+
+        if !output.status.success() {
+            let stderr: String = String::from_utf8_lossy(&output.stderr)
+                .trim()
+                .chars()
+                .take(MAX_STDERR_CHARS)
+                .collect();
+            if stderr.is_empty() {
+                anyhow::bail!("Command '{}' failed ({})", self.command, output.status);
+            }
+            anyhow::bail!("Command '{}' failed ({}): {}", self.command, output.status, stderr);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AlertHandler for ExecAlert {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        self.send_with_context(identity, rule_name, message, &AlertContext::default())
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        let command = self.build_command(identity, rule_name, message, context);
+        self.run(command, message).await?;
+
+        tracing::info!("Ran exec alert '{}' for rule: {} (from {})", self.name, rule_name, identity);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str, timeout: Option<Duration>) -> ExecAlert {
+        ExecAlert::new(
+            "script".to_string(),
+            "sh".to_string(),
+            vec!["-c".to_string(), script.to_string()],
+            timeout,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_command_receives_env_vars_and_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let script = format!(
+            "printf '%s|%s|%s|%s|' \"$TW_RULE\" \"$TW_IDENTITY\" \"$TW_MESSAGE\" \"$TW_SEVERITY\" > {0}; cat >> {0}",
+            out.display()
+        );
+
+        shell(&script, None)
+            .send("server-1", "errors", "ERROR boom")
+            .await
+            .unwrap();
+
+        let written = std::fs::read_to_string(&out).unwrap();
+        assert_eq!(written, "errors|server-1|ERROR boom|error|ERROR boom");
+    }
+
+    #[tokio::test]
+    async fn test_fields_are_passed_as_env_vars() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let mut context = AlertContext::default();
+        context.fields.insert("user".to_string(), "alice".to_string());
+
+        shell(&format!("printf '%s' \"$TW_FIELD_USER\" > {}", out.display()), None)
+            .send_with_context("server-1", "errors", "ERROR boom", &context)
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read_to_string(&out).unwrap(), "alice");
+    }
+
+    #[tokio::test]
+    async fn test_failure_includes_stderr() {
+        let err = shell("echo 'ticket API down' >&2; exit 3", None)
+            .send("server-1", "errors", "ERROR boom")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("ticket API down"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_hung_command_times_out() {
+        let started = std::time::Instant::now();
+        let err = shell("sleep 10", Some(Duration::from_millis(200)))
+            .send("server-1", "errors", "ERROR boom")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("timed out"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
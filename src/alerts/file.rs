@@ -0,0 +1,158 @@
+use super::{AlertContext, AlertHandler};
+use crate::config::FileFormat;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde_json::json;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Appends one line per alert to a local file, for log shippers and testing
+pub struct FileAlert {
+    name: String,
+    path: PathBuf,
+    format: FileFormat,
+    /// Serializes appends so concurrent alerts don't interleave
+    write_lock: Mutex<()>,
+}
+
+impl FileAlert {
+    pub fn new(name: String, path: PathBuf, format: FileFormat) -> Self {
+        Self {
+            name,
+            path,
+            format,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    fn format_line(&self, identity: &str, rule_name: &str, message: &str, context: &AlertContext) -> String {
+        match self.format {
+            FileFormat::Text => format!(
+                "[{}] ALERT [{}] [{}]: {}",
+                crate::timestamp::now(),
+                identity,
+                rule_name,
+                // Keep multi-line messages on one line
+                context.render_text(message).replace('\n', "\\n")
+            ),
+            FileFormat::Json => {
+                let mut line = json!({
+                    "timestamp": crate::timestamp::now_rfc3339(),
+                    "identity": identity,
+                    "rule": rule_name,
+                    "severity": context.severity.as_str(),
+                    "message": message,
+                });
+                if !context.fields.is_empty() {
+                    line["fields"] = json!(context.fields);
+                }
+                if let Some(source) = &context.source {
+                    line["source"] = json!(source);
+                }
+                line.to_string()
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AlertHandler for FileAlert {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        self.send_with_context(identity, rule_name, message, &AlertContext::default())
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        let mut line = self.format_line(identity, rule_name, message, context);
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        // Reopened for every alert, so a rotated file is picked up without a restart
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        file.write_all(line.as_bytes())
+            .await
+            .with_context(|| format!("Failed to write to {}", self.path.display()))?;
+        file.flush().await?;
+
+        tracing::debug!("Wrote file alert '{}' for rule: {} (from {})", self.name, rule_name, identity);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_appends_text_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.log");
+        let alert = FileAlert::new("file".to_string(), path.clone(), FileFormat::Text);
+
+        alert.send("server-1", "errors", "ERROR boom").await.unwrap();
+        alert.send("server-1", "panics", "panic at\nmain.rs:3").await.unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("ALERT [server-1] [errors]: ERROR boom"), "{}", lines[0]);
+        assert!(lines[1].ends_with("[panics]: panic at\\nmain.rs:3"), "{}", lines[1]);
+    }
+
+    #[tokio::test]
+    async fn test_appends_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.jsonl");
+        std::fs::write(&path, "existing\n").unwrap();
+        let alert = FileAlert::new("file".to_string(), path.clone(), FileFormat::Json);
+
+        let mut context = AlertContext::default();
+        context.fields.insert("user".to_string(), "alice".to_string());
+        alert
+            .send_with_context("server-1", "errors", "ERROR boom", &context)
+            .await
+            .unwrap();
+        alert.send("server-1", "errors", "ERROR again").await.unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "existing");
+
+        let first: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(first["identity"], "server-1");
+        assert_eq!(first["rule"], "errors");
+        assert_eq!(first["message"], "ERROR boom");
+        assert_eq!(first["severity"], "error");
+        assert_eq!(first["fields"]["user"], "alice");
+        assert!(first["timestamp"].is_string());
+
+        let second: serde_json::Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(second["message"], "ERROR again");
+        assert!(second.get("fields").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_missing_directory_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing").join("alerts.log");
+        let alert = FileAlert::new("file".to_string(), path, FileFormat::Text);
+        assert!(alert.send("server-1", "errors", "ERROR").await.is_err());
+    }
+}
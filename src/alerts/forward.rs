@@ -0,0 +1,184 @@
+use super::{AlertContext, AlertHandler, AlertSource};
+use crate::config::Severity;
+use async_trait::async_trait;
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Version of the forwarded event schema, bumped on incompatible changes
+const EVENT_VERSION: u32 = 1;
+
+/// A matched event as sent to a central tinywatcher, which does its own routing and dedup
+#[derive(Debug, Serialize)]
+struct ForwardedEvent<'a> {
+    version: u32,
+    /// Identity of the edge agent that saw the event
+    identity: &'a str,
+    rule: &'a str,
+    message: &'a str,
+    severity: Severity,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    fields: &'a BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<&'a AlertSource>,
+    timestamp: String,
+}
+
+pub struct ForwardAlert {
+    name: String,
+    url: String,
+    client: reqwest::Client,
+}
+
+impl ForwardAlert {
+    pub fn new(name: String, url: String, client: reqwest::Client) -> Self {
+        Self {
+            name,
+            url,
+            client,
+        }
+    }
+
+    fn build_event<'a>(
+        &self,
+        identity: &'a str,
+        rule_name: &'a str,
+        message: &'a str,
+        context: &'a AlertContext,
+    ) -> ForwardedEvent<'a> {
+        ForwardedEvent {
+            version: EVENT_VERSION,
+            identity,
+            rule: rule_name,
+            message,
+            severity: context.severity,
+            fields: &context.fields,
+            source: context.source.as_ref(),
+            timestamp: crate::timestamp::now_rfc3339(),
+        }
+    }
+}
+
+#[async_trait]
+impl AlertHandler for ForwardAlert {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        self.send_with_context(identity, rule_name, message, &AlertContext::default())
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        let event = self.build_event(identity, rule_name, message, context);
+
+        self.client
+            .post(&self.url)
+            .json(&event)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        tracing::info!("Forwarded alert '{}' for rule: {} (from {})", self.name, rule_name, identity);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::AlertSourceKind;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn context() -> AlertContext {
+        let mut context = AlertContext {
+            source: Some(AlertSource {
+                kind: AlertSourceKind::File,
+                name: "/var/log/app.log".to_string(),
+                url: None,
+            }),
+            severity: Severity::Critical,
+            ..Default::default()
+        };
+        context.fields.insert("status".to_string(), "500".to_string());
+        context
+    }
+
+    #[test]
+    fn test_event_payload() {
+        let alert = ForwardAlert::new("central".to_string(), "http://localhost".to_string(), reqwest::Client::new());
+        let context = context();
+        let event = serde_json::to_value(alert.build_event("edge-1", "errors", "ERROR boom", &context)).unwrap();
+
+        assert_eq!(event["version"], 1);
+        assert_eq!(event["identity"], "edge-1");
+        assert_eq!(event["rule"], "errors");
+        assert_eq!(event["message"], "ERROR boom");
+        assert_eq!(event["severity"], "critical");
+        assert_eq!(event["fields"], json!({"status": "500"}));
+        assert_eq!(event["source"], json!({"type": "file", "name": "/var/log/app.log"}));
+        assert!(event["timestamp"].is_string());
+    }
+
+    #[test]
+    fn test_event_omits_empty_context() {
+        let alert = ForwardAlert::new("central".to_string(), "http://localhost".to_string(), reqwest::Client::new());
+        let context = AlertContext::default();
+        let event = serde_json::to_value(alert.build_event("edge-1", "errors", "ERROR boom", &context)).unwrap();
+
+        assert!(event.get("fields").is_none());
+        assert!(event.get("source").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_event_is_posted_to_central() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = vec![0u8; 4096];
+            // Read until the JSON body has arrived
+            while !request.ends_with(b"}") {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            let response = "HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\n\r\n";
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+
+        let alert = ForwardAlert::new("central".to_string(), format!("http://{}/ingest", addr), reqwest::Client::new());
+        alert
+            .send_with_context("edge-1", "errors", "ERROR boom", &context())
+            .await
+            .unwrap();
+
+        let request = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("no event received")
+            .unwrap();
+        assert!(request.starts_with("POST /ingest "), "unexpected request: {}", request);
+        assert!(request.contains("application/json"), "unexpected request: {}", request);
+
+        let body = &request[request.find("\r\n\r\n").unwrap() + 4..];
+        let event: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(event["identity"], "edge-1");
+        assert_eq!(event["rule"], "errors");
+        assert_eq!(event["severity"], "critical");
+        assert_eq!(event["fields"]["status"], "500");
+    }
+}
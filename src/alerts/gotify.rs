@@ -0,0 +1,146 @@
+use super::{AlertContext, AlertHandler};
+use crate::config::Severity;
+use async_trait::async_trait;
+use anyhow::Result;
+use serde_json::json;
+
+/// Header carrying the application token, which keeps it out of the URL and so out of error messages
+const TOKEN_HEADER: &str = "X-Gotify-Key";
+
+pub struct GotifyAlert {
+    name: String,
+    url: String,
+    token: String,
+    priority: Option<u8>,
+    client: reqwest::Client,
+}
+
+/// Map a rule's severity onto Gotify's 0-10 priority scale; `error` keeps the previous default of 8
+fn severity_priority(severity: Severity) -> u8 {
+    match severity {
+        Severity::Info => 2,
+        Severity::Warning => 5,
+        Severity::Error => 8,
+        Severity::Critical => 10,
+    }
+}
+
+impl GotifyAlert {
+    pub fn new(name: String, url: String, token: String, priority: Option<u8>, client: reqwest::Client) -> Self {
+        Self {
+            name,
+            url,
+            token,
+            priority,
+            client,
+        }
+    }
+
+    fn message_url(&self) -> String {
+        format!("{}/message", self.url.trim_end_matches('/'))
+    }
+
+    /// Build the message body; a configured `priority` overrides the severity mapping
+    fn build_payload(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> serde_json::Value {
+        let priority = self
+            .priority
+            .unwrap_or_else(|| severity_priority(context.severity));
+
+        json!({
+            "title": format!("TinyWatcher: {}", rule_name),
+            "message": format!("Host: {}\n\n{}", identity, context.render_text(message)),
+            "priority": priority,
+        })
+    }
+
+    fn build_request(&self, payload: &serde_json::Value) -> Result<reqwest::Request> {
+        Ok(self
+            .client
+            .post(self.message_url())
+            .header(TOKEN_HEADER, &self.token)
+            .json(payload)
+            .build()?)
+    }
+}
+
+#[async_trait]
+impl AlertHandler for GotifyAlert {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        self.send_with_context(identity, rule_name, message, &AlertContext::default())
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        let payload = self.build_payload(identity, rule_name, message, context);
+        let request = self.build_request(&payload)?;
+
+        self.client.execute(request).await?.error_for_status()?;
+
+        tracing::info!("Sent Gotify alert '{}' for rule: {} (from {})", self.name, rule_name, identity);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(priority: Option<u8>) -> GotifyAlert {
+        GotifyAlert::new(
+            "gotify".to_string(),
+            "https://gotify.example.com/".to_string(),
+            "AbC123".to_string(),
+            priority,
+            reqwest::Client::new(),
+        )
+    }
+
+    #[test]
+    fn test_token_sent_in_header() {
+        let alert = alert(None);
+        let payload = alert.build_payload("server-1", "errors", "ERROR boom", &AlertContext::default());
+        let request = alert.build_request(&payload).unwrap();
+
+        assert_eq!(request.url().as_str(), "https://gotify.example.com/message");
+        assert_eq!(request.headers()[TOKEN_HEADER], "AbC123");
+    }
+
+    #[test]
+    fn test_payload() {
+        let payload = alert(Some(5)).build_payload("server-1", "errors", "ERROR boom", &AlertContext::default());
+
+        assert_eq!(payload["title"], "TinyWatcher: errors");
+        assert_eq!(payload["message"], "Host: server-1\n\nERROR boom");
+        assert_eq!(payload["priority"], 5);
+    }
+
+    #[test]
+    fn test_priority_follows_severity() {
+        let context = AlertContext {
+            severity: Severity::Critical,
+            ..Default::default()
+        };
+        assert_eq!(alert(None).build_payload("server-1", "errors", "ERROR", &context)["priority"], 10);
+        assert_eq!(alert(Some(3)).build_payload("server-1", "errors", "ERROR", &context)["priority"], 3);
+
+        // Rules default to `error` severity, which keeps the old default priority
+        let payload = alert(None).build_payload("server-1", "errors", "ERROR", &AlertContext::default());
+        assert_eq!(payload["priority"], 8);
+    }
+}
@@ -0,0 +1,210 @@
+use super::{AlertContext, AlertHandler};
+use crate::config::Severity;
+use async_trait::async_trait;
+use anyhow::{Context, Result};
+use std::sync::Mutex;
+use syslog::{Facility, Formatter3164, Logger, LoggerBackend};
+
+/// Syslog priorities used for alerts
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Priority {
+    Crit,
+    Err,
+    Warning,
+    Info,
+}
+
+/// Map a rule's severity onto a syslog priority
+fn severity_priority(severity: Severity) -> Priority {
+    match severity {
+        Severity::Info => Priority::Info,
+        Severity::Warning => Priority::Warning,
+        Severity::Error => Priority::Err,
+        Severity::Critical => Priority::Crit,
+    }
+}
+
+/// Destination for formatted syslog lines
+trait SyslogWriter: Send {
+    fn write(&mut self, priority: Priority, line: &str) -> Result<()>;
+}
+
+/// Writes to the local syslog socket (`/dev/log`), which journald also listens on
+struct SocketWriter {
+    facility: Facility,
+    logger: Option<Logger<LoggerBackend, Formatter3164>>,
+}
+
+impl SyslogWriter for SocketWriter {
+    fn write(&mut self, priority: Priority, line: &str) -> Result<()> {
+        // Connect lazily so a missing syslog daemon doesn't prevent startup
+        let logger = match &mut self.logger {
+            Some(logger) => logger,
+            None => {
+                let formatter = Formatter3164 {
+                    facility: self.facility,
+                    process: "tinywatcher".to_string(),
+                    ..Default::default()
+                };
+                let logger = syslog::unix(formatter)
+                    .map_err(|e| anyhow::anyhow!("{}", e))
+                    .context("Failed to connect to local syslog")?;
+                self.logger.insert(logger)
+            }
+        };
+
+        let result = match priority {
+            Priority::Crit => logger.crit(line),
+            Priority::Err => logger.err(line),
+            Priority::Warning => logger.warning(line),
+            Priority::Info => logger.info(line),
+        };
+
+        if let Err(e) = result {
+            // Reconnect on the next alert, e.g. after the syslog daemon restarts
+            self.logger = None;
+            anyhow::bail!("Failed to write to local syslog: {}", e);
+        }
+        Ok(())
+    }
+}
+
+pub struct LocalSyslogAlert {
+    name: String,
+    writer: Mutex<Box<dyn SyslogWriter>>,
+}
+
+impl LocalSyslogAlert {
+    pub fn new(name: String, facility: Option<String>) -> Result<Self> {
+        let facility = match facility {
+            Some(facility) => facility
+                .parse::<Facility>()
+                .map_err(|_| anyhow::anyhow!("Unknown syslog facility '{}'", facility))?,
+            None => Facility::LOG_USER,
+        };
+
+        Ok(Self::with_writer(
+            name,
+            Box::new(SocketWriter {
+                facility,
+                logger: None,
+            }),
+        ))
+    }
+
+    fn with_writer(name: String, writer: Box<dyn SyslogWriter>) -> Self {
+        Self {
+            name,
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+/// Syslog messages are single lines
+fn format_line(identity: &str, rule_name: &str, message: &str) -> String {
+    let message = message
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" | ");
+    format!("[{}] {}: {}", identity, rule_name, message)
+}
+
+#[async_trait]
+impl AlertHandler for LocalSyslogAlert {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        self.send_with_context(identity, rule_name, message, &AlertContext::default())
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        let line = format_line(identity, rule_name, &context.render_text(message));
+        let priority = severity_priority(context.severity);
+
+        self.writer
+            .lock()
+            .map_err(|_| anyhow::anyhow!("Syslog writer lock poisoned"))?
+            .write(priority, &line)?;
+
+        tracing::info!("Sent local syslog alert '{}' for rule: {} (from {})", self.name, rule_name, identity);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    type Written = Arc<Mutex<Vec<(Priority, String)>>>;
+
+    struct RecordingWriter {
+        written: Written,
+    }
+
+    impl SyslogWriter for RecordingWriter {
+        fn write(&mut self, priority: Priority, line: &str) -> Result<()> {
+            self.written.lock().unwrap().push((priority, line.to_string()));
+            Ok(())
+        }
+    }
+
+    fn recording_alert() -> (LocalSyslogAlert, Written) {
+        let written = Written::default();
+        let alert = LocalSyslogAlert::with_writer(
+            "syslog".to_string(),
+            Box::new(RecordingWriter {
+                written: written.clone(),
+            }),
+        );
+        (alert, written)
+    }
+
+    #[tokio::test]
+    async fn test_priority_follows_severity() {
+        let (alert, written) = recording_alert();
+
+        for severity in [Severity::Info, Severity::Warning, Severity::Error, Severity::Critical] {
+            let context = AlertContext {
+                severity,
+                ..Default::default()
+            };
+            alert.send_with_context("server-1", "errors", "ERROR boom", &context).await.unwrap();
+        }
+
+        let priorities: Vec<_> = written.lock().unwrap().iter().map(|(p, _)| *p).collect();
+        assert_eq!(
+            priorities,
+            vec![Priority::Info, Priority::Warning, Priority::Err, Priority::Crit]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_line_format() {
+        let (alert, written) = recording_alert();
+
+        alert.send("server-1", "errors", "Rule 'errors' triggered\nLine: ERROR boom").await.unwrap();
+
+        let written = written.lock().unwrap();
+        assert_eq!(written[0].0, Priority::Err);
+        assert_eq!(written[0].1, "[server-1] errors: Rule 'errors' triggered | Line: ERROR boom");
+    }
+
+    #[test]
+    fn test_unknown_facility() {
+        assert!(LocalSyslogAlert::new("syslog".to_string(), Some("local9".to_string())).is_err());
+        assert!(LocalSyslogAlert::new("syslog".to_string(), Some("local0".to_string())).is_ok());
+        assert!(LocalSyslogAlert::new("syslog".to_string(), None).is_ok());
+    }
+}
@@ -1,29 +1,127 @@
+#[cfg(feature = "desktop")]
+mod desktop;
 mod discord;
 mod email;
+mod exec;
+mod file;
+mod forward;
+mod gotify;
+#[cfg(unix)]
+mod local_syslog;
 mod ntfy;
+mod opsgenie;
 mod pagerduty;
+mod pushover;
+mod retry;
 mod sendgrid;
 mod slack;
 mod stdout;
 mod telegram;
 mod webhook;
 
+use crate::config::{CooldownMode, Severity};
+use crate::state::{self, Clock, CooldownState};
 use async_trait::async_trait;
 use anyhow::Result;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{watch, Mutex};
 
+#[cfg(feature = "desktop")]
+pub use desktop::DesktopAlert;
 pub use discord::DiscordAlert;
 pub use email::EmailAlert;
+pub use exec::ExecAlert;
+pub use file::FileAlert;
+pub use forward::ForwardAlert;
+pub use gotify::GotifyAlert;
+#[cfg(unix)]
+pub use local_syslog::LocalSyslogAlert;
 pub use ntfy::NtfyAlert;
+pub use opsgenie::OpsgenieAlert;
 pub use pagerduty::PagerDutyAlert;
+pub use pushover::PushoverAlert;
+pub use retry::RetryingHandler;
 pub use sendgrid::SendGridAlert;
 pub use slack::SlackAlert;
 pub use stdout::StdoutAlert;
 pub use telegram::TelegramAlert;
-pub use webhook::WebhookAlert;
+pub use webhook::{WebhookAlert, DEFAULT_SIGNATURE_HEADER};
+
+/// Kind of input that produced an alert
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertSourceKind {
+    File,
+    Container,
+    Websocket,
+    Http,
+    Tcp,
+    Sse,
+    #[cfg(windows)]
+    EventLog,
+    Journal,
+    Pod,
+}
+
+impl AlertSourceKind {
+    /// Same lowercase name used in JSON payloads
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertSourceKind::File => "file",
+            AlertSourceKind::Container => "container",
+            AlertSourceKind::Websocket => "websocket",
+            AlertSourceKind::Http => "http",
+            AlertSourceKind::Tcp => "tcp",
+            AlertSourceKind::Sse => "sse",
+            #[cfg(windows)]
+            AlertSourceKind::EventLog => "eventlog",
+            AlertSourceKind::Journal => "journal",
+            AlertSourceKind::Pod => "pod",
+        }
+    }
+}
+
+/// The input that produced the matched line
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AlertSource {
+    #[serde(rename = "type")]
+    pub kind: AlertSourceKind,
+    pub name: String,
+    /// Stream URL, for stream sources
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Structured context that accompanies an alert message
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlertContext {
+    /// Named capture groups extracted from the matched line (rule `fields`)
+    pub fields: BTreeMap<String, String>,
+    /// Input that produced the matched line, for log and stream alerts
+    pub source: Option<AlertSource>,
+    /// Severity of the rule that fired
+    pub severity: Severity,
+}
+
+impl AlertContext {
+    /// Render the alert for text-only destinations
+    /// Captured fields replace the raw message as "key: value" lines when present
+    pub fn render_text(&self, message: &str) -> String {
+        if self.fields.is_empty() {
+            return message.to_string();
+        }
+
+        self.fields
+            .iter()
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
 
 /// Trait that all alert handlers must implement
 #[async_trait]
@@ -31,16 +129,145 @@ pub trait AlertHandler: Send + Sync {
     /// Send an alert with the given identity, rule name, and message
     async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()>;
     
+    /// Send an alert with structured context
+    /// Handlers that can carry structured data (e.g. JSON payloads) override this;
+    /// the default renders the context as text and calls `send`
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        self.send(identity, rule_name, &context.render_text(message)).await
+    }
+    
     /// Get a human-readable name for this alert handler
     #[allow(dead_code)]
     fn name(&self) -> &str;
 }
 
+/// Rule name used for the alert storm notice
+pub const ALERT_STORM_RULE: &str = "alert_storm";
+
+/// Rule name used for delivery failure summaries
+pub const DELIVERY_FAILURE_RULE: &str = "delivery_failure";
+
+/// Process-wide cap on alert deliveries (the `--max-alerts` kill-switch)
+struct AlertLimit {
+    max_alerts: usize,
+    window: Duration,
+    /// Delivery timestamps within the current window
+    sent: VecDeque<Instant>,
+    /// While set, all alerts are suppressed until this instant
+    suppressed_until: Option<Instant>,
+}
+
+/// Failed deliveries waiting to be summarized to a designated alert (`delivery_failures`)
+struct DeliveryFailures {
+    alert_name: String,
+    window: Duration,
+    /// Failures since the last summary, by destination
+    failures: BTreeMap<String, FailedDestination>,
+}
+
+#[derive(Default)]
+struct FailedDestination {
+    /// Failure count per rule
+    rules: BTreeMap<String, usize>,
+    last_error: String,
+}
+
+/// Recently sent message fingerprints (the `dedup` option)
+struct Dedup {
+    window: Duration,
+    normalizers: Vec<(regex::Regex, String)>,
+    /// When each fingerprint was last sent
+    seen: HashMap<u64, Instant>,
+}
+
+impl Dedup {
+    /// Fingerprint of the message after normalization
+    fn fingerprint(&self, message: &str) -> u64 {
+        use std::hash::{Hash, Hasher};
+
+        let mut normalized = message.to_string();
+        for (pattern, replacement) in &self.normalizers {
+            normalized = pattern.replace_all(&normalized, replacement.as_str()).into_owned();
+        }
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        normalized.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// Upper bound on a single HTTP alert delivery, so a stalled destination can't hold up alerting
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+const HTTP_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long idle connections stay pooled between alerts
+const HTTP_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+fn build_http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .user_agent(concat!("tinywatcher/", env!("CARGO_PKG_VERSION")))
+        .timeout(HTTP_TIMEOUT)
+        .connect_timeout(HTTP_CONNECT_TIMEOUT)
+        .pool_idle_timeout(HTTP_POOL_IDLE_TIMEOUT)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
 /// Manages alert handlers and cooldowns
 pub struct AlertManager {
     handlers: HashMap<String, Arc<dyn AlertHandler>>,
-    cooldowns: Arc<Mutex<HashMap<String, Instant>>>,
+    /// Last alert and cooldown length per rule
+    cooldowns: Arc<Mutex<HashMap<String, (Instant, Duration)>>>,
+    /// Alerts a rule may send within its cooldown window before suppression starts
+    bursts: HashMap<String, usize>,
+    /// Alerts sent in each rule's current cooldown window
+    burst_sent: Mutex<HashMap<String, usize>>,
     identity: String,
+    container_identity: bool,
+    cooldown_mode: CooldownMode,
+    alert_limit: Option<Mutex<AlertLimit>>,
+    delivery_failures: Option<Mutex<DeliveryFailures>>,
+    dedup: Option<Mutex<Dedup>>,
+    /// When a maintenance mute ends, shared by every delivery in this process
+    mute: Arc<Mutex<Option<Instant>>>,
+    /// State directory whose mute marker (from `tinywatcher mute`) is checked before each delivery
+    mute_dir: Option<PathBuf>,
+    /// Modification time of the mute marker when it was last read (`None` while there is none),
+    /// so it is only read again after it changes; unset until the first read
+    mute_marker_read: Mutex<Option<Option<SystemTime>>>,
+    /// Deliveries in progress, so shutdown can let them finish
+    in_flight: watch::Sender<usize>,
+    /// Shared by every HTTP destination, so they pool connections instead of each doing its own TLS setup
+    http_client: reqwest::Client,
+}
+
+/// Counts a delivery as in flight until dropped
+struct DeliveryGuard<'a>(&'a watch::Sender<usize>);
+
+impl Drop for DeliveryGuard<'_> {
+    fn drop(&mut self) {
+        self.0.send_modify(|count| *count -= 1);
+    }
+}
+
+/// An alert error as text, with request URLs cut down to scheme, host and port
+/// Webhook URLs (Slack, Discord) and some APIs (Telegram) carry their secret in the URL, which
+/// reqwest includes in its errors; the text is sent on to another destination, so it must not leak
+fn redacted_error(error: &anyhow::Error) -> String {
+    let mut text = error.to_string();
+    for cause in error.chain() {
+        if let Some(url) = cause.downcast_ref::<reqwest::Error>().and_then(reqwest::Error::url) {
+            let port = url.port().map(|port| format!(":{}", port)).unwrap_or_default();
+            let redacted = format!("{}://{}{}/...", url.scheme(), url.host_str().unwrap_or_default(), port);
+            text = text.replace(url.as_str(), &redacted);
+        }
+    }
+    text
 }
 
 impl AlertManager {
@@ -48,7 +275,93 @@ impl AlertManager {
         Self {
             handlers: HashMap::new(),
             cooldowns: Arc::new(Mutex::new(HashMap::new())),
+            bursts: HashMap::new(),
+            burst_sent: Mutex::new(HashMap::new()),
             identity,
+            container_identity: false,
+            cooldown_mode: CooldownMode::default(),
+            alert_limit: None,
+            delivery_failures: None,
+            dedup: None,
+            mute: Arc::new(Mutex::new(None)),
+            mute_dir: None,
+            mute_marker_read: Mutex::new(None),
+            in_flight: watch::channel(0).0,
+            http_client: build_http_client(),
+        }
+    }
+
+    /// Client for HTTP destinations to send through; clones share one connection pool
+    pub fn http_client(&self) -> &reqwest::Client {
+        &self.http_client
+    }
+
+    fn start_delivery(&self) -> DeliveryGuard<'_> {
+        self.in_flight.send_modify(|count| *count += 1);
+        DeliveryGuard(&self.in_flight)
+    }
+
+    /// Wait until no alert deliveries are in progress
+    pub async fn idle(&self) {
+        let mut in_flight = self.in_flight.subscribe();
+        let _ = in_flight.wait_for(|count| *count == 0).await;
+    }
+
+    /// Drop alerts whose normalized message was already sent within `window`
+    pub fn set_dedup(&mut self, window: Duration, normalizers: Vec<(regex::Regex, String)>) {
+        self.dedup = Some(Mutex::new(Dedup {
+            window,
+            normalizers,
+            seen: HashMap::new(),
+        }));
+    }
+
+    /// Summarize failed deliveries to `alert_name` once per `window`
+    pub fn set_failure_alert(&mut self, alert_name: String, window: Duration) {
+        self.delivery_failures = Some(Mutex::new(DeliveryFailures {
+            alert_name,
+            window,
+            failures: BTreeMap::new(),
+        }));
+    }
+
+    /// Stop alerting for `window` once more than `max_alerts` deliveries happen within it
+    pub fn set_alert_limit(&mut self, max_alerts: usize, window: Duration) {
+        self.alert_limit = Some(Mutex::new(AlertLimit {
+            max_alerts,
+            window,
+            sent: VecDeque::new(),
+            suppressed_until: None,
+        }));
+    }
+
+    /// Let the first `burst` alerts of a rule through immediately, then apply its cooldown
+    pub fn set_burst(&mut self, rule_name: &str, burst: usize) {
+        self.bursts.insert(rule_name.to_string(), burst);
+    }
+
+    /// Choose whether suppressed alerts extend the cooldown (sliding) or not (fixed)
+    pub fn set_cooldown_mode(&mut self, mode: CooldownMode) {
+        self.cooldown_mode = mode;
+    }
+
+    /// Follow the mute marker `tinywatcher mute` and `tinywatcher unmute` write to `dir`
+    pub fn set_mute_dir(&mut self, dir: PathBuf) {
+        self.mute_dir = Some(dir);
+    }
+
+    /// Suffix the identity with the container name for alerts from container sources
+    pub fn set_container_identity(&mut self, enabled: bool) {
+        self.container_identity = enabled;
+    }
+
+    /// Identity to report for an alert, e.g. `host/nginx` for container alerts
+    fn identity_for(&self, context: &AlertContext) -> String {
+        match &context.source {
+            Some(source) if self.container_identity && source.kind == AlertSourceKind::Container => {
+                format!("{}/{}", self.identity, source.name)
+            }
+            _ => self.identity.clone(),
         }
     }
 
@@ -57,7 +370,13 @@ impl AlertManager {
         self.handlers.insert(name, handler);
     }
 
+    /// Whether a handler is registered under this name
+    pub fn has_handler(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+
     /// Send an alert to a specific handler by name
+    #[allow(dead_code)]
     pub async fn send_alert(
         &self,
         alert_name: &str,
@@ -65,8 +384,12 @@ impl AlertManager {
         message: &str,
         cooldown_secs: u64,
     ) -> Result<()> {
+        if self.is_muted(rule_name).await {
+            return Ok(());
+        }
+
         // Check cooldown
-        if !self.check_cooldown(rule_name, cooldown_secs).await {
+        if !self.check_cooldown(rule_name, rule_name, cooldown_secs).await {
             return Ok(());
         }
 
@@ -75,7 +398,21 @@ impl AlertManager {
             anyhow::anyhow!("Alert '{}' not found in configuration", alert_name)
         })?;
 
-        handler.send(&self.identity, rule_name, message).await
+        if self.is_duplicate(message).await {
+            return Ok(());
+        }
+
+        if !self.check_alert_limit(1).await {
+            return Ok(());
+        }
+
+        let _delivery = self.start_delivery();
+        let result = handler.send(&self.identity, rule_name, message).await;
+        match &result {
+            Ok(()) => crate::metrics::alert_sent(alert_name),
+            Err(e) => self.record_failure(alert_name, rule_name, e).await,
+        }
+        result
     }
 
     /// Send an alert to multiple handlers
@@ -86,37 +423,404 @@ impl AlertManager {
         message: &str,
         cooldown_secs: u64,
     ) -> Result<()> {
+        self.send_alert_multi_with_context(
+            alert_names,
+            rule_name,
+            message,
+            cooldown_secs,
+            &AlertContext::default(),
+        )
+        .await
+    }
+
+    /// Send an alert with structured context to multiple handlers
+    pub async fn send_alert_multi_with_context(
+        &self,
+        alert_names: &[String],
+        rule_name: &str,
+        message: &str,
+        cooldown_secs: u64,
+        context: &AlertContext,
+    ) -> Result<()> {
+        self.send_routed_alert(alert_names, rule_name, rule_name, message, cooldown_secs, context)
+            .await
+            .map(|_| ())
+    }
+
+    /// Send a rule's alert with its cooldown (and burst allowance) tracked under `cooldown_key`,
+    /// e.g. separately for each destination a capture-routed rule resolved to
+    /// Returns whether any destination received it; not when muted, cooling down, a duplicate,
+    /// over the alert limit, or when every delivery failed
+    pub async fn send_routed_alert(
+        &self,
+        alert_names: &[String],
+        rule_name: &str,
+        cooldown_key: &str,
+        message: &str,
+        cooldown_secs: u64,
+        context: &AlertContext,
+    ) -> Result<bool> {
+        if self.is_muted(rule_name).await {
+            return Ok(false);
+        }
+
         // Check cooldown
-        if !self.check_cooldown(rule_name, cooldown_secs).await {
-            return Ok(());
+        if !self.check_cooldown(rule_name, cooldown_key, cooldown_secs).await {
+            return Ok(false);
+        }
+
+        if self.is_duplicate(message).await {
+            return Ok(false);
+        }
+
+        if !self.check_alert_limit(alert_names.len()).await {
+            return Ok(false);
+        }
+
+        let identity = self.identity_for(context);
+        let _delivery = self.start_delivery();
+
+        let handlers = alert_names
+            .iter()
+            .map(|alert_name| {
+                self.handlers
+                    .get(alert_name)
+                    .map(|handler| (alert_name, handler))
+                    .ok_or_else(|| anyhow::anyhow!("Alert '{}' not found in configuration", alert_name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Send to all specified handlers at once, so a slow destination doesn't hold up the rest
+        let sends = handlers.into_iter().map(|(alert_name, handler)| {
+            let identity = &identity;
+            async move {
+                match handler
+                    .send_with_context(identity, rule_name, message, context)
+                    .await
+                {
+                    Ok(()) => {
+                        crate::metrics::alert_sent(alert_name);
+                        true
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to send alert to '{}': {}", alert_name, e);
+                        self.record_failure(alert_name, rule_name, &e).await;
+                        false
+                    }
+                }
+            }
+        });
+        let delivered = futures::future::join_all(sends).await;
+
+        Ok(delivered.contains(&true))
+    }
+
+    /// Tell a rule's destinations that its resolving line appeared
+    /// Recoveries skip the rule's cooldown, so the next alert isn't held back by one
+    pub async fn send_recovery(&self, alert_names: &[String], rule_name: &str, line: &str, source: Option<AlertSource>) {
+        let alert_names: Vec<String> = alert_names
+            .iter()
+            .filter(|name| self.has_handler(name))
+            .cloned()
+            .collect();
+        if alert_names.is_empty() {
+            return;
+        }
+
+        let message = format!("Rule '{}' resolved\n{}", rule_name, line);
+        let context = AlertContext {
+            source,
+            severity: Severity::Info,
+            ..Default::default()
+        };
+
+        if let Err(e) = self
+            .send_alert_multi_with_context(&alert_names, rule_name, &message, 0, &context)
+            .await
+        {
+            tracing::error!("Failed to send recovery alert for rule '{}': {}", rule_name, e);
+        }
+    }
+
+    /// Record a failed delivery for the next `delivery_failures` summary
+    async fn record_failure(&self, alert_name: &str, rule_name: &str, error: &anyhow::Error) {
+        crate::metrics::alert_failed(alert_name);
+        let Some(delivery_failures) = &self.delivery_failures else {
+            return;
+        };
+
+        let mut delivery_failures = delivery_failures.lock().await;
+        let destination = delivery_failures
+            .failures
+            .entry(alert_name.to_string())
+            .or_default();
+        *destination.rules.entry(rule_name.to_string()).or_default() += 1;
+        destination.last_error = redacted_error(error);
+    }
+
+    /// Periodically send a summary of failed deliveries to the `delivery_failures` alert
+    pub async fn watch_delivery_failures(&self) {
+        let Some(delivery_failures) = &self.delivery_failures else {
+            return;
+        };
+
+        let window = delivery_failures.lock().await.window;
+        let mut interval_timer = tokio::time::interval(window);
+        interval_timer.tick().await;
+        loop {
+            interval_timer.tick().await;
+            self.report_delivery_failures().await;
+        }
+    }
+
+    /// Send one summary per failing destination and reset the counts
+    async fn report_delivery_failures(&self) {
+        let Some(delivery_failures) = &self.delivery_failures else {
+            return;
+        };
+
+        let (alert_name, window, failures) = {
+            let mut delivery_failures = delivery_failures.lock().await;
+            (
+                delivery_failures.alert_name.clone(),
+                delivery_failures.window,
+                std::mem::take(&mut delivery_failures.failures),
+            )
+        };
+
+        let Some(handler) = self.handlers.get(&alert_name) else {
+            return;
+        };
+
+        for (destination, failed) in failures {
+            let total: usize = failed.rules.values().sum();
+            let rules = failed
+                .rules
+                .iter()
+                .map(|(rule, count)| format!("{} ({})", rule, count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let message = format!(
+                "Alert delivery to '{}' failing: {} failure(s) in {}\nRules: {}\nLast error: {}",
+                destination,
+                total,
+                crate::config::format_duration(window),
+                rules,
+                failed.last_error
+            );
+            tracing::warn!("{}", message);
+
+            // Failures of the summary itself are only logged, so they can't feed the next one
+            if let Err(e) = handler.send(&self.identity, DELIVERY_FAILURE_RULE, &message).await {
+                tracing::error!("Failed to send delivery failure summary to '{}': {}", alert_name, e);
+            }
+        }
+    }
+
+    /// Whether alerts are muted for maintenance, following the mute marker so a
+    /// `tinywatcher mute` or `unmute` from another invocation takes effect right away
+    async fn is_muted(&self, rule_name: &str) -> bool {
+        if let Some(dir) = &self.mute_dir {
+            self.refresh_mute(dir).await;
         }
 
-        // Send to all specified handlers
-        for alert_name in alert_names {
-            let handler = self.handlers.get(alert_name).ok_or_else(|| {
-                anyhow::anyhow!("Alert '{}' not found in configuration", alert_name)
-            })?;
+        let mut mute = self.mute.lock().await;
+        let clock = Clock::now();
+        match *mute {
+            Some(until) if clock.instant < until => {
+                tracing::info!(
+                    "Alerts muted until {}; not sending alert for rule: {}",
+                    (clock.utc + chrono::Duration::from_std(until - clock.instant).unwrap_or_default())
+                        .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                    rule_name
+                );
+                true
+            }
+            Some(_) => {
+                tracing::info!("Maintenance mute ended, resuming alerts");
+                *mute = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Read the mute marker again if it changed since the last read
+    /// The read runs off the async runtime, without holding any lock other deliveries wait on
+    async fn refresh_mute(&self, dir: &Path) {
+        let modified = state::mute_modified(dir).await;
+        if *self.mute_marker_read.lock().await == Some(modified) {
+            return;
+        }
 
-            if let Err(e) = handler.send(&self.identity, rule_name, message).await {
-                tracing::error!("Failed to send alert to '{}': {}", alert_name, e);
+        let marker = {
+            let dir = dir.to_path_buf();
+            tokio::task::spawn_blocking(move || state::load_mute(&dir)).await
+        };
+        match marker {
+            Ok(Ok(until)) => {
+                let clock = Clock::now();
+                *self.mute.lock().await = until.and_then(|until| clock.instant_until(until));
             }
+            Ok(Err(e)) => tracing::warn!("Ignoring mute marker: {:#}", e),
+            Err(e) => tracing::warn!("Ignoring mute marker: {}", e),
+        }
+        *self.mute_marker_read.lock().await = Some(modified);
+    }
+
+    /// Whether an identical (normalized) message was sent within the dedup window
+    async fn is_duplicate(&self, message: &str) -> bool {
+        let Some(dedup) = &self.dedup else {
+            return false;
+        };
+
+        let mut dedup = dedup.lock().await;
+        let now = Instant::now();
+        let window = dedup.window;
+        dedup.seen.retain(|_, sent| now.duration_since(*sent) < window);
+
+        let fingerprint = dedup.fingerprint(message);
+        if dedup.seen.contains_key(&fingerprint) {
+            tracing::debug!("Suppressing duplicate alert: {}", message);
+            return true;
         }
 
-        Ok(())
+        dedup.seen.insert(fingerprint, now);
+        false
     }
 
-    async fn check_cooldown(&self, rule_name: &str, cooldown_secs: u64) -> bool {
+    /// Record `deliveries` against the global alert limit
+    /// Returns false if alerts are suppressed; the delivery that trips the limit sends
+    /// a single storm notice to every registered destination instead
+    async fn check_alert_limit(&self, deliveries: usize) -> bool {
+        let Some(alert_limit) = &self.alert_limit else {
+            return true;
+        };
+
+        let (max_alerts, window) = {
+            let mut limit = alert_limit.lock().await;
+            let now = Instant::now();
+
+            match limit.suppressed_until {
+                Some(until) if now < until => return false,
+                Some(_) => {
+                    tracing::info!("Alert storm suppression ended, resuming alerts");
+                    limit.suppressed_until = None;
+                    limit.sent.clear();
+                }
+                None => {}
+            }
+
+            let window = limit.window;
+            while limit.sent.front().is_some_and(|sent| now.duration_since(*sent) >= window) {
+                limit.sent.pop_front();
+            }
+
+            if limit.sent.len() + deliveries <= limit.max_alerts {
+                limit.sent.extend(std::iter::repeat_n(now, deliveries));
+                return true;
+            }
+
+            limit.suppressed_until = Some(now + window);
+            (limit.max_alerts, window)
+        };
+
+        let message = format!(
+            "Alert storm detected: more than {} alerts in {}s. Suppressing all alerts for {}s.",
+            max_alerts,
+            window.as_secs(),
+            window.as_secs()
+        );
+        tracing::warn!("{}", message);
+
+        let mut names: Vec<&String> = self.handlers.keys().collect();
+        names.sort();
+        for name in names {
+            if let Err(e) = self.handlers[name]
+                .send(&self.identity, ALERT_STORM_RULE, &message)
+                .await
+            {
+                tracing::error!("Failed to send alert storm notice to '{}': {}", name, e);
+            }
+        }
+
+        false
+    }
+
+    /// Whether an alert may be sent now; `key` is what the cooldown is tracked under and
+    /// `rule_name` picks the configured burst allowance
+    /// A zero cooldown never holds anything back, so it neither checks nor restarts the key's clock;
+    /// recoveries sent under a rule's name thus leave the rule's own cooldown running as it was
+    async fn check_cooldown(&self, rule_name: &str, key: &str, cooldown_secs: u64) -> bool {
+        if cooldown_secs == 0 {
+            return true;
+        }
+
         let mut cooldowns = self.cooldowns.lock().await;
         
-        if let Some(last_alert) = cooldowns.get(rule_name) {
-            if last_alert.elapsed() < Duration::from_secs(cooldown_secs) {
+        let now = Instant::now();
+        let cooldown = Duration::from_secs(cooldown_secs);
+        
+        let burst = self.bursts.get(rule_name).copied().unwrap_or(0);
+        if let Some((last_alert, _)) = cooldowns.get_mut(key) {
+            if now.duration_since(*last_alert) < cooldown {
+                if burst > 0 {
+                    let mut burst_sent = self.burst_sent.lock().await;
+                    let sent = burst_sent.entry(key.to_string()).or_insert(1);
+                    if *sent < burst {
+                        *sent += 1;
+                        return true;
+                    }
+                }
+                if self.cooldown_mode == CooldownMode::Sliding {
+                    // Suppressed attempts push the window forward
+                    *last_alert = now;
+                }
                 return false;
             }
         }
         
-        cooldowns.insert(rule_name.to_string(), Instant::now());
+        cooldowns.insert(key.to_string(), (now, cooldown));
+        if burst > 0 {
+            // A new window starts with this alert
+            self.burst_sent.lock().await.insert(key.to_string(), 1);
+        }
         true
     }
+
+    /// Cooldowns still in effect, as timestamps for saving
+    pub async fn export_cooldowns(&self, clock: &Clock) -> BTreeMap<String, CooldownState> {
+        self.cooldowns
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, (last_alert, cooldown))| clock.instant.saturating_duration_since(*last_alert) < *cooldown)
+            .map(|(rule, (last_alert, cooldown))| {
+                (
+                    rule.clone(),
+                    CooldownState {
+                        last_alert: clock.timestamp(*last_alert),
+                        window_secs: cooldown.as_secs(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Restore saved cooldowns, skipping those that have already expired
+    /// Returns the number restored
+    pub async fn restore_cooldowns(&self, saved: BTreeMap<String, CooldownState>, clock: &Clock) -> usize {
+        let mut cooldowns = self.cooldowns.lock().await;
+        let mut restored = 0;
+        for (rule, state) in saved {
+            let cooldown = Duration::from_secs(state.window_secs);
+            if let Some(last_alert) = clock.instant_for(state.last_alert, cooldown) {
+                cooldowns.insert(rule, (last_alert, cooldown));
+                restored += 1;
+            }
+        }
+        restored
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod tests {
-    use crate::alerts::{AlertHandler, AlertManager};
+    use crate::alerts::{AlertContext, AlertHandler, AlertManager, AlertSource, AlertSourceKind};
+    use crate::config::CooldownMode;
     use anyhow::Result;
     use std::sync::Arc;
     use std::sync::atomic::{AtomicUsize, Ordering};
@@ -38,6 +39,38 @@ mod tests {
         }
     }
 
+    // Mock alert handler that records the identity it was sent
+    struct IdentityAlertHandler {
+        name: String,
+        last_identity: Arc<tokio::sync::Mutex<String>>,
+    }
+
+    #[async_trait]
+    impl AlertHandler for IdentityAlertHandler {
+        async fn send(&self, identity: &str, _rule_name: &str, _message: &str) -> Result<()> {
+            *self.last_identity.lock().await = identity.to_string();
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    fn identity_manager(container_identity: bool) -> (AlertManager, Arc<tokio::sync::Mutex<String>>) {
+        let mut manager = AlertManager::new("web-1".to_string());
+        manager.set_container_identity(container_identity);
+        let last_identity = Arc::new(tokio::sync::Mutex::new(String::new()));
+        manager.register(
+            "test-alert".to_string(),
+            Arc::new(IdentityAlertHandler {
+                name: "test-alert".to_string(),
+                last_identity: last_identity.clone(),
+            }),
+        );
+        (manager, last_identity)
+    }
+
     // Mock failing alert handler
     struct FailingAlertHandler {
         name: String,
@@ -119,6 +152,93 @@ mod tests {
         assert_eq!(call_count.load(Ordering::SeqCst), 1);
     }
 
+    #[tokio::test]
+    async fn test_burst_sends_first_alerts_then_applies_cooldown() {
+        let mut manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+        manager.set_burst("new-error", 3);
+
+        for i in 0..5 {
+            manager
+                .send_alert("test-alert", "new-error", &format!("message {}", i), 60)
+                .await
+                .unwrap();
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 3);
+
+        // Rules without a burst keep the plain cooldown
+        for _ in 0..2 {
+            manager.send_alert("test-alert", "other", "message", 60).await.unwrap();
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_burst_resets_when_cooldown_expires() {
+        let mut manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+        manager.set_burst("new-error", 2);
+
+        for _ in 0..3 {
+            manager.send_alert("test-alert", "new-error", "message", 1).await.unwrap();
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+
+        for _ in 0..3 {
+            manager.send_alert("test-alert", "new-error", "message", 1).await.unwrap();
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 4);
+    }
+
+    fn dedup_manager(normalize: &[(&str, &str)]) -> (AlertManager, Arc<AtomicUsize>) {
+        let mut manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let normalizers = normalize
+            .iter()
+            .map(|(pattern, replacement)| (regex::Regex::new(pattern).unwrap(), replacement.to_string()))
+            .collect();
+        manager.set_dedup(Duration::from_secs(60), normalizers);
+        (manager, call_count)
+    }
+
+    #[tokio::test]
+    async fn test_dedup_collapses_normalized_messages() {
+        let (manager, call_count) = dedup_manager(&[("req-[0-9a-f]+", "req-*")]);
+        let alerts = vec!["test-alert".to_string()];
+
+        manager
+            .send_alert_multi(&alerts, "rule-a", "ERROR timeout for req-8f3a21", 0)
+            .await
+            .unwrap();
+        manager
+            .send_alert_multi(&alerts, "rule-b", "ERROR timeout for req-c0ffee", 0)
+            .await
+            .unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        manager
+            .send_alert_multi(&alerts, "rule-a", "ERROR refused for req-8f3a21", 0)
+            .await
+            .unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_dedup_without_normalization_compares_exact_messages() {
+        let (manager, call_count) = dedup_manager(&[]);
+
+        manager.send_alert("test-alert", "rule", "ERROR req-1", 0).await.unwrap();
+        manager.send_alert("test-alert", "rule", "ERROR req-1", 0).await.unwrap();
+        manager.send_alert("test-alert", "rule", "ERROR req-2", 0).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+    }
+
     #[tokio::test]
     async fn test_alert_manager_cooldown_expired() {
         let mut manager = AlertManager::new("test-server".to_string());
@@ -294,4 +414,566 @@ mod tests {
         
         assert_eq!(manager.handlers.len(), 10);
     }
+
+    #[tokio::test]
+    async fn test_container_identity_includes_container_name() {
+        let (manager, last_identity) = identity_manager(true);
+        let alerts = vec!["test-alert".to_string()];
+        let context = AlertContext {
+            source: Some(AlertSource {
+                kind: AlertSourceKind::Container,
+                name: "nginx".to_string(),
+                url: None,
+            }),
+            ..Default::default()
+        };
+
+        manager
+            .send_alert_multi_with_context(&alerts, "errors", "ERROR", 0, &context)
+            .await
+            .unwrap();
+        assert_eq!(*last_identity.lock().await, "web-1/nginx");
+    }
+
+    #[tokio::test]
+    async fn test_container_identity_skips_resource_alerts() {
+        let (manager, last_identity) = identity_manager(true);
+        let alerts = vec!["test-alert".to_string()];
+
+        // Resource alerts carry no container, so the plain host identity is kept
+        manager
+            .send_alert_multi(&alerts, "high_cpu", "CPU at 95%", 0)
+            .await
+            .unwrap();
+        assert_eq!(*last_identity.lock().await, "web-1");
+    }
+
+    #[tokio::test]
+    async fn test_send_alert_passes_identity() {
+        let (manager, last_identity) = identity_manager(false);
+
+        manager
+            .send_alert("test-alert", "disk_full", "Disk at 99%", 0)
+            .await
+            .unwrap();
+        assert_eq!(*last_identity.lock().await, "web-1");
+    }
+
+    #[tokio::test]
+    async fn test_container_identity_disabled_by_default() {
+        let (manager, last_identity) = identity_manager(false);
+        let alerts = vec!["test-alert".to_string()];
+        let context = AlertContext {
+            source: Some(AlertSource {
+                kind: AlertSourceKind::Container,
+                name: "nginx".to_string(),
+                url: None,
+            }),
+            ..Default::default()
+        };
+
+        manager
+            .send_alert_multi_with_context(&alerts, "errors", "ERROR", 0, &context)
+            .await
+            .unwrap();
+        assert_eq!(*last_identity.lock().await, "web-1");
+    }
+
+    #[tokio::test]
+    async fn test_container_identity_ignores_other_sources() {
+        let (manager, last_identity) = identity_manager(true);
+        let alerts = vec!["test-alert".to_string()];
+        let context = AlertContext {
+            source: Some(AlertSource {
+                kind: AlertSourceKind::File,
+                name: "/var/log/app.log".to_string(),
+                url: None,
+            }),
+            ..Default::default()
+        };
+
+        manager
+            .send_alert_multi_with_context(&alerts, "errors", "ERROR", 0, &context)
+            .await
+            .unwrap();
+        assert_eq!(*last_identity.lock().await, "web-1");
+    }
+
+    // Send a match every 200ms for 1.6s against a 1s cooldown, returning the alert count
+    async fn steady_stream_alerts(mode: CooldownMode) -> usize {
+        let mut manager = AlertManager::new("test-server".to_string());
+        manager.set_cooldown_mode(mode);
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+        let alerts = vec!["test-alert".to_string()];
+
+        for _ in 0..9 {
+            manager.send_alert_multi(&alerts, "errors", "ERROR", 1).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+        }
+
+        call_count.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_fixed_mode_alerts_once_per_period() {
+        // The window isn't extended by suppressed matches, so a second alert fires after 1s
+        assert_eq!(steady_stream_alerts(CooldownMode::Fixed).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_sliding_mode_waits_for_quiet_period() {
+        // Every suppressed match restarts the window, so a steady stream alerts only once
+        assert_eq!(steady_stream_alerts(CooldownMode::Sliding).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_sliding_mode_alerts_after_quiet_period() {
+        let mut manager = AlertManager::new("test-server".to_string());
+        manager.set_cooldown_mode(CooldownMode::Sliding);
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+        let alerts = vec!["test-alert".to_string()];
+
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 1).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 1).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        // 1.1s after the suppressed attempt, but only 1.6s after the first alert
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 1).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+    }
+
+    // Mock alert handler that records every rule it is sent
+    struct RecordingAlertHandler {
+        name: String,
+        rules: Arc<tokio::sync::Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl AlertHandler for RecordingAlertHandler {
+        async fn send(&self, _identity: &str, rule_name: &str, _message: &str) -> Result<()> {
+            self.rules.lock().await.push(rule_name.to_string());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    fn limited_manager(max_alerts: usize, window: Duration) -> (AlertManager, Arc<tokio::sync::Mutex<Vec<String>>>) {
+        let mut manager = AlertManager::new("test-server".to_string());
+        manager.set_alert_limit(max_alerts, window);
+        let rules = Arc::new(tokio::sync::Mutex::new(Vec::new()));
+        manager.register(
+            "test-alert".to_string(),
+            Arc::new(RecordingAlertHandler {
+                name: "test-alert".to_string(),
+                rules: rules.clone(),
+            }),
+        );
+        (manager, rules)
+    }
+
+    #[tokio::test]
+    async fn test_alert_limit_triggers_single_storm_notice() {
+        let (manager, rules) = limited_manager(3, Duration::from_secs(60));
+        let alerts = vec!["test-alert".to_string()];
+
+        for i in 0..10 {
+            manager
+                .send_alert_multi(&alerts, &format!("rule{}", i), "ERROR", 0)
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            *rules.lock().await,
+            vec!["rule0", "rule1", "rule2", crate::alerts::ALERT_STORM_RULE]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_alert_limit_resumes_after_window() {
+        let (manager, rules) = limited_manager(2, Duration::from_millis(300));
+        let alerts = vec!["test-alert".to_string()];
+
+        for i in 0..4 {
+            manager
+                .send_alert_multi(&alerts, &format!("rule{}", i), "ERROR", 0)
+                .await
+                .unwrap();
+        }
+        assert_eq!(rules.lock().await.len(), 3); // two alerts + storm notice
+
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        manager.send_alert_multi(&alerts, "after_storm", "ERROR", 0).await.unwrap();
+        assert_eq!(rules.lock().await.last().unwrap(), "after_storm");
+    }
+
+    #[tokio::test]
+    async fn test_alert_limit_counts_each_destination() {
+        let (mut manager, rules) = limited_manager(3, Duration::from_secs(60));
+        let (handler, call_count) = MockAlertHandler::new("second");
+        manager.register("second".to_string(), Arc::new(handler));
+        let alerts = vec!["test-alert".to_string(), "second".to_string()];
+
+        manager.send_alert_multi(&alerts, "rule0", "ERROR", 0).await.unwrap();
+        // Two more deliveries would exceed the cap of 3
+        manager.send_alert_multi(&alerts, "rule1", "ERROR", 0).await.unwrap();
+
+        assert_eq!(*rules.lock().await, vec!["rule0", crate::alerts::ALERT_STORM_RULE]);
+        assert_eq!(call_count.load(Ordering::SeqCst), 2); // rule0 + storm notice
+    }
+
+    #[tokio::test]
+    async fn test_no_alert_limit_by_default() {
+        let mut manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+        let alerts = vec!["test-alert".to_string()];
+
+        for i in 0..50 {
+            manager
+                .send_alert_multi(&alerts, &format!("rule{}", i), "ERROR", 0)
+                .await
+                .unwrap();
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 50);
+    }
+
+    type Messages = Arc<tokio::sync::Mutex<Vec<(String, String)>>>;
+
+    // Mock alert handler that records every rule and message it is sent
+    struct MessageAlertHandler {
+        name: String,
+        messages: Messages,
+    }
+
+    #[async_trait]
+    impl AlertHandler for MessageAlertHandler {
+        async fn send(&self, _identity: &str, rule_name: &str, message: &str) -> Result<()> {
+            self.messages
+                .lock()
+                .await
+                .push((rule_name.to_string(), message.to_string()));
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    fn failure_report_manager() -> (AlertManager, Messages) {
+        let mut manager = AlertManager::new("test-server".to_string());
+        manager.set_failure_alert("oncall".to_string(), Duration::from_secs(60));
+        manager.register(
+            "slack".to_string(),
+            Arc::new(FailingAlertHandler { name: "slack".to_string() }),
+        );
+        let messages = Arc::new(tokio::sync::Mutex::new(Vec::new()));
+        manager.register(
+            "oncall".to_string(),
+            Arc::new(MessageAlertHandler {
+                name: "oncall".to_string(),
+                messages: messages.clone(),
+            }),
+        );
+        (manager, messages)
+    }
+
+    #[tokio::test]
+    async fn test_delivery_failures_summarized() {
+        let (manager, messages) = failure_report_manager();
+        let alerts = vec!["slack".to_string()];
+
+        for _ in 0..5 {
+            manager.send_alert_multi(&alerts, "errors", "ERROR", 0).await.unwrap();
+        }
+        manager.send_alert_multi(&alerts, "disk", "ERROR", 0).await.unwrap();
+        assert!(messages.lock().await.is_empty());
+
+        manager.report_delivery_failures().await;
+
+        let messages = messages.lock().await;
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].0, crate::alerts::DELIVERY_FAILURE_RULE);
+        assert!(messages[0]
+            .1
+            .starts_with("Alert delivery to 'slack' failing: 6 failure(s) in 1m\nRules: disk (1), errors (5)\nLast error:"));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_failure_summary_hides_webhook_url() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = socket.read(&mut buf).await;
+            let response = "HTTP/1.1 500 Internal Server Error\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+
+        let (mut manager, messages) = failure_report_manager();
+        let webhook_url = format!("http://{}/services/T000/B000/webhook-secret", addr);
+        manager.register(
+            "slack".to_string(),
+            Arc::new(crate::alerts::SlackAlert::new(
+                "slack".to_string(),
+                webhook_url,
+                crate::config::SlackFormat::Text,
+                reqwest::Client::new(),
+            )),
+        );
+
+        manager.send_alert_multi(&["slack".to_string()], "errors", "ERROR", 0).await.unwrap();
+        manager.report_delivery_failures().await;
+
+        let messages = messages.lock().await;
+        let summary = &messages[0].1;
+        assert!(summary.contains("500"), "unexpected summary: {}", summary);
+        assert!(summary.contains(&format!("http://{}/...", addr)), "unexpected summary: {}", summary);
+        assert!(!summary.contains("webhook-secret"), "webhook URL leaked: {}", summary);
+    }
+
+    #[tokio::test]
+    async fn test_delivery_failures_reset_after_summary() {
+        let (manager, messages) = failure_report_manager();
+        let alerts = vec!["slack".to_string(), "oncall".to_string()];
+
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 0).await.unwrap();
+        manager.report_delivery_failures().await;
+        // Nothing failed since the last summary
+        manager.report_delivery_failures().await;
+
+        let rules: Vec<String> = messages.lock().await.iter().map(|(rule, _)| rule.clone()).collect();
+        assert_eq!(rules, vec!["errors".to_string(), crate::alerts::DELIVERY_FAILURE_RULE.to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_cooldown_survives_restart() {
+        use crate::state::Clock;
+
+        let mut before = AlertManager::new("test-server".to_string());
+        let (handler, _) = MockAlertHandler::new("test-alert");
+        before.register("test-alert".to_string(), Arc::new(handler));
+        let alerts = vec!["test-alert".to_string()];
+        before.send_alert_multi(&alerts, "errors", "ERROR", 3600).await.unwrap();
+        let saved = before.export_cooldowns(&Clock::now()).await;
+
+        // Simulated restart: a fresh manager restored from the saved state
+        let mut after = AlertManager::new("test-server".to_string());
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        after.register("test-alert".to_string(), Arc::new(handler));
+        assert_eq!(after.restore_cooldowns(saved, &Clock::now()).await, 1);
+
+        after.send_alert_multi(&alerts, "errors", "ERROR", 3600).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        after.send_alert_multi(&alerts, "other", "ERROR", 3600).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_cooldown_state_is_dropped() {
+        use crate::state::{Clock, CooldownState};
+
+        let clock = Clock::now();
+        let mut saved = std::collections::BTreeMap::new();
+        saved.insert(
+            "errors".to_string(),
+            CooldownState {
+                last_alert: clock.utc - chrono::Duration::seconds(120),
+                window_secs: 60,
+            },
+        );
+
+        let mut manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+        assert_eq!(manager.restore_cooldowns(saved, &clock).await, 0);
+
+        let alerts = vec!["test-alert".to_string()];
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 60).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    // Mock alert handler that takes a while to deliver
+    struct SleepingAlertHandler {
+        delay: Duration,
+        call_count: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl AlertHandler for SleepingAlertHandler {
+        async fn send(&self, _identity: &str, _rule_name: &str, _message: &str) -> Result<()> {
+            tokio::time::sleep(self.delay).await;
+            self.call_count.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "sleeping"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_alert_multi_dispatches_concurrently() {
+        let mut manager = AlertManager::new("test-server".to_string());
+        let call_count = Arc::new(AtomicUsize::new(0));
+        for name in ["slack", "pagerduty"] {
+            manager.register(
+                name.to_string(),
+                Arc::new(SleepingAlertHandler {
+                    delay: Duration::from_millis(300),
+                    call_count: call_count.clone(),
+                }),
+            );
+        }
+
+        let alerts = vec!["slack".to_string(), "pagerduty".to_string()];
+        let started = std::time::Instant::now();
+        manager
+            .send_alert_multi(&alerts, "test-rule", "test message", 0)
+            .await
+            .unwrap();
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+        // Bounded by the slowest handler, not the sum of both
+        assert!(started.elapsed() < Duration::from_millis(550), "took {:?}", started.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_handlers_send_through_shared_client() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 8192];
+                let n = socket.read(&mut buf).await.unwrap();
+                tx.send(String::from_utf8_lossy(&buf[..n]).to_lowercase()).unwrap();
+                let _ = socket
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n")
+                    .await;
+            }
+        });
+
+        let mut manager = AlertManager::new("test-server".to_string());
+        let client = manager.http_client().clone();
+        let url = format!("http://{}/hook", addr);
+        manager.register(
+            "hook".to_string(),
+            Arc::new(crate::alerts::WebhookAlert::new("hook".to_string(), url.clone(), None, client.clone())),
+        );
+        manager.register(
+            "slack".to_string(),
+            Arc::new(crate::alerts::SlackAlert::new("slack".to_string(), url, Default::default(), client)),
+        );
+
+        let alerts = vec!["hook".to_string(), "slack".to_string()];
+        manager
+            .send_alert_multi(&alerts, "test-rule", "test message", 0)
+            .await
+            .unwrap();
+
+        // Both requests carry the user agent only the manager's client sets
+        let user_agent = format!("user-agent: tinywatcher/{}", env!("CARGO_PKG_VERSION"));
+        for _ in 0..2 {
+            let request = rx.recv().await.unwrap();
+            assert!(request.contains(&user_agent), "{}", request);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mute_window_suppresses_alerts() {
+        let mut manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+        let alerts = vec!["test-alert".to_string()];
+
+        *manager.mute.lock().await = Some(std::time::Instant::now() + Duration::from_secs(60));
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 0).await.unwrap();
+        manager.send_alert("test-alert", "errors", "ERROR", 0).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        // An elapsed mute is cleared and alerts flow again
+        *manager.mute.lock().await = Some(std::time::Instant::now());
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 0).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert!(manager.mute.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_mute_marker_is_followed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+        manager.set_mute_dir(dir.path().to_path_buf());
+        let alerts = vec!["test-alert".to_string()];
+
+        crate::state::save_mute(dir.path(), chrono::Utc::now() + chrono::Duration::minutes(30)).unwrap();
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 0).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        // `tinywatcher unmute` takes effect on the next alert
+        crate::state::clear_mute(dir.path()).unwrap();
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 0).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        // A marker left behind after its window doesn't mute
+        crate::state::save_mute(dir.path(), chrono::Utc::now() - chrono::Duration::minutes(1)).unwrap();
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 0).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mute_marker_is_read_again_only_after_it_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+        manager.set_mute_dir(dir.path().to_path_buf());
+        let alerts = vec!["test-alert".to_string()];
+
+        crate::state::save_mute(dir.path(), chrono::Utc::now() + chrono::Duration::minutes(30)).unwrap();
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 0).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        // The unchanged marker isn't read again, so the in-memory mute decides
+        *manager.mute.lock().await = None;
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 0).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        // Writing it again is picked up
+        crate::state::save_mute(dir.path(), chrono::Utc::now() + chrono::Duration::minutes(30)).unwrap();
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 0).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_muted_alerts_do_not_start_cooldown() {
+        let mut manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count) = MockAlertHandler::new("test-alert");
+        manager.register("test-alert".to_string(), Arc::new(handler));
+        let alerts = vec!["test-alert".to_string()];
+
+        *manager.mute.lock().await = Some(std::time::Instant::now() + Duration::from_secs(60));
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 3600).await.unwrap();
+
+        *manager.mute.lock().await = None;
+        manager.send_alert_multi(&alerts, "errors", "ERROR", 3600).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
 }
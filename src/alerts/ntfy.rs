@@ -10,12 +10,12 @@ pub struct NtfyAlert {
 }
 
 impl NtfyAlert {
-    pub fn new(name: String, topic: String, server: Option<String>) -> Self {
+    pub fn new(name: String, topic: String, server: Option<String>, client: reqwest::Client) -> Self {
         Self {
             name,
             topic,
             server: server.unwrap_or_else(|| "https://ntfy.sh".to_string()),
-            client: reqwest::Client::new(),
+            client,
         }
     }
 }
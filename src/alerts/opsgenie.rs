@@ -0,0 +1,161 @@
+use super::{AlertContext, AlertHandler};
+use crate::config::{OpsgeniePriority, Severity};
+use async_trait::async_trait;
+use anyhow::Result;
+use reqwest::header::AUTHORIZATION;
+use serde_json::json;
+
+const OPSGENIE_API_URL: &str = "https://api.opsgenie.com/v2/alerts";
+
+/// Opsgenie caps the alert `message` at 130 characters
+const MAX_MESSAGE_CHARS: usize = 130;
+
+pub struct OpsgenieAlert {
+    name: String,
+    api_key: String,
+    priority: Option<OpsgeniePriority>,
+    client: reqwest::Client,
+}
+
+/// Map a rule's severity onto Opsgenie's P1 (highest) to P5 scale
+fn severity_priority(severity: Severity) -> OpsgeniePriority {
+    match severity {
+        Severity::Info => OpsgeniePriority::P4,
+        Severity::Warning => OpsgeniePriority::P3,
+        Severity::Error => OpsgeniePriority::P2,
+        Severity::Critical => OpsgeniePriority::P1,
+    }
+}
+
+impl OpsgenieAlert {
+    pub fn new(name: String, api_key: String, priority: Option<OpsgeniePriority>, client: reqwest::Client) -> Self {
+        Self {
+            name,
+            api_key,
+            priority,
+            client,
+        }
+    }
+
+    /// Build the alert body; a configured `priority` overrides the severity mapping
+    fn build_payload(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> serde_json::Value {
+        let priority = self
+            .priority
+            .unwrap_or_else(|| severity_priority(context.severity));
+        let title: String = format!("TinyWatcher: {} on {}", rule_name, identity)
+            .chars()
+            .take(MAX_MESSAGE_CHARS)
+            .collect();
+
+        let mut payload = json!({
+            "message": title,
+            "description": message,
+            "source": identity,
+            "alias": format!("{}:{}", identity, rule_name),
+            "tags": ["tinywatcher", rule_name],
+            "priority": priority,
+            "details": {
+                "rule": rule_name,
+                "alert_name": self.name,
+            },
+        });
+
+        for (key, value) in &context.fields {
+            payload["details"][key] = json!(value);
+        }
+
+        payload
+    }
+
+    fn build_request(&self, payload: &serde_json::Value) -> Result<reqwest::Request> {
+        Ok(self
+            .client
+            .post(OPSGENIE_API_URL)
+            .header(AUTHORIZATION, format!("GenieKey {}", self.api_key))
+            .json(payload)
+            .build()?)
+    }
+}
+
+#[async_trait]
+impl AlertHandler for OpsgenieAlert {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        self.send_with_context(identity, rule_name, message, &AlertContext::default())
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        let payload = self.build_payload(identity, rule_name, message, context);
+        let request = self.build_request(&payload)?;
+
+        self.client.execute(request).await?.error_for_status()?;
+
+        tracing::info!("Sent Opsgenie alert '{}' for rule: {} (from {})", self.name, rule_name, identity);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(priority: Option<OpsgeniePriority>) -> OpsgenieAlert {
+        OpsgenieAlert::new("opsgenie".to_string(), "key-123".to_string(), priority, reqwest::Client::new())
+    }
+
+    #[test]
+    fn test_request_auth_header_and_body() {
+        let alert = alert(Some(OpsgeniePriority::P2));
+        let payload = alert.build_payload("server-1", "errors", "ERROR boom", &AlertContext::default());
+        let request = alert.build_request(&payload).unwrap();
+
+        assert_eq!(request.url().as_str(), OPSGENIE_API_URL);
+        assert_eq!(request.headers()[AUTHORIZATION], "GenieKey key-123");
+
+        let body: serde_json::Value =
+            serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(body["message"], "TinyWatcher: errors on server-1");
+        assert_eq!(body["description"], "ERROR boom");
+        assert_eq!(body["source"], "server-1");
+        assert_eq!(body["alias"], "server-1:errors");
+        assert_eq!(body["priority"], "P2");
+        assert_eq!(body["details"]["rule"], "errors");
+    }
+
+    #[test]
+    fn test_priority_follows_severity() {
+        let alert = alert(None);
+        let context = AlertContext {
+            severity: Severity::Critical,
+            ..Default::default()
+        };
+        let payload = alert.build_payload("server-1", "errors", "ERROR boom", &context);
+        assert_eq!(payload["priority"], "P1");
+
+        let payload = alert.build_payload("server-1", "errors", "ERROR boom", &AlertContext::default());
+        assert_eq!(payload["priority"], "P2");
+    }
+
+    #[test]
+    fn test_message_is_truncated() {
+        let rule = "r".repeat(200);
+        let payload = alert(None).build_payload("server-1", &rule, "ERROR", &AlertContext::default());
+        assert_eq!(payload["message"].as_str().unwrap().chars().count(), MAX_MESSAGE_CHARS);
+    }
+}
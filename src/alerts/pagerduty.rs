@@ -1,4 +1,4 @@
-use super::AlertHandler;
+use super::{AlertContext, AlertHandler};
 use async_trait::async_trait;
 use anyhow::Result;
 use serde_json::json;
@@ -10,11 +10,11 @@ pub struct PagerDutyAlert {
 }
 
 impl PagerDutyAlert {
-    pub fn new(name: String, routing_key: String) -> Self {
+    pub fn new(name: String, routing_key: String, client: reqwest::Client) -> Self {
         Self {
             name,
             routing_key,
-            client: reqwest::Client::new(),
+            client,
         }
     }
 }
@@ -22,14 +22,25 @@ impl PagerDutyAlert {
 #[async_trait]
 impl AlertHandler for PagerDutyAlert {
     async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        self.send_with_context(identity, rule_name, message, &AlertContext::default())
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
         let url = "https://events.pagerduty.com/v2/enqueue";
         
-        let payload = json!({
+        let mut payload = json!({
             "routing_key": self.routing_key,
             "event_action": "trigger",
             "payload": {
                 "summary": format!("TinyWatcher Alert: {} on {}", rule_name, identity),
-                "severity": "error",
+                "severity": context.severity.as_str(),
                 "source": identity,
                 "component": "TinyWatcher",
                 "group": rule_name,
@@ -41,6 +52,14 @@ impl AlertHandler for PagerDutyAlert {
             }
         });
 
+        if !context.fields.is_empty() {
+            payload["payload"]["custom_details"]["fields"] = json!(context.fields);
+        }
+
+        if let Some(source) = &context.source {
+            payload["payload"]["custom_details"]["source"] = json!(source);
+        }
+
         self.client
             .post(url)
             .json(&payload)
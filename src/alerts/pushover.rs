@@ -0,0 +1,220 @@
+use super::{AlertContext, AlertHandler};
+use crate::config::Severity;
+use async_trait::async_trait;
+use anyhow::Result;
+
+const PUSHOVER_API_URL: &str = "https://api.pushover.net/1/messages.json";
+
+/// Pushover rejects messages longer than 1024 characters
+const MAX_MESSAGE_CHARS: usize = 1024;
+
+/// Pushover rejects titles longer than 250 characters
+const MAX_TITLE_CHARS: usize = 250;
+
+/// Pushover's emergency priority, which repeats until acknowledged
+const EMERGENCY_PRIORITY: i8 = 2;
+
+/// How often (seconds) Pushover re-sends an unacknowledged emergency alert (minimum 30)
+const EMERGENCY_RETRY_SECS: u32 = 60;
+
+/// How long (seconds) Pushover keeps re-sending an emergency alert (maximum 10800)
+const EMERGENCY_EXPIRE_SECS: u32 = 3600;
+
+pub struct PushoverAlert {
+    name: String,
+    token: String,
+    user: String,
+    priority: Option<i8>,
+    client: reqwest::Client,
+}
+
+/// Map a rule's severity onto Pushover's -2..=2 priority scale
+fn severity_priority(severity: Severity) -> i8 {
+    match severity {
+        Severity::Info => -1,
+        Severity::Warning => 0,
+        Severity::Error => 1,
+        Severity::Critical => EMERGENCY_PRIORITY,
+    }
+}
+
+/// The `errors` Pushover returns with a failed request, e.g. "user identifier is invalid"
+fn api_errors(body: &str) -> Option<String> {
+    let body: serde_json::Value = serde_json::from_str(body).ok()?;
+    let errors: Vec<&str> = body["errors"].as_array()?.iter().filter_map(|e| e.as_str()).collect();
+    (!errors.is_empty()).then(|| errors.join("; "))
+}
+
+impl PushoverAlert {
+    pub fn new(name: String, token: String, user: String, priority: Option<i8>, client: reqwest::Client) -> Self {
+        Self {
+            name,
+            token,
+            user,
+            priority,
+            client,
+        }
+    }
+
+    /// Build the form body; a configured `priority` overrides the severity mapping
+    fn build_form(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Vec<(&'static str, String)> {
+        let priority = self
+            .priority
+            .unwrap_or_else(|| severity_priority(context.severity));
+
+        let mut form = vec![
+            ("token", self.token.clone()),
+            ("user", self.user.clone()),
+            ("title", format!("TinyWatcher: {}", rule_name).chars().take(MAX_TITLE_CHARS).collect()),
+            (
+                "message",
+                format!("Host: {}\n\n{}", identity, context.render_text(message))
+                    .chars()
+                    .take(MAX_MESSAGE_CHARS)
+                    .collect(),
+            ),
+            ("priority", priority.to_string()),
+        ];
+
+        // Emergency priority is rejected by Pushover without retry/expire
+        if priority >= EMERGENCY_PRIORITY {
+            form.push(("retry", EMERGENCY_RETRY_SECS.to_string()));
+            form.push(("expire", EMERGENCY_EXPIRE_SECS.to_string()));
+        }
+
+        form
+    }
+}
+
+#[async_trait]
+impl AlertHandler for PushoverAlert {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        self.send_with_context(identity, rule_name, message, &AlertContext::default())
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        let form = self.build_form(identity, rule_name, message, context);
+
+        let response = self.client
+            .post(PUSHOVER_API_URL)
+            .form(&form)
+            .send()
+            .await?;
+
+        if let Err(e) = response.error_for_status_ref() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(match api_errors(&body) {
+                // Keep the status error underneath so retries can still tell a rejected request apart
+                Some(errors) => anyhow::Error::new(e).context(format!("Pushover rejected the alert: {}", errors)),
+                None => e.into(),
+            });
+        }
+
+        tracing::info!("Sent Pushover alert '{}' for rule: {} (from {})", self.name, rule_name, identity);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert(priority: Option<i8>) -> PushoverAlert {
+        PushoverAlert::new(
+            "pushover".to_string(),
+            "app-token".to_string(),
+            "user-key".to_string(),
+            priority,
+            reqwest::Client::new(),
+        )
+    }
+
+    fn context(severity: Severity) -> AlertContext {
+        AlertContext {
+            severity,
+            ..Default::default()
+        }
+    }
+
+    fn value<'a>(form: &'a [(&'static str, String)], key: &str) -> Option<&'a str> {
+        form.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn test_form_body() {
+        let form = alert(None).build_form("server-1", "errors", "ERROR boom", &context(Severity::Error));
+
+        assert_eq!(value(&form, "token"), Some("app-token"));
+        assert_eq!(value(&form, "user"), Some("user-key"));
+        assert_eq!(value(&form, "title"), Some("TinyWatcher: errors"));
+        assert_eq!(value(&form, "message"), Some("Host: server-1\n\nERROR boom"));
+        assert_eq!(value(&form, "priority"), Some("1"));
+        assert_eq!(value(&form, "retry"), None);
+        assert_eq!(value(&form, "expire"), None);
+    }
+
+    #[test]
+    fn test_severity_to_priority() {
+        assert_eq!(severity_priority(Severity::Info), -1);
+        assert_eq!(severity_priority(Severity::Warning), 0);
+        assert_eq!(severity_priority(Severity::Error), 1);
+        assert_eq!(severity_priority(Severity::Critical), 2);
+    }
+
+    #[test]
+    fn test_critical_adds_retry_and_expire() {
+        let form = alert(None).build_form("server-1", "errors", "ERROR", &context(Severity::Critical));
+
+        assert_eq!(value(&form, "priority"), Some("2"));
+        assert_eq!(value(&form, "retry"), Some("60"));
+        assert_eq!(value(&form, "expire"), Some("3600"));
+    }
+
+    #[test]
+    fn test_configured_priority_overrides_severity() {
+        let form = alert(Some(-2)).build_form("server-1", "errors", "ERROR", &context(Severity::Critical));
+
+        assert_eq!(value(&form, "priority"), Some("-2"));
+        assert_eq!(value(&form, "retry"), None);
+    }
+
+    #[test]
+    fn test_long_message_and_title_are_truncated() {
+        let rule = "r".repeat(300);
+        let line = "é".repeat(2000);
+        let form = alert(None).build_form("server-1", &rule, &line, &context(Severity::Error));
+
+        assert_eq!(value(&form, "title").unwrap().chars().count(), MAX_TITLE_CHARS);
+        let message = value(&form, "message").unwrap();
+        assert_eq!(message.chars().count(), MAX_MESSAGE_CHARS);
+        assert!(message.starts_with("Host: server-1\n\néé"));
+    }
+
+    #[test]
+    fn test_api_errors() {
+        let body = r#"{"user":"invalid","errors":["user identifier is invalid","application token is invalid"],"status":0,"request":"5042853c"}"#;
+        assert_eq!(
+            api_errors(body).as_deref(),
+            Some("user identifier is invalid; application token is invalid")
+        );
+        assert_eq!(api_errors(r#"{"status":0,"errors":[]}"#), None);
+        assert_eq!(api_errors("<html>Bad Gateway</html>"), None);
+    }
+}
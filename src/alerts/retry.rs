@@ -0,0 +1,217 @@
+use super::{AlertContext, AlertHandler};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Upper bound on the delay between two attempts, however many retries are configured
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Retries failed deliveries of the wrapped handler with exponential backoff and jitter
+pub struct RetryingHandler {
+    inner: Arc<dyn AlertHandler>,
+    retries: u32,
+    backoff: Duration,
+}
+
+impl RetryingHandler {
+    pub fn new(inner: Arc<dyn AlertHandler>, retries: u32, backoff: Duration) -> Self {
+        Self {
+            inner,
+            retries,
+            backoff,
+        }
+    }
+
+    async fn with_retries<F, Fut>(&self, rule_name: &str, mut attempt: F) -> Result<()>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let mut delay = self.backoff;
+        let mut retries_left = self.retries;
+        loop {
+            match attempt().await {
+                Ok(()) => return Ok(()),
+                Err(e) if retries_left > 0 && is_retryable(&e) => {
+                    let wait = jitter(delay);
+                    tracing::warn!(
+                        "Alert '{}' failed for rule {}: {}; retrying in {:?} ({} left)",
+                        self.inner.name(),
+                        rule_name,
+                        e,
+                        wait,
+                        retries_left
+                    );
+                    tokio::time::sleep(wait).await;
+                    delay = (delay * 2).min(MAX_BACKOFF);
+                    retries_left -= 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AlertHandler for RetryingHandler {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        self.with_retries(rule_name, || self.inner.send(identity, rule_name, message))
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        self.with_retries(rule_name, || {
+            self.inner.send_with_context(identity, rule_name, message, context)
+        })
+        .await
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+}
+
+/// An HTTP error response whose body was read into the message, e.g. an API's explanation of a rejected request
+/// Carries the status so `is_retryable` can tell client errors from server errors
+#[derive(Debug)]
+pub struct StatusError {
+    pub status: reqwest::StatusCode,
+    pub message: String,
+}
+
+impl std::fmt::Display for StatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+/// Whether another attempt could succeed; client errors (4xx other than 408 and 429) won't
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    let status = error.chain().find_map(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .and_then(|e| e.status())
+            .or_else(|| cause.downcast_ref::<StatusError>().map(|e| e.status))
+    });
+    match status {
+        Some(status) if status.is_client_error() => {
+            status == reqwest::StatusCode::REQUEST_TIMEOUT || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        }
+        _ => true,
+    }
+}
+
+/// Randomize a delay to between half and all of it, so retries from many agents don't line up
+fn jitter(delay: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    let fraction = 0.5 + (random % 1000) as f64 / 2000.0;
+    delay.mul_f64(fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Fails with `error` for the first `failures` calls, then succeeds
+    struct FlakyHandler {
+        failures: usize,
+        calls: Arc<AtomicUsize>,
+        error: fn() -> anyhow::Error,
+    }
+
+    #[async_trait]
+    impl AlertHandler for FlakyHandler {
+        async fn send(&self, _identity: &str, _rule_name: &str, _message: &str) -> Result<()> {
+            if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err((self.error)());
+            }
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "flaky"
+        }
+    }
+
+    fn flaky(failures: usize, error: fn() -> anyhow::Error) -> (Arc<dyn AlertHandler>, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler = FlakyHandler {
+            failures,
+            calls: calls.clone(),
+            error,
+        };
+        (Arc::new(handler), calls)
+    }
+
+    fn connection_refused() -> anyhow::Error {
+        anyhow::anyhow!("connection refused")
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() {
+        let (inner, calls) = flaky(2, connection_refused);
+        let handler = RetryingHandler::new(inner, 3, Duration::from_millis(10));
+
+        handler.send("host", "errors", "ERROR boom").await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_retries() {
+        let (inner, calls) = flaky(5, connection_refused);
+        let handler = RetryingHandler::new(inner, 2, Duration::from_millis(10));
+
+        let err = handler.send("host", "errors", "ERROR boom").await.unwrap_err();
+        assert_eq!(err.to_string(), "connection refused");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    async fn status_error(status: u16) -> anyhow::Error {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let response = format!("HTTP/1.1 {} Status\r\ncontent-length: 0\r\nconnection: close\r\n\r\n", status);
+            let _ = socket.write_all(response.as_bytes()).await;
+        });
+        reqwest::get(format!("http://{}/", addr))
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap_err()
+            .into()
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_are_not_retried() {
+        assert!(!is_retryable(&status_error(400).await));
+        assert!(!is_retryable(&status_error(404).await.context("Failed to send")));
+        assert!(is_retryable(&status_error(429).await));
+        assert!(is_retryable(&status_error(503).await));
+        assert!(is_retryable(&connection_refused()));
+    }
+
+    #[test]
+    fn test_jitter_stays_within_delay() {
+        for _ in 0..100 {
+            let wait = jitter(Duration::from_secs(2));
+            assert!(wait >= Duration::from_secs(1) && wait <= Duration::from_secs(2), "{:?}", wait);
+        }
+    }
+}
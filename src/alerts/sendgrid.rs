@@ -1,26 +1,34 @@
+use super::retry::StatusError;
 use super::AlertHandler;
 use async_trait::async_trait;
 use anyhow::{Result, Context};
 use serde_json::json;
 
+const SEND_URL: &str = "https://api.sendgrid.com/v3/mail/send";
+
 pub struct SendGridAlert {
     name: String,
+    /// Mail send endpoint; only tests point it elsewhere
+    url: String,
     api_key: String,
     from: String,
     to: Vec<String>,
+    client: reqwest::Client,
 }
 
 impl SendGridAlert {
-    pub fn new(name: String, api_key: String, from: String, to: Vec<String>) -> Self {
+    pub fn new(name: String, api_key: String, from: String, to: Vec<String>, client: reqwest::Client) -> Self {
         tracing::info!(
             "Created SendGrid alert '{}' - from: {}, to: {:?}",
             name, from, to
         );
         Self {
             name,
+            url: SEND_URL.to_string(),
             api_key,
             from,
             to,
+            client,
         }
     }
 }
@@ -44,7 +52,7 @@ impl AlertHandler for SendGridAlert {
              {}\n",
             identity,
             rule_name,
-            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            crate::timestamp::now(),
             message
         );
 
@@ -68,9 +76,8 @@ impl AlertHandler for SendGridAlert {
 
         tracing::debug!("Sending SendGrid API request");
         
-        let client = reqwest::Client::new();
-        let response = client
-            .post("https://api.sendgrid.com/v3/mail/send")
+        let response = self.client
+            .post(&self.url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
             .json(&payload)
@@ -91,10 +98,11 @@ impl AlertHandler for SendGridAlert {
                 "❌ SendGrid API request failed with status {}: {}",
                 status, error_body
             );
-            Err(anyhow::anyhow!(
-                "SendGrid API request failed with status {}: {}",
-                status, error_body
-            ))
+            Err(StatusError {
+                status,
+                message: format!("SendGrid API request failed with status {}: {}", status, error_body),
+            }
+            .into())
         }
     }
 
@@ -102,3 +110,51 @@ impl AlertHandler for SendGridAlert {
         &self.name
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::RetryingHandler;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_rejected_request_is_not_retried() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let body = r#"{"errors":[{"message":"The provided authorization grant is invalid"}]}"#;
+                let response = format!(
+                    "HTTP/1.1 401 Unauthorized\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        let mut alert = SendGridAlert::new(
+            "sendgrid".to_string(),
+            "SG.invalid".to_string(),
+            "alerts@example.com".to_string(),
+            vec!["ops@example.com".to_string()],
+            reqwest::Client::new(),
+        );
+        alert.url = format!("http://{}/v3/mail/send", addr);
+        let handler = RetryingHandler::new(Arc::new(alert), 3, Duration::from_millis(10));
+
+        let err = handler.send("server-1", "errors", "ERROR boom").await.unwrap_err();
+        assert!(err.to_string().contains("401"), "{}", err);
+        assert!(err.to_string().contains("authorization grant is invalid"), "{}", err);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,39 +1,146 @@
-use super::AlertHandler;
+use super::{AlertContext, AlertHandler};
+use crate::config::{Severity, SlackFormat};
 use async_trait::async_trait;
 use anyhow::Result;
 use serde_json::json;
 
+/// Slack rejects section text longer than 3000 characters
+const MAX_SECTION_TEXT: usize = 2900;
+
+/// Slack allows at most 10 fields per section block
+const MAX_SECTION_FIELDS: usize = 10;
+
 pub struct SlackAlert {
     name: String,
     webhook_url: String,
+    format: SlackFormat,
     client: reqwest::Client,
 }
 
+/// Attachment bar color for each severity
+fn severity_color(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Info => "#439FE0",
+        Severity::Warning => "#DAA038",
+        Severity::Error => "#D00000",
+        Severity::Critical => "#7A0019",
+    }
+}
+
+fn mrkdwn_field(label: &str, value: &str) -> serde_json::Value {
+    json!({ "type": "mrkdwn", "text": format!("*{}:*\n{}", label, value) })
+}
+
 impl SlackAlert {
-    pub fn new(name: String, webhook_url: String) -> Self {
+    pub fn new(name: String, webhook_url: String, format: SlackFormat, client: reqwest::Client) -> Self {
         Self {
             name,
             webhook_url,
-            client: reqwest::Client::new(),
+            format,
+            client,
+        }
+    }
+
+    fn build_payload(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> serde_json::Value {
+        match self.format {
+            SlackFormat::Text => json!({
+                "text": format!(
+                    "*Alert: {}*\n*Host:* `{}`\n```{}```",
+                    rule_name,
+                    identity,
+                    context.render_text(message)
+                ),
+                "username": "TinyWatcher",
+                "icon_emoji": ":eyes:"
+            }),
+            SlackFormat::Blocks => self.build_blocks_payload(identity, rule_name, message, context),
         }
     }
+
+    fn build_blocks_payload(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> serde_json::Value {
+        let mut summary = vec![
+            mrkdwn_field("Host", &format!("`{}`", identity)),
+            mrkdwn_field("Severity", context.severity.as_str()),
+        ];
+        if let Some(source) = &context.source {
+            summary.push(mrkdwn_field(
+                "Source",
+                &format!("{} `{}`", source.kind.as_str(), source.name),
+            ));
+        }
+
+        let mut blocks = vec![
+            json!({
+                "type": "header",
+                "text": { "type": "plain_text", "text": format!("Alert: {}", rule_name), "emoji": false }
+            }),
+            json!({ "type": "section", "fields": summary }),
+        ];
+
+        if !context.fields.is_empty() {
+            let fields: Vec<_> = context
+                .fields
+                .iter()
+                .take(MAX_SECTION_FIELDS)
+                .map(|(key, value)| mrkdwn_field(key, value))
+                .collect();
+            blocks.push(json!({ "type": "section", "fields": fields }));
+        }
+
+        let body: String = message.chars().take(MAX_SECTION_TEXT).collect();
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("```{}```", body) }
+        }));
+
+        json!({
+            // Fallback for notifications and clients that can't render blocks
+            "text": format!("Alert: {} on {}", rule_name, identity),
+            "username": "TinyWatcher",
+            "icon_emoji": ":eyes:",
+            "attachments": [{
+                "color": severity_color(context.severity),
+                "blocks": blocks
+            }]
+        })
+    }
 }
 
 #[async_trait]
 impl AlertHandler for SlackAlert {
     async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
-        let payload = json!({
-            "text": format!("*Alert: {}*\n*Host:* `{}`\n```{}```", rule_name, identity, message),
-            "username": "TinyWatcher",
-            "icon_emoji": ":eyes:"
-        });
+        self.send_with_context(identity, rule_name, message, &AlertContext::default())
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        let payload = self.build_payload(identity, rule_name, message, context);
 
         self.client
             .post(&self.webhook_url)
             .json(&payload)
             .send()
-            .await?;
-        
+            .await?
+            .error_for_status()?;
+
         tracing::info!("Sent Slack alert '{}' for rule: {} (from {})", self.name, rule_name, identity);
         Ok(())
     }
@@ -42,3 +149,72 @@ impl AlertHandler for SlackAlert {
         &self.name
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::{AlertSource, AlertSourceKind};
+
+    fn slack(format: SlackFormat) -> SlackAlert {
+        SlackAlert::new("slack".to_string(), "http://localhost".to_string(), format, reqwest::Client::new())
+    }
+
+    #[test]
+    fn test_blocks_payload() {
+        let context = AlertContext {
+            source: Some(AlertSource {
+                kind: AlertSourceKind::File,
+                name: "/var/log/app.log".to_string(),
+                url: None,
+            }),
+            severity: Severity::Critical,
+            ..Default::default()
+        };
+
+        let payload = slack(SlackFormat::Blocks).build_payload("server-1", "errors", "ERROR boom", &context);
+
+        assert_eq!(payload["text"], "Alert: errors on server-1");
+        let attachment = &payload["attachments"][0];
+        assert_eq!(attachment["color"], "#7A0019");
+
+        let blocks = attachment["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0]["type"], "header");
+        assert_eq!(blocks[0]["text"]["text"], "Alert: errors");
+        assert_eq!(
+            blocks[1]["fields"],
+            json!([
+                {"type": "mrkdwn", "text": "*Host:*\n`server-1`"},
+                {"type": "mrkdwn", "text": "*Severity:*\ncritical"},
+                {"type": "mrkdwn", "text": "*Source:*\nfile `/var/log/app.log`"}
+            ])
+        );
+        assert_eq!(blocks[2]["text"]["text"], "```ERROR boom```");
+    }
+
+    #[test]
+    fn test_blocks_payload_with_fields() {
+        let mut context = AlertContext::default();
+        context.fields.insert("status".to_string(), "500".to_string());
+
+        let payload = slack(SlackFormat::Blocks).build_payload("server-1", "errors", "ERROR boom", &context);
+        let blocks = payload["attachments"][0]["blocks"].as_array().unwrap();
+
+        assert_eq!(blocks.len(), 4);
+        assert_eq!(blocks[2]["fields"], json!([{"type": "mrkdwn", "text": "*status:*\n500"}]));
+        assert_eq!(payload["attachments"][0]["color"], "#D00000");
+    }
+
+    #[test]
+    fn test_text_payload() {
+        let payload = slack(SlackFormat::Text).build_payload(
+            "server-1",
+            "errors",
+            "ERROR boom",
+            &AlertContext::default(),
+        );
+
+        assert_eq!(payload["text"], "*Alert: errors*\n*Host:* `server-1`\n```ERROR boom```");
+        assert!(payload.get("attachments").is_none());
+    }
+}
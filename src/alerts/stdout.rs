@@ -1,7 +1,6 @@
 use super::AlertHandler;
 use async_trait::async_trait;
 use anyhow::Result;
-use chrono::Utc;
 
 pub struct StdoutAlert {
     #[allow(dead_code)]
@@ -17,7 +16,7 @@ impl StdoutAlert {
 #[async_trait]
 impl AlertHandler for StdoutAlert {
     async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
-        let timestamp = Utc::now().format("%Y-%m-%d %H:%M:%S");
+        let timestamp = crate::timestamp::now();
         println!("[{}] ALERT [{}] [{}]: {}", timestamp, identity, rule_name, message);
         Ok(())
     }
@@ -11,12 +11,12 @@ pub struct TelegramAlert {
 }
 
 impl TelegramAlert {
-    pub fn new(name: String, bot_token: String, chat_id: String) -> Self {
+    pub fn new(name: String, bot_token: String, chat_id: String, client: reqwest::Client) -> Self {
         Self {
             name,
             bot_token,
             chat_id,
-            client: reqwest::Client::new(),
+            client,
         }
     }
 }
@@ -1,41 +1,218 @@
-use super::AlertHandler;
+use super::{AlertContext, AlertHandler};
 use async_trait::async_trait;
 use anyhow::Result;
-use chrono::Utc;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_ENCODING, CONTENT_TYPE};
+use reqwest::Method;
 use serde_json::json;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::io::Write;
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// Header carrying the body signature when no `signature_header` is configured
+pub const DEFAULT_SIGNATURE_HEADER: &str = "X-TinyWatcher-Signature";
 
 pub struct WebhookAlert {
     name: String,
     webhook_url: String,
+    /// Gzip bodies larger than this many bytes
+    compress_above: Option<usize>,
+    method: Method,
+    headers: HeaderMap,
+    /// Body template with `{{rule}}`-style placeholders, replacing the default JSON payload
+    template: Option<String>,
+    content_type: String,
+    /// Secret and header name for HMAC-SHA256 signing of the body
+    signing: Option<(String, HeaderName)>,
     client: reqwest::Client,
 }
 
+/// Fill a body template's `{{name}}` placeholders, JSON-escaping values for JSON bodies
+fn render_template(template: &str, values: &[(&str, &str)], json_escape: bool) -> String {
+    let mut rendered = template.to_string();
+    for (name, value) in values {
+        let value = if json_escape {
+            let quoted = serde_json::to_string(value).unwrap_or_default();
+            quoted[1..quoted.len() - 1].to_string()
+        } else {
+            value.to_string()
+        };
+        rendered = rendered.replace(&format!("{{{{{}}}}}", name), &value);
+    }
+    rendered
+}
+
+/// Hex-encoded HMAC-SHA256 of `body` keyed with `secret`
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn gzip(body: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body)?;
+    Ok(encoder.finish()?)
+}
+
 impl WebhookAlert {
-    pub fn new(name: String, webhook_url: String) -> Self {
+    pub fn new(name: String, webhook_url: String, compress_above: Option<usize>, client: reqwest::Client) -> Self {
         Self {
             name,
             webhook_url,
-            client: reqwest::Client::new(),
+            compress_above,
+            method: Method::POST,
+            headers: HeaderMap::new(),
+            template: None,
+            content_type: JSON_CONTENT_TYPE.to_string(),
+            signing: None,
+            client,
         }
     }
-}
 
-#[async_trait]
-impl AlertHandler for WebhookAlert {
-    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
-        let payload = json!({
+    /// Sign each body with `secret`, sending the signature in `header` (default `X-TinyWatcher-Signature`)
+    pub fn with_signature(mut self, secret: Option<String>, header: Option<&str>) -> Result<Self> {
+        if let Some(secret) = secret {
+            let header = header.unwrap_or(DEFAULT_SIGNATURE_HEADER);
+            let header = HeaderName::from_bytes(header.as_bytes())
+                .map_err(|_| anyhow::anyhow!("invalid header name '{}'", header))?;
+            self.signing = Some((secret, header));
+        }
+        Ok(self)
+    }
+
+    /// Parse a webhook's `method` option, e.g. "PUT"
+    pub fn parse_method(method: &str) -> Result<Method> {
+        Method::from_bytes(method.to_uppercase().as_bytes())
+            .map_err(|_| anyhow::anyhow!("invalid HTTP method '{}'", method))
+    }
+
+    /// Parse a webhook's extra `headers`
+    pub fn parse_headers(headers: &HashMap<String, String>) -> Result<HeaderMap> {
+        let mut parsed = HeaderMap::new();
+        for (name, value) in headers {
+            let header = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| anyhow::anyhow!("invalid header name '{}'", name))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| anyhow::anyhow!("invalid value for header '{}'", name))?;
+            parsed.insert(header, value);
+        }
+        Ok(parsed)
+    }
+
+    /// Send with a custom method, extra headers and a body template instead of the default JSON payload
+    pub fn with_request(
+        mut self,
+        method: Option<&str>,
+        headers: Option<&HashMap<String, String>>,
+        template: Option<String>,
+        content_type: Option<String>,
+    ) -> Result<Self> {
+        if let Some(method) = method {
+            self.method = Self::parse_method(method)?;
+        }
+        if let Some(headers) = headers {
+            self.headers = Self::parse_headers(headers)?;
+        }
+        self.template = template;
+        if let Some(content_type) = content_type {
+            self.content_type = content_type;
+        }
+        Ok(self)
+    }
+
+    /// The request body: the rendered `template` if set, otherwise the default JSON payload
+    fn build_body(&self, identity: &str, rule_name: &str, message: &str, context: &AlertContext) -> Result<Vec<u8>> {
+        match &self.template {
+            Some(template) => {
+                let timestamp = crate::timestamp::now_rfc3339();
+                let values = [
+                    ("rule", rule_name),
+                    ("message", message),
+                    ("identity", identity),
+                    ("timestamp", timestamp.as_str()),
+                    ("severity", context.severity.as_str()),
+                ];
+                let json_escape = self.content_type.contains("json");
+                Ok(render_template(template, &values, json_escape).into_bytes())
+            }
+            None => Ok(serde_json::to_vec(&self.build_payload(identity, rule_name, message, context))?),
+        }
+    }
+
+    /// Build the request, gzipping the body if it exceeds `compress_above`
+    /// The signature covers the exact bytes sent, i.e. the compressed body when gzipped
+    fn build_request(&self, body: Vec<u8>) -> Result<reqwest::Request> {
+        let mut request = self
+            .client
+            .request(self.method.clone(), &self.webhook_url)
+            .headers(self.headers.clone())
+            .header(CONTENT_TYPE, &self.content_type);
+
+        let body = match self.compress_above {
+            Some(limit) if body.len() > limit => {
+                request = request.header(CONTENT_ENCODING, "gzip");
+                gzip(&body)?
+            }
+            _ => body,
+        };
+
+        if let Some((secret, header)) = &self.signing {
+            request = request.header(header.clone(), sign(secret, &body));
+        }
+
+        Ok(request.body(body).build()?)
+    }
+
+    fn build_payload(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> serde_json::Value {
+        let mut payload = json!({
             "identity": identity,
             "rule": rule_name,
             "message": message,
-            "timestamp": Utc::now().to_rfc3339(),
+            "timestamp": crate::timestamp::now_rfc3339(),
             "alert_name": self.name,
         });
 
-        self.client
-            .post(&self.webhook_url)
-            .json(&payload)
-            .send()
-            .await?;
+        if !context.fields.is_empty() {
+            payload["fields"] = json!(context.fields);
+        }
+
+        if let Some(source) = &context.source {
+            payload["source"] = json!(source);
+        }
+
+        payload
+    }
+}
+
+#[async_trait]
+impl AlertHandler for WebhookAlert {
+    async fn send(&self, identity: &str, rule_name: &str, message: &str) -> Result<()> {
+        self.send_with_context(identity, rule_name, message, &AlertContext::default())
+            .await
+    }
+
+    async fn send_with_context(
+        &self,
+        identity: &str,
+        rule_name: &str,
+        message: &str,
+        context: &AlertContext,
+    ) -> Result<()> {
+        let body = self.build_body(identity, rule_name, message, context)?;
+        let request = self.build_request(body)?;
+
+        self.client.execute(request).await?.error_for_status()?;
         
         tracing::info!("Sent webhook alert '{}' for rule: {} (from {})", self.name, rule_name, identity);
         Ok(())
@@ -45,3 +222,200 @@ impl AlertHandler for WebhookAlert {
         &self.name
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::{AlertSource, AlertSourceKind};
+
+    #[test]
+    fn test_payload_without_fields() {
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), None, reqwest::Client::new());
+        let payload = alert.build_payload("server-1", "errors", "ERROR boom", &AlertContext::default());
+
+        assert_eq!(payload["identity"], "server-1");
+        assert_eq!(payload["rule"], "errors");
+        assert_eq!(payload["message"], "ERROR boom");
+        assert!(payload.get("fields").is_none());
+    }
+
+    #[test]
+    fn test_payload_with_fields() {
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), None, reqwest::Client::new());
+        let mut context = AlertContext::default();
+        context.fields.insert("user".to_string(), "alice".to_string());
+        context.fields.insert("status".to_string(), "500".to_string());
+
+        let payload = alert.build_payload("server-1", "errors", "ERROR boom", &context);
+
+        assert_eq!(payload["fields"], json!({"user": "alice", "status": "500"}));
+    }
+
+    #[test]
+    fn test_payload_includes_source() {
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), None, reqwest::Client::new());
+        let context = AlertContext {
+            source: Some(AlertSource {
+                kind: AlertSourceKind::Http,
+                name: "feed".to_string(),
+                url: Some("https://example.com/feed".to_string()),
+            }),
+            ..Default::default()
+        };
+
+        let payload = alert.build_payload("server-1", "errors", "ERROR boom", &context);
+        assert_eq!(
+            payload["source"],
+            json!({"type": "http", "name": "feed", "url": "https://example.com/feed"})
+        );
+    }
+
+    #[test]
+    fn test_large_body_is_gzipped() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), Some(1024), reqwest::Client::new());
+        let message = "ERROR ".repeat(500);
+        let body = alert.build_body("server-1", "errors", &message, &AlertContext::default()).unwrap();
+
+        let request = alert.build_request(body).unwrap();
+        assert_eq!(request.headers()[CONTENT_ENCODING], "gzip");
+        assert_eq!(request.headers()[CONTENT_TYPE], "application/json");
+
+        let compressed = request.body().unwrap().as_bytes().unwrap();
+        let mut decompressed = String::new();
+        GzDecoder::new(compressed).read_to_string(&mut decompressed).unwrap();
+        let sent: serde_json::Value = serde_json::from_str(&decompressed).unwrap();
+        assert_eq!(sent["message"], message);
+    }
+
+    #[test]
+    fn test_small_body_is_sent_plain() {
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), Some(1024), reqwest::Client::new());
+        let body = alert.build_body("server-1", "errors", "ERROR boom", &AlertContext::default()).unwrap();
+
+        let request = alert.build_request(body).unwrap();
+        assert!(request.headers().get(CONTENT_ENCODING).is_none());
+        let sent: serde_json::Value = serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(sent["message"], "ERROR boom");
+    }
+
+    #[test]
+    fn test_no_compression_by_default() {
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), None, reqwest::Client::new());
+        let body = alert.build_body("server-1", "errors", &"ERROR ".repeat(5000), &AlertContext::default()).unwrap();
+
+        let request = alert.build_request(body).unwrap();
+        assert!(request.headers().get(CONTENT_ENCODING).is_none());
+    }
+
+    #[test]
+    fn test_render_template() {
+        let values = [("rule", "errors"), ("message", "say \"hi\"\nbye")];
+        assert_eq!(
+            render_template("{{rule}}: {{message}} {{unknown}}", &values, false),
+            "errors: say \"hi\"\nbye {{unknown}}"
+        );
+        assert_eq!(
+            render_template(r#"{"text": "{{message}}"}"#, &values, true),
+            r#"{"text": "say \"hi\"\nbye"}"#
+        );
+    }
+
+    #[test]
+    fn test_templated_request() {
+        let mut headers = HashMap::new();
+        headers.insert("X-Api-Key".to_string(), "secret".to_string());
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost/events".to_string(), None, reqwest::Client::new())
+            .with_request(
+                Some("put"),
+                Some(&headers),
+                Some(r#"{"title": "{{rule}} on {{identity}}", "body": "{{message}}", "level": "{{severity}}"}"#.to_string()),
+                None,
+            )
+            .unwrap();
+
+        let body = alert.build_body("server-1", "errors", "ERROR \"boom\"", &AlertContext::default()).unwrap();
+        let request = alert.build_request(body).unwrap();
+
+        assert_eq!(request.method(), Method::PUT);
+        assert_eq!(request.headers()["x-api-key"], "secret");
+        assert_eq!(request.headers()[CONTENT_TYPE], "application/json");
+        let sent: serde_json::Value = serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+        assert_eq!(sent, json!({"title": "errors on server-1", "body": "ERROR \"boom\"", "level": "error"}));
+    }
+
+    #[test]
+    fn test_plain_text_template() {
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), None, reqwest::Client::new())
+            .with_request(None, None, Some("{{rule}}: {{message}}".to_string()), Some("text/plain".to_string()))
+            .unwrap();
+
+        let body = alert.build_body("server-1", "errors", "ERROR \"boom\"", &AlertContext::default()).unwrap();
+        let request = alert.build_request(body).unwrap();
+
+        assert_eq!(request.method(), Method::POST);
+        assert_eq!(request.headers()[CONTENT_TYPE], "text/plain");
+        assert_eq!(request.body().unwrap().as_bytes().unwrap(), b"errors: ERROR \"boom\"");
+    }
+
+    #[test]
+    fn test_invalid_method_and_headers() {
+        let alert = || WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), None, reqwest::Client::new());
+        assert!(alert().with_request(Some("GET POST"), None, None, None).is_err());
+
+        let mut headers = HashMap::new();
+        headers.insert("bad header".to_string(), "x".to_string());
+        assert!(alert().with_request(None, Some(&headers), None, None).is_err());
+    }
+
+    #[test]
+    fn test_sign_matches_known_hmac() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_signature_header_covers_sent_body() {
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), Some(1024), reqwest::Client::new())
+            .with_signature(Some("s3cret".to_string()), None)
+            .unwrap();
+
+        for message in ["ERROR boom".to_string(), "ERROR ".repeat(500)] {
+            let body = alert.build_body("server-1", "errors", &message, &AlertContext::default()).unwrap();
+            let request = alert.build_request(body).unwrap();
+
+            let sent = request.body().unwrap().as_bytes().unwrap();
+            let mut mac = Hmac::<Sha256>::new_from_slice(b"s3cret").unwrap();
+            mac.update(sent);
+            let expected = hex::encode(mac.finalize().into_bytes());
+            assert_eq!(request.headers()[DEFAULT_SIGNATURE_HEADER], expected.as_str());
+        }
+    }
+
+    #[test]
+    fn test_custom_signature_header() {
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), None, reqwest::Client::new())
+            .with_signature(Some("s3cret".to_string()), Some("X-Hub-Signature"))
+            .unwrap();
+        let body = alert.build_body("server-1", "errors", "ERROR", &AlertContext::default()).unwrap();
+        let request = alert.build_request(body.clone()).unwrap();
+
+        assert_eq!(request.headers()["x-hub-signature"], sign("s3cret", &body).as_str());
+        assert!(request.headers().get(DEFAULT_SIGNATURE_HEADER).is_none());
+    }
+
+    #[test]
+    fn test_unsigned_by_default() {
+        let alert = WebhookAlert::new("hook".to_string(), "http://localhost".to_string(), None, reqwest::Client::new())
+            .with_signature(None, Some("X-Hub-Signature"))
+            .unwrap();
+        let body = alert.build_body("server-1", "errors", "ERROR", &AlertContext::default()).unwrap();
+        let request = alert.build_request(body).unwrap();
+        assert!(request.headers().get("x-hub-signature").is_none());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::alerts::{AlertHandler, AlertManager};
+use crate::config::{Config, SourceType};
+use crate::log_monitor::LogMonitor;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Result of running a rule set over a batch of lines
+#[derive(Debug)]
+pub struct BenchReport {
+    pub lines: usize,
+    pub elapsed: Duration,
+    /// Matching lines per rule, in config order
+    pub matches: Vec<(String, usize)>,
+}
+
+impl BenchReport {
+    pub fn lines_per_sec(&self) -> f64 {
+        self.lines as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Alert handler that drops every alert, so the benchmark measures matching only
+struct NullAlert {
+    name: String,
+}
+
+#[async_trait]
+impl AlertHandler for NullAlert {
+    async fn send(&self, _identity: &str, _rule_name: &str, _message: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Synthetic log lines: mostly routine traffic with occasional warnings and errors
+pub fn generate_lines(count: usize) -> Vec<String> {
+    (0..count)
+        .map(|i| {
+            let timestamp = format!("2025-01-01T00:{:02}:{:02}Z", (i / 60) % 60, i % 60);
+            match i % 20 {
+                0 => format!("{} ERROR service=payments connection refused to db-{}:5432", timestamp, i % 5),
+                7 => format!("{} WARN service=orders slow query took {}ms", timestamp, 200 + (i * 37) % 3000),
+                _ => format!(
+                    "{} INFO service=api request id={} method=GET path=/api/items/{} status=200 duration={}ms",
+                    timestamp,
+                    i,
+                    i % 1000,
+                    i % 250
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Read up to `count` lines from a file, repeating it if it is shorter
+pub fn read_lines(path: &PathBuf, count: usize) -> Result<Vec<String>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let lines: Vec<&str> = contents.lines().collect();
+    if lines.is_empty() {
+        anyhow::bail!("{} is empty", path.display());
+    }
+
+    Ok(lines.iter().cycle().take(count).map(|line| line.to_string()).collect())
+}
+
+/// Time the config's rules over `lines` without sending any alerts
+/// Rule `sources` filters are ignored so every rule sees every line
+pub async fn run(config: &Config, lines: &[String]) -> Result<BenchReport> {
+    let mut alert_manager = AlertManager::new("bench".to_string());
+    for name in config.alerts.keys() {
+        alert_manager.register(name.clone(), Arc::new(NullAlert { name: name.clone() }));
+    }
+
+    let rules: Vec<_> = config
+        .rules
+        .iter()
+        .cloned()
+        .map(|mut rule| {
+            rule.sources = None;
+            rule
+        })
+        .collect();
+    let monitor = LogMonitor::new(rules, Arc::new(alert_manager))?;
+    let source = SourceType::File(PathBuf::from("bench"));
+
+    let started = Instant::now();
+    for line in lines {
+        monitor.process_line(line, &source).await;
+    }
+    let elapsed = started.elapsed();
+
+    // Count outside the timed loop so counting doesn't skew the throughput
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for line in lines {
+        for rule in monitor.matching_rules(line) {
+            *counts.entry(rule).or_default() += 1;
+        }
+    }
+
+    let matches = config
+        .rules
+        .iter()
+        .map(|rule| (rule.name.clone(), counts.get(rule.name.as_str()).copied().unwrap_or(0)))
+        .collect();
+
+    Ok(BenchReport {
+        lines: lines.len(),
+        elapsed,
+        matches,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bench_counts_matches() {
+        let yaml = r#"
+alerts:
+  console:
+    type: stdout
+rules:
+  - name: errors
+    text: ERROR
+    alert: console
+  - name: slow
+    pattern: 'took (?P<ms>\d+)ms'
+    condition: "ms > 1000"
+    alert: console
+  - name: never
+    text: PANIC
+    alert: console
+    sources:
+      files: [/var/log/other.log]
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let lines = generate_lines(200);
+
+        let report = run(&config, &lines).await.unwrap();
+
+        assert_eq!(report.lines, 200);
+        assert!(report.lines_per_sec() > 0.0);
+        assert_eq!(report.matches[0], ("errors".to_string(), 10));
+        assert_eq!(report.matches[1].0, "slow");
+        assert!(report.matches[1].1 > 0 && report.matches[1].1 < 10);
+        assert_eq!(report.matches[2], ("never".to_string(), 0));
+    }
+
+    #[test]
+    fn test_read_lines_repeats_short_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "one\ntwo\n").unwrap();
+
+        assert_eq!(read_lines(&path, 5).unwrap(), vec!["one", "two", "one", "two", "one"]);
+    }
+}
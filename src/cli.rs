@@ -1,5 +1,6 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser, Debug)]
 #[command(
@@ -15,6 +16,20 @@ pub struct Cli {
     /// Enable verbose logging
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Keep emoji out of log output, for terminals and log sinks that don't render them
+    #[arg(long, global = true, env = "TW_NO_EMOJI")]
+    pub no_emoji: bool,
+
+    /// Log output format: human-readable text, or one JSON object per line for log pipelines
+    #[arg(long, global = true, env = "TW_LOG_FORMAT", value_enum, default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum LogFormat {
+    Text,
+    Json,
 }
 
 #[derive(Subcommand, Debug)]
@@ -28,6 +43,18 @@ pub enum Commands {
         /// Disable resource monitoring
         #[arg(long)]
         no_resources: bool,
+
+        /// Stop alerting after this many alerts within --max-alerts-window, sending one storm notice
+        #[arg(long)]
+        max_alerts: Option<usize>,
+
+        /// Window for --max-alerts in minutes; alerts stay suppressed this long once the cap is hit
+        #[arg(long, default_value = "10", requires = "max_alerts")]
+        max_alerts_window: u64,
+
+        /// Fail to start if any configured file can't be read due to permissions
+        #[arg(long)]
+        require_readable: bool,
     },
 
     /// Test configuration and rules without watching
@@ -54,6 +81,45 @@ pub enum Commands {
         /// Watch specific Docker containers (overrides config)
         #[arg(short = 'c', long)]
         container: Vec<String>,
+
+        /// Seconds to wait for each `tail`/`docker logs` command before skipping the source
+        #[arg(long, default_value = "30")]
+        timeout: u64,
+    },
+
+    /// Measure how many lines per second the configured rules can process
+    Bench {
+        /// Configuration file path
+        #[arg(long, required = true)]
+        config: PathBuf,
+
+        /// Number of lines to run through the rules
+        #[arg(short = 'n', long, default_value = "100000")]
+        lines: usize,
+
+        /// Read lines from this file (repeated as needed) instead of generating them
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+
+    /// Mute all alerts for a maintenance window
+    ///
+    /// Writes a mute marker to the config's state_dir, which running watchers check before each alert
+    Mute {
+        /// Configuration file path (its state_dir holds the mute marker)
+        #[arg(long, required = true)]
+        config: PathBuf,
+
+        /// How long to mute alerts for, like "30m" or "2h"
+        #[arg(long, value_parser = crate::config::parse_duration)]
+        duration: Duration,
+    },
+
+    /// End a maintenance mute early
+    Unmute {
+        /// Configuration file path (its state_dir holds the mute marker)
+        #[arg(long, required = true)]
+        config: PathBuf,
     },
 
     /// Start tinywatcher as a background service/daemon
@@ -61,14 +127,40 @@ pub enum Commands {
         /// Configuration file path (required for first-time setup)
         #[arg(long)]
         config: Option<PathBuf>,
+
+        /// Print the service definition that would be installed without writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Manage a separately named service instance, so several watchers can run side by side
+        #[arg(long)]
+        name: Option<String>,
     },
 
     /// Stop the tinywatcher background service/daemon
-    Stop,
+    Stop {
+        /// Manage a separately named service instance, so several watchers can run side by side
+        #[arg(long)]
+        name: Option<String>,
+    },
 
     /// Restart the tinywatcher background service/daemon
-    Restart,
+    Restart {
+        /// Manage a separately named service instance, so several watchers can run side by side
+        #[arg(long)]
+        name: Option<String>,
+    },
 
     /// Show the status of the tinywatcher background service/daemon
-    Status,
+    ///
+    /// Exits with 0 if running, 3 if stopped, 4 if not installed
+    Status {
+        /// Print the status as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Manage a separately named service instance, so several watchers can run side by side
+        #[arg(long)]
+        name: Option<String>,
+    },
 }
@@ -0,0 +1,221 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, TimeZone, Utc};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+
+/// Seconds between the NTP epoch (1900) and the Unix epoch (1970)
+const NTP_UNIX_OFFSET: i64 = 2_208_988_800;
+
+/// Default NTP port when the reference doesn't name one
+const NTP_PORT: u16 = 123;
+
+/// Where to read the reference time from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClockReference {
+    /// SNTP query to `host:port`
+    Ntp(String),
+    /// `Date` header of an HTTP(S) response
+    Http(String),
+}
+
+impl ClockReference {
+    /// Parse `ntp://host[:port]` or an `http(s)://` URL
+    pub fn parse(url: &str) -> Result<Self> {
+        if let Some(host) = url.strip_prefix("ntp://") {
+            let host = host.trim_end_matches('/');
+            if host.is_empty() {
+                anyhow::bail!("NTP reference '{}' has no host", url);
+            }
+            if host.contains(':') {
+                Ok(ClockReference::Ntp(host.to_string()))
+            } else {
+                Ok(ClockReference::Ntp(format!("{}:{}", host, NTP_PORT)))
+            }
+        } else if url.starts_with("http://") || url.starts_with("https://") {
+            Ok(ClockReference::Http(url.to_string()))
+        } else {
+            anyhow::bail!("Clock reference '{}' must be an ntp:// or http(s):// URL", url)
+        }
+    }
+}
+
+/// Convert a 64-bit NTP timestamp (seconds and fraction since 1900) to UTC
+fn from_ntp_timestamp(bytes: &[u8]) -> DateTime<Utc> {
+    let seconds = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as i64;
+    let fraction = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as u64;
+    let nanos = ((fraction * 1_000_000_000) >> 32) as u32;
+    Utc.timestamp_opt(seconds - NTP_UNIX_OFFSET, nanos)
+        .single()
+        .unwrap_or_default()
+}
+
+/// Local clock offset from NTP's four timestamps: positive when the local clock is behind
+/// `sent`/`received` are local times, `server_received`/`server_sent` are the server's
+pub fn ntp_offset(
+    sent: DateTime<Utc>,
+    server_received: DateTime<Utc>,
+    server_sent: DateTime<Utc>,
+    received: DateTime<Utc>,
+) -> chrono::Duration {
+    ((server_received - sent) + (server_sent - received)) / 2
+}
+
+/// Local clock offset from an HTTP `Date` header, assumed to be stamped halfway through the request
+pub fn http_date_offset(
+    date: &str,
+    sent: DateTime<Utc>,
+    received: DateTime<Utc>,
+) -> Result<chrono::Duration> {
+    let server_time = DateTime::parse_from_rfc2822(date)
+        .with_context(|| format!("Invalid Date header '{}'", date))?
+        .with_timezone(&Utc);
+    let midpoint = sent + (received - sent) / 2;
+    Ok(server_time - midpoint)
+}
+
+/// Whether an offset is outside the tolerance in either direction
+pub fn exceeds(offset: chrono::Duration, max_skew: Duration) -> bool {
+    offset.abs().to_std().unwrap_or(Duration::MAX) > max_skew
+}
+
+/// Human-readable description of an offset, e.g. "3.20s behind"
+pub fn describe(offset: chrono::Duration) -> String {
+    let seconds = offset.num_milliseconds() as f64 / 1000.0;
+    if seconds >= 0.0 {
+        format!("{:.2}s behind", seconds)
+    } else {
+        format!("{:.2}s ahead", -seconds)
+    }
+}
+
+async fn ntp_query(address: &str) -> Result<chrono::Duration> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await.context("Failed to bind UDP socket")?;
+    socket
+        .connect(address)
+        .await
+        .with_context(|| format!("Failed to resolve NTP server '{}'", address))?;
+
+    // LI = 0, version 3, mode 3 (client)
+    let mut request = [0u8; 48];
+    request[0] = 0x1B;
+
+    let sent = Utc::now();
+    socket.send(&request).await.context("Failed to send NTP request")?;
+    let mut response = [0u8; 48];
+    let len = socket.recv(&mut response).await.context("Failed to read NTP response")?;
+    let received = Utc::now();
+
+    if len < 48 {
+        anyhow::bail!("Short NTP response ({} bytes)", len);
+    }
+
+    Ok(ntp_offset(
+        sent,
+        from_ntp_timestamp(&response[32..40]),
+        from_ntp_timestamp(&response[40..48]),
+        received,
+    ))
+}
+
+async fn http_query(url: &str, client: &reqwest::Client) -> Result<chrono::Duration> {
+    let sent = Utc::now();
+    let response = client.head(url).send().await.context("HTTP request failed")?;
+    let received = Utc::now();
+
+    let date = response
+        .headers()
+        .get(reqwest::header::DATE)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| anyhow::anyhow!("Response from '{}' has no Date header", url))?;
+
+    http_date_offset(date, sent, received)
+}
+
+/// Measure the local clock's offset from the reference
+pub async fn measure(
+    reference: &ClockReference,
+    client: &reqwest::Client,
+    timeout: Duration,
+) -> Result<chrono::Duration> {
+    let query = async {
+        match reference {
+            ClockReference::Ntp(address) => ntp_query(address).await,
+            ClockReference::Http(url) => http_query(url, client).await,
+        }
+    };
+
+    tokio::time::timeout(timeout, query)
+        .await
+        .map_err(|_| anyhow::anyhow!("Clock reference timed out after {:?}", timeout))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(seconds: i64, millis: u32) -> DateTime<Utc> {
+        Utc.timestamp_opt(seconds, millis * 1_000_000).unwrap()
+    }
+
+    #[test]
+    fn test_parse_reference() {
+        assert_eq!(
+            ClockReference::parse("ntp://pool.ntp.org").unwrap(),
+            ClockReference::Ntp("pool.ntp.org:123".to_string())
+        );
+        assert_eq!(
+            ClockReference::parse("ntp://10.0.0.1:1123").unwrap(),
+            ClockReference::Ntp("10.0.0.1:1123".to_string())
+        );
+        assert_eq!(
+            ClockReference::parse("https://example.com").unwrap(),
+            ClockReference::Http("https://example.com".to_string())
+        );
+        assert!(ClockReference::parse("pool.ntp.org").is_err());
+        assert!(ClockReference::parse("ntp://").is_err());
+    }
+
+    #[test]
+    fn test_ntp_offset() {
+        // Server is 5s ahead; 100ms each way on the network
+        let offset = ntp_offset(at(1000, 0), at(1005, 100), at(1005, 150), at(1000, 250));
+        assert_eq!(offset, chrono::Duration::seconds(5));
+
+        // Local clock 2s ahead of the server
+        let offset = ntp_offset(at(1002, 0), at(1000, 50), at(1000, 50), at(1002, 100));
+        assert_eq!(offset, chrono::Duration::seconds(-2));
+    }
+
+    #[test]
+    fn test_from_ntp_timestamp() {
+        let mut bytes = [0u8; 8];
+        bytes[..4].copy_from_slice(&((NTP_UNIX_OFFSET + 1_700_000_000) as u32).to_be_bytes());
+        bytes[4..].copy_from_slice(&(1u32 << 31).to_be_bytes());
+        assert_eq!(from_ntp_timestamp(&bytes), at(1_700_000_000, 500));
+    }
+
+    #[test]
+    fn test_http_date_offset() {
+        let sent = DateTime::parse_from_rfc3339("2024-03-01T12:00:00.000Z").unwrap().with_timezone(&Utc);
+        let received = sent + chrono::Duration::milliseconds(400);
+
+        let offset = http_date_offset("Fri, 01 Mar 2024 12:00:30 GMT", sent, received).unwrap();
+        assert_eq!(offset, chrono::Duration::milliseconds(29_800));
+        assert!(http_date_offset("yesterday", sent, received).is_err());
+    }
+
+    #[test]
+    fn test_exceeds_tolerance() {
+        let tolerance = Duration::from_secs(2);
+        assert!(!exceeds(chrono::Duration::milliseconds(1500), tolerance));
+        assert!(!exceeds(chrono::Duration::milliseconds(-1500), tolerance));
+        assert!(exceeds(chrono::Duration::seconds(3), tolerance));
+        assert!(exceeds(chrono::Duration::seconds(-3), tolerance));
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe(chrono::Duration::milliseconds(3200)), "3.20s behind");
+        assert_eq!(describe(chrono::Duration::milliseconds(-450)), "0.45s ahead");
+    }
+}
@@ -1,6 +1,9 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::Duration;
 use regex::Regex;
 
@@ -78,6 +81,311 @@ impl Serialize for Threshold {
     }
 }
 
+/// Parse a duration string like "30s", "5m", "500ms" or "1h"
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let re = Regex::new(r"^\s*(?P<value>\d+)(?P<unit>ms|s|m|h)\s*$").unwrap();
+
+    let caps = re.captures(s)
+        .ok_or_else(|| format!("Invalid duration format: '{}'. Expected format like '30s' or '5m'", s))?;
+
+    let value: u64 = caps["value"].parse()
+        .map_err(|_| format!("Invalid value in duration: '{}'", &caps["value"]))?;
+
+    match &caps["unit"] {
+        "ms" => Ok(Duration::from_millis(value)),
+        "s"  => Ok(Duration::from_secs(value)),
+        "m"  => Ok(Duration::from_secs(value * 60)),
+        "h"  => Ok(Duration::from_secs(value * 3600)),
+        _ => Err(format!("Invalid time unit in '{}'", s)),
+    }
+}
+
+/// Format a duration in the largest whole unit, the inverse of `parse_duration`
+pub fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_millis();
+
+    if !millis.is_multiple_of(1000) {
+        format!("{}ms", millis)
+    } else if !millis.is_multiple_of(60_000) {
+        format!("{}s", millis / 1000)
+    } else if !millis.is_multiple_of(3_600_000) {
+        format!("{}m", millis / 60_000)
+    } else {
+        format!("{}h", millis / 3_600_000)
+    }
+}
+
+const SIZE_UNITS: [(&str, u64); 4] = [("TB", 1 << 40), ("GB", 1 << 30), ("MB", 1 << 20), ("KB", 1 << 10)];
+
+/// Parse a size like "2GB", "500MB", "1.5G" or "4096" (bytes); units are powers of 1024
+pub fn parse_size(s: &str) -> Result<u64, String> {
+    let re = Regex::new(r"(?i)^\s*(?P<value>\d+(?:\.\d+)?)\s*(?:(?P<unit>[KMGT])(?:i?B)?|B)?\s*$").unwrap();
+
+    let caps = re.captures(s)
+        .ok_or_else(|| format!("Invalid size: '{}'. Expected a size like '500MB' or '2GB'", s))?;
+
+    let value: f64 = caps["value"].parse()
+        .map_err(|_| format!("Invalid value in size: '{}'", &caps["value"]))?;
+
+    let multiplier = match caps.name("unit").map(|unit| unit.as_str().to_ascii_uppercase()) {
+        None => 1,
+        Some(unit) => SIZE_UNITS
+            .iter()
+            .find(|(name, _)| name.starts_with(unit.as_str()))
+            .map(|(_, multiplier)| *multiplier)
+            .ok_or_else(|| format!("Invalid size unit in '{}'", s))?,
+    };
+
+    Ok((value * multiplier as f64).round() as u64)
+}
+
+/// Format a size in the largest whole unit, the inverse of `parse_size`
+pub fn format_size(bytes: u64) -> String {
+    SIZE_UNITS
+        .iter()
+        .find(|(_, multiplier)| bytes > 0 && bytes.is_multiple_of(*multiplier))
+        .map(|(name, multiplier)| format!("{}{}", bytes / multiplier, name))
+        .unwrap_or_else(|| format!("{}B", bytes))
+}
+
+// Serde helpers for optional durations written as a number of seconds or a string like "5m"
+mod optional_seconds {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Seconds {
+        Number(u64),
+        Text(String),
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<Seconds>::deserialize(deserializer)? {
+            Some(Seconds::Number(secs)) => Ok(Some(Duration::from_secs(secs))),
+            Some(Seconds::Text(s)) => super::parse_duration(&s).map(Some).map_err(serde::de::Error::custom),
+            None => Ok(None),
+        }
+    }
+
+    pub fn serialize<S>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        super::optional_duration::serialize(value, serializer)
+    }
+}
+
+// Deserializers for whole seconds written as a number or a string like "5m"
+// Serialized as the plain number, so existing configs keep round-tripping unchanged
+mod seconds {
+    use serde::{Deserialize, Deserializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Seconds {
+        Number(u64),
+        Text(String),
+    }
+
+    impl Seconds {
+        fn into_secs(self) -> Result<u64, String> {
+            match self {
+                Seconds::Number(secs) => Ok(secs),
+                Seconds::Text(s) => {
+                    let duration = super::parse_duration(&s)?;
+                    if duration.subsec_nanos() != 0 {
+                        return Err(format!("Duration '{}' must be a whole number of seconds", s));
+                    }
+                    Ok(duration.as_secs())
+                }
+            }
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Seconds::deserialize(deserializer)?
+            .into_secs()
+            .map_err(serde::de::Error::custom)
+    }
+
+    pub fn deserialize_optional<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<Seconds>::deserialize(deserializer)?
+            .map(Seconds::into_secs)
+            .transpose()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// Serde helpers for optional sizes written as "2GB" or a plain number of bytes
+mod optional_size {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<Size>::deserialize(deserializer)? {
+            Some(Size::Bytes(bytes)) => Ok(Some(bytes)),
+            Some(Size::Text(s)) => super::parse_size(&s).map(Some).map_err(serde::de::Error::custom),
+            None => Ok(None),
+        }
+    }
+
+    pub fn serialize<S>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(bytes) => serializer.serialize_str(&super::format_size(*bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+// Serde helpers for optional duration strings like "5m"
+mod optional_duration {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(s) => super::parse_duration(&s).map(Some).map_err(serde::de::Error::custom),
+            None => Ok(None),
+        }
+    }
+
+    pub fn serialize<S>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(duration) => serializer.serialize_str(&super::format_duration(*duration)),
+            None => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Numeric comparison used by rule conditions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Gt,
+    Lt,
+    Ge,
+    Le,
+}
+
+impl Comparison {
+    pub fn compare(&self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Gt => left > right,
+            Comparison::Lt => left < right,
+            Comparison::Ge => left >= right,
+            Comparison::Le => left <= right,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Gt => ">",
+            Comparison::Lt => "<",
+            Comparison::Ge => ">=",
+            Comparison::Le => "<=",
+        }
+    }
+}
+
+/// Numeric condition on a named capture group
+/// Example: "ms > 1000" means alert only if the `ms` capture parses to a number above 1000
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    pub field: String,
+    pub op: Comparison,
+    pub value: f64,
+}
+
+impl Condition {
+    /// Parse a condition string like "ms > 1000"
+    /// Supported operators: `>`, `<`, `>=`, `<=` or `gt`, `lt`, `ge`, `le`
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let re = Regex::new(r"^\s*(?P<field>[A-Za-z_][A-Za-z0-9_]*)\s*(?P<op>>=|<=|>|<|\bgt\b|\blt\b|\bge\b|\ble\b)\s*(?P<value>-?\d+(?:\.\d+)?)\s*$")
+            .unwrap();
+
+        let caps = re.captures(s)
+            .ok_or_else(|| format!("Invalid condition format: '{}'. Expected format like 'ms > 1000'", s))?;
+
+        let op = match &caps["op"] {
+            ">" | "gt" => Comparison::Gt,
+            "<" | "lt" => Comparison::Lt,
+            ">=" | "ge" => Comparison::Ge,
+            "<=" | "le" => Comparison::Le,
+            other => return Err(format!("Invalid operator '{}' in condition '{}'", other, s)),
+        };
+
+        let value: f64 = caps["value"].parse()
+            .map_err(|_| format!("Invalid value in condition: '{}'", &caps["value"]))?;
+
+        Ok(Condition {
+            field: caps["field"].to_string(),
+            op,
+            value,
+        })
+    }
+
+    /// Check the condition against a captured value; non-numeric values never match
+    pub fn is_met(&self, captured: &str) -> bool {
+        captured
+            .trim()
+            .parse::<f64>()
+            .map(|value| self.op.compare(value, self.value))
+            .unwrap_or(false)
+    }
+}
+
+impl std::fmt::Display for Condition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.field, self.op.symbol(), self.value)
+    }
+}
+
+impl<'de> Deserialize<'de> for Condition {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Condition::parse(&s).map_err(serde::de::Error::custom)
+    }
+}
+
+impl Serialize for Condition {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
 // Helper function to expand environment variables in strings
 fn expand_env_vars(value: &str) -> String {
     let re = Regex::new(r"\$\{([^}]+)\}|\$([A-Za-z_][A-Za-z0-9_]*)").unwrap();
@@ -91,11 +399,29 @@ fn expand_env_vars(value: &str) -> String {
     }).to_string()
 }
 
+/// Files an `include` entry refers to, relative to the including file's directory
+/// A glob matching nothing is fine; a plain path must exist
+fn resolve_include(base: &Path, pattern: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let pattern = base.join(pattern);
+    let pattern_str = pattern.to_string_lossy();
+    if !(pattern_str.contains('*') || pattern_str.contains('?') || pattern_str.contains('[')) {
+        return Ok(vec![pattern]);
+    }
+
+    let mut files = glob::glob(&pattern_str)
+        .map_err(|e| anyhow::anyhow!("Invalid include pattern '{}': {}", pattern_str, e))?
+        .filter_map(|entry| entry.ok())
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
     #[serde(default)]
     pub inputs: Inputs,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "deserialize_alerts")]
     pub alerts: HashMap<String, Alert>,
     #[serde(default)]
     pub rules: Vec<Rule>,
@@ -104,13 +430,310 @@ pub struct Config {
     pub identity: Identity,
     #[serde(default)]
     pub system_checks: Vec<SystemCheck>,
-    pub heartbeat: Option<HeartbeatConfig>,
+    /// One heartbeat endpoint or a list, each pinged on its own interval
+    #[serde(default, deserialize_with = "one_or_many", skip_serializing_if = "Vec::is_empty")]
+    pub heartbeat: Vec<HeartbeatConfig>,
+    /// How rule cooldowns behave under a steady stream of matches
+    #[serde(default)]
+    pub cooldown_mode: CooldownMode,
+    /// Maximum number of file and container watchers; sources past the limit are not watched
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_watchers: Option<usize>,
+    /// Send a periodic summary of failed alert deliveries to another alert
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery_failures: Option<DeliveryFailureConfig>,
+    /// How failed alert deliveries are retried; alerts can override it with `retries`/`retry_backoff`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_retry: Option<RetryConfig>,
+    /// Quiet log alerts while a deploy is in progress
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deploy: Option<DeployConfig>,
+    /// Directory where cooldown, threshold and file offset state is saved across restarts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_dir: Option<PathBuf>,
+    /// Alert when configured files can't be read due to permissions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unreadable_files: Option<UnreadableFilesConfig>,
+    /// Collapse alerts with the same (normalized) message within a short window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dedup: Option<DedupConfig>,
+    /// Timezone for alert timestamps: "utc" (default), "local" or an offset like "+02:00"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// strftime format for timestamps in alert bodies (default "%Y-%m-%d %H:%M:%S")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_format: Option<String>,
+    /// Serve Prometheus metrics about tinywatcher itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MetricsConfig>,
+    /// Alert when a log source stops producing lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub silent_sources: Option<SilentSourcesConfig>,
+    /// More config files (or globs) whose rules, alerts and system checks are merged in,
+    /// relative to this file's directory; already merged by the time `from_file` returns
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+}
+
+/// Alert when a file, container, stream, pod or journal produces no lines for a while
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SilentSourcesConfig {
+    #[serde(deserialize_with = "string_or_seq_string")]
+    pub alert: Vec<String>,
+    /// How long a source may go without a line (default "5m")
+    #[serde(default, with = "optional_duration", skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<Duration>,
+    /// Per-source timeouts (e.g. "30s"), keyed by file path or glob, container, stream, pod, journal or event log name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sources: HashMap<String, String>,
+}
+
+impl SilentSourcesConfig {
+    pub fn timeout(&self) -> Duration {
+        self.timeout.unwrap_or(Duration::from_secs(5 * 60))
+    }
+
+    /// Parsed per-source timeouts, most specific (longest) pattern first
+    pub fn source_timeouts(&self) -> anyhow::Result<Vec<(glob::Pattern, Duration)>> {
+        let mut timeouts = self
+            .sources
+            .iter()
+            .map(|(source, timeout)| {
+                let pattern = glob::Pattern::new(source)
+                    .map_err(|e| anyhow::anyhow!("Invalid silent_sources source '{}': {}", source, e))?;
+                let timeout = parse_duration(timeout)
+                    .map_err(|e| anyhow::anyhow!("silent_sources timeout for '{}': {}", source, e))?;
+                if timeout.is_zero() {
+                    anyhow::bail!("silent_sources timeout for '{}' must be greater than zero", source);
+                }
+                Ok((pattern, timeout))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        timeouts.sort_by(|(a, _), (b, _)| b.as_str().len().cmp(&a.as_str().len()).then(a.cmp(b)));
+        Ok(timeouts)
+    }
+}
+
+/// Where to serve `/metrics` in the Prometheus text format
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MetricsConfig {
+    /// Address and port to listen on, e.g. "127.0.0.1:9184"
+    pub bind: std::net::SocketAddr,
+}
+
+/// Drop alerts whose message matches one already sent within `window`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DedupConfig {
+    /// How long a sent message suppresses identical ones (default "1m")
+    #[serde(default, with = "optional_duration", skip_serializing_if = "Option::is_none")]
+    pub window: Option<Duration>,
+    /// Replacements applied to the message before comparing, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub normalize: Vec<Normalization>,
+}
+
+/// Regex replacement that masks the volatile parts of a message (request ids, timestamps)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Normalization {
+    pub pattern: String,
+    #[serde(default)]
+    pub replacement: String,
+}
+
+impl DedupConfig {
+    pub fn window(&self) -> Duration {
+        self.window.unwrap_or(Duration::from_secs(60))
+    }
+
+    /// Compile the `normalize` patterns
+    pub fn normalizers(&self) -> anyhow::Result<Vec<(Regex, String)>> {
+        self.normalize
+            .iter()
+            .map(|n| {
+                let regex = Regex::new(&n.pattern)
+                    .map_err(|e| anyhow::anyhow!("Invalid dedup normalize pattern '{}': {}", n.pattern, e))?;
+                Ok((regex, n.replacement.clone()))
+            })
+            .collect()
+    }
+}
+
+/// Where to alert about configured files the agent can't read
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UnreadableFilesConfig {
+    #[serde(deserialize_with = "string_or_seq_string")]
+    pub alert: Vec<String>,
+    /// How often to recheck permissions (default "5m")
+    #[serde(default, with = "optional_duration", skip_serializing_if = "Option::is_none")]
+    pub interval: Option<Duration>,
+}
+
+impl UnreadableFilesConfig {
+    pub fn interval(&self) -> Duration {
+        self.interval.unwrap_or(Duration::from_secs(5 * 60))
+    }
+}
+
+/// Log lines that bracket a deploy, during which log alerts are quieted
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DeployConfig {
+    /// Regex for the line that marks the start of a deploy
+    pub start: String,
+    /// Regex for the line that marks the end; without it the deploy lasts until `timeout`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    /// Resume normal alerting after this long even if no end marker is seen (default "15m")
+    #[serde(default, with = "optional_duration", skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<Duration>,
+    #[serde(default)]
+    pub action: DeployAction,
+}
+
+/// How a rule groups continuation lines into records, e.g. a stack trace under its exception line
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MultilineConfig {
+    /// Regex for the first line of a record; lines that don't match it continue the current record
+    pub start: String,
+    /// Evaluate a record once no line has been added for this long (default "2s")
+    #[serde(default, with = "optional_duration", skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<Duration>,
+    /// Evaluate a record early once it reaches this many lines (default 500)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_lines: Option<usize>,
+}
+
+impl MultilineConfig {
+    pub fn timeout(&self) -> Duration {
+        self.timeout.unwrap_or(Duration::from_secs(2))
+    }
+
+    pub fn max_lines(&self) -> usize {
+        self.max_lines.unwrap_or(500)
+    }
+}
+
+impl DeployConfig {
+    pub fn timeout(&self) -> Duration {
+        self.timeout.unwrap_or(Duration::from_secs(15 * 60))
+    }
+}
+
+/// What happens to log alerts during a deploy
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeployAction {
+    /// Drop the alerts
+    #[default]
+    Suppress,
+    /// Send the alerts with severity `info`
+    Downgrade,
+}
+
+/// Where and how often to report failed alert deliveries
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DeliveryFailureConfig {
+    /// Alert that receives the summary
+    pub alert: String,
+    /// How often to summarize failures (default "1m")
+    #[serde(default, with = "optional_duration", skip_serializing_if = "Option::is_none")]
+    pub window: Option<Duration>,
+}
+
+impl DeliveryFailureConfig {
+    pub fn window(&self) -> Duration {
+        self.window.unwrap_or(Duration::from_secs(60))
+    }
+}
+
+/// Retry policy for failed alert deliveries
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RetryConfig {
+    /// Retries after the first failed attempt (default 2; 0 disables retrying)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Delay before the first retry, doubled for each one after (default "1s")
+    #[serde(default, with = "optional_seconds", skip_serializing_if = "Option::is_none")]
+    pub backoff: Option<Duration>,
+}
+
+impl RetryConfig {
+    pub fn retries(&self) -> u32 {
+        self.retries.unwrap_or(2)
+    }
+
+    pub fn backoff(&self) -> Duration {
+        self.backoff.unwrap_or(Duration::from_secs(1))
+    }
+}
+
+impl Alert {
+    /// Retries and initial backoff for this alert, falling back to the global `alert_retry`
+    pub fn retry_policy(&self, defaults: Option<&RetryConfig>) -> (u32, Duration) {
+        let defaults = defaults.cloned().unwrap_or_default();
+        (
+            self.retries.unwrap_or_else(|| defaults.retries()),
+            self.retry_backoff.unwrap_or_else(|| defaults.backoff()),
+        )
+    }
+}
+
+/// File and container sources to watch, after glob expansion and `max_watchers`
+#[derive(Debug, Default, PartialEq)]
+pub struct WatchSources {
+    pub files: Vec<PathBuf>,
+    pub containers: Vec<String>,
+    /// Sources over the `max_watchers` limit, which won't be watched
+    pub dropped: Vec<String>,
+}
+
+/// Cooldown semantics for repeated alerts from the same rule
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CooldownMode {
+    /// At most one alert per cooldown period; suppressed matches don't extend it
+    #[default]
+    Fixed,
+    /// Every match, including suppressed ones, restarts the cooldown,
+    /// so the next alert needs a quiet period of the full cooldown
+    Sliding,
+}
+
+/// How serious a rule's alerts are; destinations with priorities map from this
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    #[default]
+    Error,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Critical => "critical",
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Severity::default()
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct HeartbeatConfig {
     pub url: String,
-    #[serde(default = "default_heartbeat_interval")]
+    #[serde(default = "default_heartbeat_interval", deserialize_with = "seconds::deserialize")]
     pub interval: u64,  // seconds
 }
 
@@ -119,10 +742,12 @@ pub struct SystemCheck {
     pub name: String,
     #[serde(rename = "type")]
     pub check_type: SystemCheckType,
+    /// Target of the check; exec checks use `command` instead
+    #[serde(default)]
     pub url: String,
-    #[serde(default = "default_check_interval")]
+    #[serde(default = "default_check_interval", deserialize_with = "seconds::deserialize")]
     pub interval: u64,
-    #[serde(default = "default_timeout")]
+    #[serde(default = "default_timeout", deserialize_with = "seconds::deserialize")]
     pub timeout: u64,
     #[serde(default = "default_missed_threshold")]
     pub missed_threshold: u32,
@@ -133,25 +758,116 @@ pub struct SystemCheck {
     /// This provides an alternative to missed_threshold for more sophisticated failure detection
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub threshold: Option<Threshold>,
+    /// Send an alert when a failing check recovers
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub notify_recovery: bool,
+    /// Clock checks: largest allowed offset from the reference time (default "2s")
+    #[serde(default, with = "optional_duration", skip_serializing_if = "Option::is_none")]
+    pub max_skew: Option<Duration>,
+    /// Ping checks: echo requests sent per check (default 3)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ping_count: Option<u32>,
+    /// Ping checks: packet loss in percent above which the check fails (default 50)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_packet_loss: Option<f64>,
+    /// Ping checks: send ICMP from a socket (default) or run the system `ping` binary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ping_method: Option<PingMethod>,
+    /// HTTP checks: status code that counts as healthy, instead of any 2xx (e.g. 401 for an auth-protected endpoint)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_status: Option<u16>,
+    /// HTTP checks: text the response body must contain, or a regex written as `/.../`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expect_body: Option<String>,
+    /// Exec checks: program to run; exit code 0 is healthy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Exec checks: arguments passed to `command`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Set to false to keep the check in the config without running it
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub enabled: bool,
+}
+
+impl SystemCheck {
+    pub fn max_skew(&self) -> Duration {
+        self.max_skew.unwrap_or(Duration::from_secs(2))
+    }
+
+    pub fn ping_settings(&self) -> crate::ping::PingSettings {
+        let defaults = crate::ping::PingSettings::default();
+        crate::ping::PingSettings {
+            count: self.ping_count.unwrap_or(defaults.count),
+            max_loss: self.max_packet_loss.unwrap_or(defaults.max_loss),
+            method: self.ping_method.unwrap_or(defaults.method),
+        }
+    }
+
+    fn uses_ping_options(&self) -> bool {
+        self.ping_count.is_some() || self.max_packet_loss.is_some() || self.ping_method.is_some()
+    }
+
+    /// Why an HTTP check's url can't be requested, if it can't
+    pub fn url_error(&self) -> Option<String> {
+        if self.check_type != SystemCheckType::Http {
+            return None;
+        }
+        http_url_error(&self.url)
+            .map(|reason| format!("System check '{}': invalid url '{}': {}", self.name, self.url, reason))
+    }
+}
+
+impl HeartbeatConfig {
+    /// Why the heartbeat url can't be pinged, if it can't
+    pub fn url_error(&self) -> Option<String> {
+        http_url_error(&self.url).map(|reason| format!("Heartbeat: invalid url '{}': {}", self.url, reason))
+    }
+}
+
+/// Why `url` is not an absolute http:// or https:// URL, if it isn't
+fn http_url_error(url: &str) -> Option<String> {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) if !matches!(parsed.scheme(), "http" | "https") => {
+            Some(format!("scheme '{}' is not http or https", parsed.scheme()))
+        }
+        Ok(parsed) if !parsed.has_host() => Some("missing host".to_string()),
+        Ok(_) => None,
+        Err(e) => Some(e.to_string()),
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum SystemCheckType {
     Http,
+    /// Connect to a `host:port` (or `tcp://host:port`) to check the port is open
+    Tcp,
+    /// Compare the local clock with an `ntp://` server or an HTTP(S) `Date` header
+    Clock,
+    /// Send ICMP echo requests to a host and fail on packet loss
+    Ping,
+    /// Run a command; a non-zero exit code or a timeout is a failure
+    Exec,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Identity {
-    pub name: Option<String>,
+/// How ping checks send their echo requests
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PingMethod {
+    /// Unprivileged ICMP socket, or a raw socket when running with CAP_NET_RAW
+    #[default]
+    Socket,
+    /// The system `ping` binary, for hosts where ICMP sockets aren't permitted
+    Command,
 }
 
-impl Default for Identity {
-    fn default() -> Self {
-        Self {
-            name: None,
-        }
-    }
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Identity {
+    pub name: Option<String>,
+    /// Suffix the identity with the container name for alerts from container sources
+    #[serde(default)]
+    pub include_container: bool,
 }
 
 impl Identity {
@@ -172,8 +888,194 @@ pub struct Inputs {
     pub files: Vec<PathBuf>,
     #[serde(default)]
     pub containers: Vec<String>,
+    /// CLI used for container logs and discovery (default docker)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<ContainerRuntime>,
+    /// Watch every running container matching this selector, re-scanning for new and removed ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_selector: Option<ContainerSelector>,
     #[serde(default)]
     pub streams: Vec<StreamConfig>,
+    /// Windows Event Log channels to watch (ignored on other platforms)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows_eventlog: Vec<EventLogConfig>,
+    /// systemd journal units (e.g. "nginx.service") or `FIELD=value` matches to follow (Linux only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub journald: Vec<String>,
+    /// Kubernetes pods to follow with `kubectl logs -f`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kubernetes: Vec<KubernetesConfig>,
+    /// Fraction of lines (0.0-1.0) to evaluate, keyed by file path or glob, container, pod, journal or event log name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub sample_rate: HashMap<String, f64>,
+}
+
+/// Container engine CLI, all of which take docker-compatible `logs` and `ps` arguments
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerRuntime {
+    #[default]
+    Docker,
+    Podman,
+    Nerdctl,
+}
+
+impl ContainerRuntime {
+    pub fn binary(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker => "docker",
+            ContainerRuntime::Podman => "podman",
+            ContainerRuntime::Nerdctl => "nerdctl",
+        }
+    }
+
+    /// Arguments to follow lines a container logs from now on
+    pub fn follow_args(&self, container: &str) -> Vec<String> {
+        ["logs", "-f", "--tail", "0", container].map(String::from).to_vec()
+    }
+
+    /// Arguments to print a container's last `lines` log lines
+    pub fn tail_args(&self, lines: usize, container: &str) -> Vec<String> {
+        vec!["logs".to_string(), "--tail".to_string(), lines.to_string(), container.to_string()]
+    }
+
+    /// Arguments to list running containers as `name<TAB>labels-as-JSON` lines
+    pub fn ps_args(&self) -> Vec<String> {
+        ["ps", "--format", "{{.Names}}\t{{json .Labels}}"].map(String::from).to_vec()
+    }
+
+    /// Explain a failure to run the CLI, calling out a missing binary
+    pub fn command_error(&self, error: std::io::Error) -> anyhow::Error {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            anyhow::anyhow!(
+                "'{}' was not found on PATH; install it or set inputs.runtime to the container runtime in use",
+                self.binary()
+            )
+        } else {
+            anyhow::anyhow!("Failed to run {}: {}", self.binary(), error)
+        }
+    }
+}
+
+impl Inputs {
+    pub fn runtime(&self) -> ContainerRuntime {
+        self.runtime.unwrap_or_default()
+    }
+}
+
+/// Picks running containers to watch by label
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ContainerSelector {
+    /// Labels a container must all have, as `key` or `key=value`; empty selects every container
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// How often to look for started and stopped containers (default 30s)
+    #[serde(default, with = "optional_duration", skip_serializing_if = "Option::is_none")]
+    pub rescan: Option<Duration>,
+}
+
+impl ContainerSelector {
+    pub fn rescan(&self) -> Duration {
+        self.rescan.unwrap_or(Duration::from_secs(30))
+    }
+
+    /// Whether a container with these labels is selected
+    pub fn matches(&self, labels: &HashMap<String, String>) -> bool {
+        self.labels.iter().all(|wanted| match wanted.split_once('=') {
+            Some((key, value)) => labels.get(key).is_some_and(|actual| actual == value),
+            None => labels.contains_key(wanted),
+        })
+    }
+}
+
+/// Pod logs to follow, picked by name or label selector
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KubernetesConfig {
+    /// Defaults to the namespace of the current kubectl context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pod: Option<String>,
+    /// Label selector such as "app=api", following every matching pod
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<String>,
+    /// Only this container's logs; defaults to all containers in the pod
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+}
+
+impl KubernetesConfig {
+    /// Name used for the pod source in rule `sources.pods`, sample rates and alerts:
+    /// the pod name or selector
+    pub fn name(&self) -> &str {
+        self.pod.as_deref().or(self.selector.as_deref()).unwrap_or_default()
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        match (&self.pod, &self.selector) {
+            (None, None) => anyhow::bail!("Kubernetes input must have either 'pod' or 'selector'"),
+            (Some(pod), Some(selector)) => anyhow::bail!(
+                "Kubernetes input cannot have both 'pod' ({}) and 'selector' ({})",
+                pod,
+                selector
+            ),
+            _ if self.name().trim().is_empty() => {
+                anyhow::bail!("Kubernetes input has an empty pod name or selector")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EventLogConfig {
+    /// Log (channel) name, e.g. "Application" or "System"
+    pub log: String,
+    /// Only watch events from these providers (event sources); empty means all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+    /// Minimum level to watch; defaults to all levels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<EventLevel>,
+}
+
+/// Windows event levels, from least to most severe
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum EventLevel {
+    Verbose,
+    Information,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl EventLevel {
+    /// Map the numeric `Level` of an event record (1 = critical .. 5 = verbose)
+    /// Level 0 ("log always") is treated as information
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub fn from_level(level: u8) -> Self {
+        match level {
+            1 => EventLevel::Critical,
+            2 => EventLevel::Error,
+            3 => EventLevel::Warning,
+            5 => EventLevel::Verbose,
+            _ => EventLevel::Information,
+        }
+    }
+}
+
+impl std::fmt::Display for EventLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            EventLevel::Verbose => "verbose",
+            EventLevel::Information => "information",
+            EventLevel::Warning => "warning",
+            EventLevel::Error => "error",
+            EventLevel::Critical => "critical",
+        };
+        write!(f, "{}", name)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -184,8 +1086,30 @@ pub struct StreamConfig {
     pub url: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub headers: Option<HashMap<String, String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, deserialize_with = "seconds::deserialize_optional", skip_serializing_if = "Option::is_none")]
     pub reconnect_delay: Option<u64>,  // seconds
+    /// Alert when the stream keeps sending data that isn't valid UTF-8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decode_errors: Option<DecodeErrorsConfig>,
+}
+
+/// Where to alert when a stream repeatedly delivers undecodable (binary or non-UTF-8) data
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DecodeErrorsConfig {
+    #[serde(deserialize_with = "string_or_seq_string")]
+    pub alert: Vec<String>,
+    /// How many undecodable frames or lines within a window trigger the alert (default "5 in 1m")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<Threshold>,
+}
+
+impl DecodeErrorsConfig {
+    pub fn threshold(&self) -> Threshold {
+        self.threshold.clone().unwrap_or(Threshold {
+            count: 5,
+            window: Duration::from_secs(60),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -194,22 +1118,252 @@ pub enum StreamType {
     Websocket,
     Http,
     Tcp,
+    /// Server-Sent Events (`text/event-stream`); each event's data is matched as log lines
+    Sse,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone)]
 pub struct Alert {
+    /// Options for the declared `type`, which the variant records
+    pub options: AlertOptions,
+    /// Retries after a failed delivery, overriding `alert_retry.retries`
+    pub retries: Option<u32>,
+    /// Delay before the first retry, overriding `alert_retry.backoff`
+    pub retry_backoff: Option<Duration>,
+}
+
+/// An alert as written in the config file, before its options are parsed
+#[derive(Deserialize)]
+struct RawAlert {
     #[serde(rename = "type")]
-    pub alert_type: AlertType,
+    alert_type: AlertType,
+    #[serde(default)]
+    retries: Option<u32>,
+    #[serde(default, with = "optional_seconds")]
+    retry_backoff: Option<Duration>,
     #[serde(flatten)]
-    pub options: AlertOptions,
+    options: serde_json::Map<String, serde_json::Value>,
+}
+
+impl RawAlert {
+    // Alert options are parsed according to the declared `type`, so destinations that share
+    // the same fields (e.g. slack/webhook/discord all take `url`) can't be confused
+    fn into_alert(self) -> Result<Alert, String> {
+        let type_name = self.alert_type.to_string();
+        let tagged = serde_json::json!({ type_name.clone(): self.options });
+        let options = serde_json::from_value(tagged)
+            .map_err(|e| format!("invalid options for '{}' alert: {}", type_name, e))?;
+
+        Ok(Alert {
+            options,
+            retries: self.retries,
+            retry_backoff: self.retry_backoff,
+        })
+    }
 }
 
+impl<'de> Deserialize<'de> for Alert {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        RawAlert::deserialize(deserializer)?
+            .into_alert()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+// Deserialize the alerts map so option errors name the offending alert
+fn deserialize_alerts<'de, D>(deserializer: D) -> Result<HashMap<String, Alert>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    HashMap::<String, RawAlert>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, raw)| {
+            let alert = raw
+                .into_alert()
+                .map_err(|e| serde::de::Error::custom(format!("alert '{}': {}", name, e)))?;
+            Ok((name, alert))
+        })
+        .collect()
+}
+
+impl Serialize for Alert {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        // Flatten the options back alongside `type`
+        let mut fields = match serde_json::to_value(&self.options).map_err(serde::ser::Error::custom)? {
+            serde_json::Value::Object(tagged) => match tagged.into_iter().next() {
+                Some((_, serde_json::Value::Object(fields))) => fields,
+                _ => serde_json::Map::new(),
+            },
+            _ => serde_json::Map::new(),
+        };
+        fields.insert("type".to_string(), serde_json::Value::String(self.alert_type().to_string()));
+        if let Some(retries) = self.retries {
+            fields.insert("retries".to_string(), retries.into());
+        }
+        if let Some(backoff) = self.retry_backoff {
+            fields.insert("retry_backoff".to_string(), format_duration(backoff).into());
+        }
+        fields.serialize(serializer)
+    }
+}
+
+impl Alert {
+    pub fn alert_type(&self) -> AlertType {
+        self.options.alert_type()
+    }
+
+    /// Check option values that are only known once environment variables are expanded
+    pub fn validate(&self, name: &str) -> anyhow::Result<()> {
+        if let AlertOptions::Webhook { method, headers, signature_header, .. } = &self.options {
+            if let Some(header) = signature_header {
+                reqwest::header::HeaderName::from_bytes(header.as_bytes())
+                    .map_err(|_| anyhow::anyhow!("Alert '{}': invalid signature_header '{}'", name, header))?;
+            }
+            if let Some(method) = method {
+                crate::alerts::WebhookAlert::parse_method(method)
+                    .map_err(|e| anyhow::anyhow!("Alert '{}': {}", name, e))?;
+            }
+            if let Some(headers) = headers {
+                crate::alerts::WebhookAlert::parse_headers(headers)
+                    .map_err(|e| anyhow::anyhow!("Alert '{}': {}", name, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Destination URLs and email addresses that won't parse, one message each
+    pub fn address_errors(&self, name: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+        match &self.options {
+            AlertOptions::Slack { url, .. }
+            | AlertOptions::Webhook { url, .. }
+            | AlertOptions::Discord { url, .. }
+            | AlertOptions::Gotify { url, .. }
+            | AlertOptions::Forward { url } => {
+                if let Some(reason) = http_url_error(url) {
+                    errors.push(format!("Alert '{}': invalid url '{}': {}", name, url, reason));
+                }
+            }
+            AlertOptions::Ntfy { server: Some(server), .. } => {
+                if let Some(reason) = http_url_error(server) {
+                    errors.push(format!("Alert '{}': invalid server '{}': {}", name, server, reason));
+                }
+            }
+            // SMTP accepts `Name <user@host>`; SendGrid takes bare addresses
+            AlertOptions::Email { from, to, .. } => {
+                for (field, address) in std::iter::once(("from", from)).chain(to.iter().map(|to| ("to", to))) {
+                    if let Err(e) = address.parse::<lettre::message::Mailbox>() {
+                        errors.push(format!("Alert '{}': invalid {} address '{}': {}", name, field, address, e));
+                    }
+                }
+            }
+            AlertOptions::SendGrid { from, to, .. } => {
+                for (field, address) in std::iter::once(("from", from)).chain(to.iter().map(|to| ("to", to))) {
+                    if let Err(e) = address.parse::<lettre::Address>() {
+                        errors.push(format!("Alert '{}': invalid {} address '{}': {}", name, field, address, e));
+                    }
+                }
+            }
+            _ => {}
+        }
+        errors
+    }
+}
+
+/// Message layout for Slack alerts
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SlackFormat {
+    /// Block Kit layout with a severity-colored attachment
+    #[default]
+    Blocks,
+    /// Plain `text` payload, for webhooks that don't support blocks
+    Text,
+}
+
+impl SlackFormat {
+    fn is_default(&self) -> bool {
+        *self == SlackFormat::default()
+    }
+}
+
+/// Line format for file alerts
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+    /// Same `[time] ALERT [identity] [rule]: message` lines as stdout alerts
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl FileFormat {
+    fn is_default(&self) -> bool {
+        *self == FileFormat::default()
+    }
+}
+
+/// Opsgenie alert priority, P1 (critical) to P5 (informational)
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum OpsgeniePriority {
+    P1,
+    P2,
+    P3,
+    P4,
+    P5,
+}
+
+/// Options for each alert type; variant names match `AlertType`
 #[derive(Debug, Clone, Deserialize, Serialize)]
-#[serde(untagged)]
+#[serde(rename_all = "lowercase")]
 pub enum AlertOptions {
-    Slack { url: String },
-    Webhook { url: String },
-    Discord { url: String },
+    Slack {
+        url: String,
+        #[serde(default, skip_serializing_if = "SlackFormat::is_default")]
+        format: SlackFormat,
+    },
+    Webhook {
+        url: String,
+        /// Gzip request bodies larger than this many bytes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        compress_above: Option<usize>,
+        /// Body with `{{rule}}`, `{{message}}`, `{{identity}}`, `{{timestamp}}` and `{{severity}}` placeholders,
+        /// sent instead of the default JSON payload
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        template: Option<String>,
+        /// Extra request headers, e.g. an API key
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        headers: Option<HashMap<String, String>>,
+        /// HTTP method (default POST)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        method: Option<String>,
+        /// Content-Type of the body (default application/json)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_type: Option<String>,
+        /// Sign each body with HMAC-SHA256 using this secret
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        secret: Option<String>,
+        /// Header carrying the hex signature (default X-TinyWatcher-Signature)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature_header: Option<String>,
+    },
+    Discord {
+        url: String,
+        /// Post into this thread of the webhook's channel
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        thread_id: Option<String>,
+        /// Who to ping: `@here`, `@everyone`, `role:<id>` or `user:<id>`
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        mentions: Vec<String>,
+    },
     Telegram { 
         bot_token: String, 
         chat_id: String 
@@ -217,11 +1371,34 @@ pub enum AlertOptions {
     PagerDuty { 
         routing_key: String 
     },
+    Opsgenie {
+        api_key: String,
+        /// Fixed priority; when unset it follows the rule's severity
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        priority: Option<OpsgeniePriority>,
+    },
     Ntfy { 
         topic: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         server: Option<String>,
     },
+    Pushover {
+        token: String,
+        user: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        priority: Option<i8>,
+    },
+    LocalSyslog {
+        /// Syslog facility, e.g. "daemon" or "local0" (default "user")
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        facility: Option<String>,
+    },
+    Gotify {
+        url: String,
+        token: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        priority: Option<u8>,
+    },
     Email { 
         from: String, 
         to: Vec<String>,
@@ -233,7 +1410,53 @@ pub enum AlertOptions {
         from: String,
         to: Vec<String>,
     },
+    /// POST matched events to a central tinywatcher, which routes them to destinations
+    Forward {
+        url: String,
+    },
+    /// Append one line per alert to a local file
+    File {
+        path: PathBuf,
+        #[serde(default, skip_serializing_if = "FileFormat::is_default")]
+        format: FileFormat,
+    },
+    /// Run a command with the alert in `TW_RULE`, `TW_IDENTITY` and `TW_MESSAGE` (and on stdin)
+    Exec {
+        command: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+        /// Kill the command if it runs longer than this (default 30s)
+        #[serde(default, with = "optional_seconds", skip_serializing_if = "Option::is_none")]
+        timeout: Option<Duration>,
+    },
     Stdout {},
+    /// Native desktop notification; needs a build with the `desktop` feature
+    Desktop {},
+}
+
+impl AlertOptions {
+    /// The alert type these options belong to
+    pub fn alert_type(&self) -> AlertType {
+        match self {
+            AlertOptions::Slack { .. } => AlertType::Slack,
+            AlertOptions::Webhook { .. } => AlertType::Webhook,
+            AlertOptions::Discord { .. } => AlertType::Discord,
+            AlertOptions::Telegram { .. } => AlertType::Telegram,
+            AlertOptions::PagerDuty { .. } => AlertType::PagerDuty,
+            AlertOptions::Opsgenie { .. } => AlertType::Opsgenie,
+            AlertOptions::Ntfy { .. } => AlertType::Ntfy,
+            AlertOptions::Gotify { .. } => AlertType::Gotify,
+            AlertOptions::LocalSyslog { .. } => AlertType::LocalSyslog,
+            AlertOptions::Pushover { .. } => AlertType::Pushover,
+            AlertOptions::Email { .. } => AlertType::Email,
+            AlertOptions::SendGrid { .. } => AlertType::SendGrid,
+            AlertOptions::Forward { .. } => AlertType::Forward,
+            AlertOptions::File { .. } => AlertType::File,
+            AlertOptions::Exec { .. } => AlertType::Exec,
+            AlertOptions::Stdout {} => AlertType::Stdout,
+            AlertOptions::Desktop {} => AlertType::Desktop,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -245,28 +1468,237 @@ pub enum AlertType {
     Discord,
     Telegram,
     PagerDuty,
+    Opsgenie,
     Ntfy,
+    Gotify,
+    LocalSyslog,
+    Pushover,
     Email,
     SendGrid,
+    Forward,
+    File,
+    Exec,
+    Desktop,
+}
+
+impl std::fmt::Display for AlertType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Same lowercase names used in the config file
+        let name = match self {
+            AlertType::Stdout => "stdout",
+            AlertType::Slack => "slack",
+            AlertType::Webhook => "webhook",
+            AlertType::Discord => "discord",
+            AlertType::Telegram => "telegram",
+            AlertType::PagerDuty => "pagerduty",
+            AlertType::Opsgenie => "opsgenie",
+            AlertType::Ntfy => "ntfy",
+            AlertType::Gotify => "gotify",
+            AlertType::LocalSyslog => "localsyslog",
+            AlertType::Pushover => "pushover",
+            AlertType::Email => "email",
+            AlertType::SendGrid => "sendgrid",
+            AlertType::Forward => "forward",
+            AlertType::File => "file",
+            AlertType::Exec => "exec",
+            AlertType::Desktop => "desktop",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Rule {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Alert names; `${group}` placeholders route by the line's named captures
+    #[serde(deserialize_with = "string_or_seq_string")]
+    pub alert: Vec<String>,  // Can be a single alert name or list of alert names
+    /// Seconds between alerts, as a number or a duration like "5m"
+    #[serde(default = "default_cooldown", deserialize_with = "seconds::deserialize")]
+    pub cooldown: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sources: Option<RuleSources>,
+    /// Optional threshold for rate-based alerting (e.g., "5 in 2s")
+    /// If specified, alert only when the pattern matches this many times within the window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<Threshold>,
+    /// Named capture groups (regex rules only) to include in the alert as structured fields
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// Optional numeric condition on a named capture group (e.g., "ms > 1000")
+    /// If specified, a matching line only counts when the condition holds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Condition>,
+    /// Expect the pattern to appear at least this often (e.g., "1h")
+    /// Matches reset the timer instead of alerting; the alert fires when the line goes missing
+    #[serde(default, with = "optional_duration", skip_serializing_if = "Option::is_none")]
+    pub expect_within: Option<Duration>,
+    /// Number of the most recent matching lines to include when a threshold fires
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub include_matches: usize,
+    /// Number of alerts sent immediately before the cooldown starts suppressing
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub burst: usize,
+    /// Regex for a line that resolves the rule; sends a recovery alert if the rule has alerted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolve: Option<String>,
+    /// Regex for lines to ignore even when the main text/pattern matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<String>,
+    /// Literal text for lines to ignore even when the main text/pattern matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude_text: Option<String>,
+    /// Match `text` regardless of case (use `(?i)` in a `pattern` instead)
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub case_insensitive: bool,
+    /// Alert message template; `${name}` and `${1}` are filled from the pattern's capture groups
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Match `text`/`pattern` against this field of a JSON line (dotted path like `error.code`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_field: Option<String>,
+    /// Join continuation lines (stack traces) into one record before matching
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multiline: Option<MultilineConfig>,
+    /// Alert on the first match only, then stay silent until tinywatcher restarts
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub once: bool,
+    /// Severity of this rule's alerts (info, warning, error or critical)
+    #[serde(default, skip_serializing_if = "Severity::is_default")]
+    pub severity: Severity,
+    /// Stop applying this rule after this time (RFC3339, e.g. "2025-06-01T00:00:00Z")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Set to false to keep the rule in the config without applying it
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub enabled: bool,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(b: &bool) -> bool {
+    *b
+}
+
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+/// Matches `${group}` placeholders in a rule's alert names
+fn route_placeholder_regex() -> &'static Regex {
+    static PLACEHOLDER: OnceLock<Regex> = OnceLock::new();
+    PLACEHOLDER.get_or_init(|| Regex::new(r"\$\{([^}]+)\}").unwrap())
+}
+
+/// Capture group names referenced by an alert route, e.g. `service` in `slack-${service}`
+pub fn route_placeholders(route: &str) -> Vec<&str> {
+    route_placeholder_regex()
+        .captures_iter(route)
+        .map(|c| c.get(1).unwrap().as_str())
+        .collect()
+}
+
+/// Fill an alert route's placeholders from a line's captures
+/// Returns None if a referenced group did not participate in the match
+pub fn resolve_route(route: &str, captures: &regex::Captures) -> Option<String> {
+    let mut resolved = String::with_capacity(route.len());
+    let mut last = 0;
+    for placeholder in route_placeholder_regex().captures_iter(route) {
+        let whole = placeholder.get(0).unwrap();
+        let value = captures.name(&placeholder[1])?.as_str();
+        resolved.push_str(&route[last..whole.start()]);
+        resolved.push_str(value);
+        last = whole.end();
+    }
+    resolved.push_str(&route[last..]);
+    Some(resolved)
+}
+
+/// Resolve a rule's alert routes for a matching line
+/// `${group}` placeholders are filled from the line's named captures, which are only looked up if a
+/// route has placeholders; routes that can't be resolved to a registered alert are dropped
+pub fn resolve_routes<'h>(
+    rule_name: &str,
+    routes: &[String],
+    captures: impl FnOnce() -> Option<regex::Captures<'h>>,
+    is_registered: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    if routes.iter().all(|route| !route.contains("${")) {
+        return routes.to_vec();
+    }
+
+    let captures = captures();
+    routes
+        .iter()
+        .filter_map(|route| {
+            if !route.contains("${") {
+                return Some(route.clone());
+            }
+            let Some(resolved) = captures.as_ref().and_then(|captures| resolve_route(route, captures)) else {
+                tracing::warn!("Rule '{}' could not resolve alert route '{}'", rule_name, route);
+                return None;
+            };
+            if !is_registered(&resolved) {
+                tracing::warn!(
+                    "Rule '{}' routed to unknown alert '{}' via '{}'; skipping it",
+                    rule_name,
+                    resolved,
+                    route
+                );
+                return None;
+            }
+            Some(resolved)
+        })
+        .collect()
+}
+
+/// Key a rule's cooldown is tracked under: the rule name, plus the resolved destinations when its
+/// routes have placeholders, so an alert routed to one service doesn't hold back another's
+pub fn route_cooldown_key(rule_name: &str, routes: &[String], resolved: &[String]) -> String {
+    if routes.iter().all(|route| !route.contains("${")) {
+        return rule_name.to_string();
+    }
+    format!("{}:{}", rule_name, resolved.join(","))
+}
+
+/// Render a rule's message template, filling `${name}` and `${1}` from a line's captures
+/// Groups that don't exist or did not participate in the match render as empty text
+pub fn render_message(template: &str, captures: Option<&regex::Captures>) -> String {
+    route_placeholder_regex()
+        .replace_all(template, |placeholder: &regex::Captures| {
+            let group = &placeholder[1];
+            let value = match group.parse::<usize>() {
+                Ok(index) => captures.and_then(|captures| captures.get(index)),
+                Err(_) => captures.and_then(|captures| captures.name(group)),
+            };
+            value.map_or("", |value| value.as_str()).to_string()
+        })
+        .into_owned()
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-pub struct Rule {
-    pub name: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub text: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pattern: Option<String>,
-    #[serde(deserialize_with = "string_or_seq_string")]
-    pub alert: Vec<String>,  // Can be a single alert name or list of alert names
-    #[serde(default = "default_cooldown")]
-    pub cooldown: u64,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub sources: Option<RuleSources>,
-    /// Optional threshold for rate-based alerting (e.g., "5 in 2s")
-    /// If specified, alert only when the pattern matches this many times within the window
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub threshold: Option<Threshold>,
+/// Whether a configured alert name fits an alert route, with each placeholder standing for any text
+pub fn route_matches(route: &str, alert_name: &str) -> bool {
+    let mut pattern = String::from("^");
+    let mut last = 0;
+    for placeholder in route_placeholder_regex().find_iter(route) {
+        pattern.push_str(&regex::escape(&route[last..placeholder.start()]));
+        pattern.push_str(".+");
+        last = placeholder.end();
+    }
+    pattern.push_str(&regex::escape(&route[last..]));
+    pattern.push('$');
+
+    Regex::new(&pattern).is_ok_and(|regex| regex.is_match(alert_name))
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -283,6 +1715,13 @@ pub struct RuleSources {
     pub files: Vec<PathBuf>,
     #[serde(default)]
     pub streams: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub windows_eventlog: Vec<String>,
+    /// Journal inputs, named as in `inputs.journald`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub journals: Vec<String>,    /// Kubernetes inputs, by pod name or selector as in `inputs.kubernetes`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pods: Vec<String>,
 }
 
 // Helper function to deserialize either a string or array of strings
@@ -319,20 +1758,143 @@ where
     deserializer.deserialize_any(StringOrVec)
 }
 
+/// Deserialize either a single value or a list of values
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany<T> {
+        One(T),
+        Many(Vec<T>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ResourceConfig {
-    #[serde(default = "default_interval")]
+    #[serde(default = "default_interval", deserialize_with = "seconds::deserialize")]
     pub interval: u64,
+    /// Minimum time between repeated alerts for the same metric, in seconds or as "5m" (default 6x interval)
+    #[serde(default, with = "optional_seconds", skip_serializing_if = "Option::is_none")]
+    pub cooldown: Option<Duration>,
     pub thresholds: ResourceThresholds,
 }
 
+impl ResourceConfig {
+    pub fn cooldown_secs(&self) -> u64 {
+        self.cooldown
+            .map(|cooldown| cooldown.as_secs())
+            .unwrap_or(self.interval * 6)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ResourceThresholds {
-    pub cpu_percent: Option<f32>,
-    pub memory_percent: Option<f32>,
-    pub disk_percent: Option<f32>,
+    pub cpu_percent: Option<Limit<f32>>,
+    pub memory_percent: Option<Limit<f32>>,
+    /// Alert when available memory drops below this size (e.g. "2GB")
+    #[serde(default, with = "optional_size", skip_serializing_if = "Option::is_none")]
+    pub memory_available_min: Option<u64>,
+    pub disk_percent: Option<Limit<f32>>,
+    /// Alert when a mount's available space drops below this size (e.g. "10GB")
+    #[serde(default, with = "optional_size", skip_serializing_if = "Option::is_none")]
+    pub disk_available_min: Option<u64>,
+    /// Per-mount disk thresholds, overriding `disk_percent` for those mount points
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub disk_mounts: HashMap<String, Limit<f32>>,
+    /// Only check mounts matching one of these globs (plus any in `disk_mounts`); empty means all
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disk_include: Vec<String>,
+    /// Never check mounts matching one of these globs, e.g. `/boot` or `/media/*`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disk_exclude: Vec<String>,
+    /// Percentage of used inodes per mount (Unix only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inode_percent: Option<Limit<f32>>,
+    /// Per-interface throughput limits, averaged over each interval
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<NetworkThresholds>,
+    /// 1-minute load average (Linux and macOS)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load1: Option<Limit<f64>>,
+    /// 5-minute load average (Linux and macOS)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load5: Option<Limit<f64>>,
+    /// 15-minute load average (Linux and macOS)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load15: Option<Limit<f64>>,
     #[serde(deserialize_with = "string_or_seq_string")]
     pub alert: Vec<String>,  // Now references alert names (can be multiple)
+    /// Send an alert when a breached metric drops back under its limit
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub notify_recovery: bool,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct NetworkThresholds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rx_bytes_per_sec: Option<Limit<u64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_bytes_per_sec: Option<Limit<u64>>,
+}
+
+/// A resource threshold, written either as a bare value (`cpu_percent: 85`) or with a
+/// duration the value must stay above before alerting (`cpu_percent: { above: 85, for: 5m }`)
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(from = "LimitRepr<T>", into = "LimitRepr<T>")]
+pub struct Limit<T: Copy> {
+    pub above: T,
+    pub sustain: Option<Duration>,
+}
+
+impl<T: Copy> Limit<T> {
+    pub fn new(above: T) -> Self {
+        Self { above, sustain: None }
+    }
+
+    /// ` for 5m` when the limit has a duration, for alert and validate output
+    pub fn sustain_label(&self) -> String {
+        match self.sustain {
+            Some(sustain) => format!(" for {}", format_duration(sustain)),
+            None => String::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum LimitRepr<T> {
+    Value(T),
+    Sustained {
+        above: T,
+        #[serde(rename = "for", default, with = "optional_duration", skip_serializing_if = "Option::is_none")]
+        sustain: Option<Duration>,
+    },
+}
+
+impl<T: Copy> From<LimitRepr<T>> for Limit<T> {
+    fn from(repr: LimitRepr<T>) -> Self {
+        match repr {
+            LimitRepr::Value(above) => Limit::new(above),
+            LimitRepr::Sustained { above, sustain } => Limit { above, sustain },
+        }
+    }
+}
+
+impl<T: Copy> From<Limit<T>> for LimitRepr<T> {
+    fn from(limit: Limit<T>) -> Self {
+        match limit.sustain {
+            None => LimitRepr::Value(limit.above),
+            sustain => LimitRepr::Sustained { above: limit.above, sustain },
+        }
+    }
 }
 
 fn default_cooldown() -> u64 {
@@ -361,12 +1923,296 @@ fn default_heartbeat_interval() -> u64 {
 
 impl Config {
     pub fn from_file(path: &str) -> anyhow::Result<Self> {
-        let content = std::fs::read_to_string(path)?;
-        let mut config: Config = serde_yaml::from_str(&content)?;
+        let mut config = Self::load_with_includes(Path::new(path), &mut Vec::new())?;
         config.expand_env_vars();
         Ok(config)
     }
 
+    /// Parse a config file and merge in everything it includes
+    /// `chain` holds the files currently being loaded, so an include cycle is reported instead of recursing forever
+    fn load_with_includes(path: &Path, chain: &mut Vec<PathBuf>) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let mut config = Self::parse(path, &content)?;
+
+        let canonical = path.canonicalize()?;
+        if chain.contains(&canonical) {
+            let cycle: Vec<String> = chain
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|file| file.display().to_string())
+                .collect();
+            anyhow::bail!("Config include cycle: {}", cycle.join(" -> "));
+        }
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        let includes = std::mem::take(&mut config.include);
+        chain.push(canonical);
+        for pattern in includes {
+            for file in resolve_include(base, &pattern)? {
+                let included = Self::load_with_includes(&file, chain).with_context(|| {
+                    format!("Failed to load {} (included from {})", file.display(), path.display())
+                })?;
+                config.merge(included);
+            }
+        }
+        chain.pop();
+
+        Ok(config)
+    }
+
+    /// Parse config text in the format its extension names: TOML for `.toml`, YAML for anything else
+    fn parse(path: &Path, content: &str) -> anyhow::Result<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("toml") => Ok(toml::from_str(content)?),
+            _ => Ok(serde_yaml::from_str(content)?),
+        }
+    }
+
+    /// Append an included file's rules, alerts and system checks; on a name clash the including file's alert wins
+    fn merge(&mut self, included: Config) {
+        self.rules.extend(included.rules);
+        self.system_checks.extend(included.system_checks);
+        for (name, alert) in included.alerts {
+            self.alerts.entry(name).or_insert(alert);
+        }
+    }
+
+    /// Timestamp settings shared by all alert handlers
+    pub fn time_settings(&self) -> anyhow::Result<crate::timestamp::TimeSettings> {
+        let mut settings = crate::timestamp::TimeSettings::default();
+        if let Some(zone) = &self.timezone {
+            settings.zone = crate::timestamp::Zone::parse(zone)?;
+        }
+        if let Some(format) = &self.time_format {
+            crate::timestamp::validate_format(format)?;
+            settings.format = format.clone();
+        }
+        Ok(settings)
+    }
+
+    /// Validate cross-references that can't be expressed in the config schema
+    /// Every alert destination, HTTP check and heartbeat URL or email address that won't parse
+    /// Collected rather than failing on the first, so one run of `tinywatcher test` shows them all
+    pub fn address_errors(&self) -> Vec<String> {
+        let mut names: Vec<&String> = self.alerts.keys().collect();
+        names.sort();
+        let mut errors: Vec<String> = names
+            .into_iter()
+            .flat_map(|name| self.alerts[name].address_errors(name))
+            .collect();
+        errors.extend(self.system_checks.iter().filter_map(SystemCheck::url_error));
+        errors.extend(self.heartbeat.iter().filter_map(HeartbeatConfig::url_error));
+        errors
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut names: Vec<&String> = self.alerts.keys().collect();
+        names.sort();
+        for name in names {
+            self.alerts[name].validate(name)?;
+        }
+
+        let address_errors = self.address_errors();
+        if !address_errors.is_empty() {
+            anyhow::bail!("Invalid addresses in config:\n  {}", address_errors.join("\n  "));
+        }
+
+        for (source, rate) in &self.inputs.sample_rate {
+            if !(0.0..=1.0).contains(rate) {
+                anyhow::bail!("Sample rate for '{}' must be between 0.0 and 1.0, got {}", source, rate);
+            }
+            glob::Pattern::new(source)
+                .map_err(|e| anyhow::anyhow!("Invalid sample_rate source '{}': {}", source, e))?;
+        }
+
+        if let Some(selector) = &self.inputs.container_selector {
+            if selector.labels.iter().any(|label| label.trim().is_empty() || label.starts_with('=')) {
+                anyhow::bail!("container_selector labels must be 'key' or 'key=value'");
+            }
+            if selector.rescan().is_zero() {
+                anyhow::bail!("container_selector rescan must be greater than zero");
+            }
+        }
+
+        for kubernetes in &self.inputs.kubernetes {
+            kubernetes.validate()?;
+        }
+
+        if let Some(resources) = &self.resources {
+            for pattern in resources.thresholds.disk_include.iter().chain(&resources.thresholds.disk_exclude) {
+                glob::Pattern::new(pattern)
+                    .map_err(|e| anyhow::anyhow!("Invalid disk mount pattern '{}': {}", pattern, e))?;
+            }
+        }
+
+        for check in &self.system_checks {
+            match check.check_type {
+                SystemCheckType::Clock => {
+                    crate::clock_skew::ClockReference::parse(&check.url)
+                        .map_err(|e| anyhow::anyhow!("System check '{}': {}", check.name, e))?;
+                }
+                SystemCheckType::Tcp => {
+                    crate::health_monitor::tcp_address(&check.url)
+                        .map_err(|e| anyhow::anyhow!("System check '{}': {}", check.name, e))?;
+                }
+                SystemCheckType::Ping => {
+                    if check.url.trim().is_empty() || check.url.contains("://") {
+                        anyhow::bail!("Ping check '{}' needs a host name or IP address as its url", check.name);
+                    }
+                    let settings = check.ping_settings();
+                    if !(1..=100).contains(&settings.count) {
+                        anyhow::bail!("Ping check '{}': ping_count must be between 1 and 100", check.name);
+                    }
+                    if !(0.0..=100.0).contains(&settings.max_loss) {
+                        anyhow::bail!("Ping check '{}': max_packet_loss must be between 0 and 100", check.name);
+                    }
+                }
+                SystemCheckType::Exec => {
+                    if check.command.as_deref().is_none_or(|command| command.trim().is_empty()) {
+                        anyhow::bail!("Exec check '{}' needs a 'command' to run", check.name);
+                    }
+                }
+                SystemCheckType::Http => {
+                    if let Some(status) = check.expected_status {
+                        if !(100..=599).contains(&status) {
+                            anyhow::bail!("System check '{}': expected_status {} is not an HTTP status code", check.name, status);
+                        }
+                    }
+                    if let Some(body) = &check.expect_body {
+                        crate::health_monitor::BodyExpectation::parse(body)
+                            .map_err(|e| anyhow::anyhow!("System check '{}': {:#}", check.name, e))?;
+                    }
+                }
+            }
+            if check.check_type != SystemCheckType::Exec {
+                if check.url.trim().is_empty() {
+                    anyhow::bail!("System check '{}' needs a 'url'", check.name);
+                }
+                if check.command.is_some() || !check.args.is_empty() {
+                    anyhow::bail!("System check '{}' uses 'command'/'args', which require type 'exec'", check.name);
+                }
+            }
+            if check.max_skew.is_some() && check.check_type != SystemCheckType::Clock {
+                anyhow::bail!("System check '{}' uses 'max_skew', which requires type 'clock'", check.name);
+            }
+            if (check.expected_status.is_some() || check.expect_body.is_some())
+                && check.check_type != SystemCheckType::Http
+            {
+                anyhow::bail!("System check '{}' uses expected_status/expect_body, which require type 'http'", check.name);
+            }
+            if check.uses_ping_options() && check.check_type != SystemCheckType::Ping {
+                anyhow::bail!("System check '{}' uses ping options, which require type 'ping'", check.name);
+            }
+        }
+
+        self.time_settings()?;
+
+        if let Some(dedup) = &self.dedup {
+            dedup.normalizers()?;
+            if dedup.window().is_zero() {
+                anyhow::bail!("'dedup' window must be greater than zero");
+            }
+        }
+
+        if self.max_watchers == Some(0) {
+            anyhow::bail!("'max_watchers' must be at least 1");
+        }
+
+        if let Some(deploy) = &self.deploy {
+            for (marker, pattern) in [("start", Some(&deploy.start)), ("end", deploy.end.as_ref())] {
+                if let Some(pattern) = pattern {
+                    Regex::new(pattern)
+                        .map_err(|e| anyhow::anyhow!("Invalid deploy {} pattern '{}': {}", marker, pattern, e))?;
+                }
+            }
+        }
+
+        for stream in &self.inputs.streams {
+            if let Some(decode_errors) = &stream.decode_errors {
+                for alert_name in &decode_errors.alert {
+                    if !self.alerts.contains_key(alert_name) {
+                        anyhow::bail!(
+                            "Stream '{}' decode_errors references undefined alert '{}'",
+                            stream.get_name(),
+                            alert_name
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(unreadable_files) = &self.unreadable_files {
+            for alert_name in &unreadable_files.alert {
+                if !self.alerts.contains_key(alert_name) {
+                    anyhow::bail!("'unreadable_files' references undefined alert '{}'", alert_name);
+                }
+            }
+            if unreadable_files.interval().is_zero() {
+                anyhow::bail!("'unreadable_files' interval must be greater than zero");
+            }
+        }
+
+        if let Some(silent_sources) = &self.silent_sources {
+            for alert_name in &silent_sources.alert {
+                if !self.alerts.contains_key(alert_name) {
+                    anyhow::bail!("'silent_sources' references undefined alert '{}'", alert_name);
+                }
+            }
+            if silent_sources.timeout().is_zero() {
+                anyhow::bail!("'silent_sources' timeout must be greater than zero");
+            }
+            silent_sources.source_timeouts()?;
+        }
+
+        if let Some(delivery_failures) = &self.delivery_failures {
+            if !self.alerts.contains_key(&delivery_failures.alert) {
+                anyhow::bail!(
+                    "'delivery_failures' references undefined alert '{}'",
+                    delivery_failures.alert
+                );
+            }
+            if delivery_failures.window().is_zero() {
+                anyhow::bail!("'delivery_failures' window must be greater than zero");
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve the file and container sources to watch
+    /// Files are kept before containers; anything over `max_watchers` is dropped
+    pub fn watch_sources(&self) -> anyhow::Result<WatchSources> {
+        let mut files = self.expand_file_globs()?;
+        let mut containers = self.inputs.containers.clone();
+        let mut dropped = Vec::new();
+
+        if let Some(max_watchers) = self.max_watchers {
+            if files.len() > max_watchers {
+                dropped.extend(
+                    files
+                        .split_off(max_watchers)
+                        .into_iter()
+                        .map(|file| format!("file {}", file.display())),
+                );
+            }
+
+            let remaining = max_watchers - files.len();
+            if containers.len() > remaining {
+                dropped.extend(
+                    containers
+                        .split_off(remaining)
+                        .into_iter()
+                        .map(|container| format!("container {}", container)),
+                );
+            }
+        }
+
+        Ok(WatchSources {
+            files,
+            containers,
+            dropped,
+        })
+    }
+
     /// Expand glob patterns in file paths
     /// Returns a new list of files with all globs expanded
     pub fn expand_file_globs(&self) -> anyhow::Result<Vec<PathBuf>> {
@@ -421,14 +2267,25 @@ impl Config {
         // Expand in alerts
         for alert in self.alerts.values_mut() {
             match &mut alert.options {
-                AlertOptions::Slack { url } => {
+                AlertOptions::Slack { url, .. } => {
                     *url = expand_env_vars(url);
                 }
-                AlertOptions::Webhook { url } => {
+                AlertOptions::Webhook { url, headers, secret, .. } => {
                     *url = expand_env_vars(url);
+                    if let Some(secret) = secret {
+                        *secret = expand_env_vars(secret);
+                    }
+                    if let Some(headers) = headers {
+                        for value in headers.values_mut() {
+                            *value = expand_env_vars(value);
+                        }
+                    }
                 }
-                AlertOptions::Discord { url } => {
+                AlertOptions::Discord { url, thread_id, .. } => {
                     *url = expand_env_vars(url);
+                    if let Some(thread_id) = thread_id {
+                        *thread_id = expand_env_vars(thread_id);
+                    }
                 }
                 AlertOptions::Telegram { bot_token, chat_id } => {
                     *bot_token = expand_env_vars(bot_token);
@@ -437,12 +2294,24 @@ impl Config {
                 AlertOptions::PagerDuty { routing_key } => {
                     *routing_key = expand_env_vars(routing_key);
                 }
+                AlertOptions::Opsgenie { api_key, priority: _ } => {
+                    *api_key = expand_env_vars(api_key);
+                }
                 AlertOptions::Ntfy { topic, server } => {
                     *topic = expand_env_vars(topic);
                     if let Some(srv) = server {
                         *srv = expand_env_vars(srv);
                     }
                 }
+                AlertOptions::Gotify { url, token, priority: _ } => {
+                    *url = expand_env_vars(url);
+                    *token = expand_env_vars(token);
+                }
+                AlertOptions::LocalSyslog { .. } => {}
+                AlertOptions::Pushover { token, user, priority: _ } => {
+                    *token = expand_env_vars(token);
+                    *user = expand_env_vars(user);
+                }
                 AlertOptions::Email { from, to, smtp_server } => {
                     *from = expand_env_vars(from);
                     for email in to.iter_mut() {
@@ -459,7 +2328,19 @@ impl Config {
                         *email = expand_env_vars(email);
                     }
                 }
-                AlertOptions::Stdout {} => {}
+                AlertOptions::Forward { url } => {
+                    *url = expand_env_vars(url);
+                }
+                AlertOptions::File { path, format: _ } => {
+                    *path = PathBuf::from(expand_env_vars(&path.to_string_lossy()));
+                }
+                AlertOptions::Exec { command, args, timeout: _ } => {
+                    *command = expand_env_vars(command);
+                    for arg in args.iter_mut() {
+                        *arg = expand_env_vars(arg);
+                    }
+                }
+                AlertOptions::Stdout {} | AlertOptions::Desktop {} => {}
             }
         }
 
@@ -482,7 +2363,7 @@ impl Config {
         }
 
         // Expand in heartbeat
-        if let Some(heartbeat) = &mut self.heartbeat {
+        for heartbeat in &mut self.heartbeat {
             heartbeat.url = expand_env_vars(&heartbeat.url);
         }
 
@@ -515,11 +2396,50 @@ impl StreamConfig {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum SourceType {
     File(PathBuf),
     Container(String),
     Stream(String),
+    /// Windows Event Log channel name
+    #[cfg(windows)]
+    EventLog(String),
+    /// systemd journal unit or match, as configured in `inputs.journald`
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    Journal(String),    /// Kubernetes pod name or selector, as configured in `inputs.kubernetes`
+    Pod(String),
+}
+
+impl SourceType {
+    /// Kind of input, as used in alert sources and metric labels
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SourceType::File(_) => "file",
+            SourceType::Container(_) => "container",
+            SourceType::Stream(_) => "stream",
+            #[cfg(windows)]
+            SourceType::EventLog(_) => "eventlog",
+            SourceType::Journal(_) => "journal",
+            SourceType::Pod(_) => "pod",
+        }
+    }
+
+    /// File path or input name
+    pub fn name(&self) -> String {
+        match self {
+            SourceType::File(path) => path.display().to_string(),
+            SourceType::Container(name) | SourceType::Stream(name) | SourceType::Journal(name) | SourceType::Pod(name) => {
+                name.clone()
+            }
+            #[cfg(windows)]
+            SourceType::EventLog(log) => log.clone(),
+        }
+    }
+}
+
+/// Whether a rule's `expires_at` has passed as of `now`; rules without one never expire
+pub fn is_expired(expires_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    expires_at.is_some_and(|expires_at| expires_at <= now)
 }
 
 impl Rule {
@@ -534,8 +2454,200 @@ impl Rule {
                 "Rule '{}' cannot have both 'text' and 'pattern' fields", 
                 self.name
             ),
-            _ => Ok(()),
+            _ => {}
+        }
+
+        if let Some(exclude) = &self.exclude {
+            Regex::new(exclude)
+                .map_err(|e| anyhow::anyhow!("Invalid exclude pattern in rule '{}': {}", self.name, e))?;
+        }
+
+        if !self.fields.is_empty() {
+            let Some(ref pattern) = self.pattern else {
+                anyhow::bail!(
+                    "Rule '{}' uses 'fields', which requires a regex 'pattern'",
+                    self.name
+                );
+            };
+
+            // An invalid pattern is reported separately when the rule is compiled
+            if let Ok(regex) = Regex::new(pattern) {
+                for field in &self.fields {
+                    if !regex.capture_names().flatten().any(|name| name == field) {
+                        anyhow::bail!(
+                            "Rule '{}' field '{}' is not a named capture group in its pattern",
+                            self.name,
+                            field
+                        );
+                    }
+                }
+            }
         }
+
+        for route in &self.alert {
+            let placeholders = route_placeholders(route);
+            if placeholders.is_empty() {
+                continue;
+            }
+
+            let Some(ref pattern) = self.pattern else {
+                anyhow::bail!(
+                    "Rule '{}' alert '{}' uses capture placeholders, which require a regex 'pattern'",
+                    self.name,
+                    route
+                );
+            };
+
+            if self.expect_within.is_some() {
+                anyhow::bail!(
+                    "Rule '{}' cannot combine 'expect_within' with capture placeholders in 'alert'",
+                    self.name
+                );
+            }
+
+            if let Ok(regex) = Regex::new(pattern) {
+                for placeholder in placeholders {
+                    if !regex.capture_names().flatten().any(|name| name == placeholder) {
+                        anyhow::bail!(
+                            "Rule '{}' alert '{}' references '{}', which is not a named capture group in its pattern",
+                            self.name,
+                            route,
+                            placeholder
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(template) = &self.message {
+            self.validate_message(template)?;
+        }
+
+        if self.expect_within.is_some() && self.threshold.is_some() {
+            anyhow::bail!(
+                "Rule '{}' cannot combine 'expect_within' with 'threshold'",
+                self.name
+            );
+        }
+
+        if let Some(resolve) = &self.resolve {
+            if self.expect_within.is_some() {
+                anyhow::bail!(
+                    "Rule '{}' cannot combine 'expect_within' with 'resolve'",
+                    self.name
+                );
+            }
+            Regex::new(resolve)
+                .map_err(|e| anyhow::anyhow!("Invalid resolve pattern in rule '{}': {}", self.name, e))?;
+        }
+
+        if self.case_insensitive && self.text.is_none() {
+            anyhow::bail!(
+                "Rule '{}' uses 'case_insensitive', which only applies to 'text'; add (?i) to the pattern instead",
+                self.name
+            );
+        }
+
+        if let Some(path) = &self.json_field {
+            if path.split('.').any(|key| key.trim().is_empty()) {
+                anyhow::bail!("Rule '{}' has an invalid json_field '{}'", self.name, path);
+            }
+        }
+
+        if let Some(multiline) = &self.multiline {
+            if self.expect_within.is_some() {
+                anyhow::bail!(
+                    "Rule '{}' cannot combine 'expect_within' with 'multiline'",
+                    self.name
+                );
+            }
+            Regex::new(&multiline.start).map_err(|e| {
+                anyhow::anyhow!("Invalid multiline start pattern in rule '{}': {}", self.name, e)
+            })?;
+            if multiline.max_lines == Some(0) {
+                anyhow::bail!("Rule '{}' multiline max_lines must be at least 1", self.name);
+            }
+        }
+
+        if self.once && self.expect_within.is_some() {
+            anyhow::bail!(
+                "Rule '{}' cannot combine 'expect_within' with 'once'",
+                self.name
+            );
+        }
+
+        if self.burst > 0 && self.cooldown == 0 {
+            anyhow::bail!(
+                "Rule '{}' uses 'burst', which requires a non-zero 'cooldown'",
+                self.name
+            );
+        }
+
+        if self.include_matches > 0 && self.threshold.is_none() {
+            anyhow::bail!(
+                "Rule '{}' uses 'include_matches', which requires a 'threshold'",
+                self.name
+            );
+        }
+
+        if let Some(ref condition) = self.condition {
+            let Some(ref pattern) = self.pattern else {
+                anyhow::bail!(
+                    "Rule '{}' uses 'condition', which requires a regex 'pattern'",
+                    self.name
+                );
+            };
+
+            if let Ok(regex) = Regex::new(pattern) {
+                if !regex.capture_names().flatten().any(|name| name == condition.field) {
+                    anyhow::bail!(
+                        "Rule '{}' condition field '{}' is not a named capture group in its pattern",
+                        self.name,
+                        condition.field
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the rule's `expires_at` has passed as of `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        is_expired(self.expires_at, now)
+    }
+
+    /// Check that a message template only references capture groups the pattern has
+    fn validate_message(&self, template: &str) -> anyhow::Result<()> {
+        let placeholders = route_placeholders(template);
+        if placeholders.is_empty() {
+            return Ok(());
+        }
+
+        let Some(ref pattern) = self.pattern else {
+            anyhow::bail!(
+                "Rule '{}' message uses capture placeholders, which require a regex 'pattern'",
+                self.name
+            );
+        };
+        let Ok(regex) = Regex::new(pattern) else {
+            return Ok(());
+        };
+
+        for placeholder in placeholders {
+            let exists = match placeholder.parse::<usize>() {
+                Ok(index) => index < regex.captures_len(),
+                Err(_) => regex.capture_names().flatten().any(|name| name == placeholder),
+            };
+            if !exists {
+                anyhow::bail!(
+                    "Rule '{}' message references '{}', which is not a capture group in its pattern",
+                    self.name,
+                    placeholder
+                );
+            }
+        }
+        Ok(())
     }
 
     /// Get the match type for this rule
@@ -580,11 +2692,31 @@ impl Rule {
                 }
                 sources.streams.iter().any(|s| s == name)
             }
+            #[cfg(windows)]
+            SourceType::EventLog(log) => {
+                if sources.windows_eventlog.is_empty() {
+                    return false;
+                }
+                sources.windows_eventlog.iter().any(|l| l.eq_ignore_ascii_case(log))
+            }
+            SourceType::Journal(name) => {
+                if sources.journals.is_empty() {
+                    return false;
+                }
+                sources.journals.iter().any(|j| j == name)
+            }
+            SourceType::Pod(name) => {
+                if sources.pods.is_empty() {
+                    return false;
+                }
+                sources.pods.iter().any(|p| p == name)
+            }
         }
     }
 }
 
 #[cfg(test)]
 #[path = "config_tests.rs"]
+#[allow(clippy::module_inception)]
 mod tests;
 
@@ -19,10 +19,23 @@ mod tests {
     fn test_identity_custom_name() {
         let identity = Identity {
             name: Some("test-server-1".to_string()),
+            include_container: false,
         };
         assert_eq!(identity.get_name(), "test-server-1");
     }
 
+    #[test]
+    fn test_identity_include_container() {
+        let config: Config = serde_yaml::from_str(
+            "identity:\n  name: web-1\n  include_container: true\nrules: []\n",
+        )
+        .unwrap();
+        assert!(config.identity.include_container);
+
+        let config: Config = serde_yaml::from_str("identity:\n  name: web-1\nrules: []\n").unwrap();
+        assert!(!config.identity.include_container);
+    }
+
     #[test]
     fn test_rule_validate_no_pattern_or_text() {
         let rule = Rule {
@@ -33,6 +46,22 @@ mod tests {
             cooldown: 60,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         };
         
         assert!(rule.validate().is_err());
@@ -48,6 +77,22 @@ mod tests {
             cooldown: 60,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         };
         
         assert!(rule.validate().is_err());
@@ -63,6 +108,22 @@ mod tests {
             cooldown: 60,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         };
         
         assert!(rule.validate().is_ok());
@@ -78,6 +139,22 @@ mod tests {
             cooldown: 60,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         };
         
         assert!(rule.validate().is_ok());
@@ -93,6 +170,22 @@ mod tests {
             cooldown: 60,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         };
         
         match rule.match_type() {
@@ -111,6 +204,22 @@ mod tests {
             cooldown: 60,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         };
         
         match rule.match_type() {
@@ -129,6 +238,22 @@ mod tests {
             cooldown: 60,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         };
         
         // Should apply to all sources when no filter is specified
@@ -149,8 +274,27 @@ mod tests {
                 files: vec![PathBuf::from("/var/log/app.log")],
                 containers: vec![],
                 streams: vec![],
+                windows_eventlog: vec![],
+                journals: vec![],
+                pods: vec![],
             }),
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         };
         
         // Should match the specified file
@@ -176,8 +320,27 @@ mod tests {
                 files: vec![],
                 containers: vec!["nginx".to_string(), "api".to_string()],
                 streams: vec![],
+                windows_eventlog: vec![],
+                journals: vec![],
+                pods: vec![],
             }),
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         };
         
         // Should match specified containers
@@ -204,8 +367,27 @@ mod tests {
                 files: vec![],
                 containers: vec![],
                 streams: vec!["azure_webapp".to_string()],
+                windows_eventlog: vec![],
+                journals: vec![],
+                pods: vec![],
             }),
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         };
         
         // Should match specified stream
@@ -227,6 +409,7 @@ mod tests {
             url: "wss://example.com/logs".to_string(),
             headers: None,
             reconnect_delay: None,
+            decode_errors: None,
         };
         
         assert_eq!(stream.get_name(), "my-stream");
@@ -240,6 +423,7 @@ mod tests {
             url: "wss://example.com/logs".to_string(),
             headers: None,
             reconnect_delay: None,
+            decode_errors: None,
         };
         
         let name = stream.get_name();
@@ -255,6 +439,7 @@ mod tests {
             url: "localhost:514".to_string(),
             headers: None,
             reconnect_delay: None,
+            decode_errors: None,
         };
         
         assert_eq!(stream.get_reconnect_delay(), 5);
@@ -268,6 +453,7 @@ mod tests {
             url: "localhost:514".to_string(),
             headers: None,
             reconnect_delay: Some(10),
+            decode_errors: None,
         };
         
         assert_eq!(stream.get_reconnect_delay(), 10);
@@ -290,7 +476,7 @@ rules: []
         config.expand_env_vars();
         
         if let Some(alert) = config.alerts.get("slack") {
-            if let AlertOptions::Slack { url } = &alert.options {
+            if let AlertOptions::Slack { url, .. } = &alert.options {
                 assert_eq!(url, "https://hooks.slack.com/test");
             } else {
                 panic!("Expected Slack alert");
@@ -316,7 +502,7 @@ rules: []
         config.expand_env_vars();
         
         if let Some(alert) = config.alerts.get("slack") {
-            if let AlertOptions::Slack { url } = &alert.options {
+            if let AlertOptions::Slack { url, .. } = &alert.options {
                 // Should be empty string when var doesn't exist
                 assert_eq!(url, "");
             }
@@ -397,10 +583,10 @@ stdout:
         
         let alerts: HashMap<String, Alert> = serde_yaml::from_str(yaml).unwrap();
         
-        assert_eq!(alerts.get("slack").unwrap().alert_type, AlertType::Slack);
-        assert_eq!(alerts.get("discord").unwrap().alert_type, AlertType::Discord);
-        assert_eq!(alerts.get("telegram").unwrap().alert_type, AlertType::Telegram);
-        assert_eq!(alerts.get("stdout").unwrap().alert_type, AlertType::Stdout);
+        assert_eq!(alerts.get("slack").unwrap().alert_type(), AlertType::Slack);
+        assert_eq!(alerts.get("discord").unwrap().alert_type(), AlertType::Discord);
+        assert_eq!(alerts.get("telegram").unwrap().alert_type(), AlertType::Telegram);
+        assert_eq!(alerts.get("stdout").unwrap().alert_type(), AlertType::Stdout);
     }
 
     #[test]
@@ -594,14 +780,33 @@ alert: slack
             inputs: Inputs {
                 files: vec![file1_path.clone(), file2_path.clone()],
                 containers: vec![],
+                runtime: None,
+                container_selector: None,
                 streams: vec![],
+                windows_eventlog: vec![],
+                journald: vec![],
+                kubernetes: vec![],
+                sample_rate: HashMap::new(),
             },
             alerts: std::collections::HashMap::new(),
             rules: vec![],
             resources: None,
             identity: Identity::default(),
             system_checks: vec![],
-            heartbeat: None,
+            heartbeat: vec![],
+            cooldown_mode: CooldownMode::Fixed,
+            max_watchers: None,
+            delivery_failures: None,
+            alert_retry: None,
+            deploy: None,
+            state_dir: None,
+            unreadable_files: None,
+            dedup: None,
+            timezone: None,
+            time_format: None,
+            metrics: None,
+            silent_sources: None,
+            include: Vec::new(),
         };
 
         let expanded = config.expand_file_globs().unwrap();
@@ -631,14 +836,33 @@ alert: slack
             inputs: Inputs {
                 files: vec![pattern],
                 containers: vec![],
+                runtime: None,
+                container_selector: None,
                 streams: vec![],
+                windows_eventlog: vec![],
+                journald: vec![],
+                kubernetes: vec![],
+                sample_rate: HashMap::new(),
             },
             alerts: std::collections::HashMap::new(),
             rules: vec![],
             resources: None,
             identity: Identity::default(),
             system_checks: vec![],
-            heartbeat: None,
+            heartbeat: vec![],
+            cooldown_mode: CooldownMode::Fixed,
+            max_watchers: None,
+            delivery_failures: None,
+            alert_retry: None,
+            deploy: None,
+            state_dir: None,
+            unreadable_files: None,
+            dedup: None,
+            timezone: None,
+            time_format: None,
+            metrics: None,
+            silent_sources: None,
+            include: Vec::new(),
         };
 
         let expanded = config.expand_file_globs().unwrap();
@@ -669,14 +893,33 @@ alert: slack
             inputs: Inputs {
                 files: vec![pattern, file3_path.clone()],
                 containers: vec![],
+                runtime: None,
+                container_selector: None,
                 streams: vec![],
+                windows_eventlog: vec![],
+                journald: vec![],
+                kubernetes: vec![],
+                sample_rate: HashMap::new(),
             },
             alerts: std::collections::HashMap::new(),
             rules: vec![],
             resources: None,
             identity: Identity::default(),
             system_checks: vec![],
-            heartbeat: None,
+            heartbeat: vec![],
+            cooldown_mode: CooldownMode::Fixed,
+            max_watchers: None,
+            delivery_failures: None,
+            alert_retry: None,
+            deploy: None,
+            state_dir: None,
+            unreadable_files: None,
+            dedup: None,
+            timezone: None,
+            time_format: None,
+            metrics: None,
+            silent_sources: None,
+            include: Vec::new(),
         };
 
         let expanded = config.expand_file_globs().unwrap();
@@ -698,14 +941,33 @@ alert: slack
             inputs: Inputs {
                 files: vec![pattern],
                 containers: vec![],
+                runtime: None,
+                container_selector: None,
                 streams: vec![],
+                windows_eventlog: vec![],
+                journald: vec![],
+                kubernetes: vec![],
+                sample_rate: HashMap::new(),
             },
             alerts: std::collections::HashMap::new(),
             rules: vec![],
             resources: None,
             identity: Identity::default(),
             system_checks: vec![],
-            heartbeat: None,
+            heartbeat: vec![],
+            cooldown_mode: CooldownMode::Fixed,
+            max_watchers: None,
+            delivery_failures: None,
+            alert_retry: None,
+            deploy: None,
+            state_dir: None,
+            unreadable_files: None,
+            dedup: None,
+            timezone: None,
+            time_format: None,
+            metrics: None,
+            silent_sources: None,
+            include: Vec::new(),
         };
 
         let expanded = config.expand_file_globs().unwrap();
@@ -733,14 +995,33 @@ alert: slack
             inputs: Inputs {
                 files: vec![pattern],
                 containers: vec![],
+                runtime: None,
+                container_selector: None,
                 streams: vec![],
+                windows_eventlog: vec![],
+                journald: vec![],
+                kubernetes: vec![],
+                sample_rate: HashMap::new(),
             },
             alerts: std::collections::HashMap::new(),
             rules: vec![],
             resources: None,
             identity: Identity::default(),
             system_checks: vec![],
-            heartbeat: None,
+            heartbeat: vec![],
+            cooldown_mode: CooldownMode::Fixed,
+            max_watchers: None,
+            delivery_failures: None,
+            alert_retry: None,
+            deploy: None,
+            state_dir: None,
+            unreadable_files: None,
+            dedup: None,
+            timezone: None,
+            time_format: None,
+            metrics: None,
+            silent_sources: None,
+            include: Vec::new(),
         };
 
         let expanded = config.expand_file_globs().unwrap();
@@ -771,14 +1052,33 @@ alert: slack
             inputs: Inputs {
                 files: vec![pattern],
                 containers: vec![],
+                runtime: None,
+                container_selector: None,
                 streams: vec![],
+                windows_eventlog: vec![],
+                journald: vec![],
+                kubernetes: vec![],
+                sample_rate: HashMap::new(),
             },
             alerts: std::collections::HashMap::new(),
             rules: vec![],
             resources: None,
             identity: Identity::default(),
             system_checks: vec![],
-            heartbeat: None,
+            heartbeat: vec![],
+            cooldown_mode: CooldownMode::Fixed,
+            max_watchers: None,
+            delivery_failures: None,
+            alert_retry: None,
+            deploy: None,
+            state_dir: None,
+            unreadable_files: None,
+            dedup: None,
+            timezone: None,
+            time_format: None,
+            metrics: None,
+            silent_sources: None,
+            include: Vec::new(),
         };
 
         let expanded = config.expand_file_globs().unwrap();
@@ -807,14 +1107,33 @@ alert: slack
             inputs: Inputs {
                 files: vec![pattern],
                 containers: vec![],
+                runtime: None,
+                container_selector: None,
                 streams: vec![],
+                windows_eventlog: vec![],
+                journald: vec![],
+                kubernetes: vec![],
+                sample_rate: HashMap::new(),
             },
             alerts: std::collections::HashMap::new(),
             rules: vec![],
             resources: None,
             identity: Identity::default(),
             system_checks: vec![],
-            heartbeat: None,
+            heartbeat: vec![],
+            cooldown_mode: CooldownMode::Fixed,
+            max_watchers: None,
+            delivery_failures: None,
+            alert_retry: None,
+            deploy: None,
+            state_dir: None,
+            unreadable_files: None,
+            dedup: None,
+            timezone: None,
+            time_format: None,
+            metrics: None,
+            silent_sources: None,
+            include: Vec::new(),
         };
 
         let expanded = config.expand_file_globs().unwrap();
@@ -830,14 +1149,33 @@ alert: slack
             inputs: Inputs {
                 files: vec![PathBuf::from("[invalid")],
                 containers: vec![],
+                runtime: None,
+                container_selector: None,
                 streams: vec![],
+                windows_eventlog: vec![],
+                journald: vec![],
+                kubernetes: vec![],
+                sample_rate: HashMap::new(),
             },
             alerts: std::collections::HashMap::new(),
             rules: vec![],
             resources: None,
             identity: Identity::default(),
             system_checks: vec![],
-            heartbeat: None,
+            heartbeat: vec![],
+            cooldown_mode: CooldownMode::Fixed,
+            max_watchers: None,
+            delivery_failures: None,
+            alert_retry: None,
+            deploy: None,
+            state_dir: None,
+            unreadable_files: None,
+            dedup: None,
+            timezone: None,
+            time_format: None,
+            metrics: None,
+            silent_sources: None,
+            include: Vec::new(),
         };
 
         let result = config.expand_file_globs();
@@ -866,8 +1204,8 @@ heartbeat:
 "#;
 
         let config: Config = serde_yaml::from_str(yaml).unwrap();
-        assert!(config.heartbeat.is_some());
-        let heartbeat = config.heartbeat.unwrap();
+        assert_eq!(config.heartbeat.len(), 1);
+        let heartbeat = &config.heartbeat[0];
         assert_eq!(heartbeat.url, "https://heartbeat.example.com/ping/abc123");
         assert_eq!(heartbeat.interval, 60);
     }
@@ -893,11 +1231,29 @@ heartbeat:
 "#;
 
         let config: Config = serde_yaml::from_str(yaml).unwrap();
-        assert!(config.heartbeat.is_some());
-        let heartbeat = config.heartbeat.unwrap();
+        assert_eq!(config.heartbeat.len(), 1);
+        let heartbeat = &config.heartbeat[0];
         assert_eq!(heartbeat.interval, 60); // Default value
     }
 
+    #[test]
+    fn test_heartbeat_config_list() {
+        let yaml = r#"
+heartbeat:
+  - url: "https://deadman.example.com/ping/abc123"
+    interval: 60
+  - url: "http://monitor.internal/heartbeat"
+    interval: 15
+"#;
+
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.heartbeat.len(), 2);
+        assert_eq!(config.heartbeat[0].url, "https://deadman.example.com/ping/abc123");
+        assert_eq!(config.heartbeat[0].interval, 60);
+        assert_eq!(config.heartbeat[1].url, "http://monitor.internal/heartbeat");
+        assert_eq!(config.heartbeat[1].interval, 15);
+    }
+
     #[test]
     fn test_heartbeat_config_optional() {
         let yaml = r#"
@@ -916,7 +1272,7 @@ rules:
 "#;
 
         let config: Config = serde_yaml::from_str(yaml).unwrap();
-        assert!(config.heartbeat.is_none());
+        assert!(config.heartbeat.is_empty());
     }
 
     #[test]
@@ -945,11 +1301,1640 @@ heartbeat:
         let mut config: Config = serde_yaml::from_str(yaml).unwrap();
         config.expand_env_vars();
         
-        assert!(config.heartbeat.is_some());
-        let heartbeat = config.heartbeat.unwrap();
+        assert_eq!(config.heartbeat.len(), 1);
+        let heartbeat = &config.heartbeat[0];
         assert_eq!(heartbeat.url, "https://heartbeat.example.com/ping/xyz");
         assert_eq!(heartbeat.interval, 30);
         
         std::env::remove_var("HEARTBEAT_URL");
     }
+
+    #[test]
+    fn test_rule_validate_fields_require_pattern() {
+        let rule = Rule {
+            name: "test".to_string(),
+            text: Some("error".to_string()),
+            pattern: None,
+            alert: vec!["slack".to_string()],
+            cooldown: 60,
+            sources: None,
+            threshold: None,
+            fields: vec!["user".to_string()],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        };
+
+        let err = rule.validate().unwrap_err();
+        assert!(err.to_string().contains("requires a regex 'pattern'"));
+    }
+
+    #[test]
+    fn test_rule_validate_fields_unknown_capture() {
+        let rule = Rule {
+            name: "test".to_string(),
+            text: None,
+            pattern: Some(r"user=(?P<user>\w+)".to_string()),
+            alert: vec!["slack".to_string()],
+            cooldown: 60,
+            sources: None,
+            threshold: None,
+            fields: vec!["user".to_string(), "status".to_string()],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        };
+
+        let err = rule.validate().unwrap_err();
+        assert!(err.to_string().contains("'status'"));
+    }
+
+    #[test]
+    fn test_rule_fields_from_yaml() {
+        let yaml = r#"
+name: "http_error"
+pattern: 'status=(?P<status>\d+)'
+alert: "webhook"
+fields: ["status"]
+"#;
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rule.fields, vec!["status".to_string()]);
+        assert!(rule.validate().is_ok());
+    }
+
+    #[test]
+    fn test_rule_burst() {
+        let yaml = "name: new_errors\ntext: ERROR\nalert: slack\ncooldown: 300\nburst: 3\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rule.burst, 3);
+        assert!(rule.validate().is_ok());
+
+        let yaml = "name: new_errors\ntext: ERROR\nalert: slack\ncooldown: 0\nburst: 3\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_alert_options_follow_declared_type() {
+        let yaml = r#"
+webhook:
+  type: webhook
+  url: "https://myapi.com/alert"
+discord:
+  type: discord
+  url: "https://discord.com/api/webhooks/test"
+sendgrid:
+  type: sendgrid
+  api_key: "SG.key"
+  from: "alerts@example.com"
+  to: ["oncall@example.com"]
+"#;
+
+        let alerts: HashMap<String, Alert> = serde_yaml::from_str(yaml).unwrap();
+
+        assert!(matches!(alerts["webhook"].options, AlertOptions::Webhook { .. }));
+        assert!(matches!(alerts["discord"].options, AlertOptions::Discord { .. }));
+        match &alerts["sendgrid"].options {
+            AlertOptions::SendGrid { api_key, .. } => assert_eq!(api_key, "SG.key"),
+            other => panic!("Expected SendGrid options, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_discord_thread_and_mentions() {
+        let yaml = r#"
+discord:
+  type: discord
+  url: "https://discord.com/api/webhooks/test"
+  thread_id: "987654321"
+  mentions: ["@here", "role:111"]
+"#;
+
+        let alerts: HashMap<String, Alert> = serde_yaml::from_str(yaml).unwrap();
+        match &alerts["discord"].options {
+            AlertOptions::Discord { thread_id, mentions, .. } => {
+                assert_eq!(thread_id.as_deref(), Some("987654321"));
+                assert_eq!(mentions, &vec!["@here".to_string(), "role:111".to_string()]);
+            }
+            other => panic!("Expected Discord options, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_alert_missing_required_option() {
+        let yaml = r#"
+alerts:
+  team_slack:
+    type: slack
+"#;
+
+        let err = serde_yaml::from_str::<Config>(yaml).unwrap_err().to_string();
+        assert!(err.contains("team_slack"), "error should name the alert: {}", err);
+        assert!(err.contains("url"), "error should name the missing option: {}", err);
+    }
+
+    #[test]
+    fn test_alert_serialization_round_trip() {
+        let yaml = r#"
+type: ntfy
+topic: "alerts"
+"#;
+
+        let alert: Alert = serde_yaml::from_str(yaml).unwrap();
+        let serialized = serde_yaml::to_string(&alert).unwrap();
+        let reparsed: Alert = serde_yaml::from_str(&serialized).unwrap();
+
+        assert_eq!(reparsed.alert_type(), AlertType::Ntfy);
+        assert!(reparsed.validate("ntfy").is_ok());
+    }
+
+    #[test]
+    fn test_condition_parse_symbols() {
+        let condition = Condition::parse("ms > 1000").unwrap();
+        assert_eq!(condition.field, "ms");
+        assert_eq!(condition.op, Comparison::Gt);
+        assert_eq!(condition.value, 1000.0);
+
+        assert_eq!(Condition::parse("ms>=1.5").unwrap().op, Comparison::Ge);
+        assert_eq!(Condition::parse("free <= -2").unwrap().value, -2.0);
+        assert_eq!(Condition::parse("  free < 10  ").unwrap().op, Comparison::Lt);
+    }
+
+    #[test]
+    fn test_condition_parse_words() {
+        assert_eq!(Condition::parse("ms gt 1000").unwrap().op, Comparison::Gt);
+        assert_eq!(Condition::parse("ms lt 1000").unwrap().op, Comparison::Lt);
+        assert_eq!(Condition::parse("ms ge 1000").unwrap().op, Comparison::Ge);
+        assert_eq!(Condition::parse("ms le 1000").unwrap().op, Comparison::Le);
+    }
+
+    #[test]
+    fn test_condition_parse_invalid() {
+        assert!(Condition::parse("ms == 1000").is_err());
+        assert!(Condition::parse("ms > fast").is_err());
+        assert!(Condition::parse("> 1000").is_err());
+        assert!(Condition::parse("msgt1000").is_err());
+    }
+
+    #[test]
+    fn test_condition_is_met() {
+        let condition = Condition::parse("ms > 1000").unwrap();
+        assert!(condition.is_met("1500"));
+        assert!(!condition.is_met("1000"));
+        assert!(!condition.is_met("999"));
+        assert!(!condition.is_met("n/a"));
+    }
+
+    #[test]
+    fn test_condition_serde_round_trip() {
+        let yaml = r#"
+name: slow_requests
+pattern: 'duration=(?P<ms>\d+)ms'
+alert: slack
+condition: "ms >= 1000"
+"#;
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        let condition = rule.condition.clone().unwrap();
+        assert_eq!(condition.to_string(), "ms >= 1000");
+        assert!(rule.validate().is_ok());
+
+        let serialized = serde_yaml::to_string(&rule).unwrap();
+        assert!(serialized.contains("condition: ms >= 1000"));
+    }
+
+    #[test]
+    fn test_rule_validate_condition_requires_named_group() {
+        let yaml = r#"
+name: slow_requests
+pattern: 'duration=(\d+)ms'
+alert: slack
+condition: "ms > 1000"
+"#;
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        let err = rule.validate().unwrap_err().to_string();
+        assert!(err.contains("condition field 'ms'"), "{}", err);
+
+        let yaml = r#"
+name: slow_requests
+text: "duration="
+alert: slack
+condition: "ms > 1000"
+"#;
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms").unwrap(), std::time::Duration::from_millis(500));
+        assert_eq!(parse_duration("30s").unwrap(), std::time::Duration::from_secs(30));
+        assert_eq!(parse_duration("5m").unwrap(), std::time::Duration::from_secs(300));
+        assert_eq!(parse_duration(" 2h ").unwrap(), std::time::Duration::from_secs(7200));
+        assert!(parse_duration("5").is_err());
+        assert!(parse_duration("5d").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(std::time::Duration::from_millis(1500)), "1500ms");
+        assert_eq!(format_duration(std::time::Duration::from_secs(90)), "90s");
+        assert_eq!(format_duration(std::time::Duration::from_secs(300)), "5m");
+        assert_eq!(format_duration(std::time::Duration::from_secs(7200)), "2h");
+    }
+
+    #[test]
+    fn test_parse_size() {
+        const GB: u64 = 1 << 30;
+        assert_eq!(parse_size("2GB").unwrap(), 2 * GB);
+        assert_eq!(parse_size("500MB").unwrap(), 500 << 20);
+        assert_eq!(parse_size("1.5G").unwrap(), GB + GB / 2);
+        assert_eq!(parse_size(" 4 gib ").unwrap(), 4 * GB);
+        assert_eq!(parse_size("64k").unwrap(), 64 << 10);
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert_eq!(parse_size("512B").unwrap(), 512);
+        assert!(parse_size("").is_err());
+        assert!(parse_size("GB").is_err());
+        assert!(parse_size("2PB").is_err());
+        assert!(parse_size("-1GB").is_err());
+        assert!(parse_size("2 gigabytes").is_err());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(2 << 30), "2GB");
+        assert_eq!(format_size(1536 << 20), "1536MB");
+        assert_eq!(format_size(1000), "1000B");
+        assert_eq!(format_size(0), "0B");
+    }
+
+    #[test]
+    fn test_available_min_thresholds() {
+        let yaml = "resources:\n  thresholds:\n    memory_percent: 90\n    memory_available_min: 2GB\n    disk_available_min: 10737418240\n    alert: ops\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let thresholds = &config.resources.as_ref().unwrap().thresholds;
+        assert_eq!(thresholds.memory_available_min, Some(2 << 30));
+        assert_eq!(thresholds.disk_available_min, Some(10 << 30));
+
+        let serialized = serde_yaml::to_string(thresholds).unwrap();
+        assert!(serialized.contains("disk_available_min: 10GB"), "unexpected yaml: {}", serialized);
+
+        assert!(serde_yaml::from_str::<Config>(&yaml.replace("2GB", "lots")).is_err());
+    }
+
+    #[test]
+    fn test_rule_expect_within() {
+        let yaml = r#"
+name: backup_completed
+text: "backup completed"
+alert: slack
+expect_within: "25h"
+"#;
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rule.expect_within, Some(std::time::Duration::from_secs(25 * 3600)));
+        assert!(rule.validate().is_ok());
+        assert!(serde_yaml::to_string(&rule).unwrap().contains("expect_within: 25h"));
+
+        let rule: Rule = serde_yaml::from_str("name: r\ntext: x\nalert: slack\n").unwrap();
+        assert_eq!(rule.expect_within, None);
+    }
+
+    #[test]
+    fn test_rule_expect_within_rejects_threshold() {
+        let yaml = r#"
+name: backup_completed
+text: "backup completed"
+alert: slack
+expect_within: "1h"
+threshold: "2 in 1h"
+"#;
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_cooldown_mode() {
+        let config: Config = serde_yaml::from_str("rules: []").unwrap();
+        assert_eq!(config.cooldown_mode, CooldownMode::Fixed);
+
+        let config: Config = serde_yaml::from_str("cooldown_mode: sliding\nrules: []").unwrap();
+        assert_eq!(config.cooldown_mode, CooldownMode::Sliding);
+
+        assert!(serde_yaml::from_str::<Config>("cooldown_mode: rolling\nrules: []").is_err());
+    }
+
+    #[test]
+    fn test_rule_include_matches() {
+        let yaml = r#"
+name: error_burst
+text: "ERROR"
+alert: slack
+threshold: "5 in 2s"
+include_matches: 3
+"#;
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rule.include_matches, 3);
+        assert!(rule.validate().is_ok());
+
+        let rule: Rule = serde_yaml::from_str("name: r\ntext: x\nalert: slack\n").unwrap();
+        assert_eq!(rule.include_matches, 0);
+        assert!(!serde_yaml::to_string(&rule).unwrap().contains("include_matches"));
+    }
+
+    #[test]
+    fn test_rule_include_matches_requires_threshold() {
+        let rule: Rule =
+            serde_yaml::from_str("name: r\ntext: ERROR\nalert: slack\ninclude_matches: 3\n").unwrap();
+        let err = rule.validate().unwrap_err().to_string();
+        assert!(err.contains("requires a 'threshold'"));
+    }
+
+    #[test]
+    fn test_gotify_alert_config() {
+        env::set_var("TEST_GOTIFY_TOKEN", "AbC123");
+
+        let yaml = r#"
+alerts:
+  gotify:
+    type: gotify
+    url: "https://gotify.example.com"
+    token: "${TEST_GOTIFY_TOKEN}"
+    priority: 5
+rules: []
+"#;
+
+        let mut config: Config = serde_yaml::from_str(yaml).unwrap();
+        config.expand_env_vars();
+
+        let alert = config.alerts.get("gotify").unwrap();
+        assert_eq!(alert.alert_type(), AlertType::Gotify);
+        match &alert.options {
+            AlertOptions::Gotify { url, token, priority } => {
+                assert_eq!(url, "https://gotify.example.com");
+                assert_eq!(token, "AbC123");
+                assert_eq!(*priority, Some(5));
+            }
+            other => panic!("Expected Gotify options, got {:?}", other),
+        }
+
+        env::remove_var("TEST_GOTIFY_TOKEN");
+    }
+
+    #[test]
+    fn test_opsgenie_alert_config() {
+        env::set_var("TEST_OPSGENIE_KEY", "key-123");
+        let yaml = r#"
+alerts:
+  oncall:
+    type: opsgenie
+    api_key: "${TEST_OPSGENIE_KEY}"
+    priority: P2
+  default_priority:
+    type: opsgenie
+    api_key: "key"
+rules: []
+"#;
+
+        let mut config: Config = serde_yaml::from_str(yaml).unwrap();
+        config.expand_env_vars();
+        assert!(config.validate().is_ok());
+
+        let alert = config.alerts.get("oncall").unwrap();
+        assert_eq!(alert.alert_type(), AlertType::Opsgenie);
+        match &alert.options {
+            AlertOptions::Opsgenie { api_key, priority } => {
+                assert_eq!(api_key, "key-123");
+                assert_eq!(*priority, Some(OpsgeniePriority::P2));
+            }
+            other => panic!("Expected Opsgenie options, got {:?}", other),
+        }
+        assert!(matches!(
+            config.alerts["default_priority"].options,
+            AlertOptions::Opsgenie { priority: None, .. }
+        ));
+
+        let invalid = "alerts:\n  oncall:\n    type: opsgenie\n    api_key: key\n    priority: P9\n";
+        assert!(serde_yaml::from_str::<Config>(invalid).is_err());
+
+        env::remove_var("TEST_OPSGENIE_KEY");
+    }
+
+    #[test]
+    fn test_webhook_template_config() {
+        env::set_var("TEST_WEBHOOK_KEY", "secret");
+        let yaml = r#"
+alerts:
+  events:
+    type: webhook
+    url: "https://events.example.com/v1"
+    method: put
+    headers:
+      X-Api-Key: "${TEST_WEBHOOK_KEY}"
+    template: '{"title": "{{rule}}", "text": "{{message}}"}'
+rules: []
+"#;
+        let mut config: Config = serde_yaml::from_str(yaml).unwrap();
+        config.expand_env_vars();
+        assert!(config.validate().is_ok());
+        match &config.alerts["events"].options {
+            AlertOptions::Webhook { method, headers, template, content_type, .. } => {
+                assert_eq!(method.as_deref(), Some("put"));
+                assert_eq!(headers.as_ref().unwrap()["X-Api-Key"], "secret");
+                assert_eq!(template.as_deref(), Some(r#"{"title": "{{rule}}", "text": "{{message}}"}"#));
+                assert_eq!(*content_type, None);
+            }
+            other => panic!("Expected Webhook options, got {:?}", other),
+        }
+        env::remove_var("TEST_WEBHOOK_KEY");
+
+        let bad_method = "alerts:\n  events:\n    type: webhook\n    url: http://x\n    method: \"NOT VALID\"\n";
+        let config: Config = serde_yaml::from_str(bad_method).unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("events") && err.contains("method"), "{}", err);
+    }
+
+    #[test]
+    fn test_webhook_secret_config() {
+        env::set_var("TEST_WEBHOOK_SECRET", "s3cret");
+        let yaml = "alerts:\n  hook:\n    type: webhook\n    url: http://x\n    secret: \"${TEST_WEBHOOK_SECRET}\"\n    signature_header: X-Hub-Signature\n";
+        let mut config: Config = serde_yaml::from_str(yaml).unwrap();
+        config.expand_env_vars();
+        assert!(config.validate().is_ok());
+        assert!(matches!(
+            &config.alerts["hook"].options,
+            AlertOptions::Webhook { secret: Some(secret), signature_header: Some(header), .. }
+                if secret == "s3cret" && header == "X-Hub-Signature"
+        ));
+        env::remove_var("TEST_WEBHOOK_SECRET");
+
+        let bad_header = "alerts:\n  hook:\n    type: webhook\n    url: http://x\n    secret: s\n    signature_header: \"bad header\"\n";
+        let config: Config = serde_yaml::from_str(bad_header).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_file_alert_config() {
+        let yaml = r#"
+alerts:
+  archive:
+    type: file
+    path: /var/log/tinywatcher/alerts.jsonl
+    format: json
+  plain:
+    type: file
+    path: alerts.log
+rules: []
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.alerts["archive"].alert_type(), AlertType::File);
+        match &config.alerts["archive"].options {
+            AlertOptions::File { path, format } => {
+                assert_eq!(path, &PathBuf::from("/var/log/tinywatcher/alerts.jsonl"));
+                assert_eq!(*format, FileFormat::Json);
+            }
+            other => panic!("Expected File options, got {:?}", other),
+        }
+        assert!(matches!(
+            config.alerts["plain"].options,
+            AlertOptions::File { format: FileFormat::Text, .. }
+        ));
+    }
+
+    #[test]
+    fn test_desktop_alert_config() {
+        let config: Config = serde_yaml::from_str("alerts:\n  laptop:\n    type: desktop\nrules: []\n").unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.alerts["laptop"].alert_type(), AlertType::Desktop);
+        assert!(matches!(config.alerts["laptop"].options, AlertOptions::Desktop {}));
+    }
+
+    #[test]
+    fn test_exec_alert_config() {
+        let yaml = r#"
+alerts:
+  ticket:
+    type: exec
+    command: /usr/local/bin/open-ticket
+    args: ["--queue", "ops"]
+    timeout: 10s
+rules: []
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_ok());
+        let alert = &config.alerts["ticket"];
+        assert_eq!(alert.alert_type(), AlertType::Exec);
+        match &alert.options {
+            AlertOptions::Exec { command, args, timeout } => {
+                assert_eq!(command, "/usr/local/bin/open-ticket");
+                assert_eq!(args, &["--queue", "ops"]);
+                assert_eq!(*timeout, Some(Duration::from_secs(10)));
+            }
+            other => panic!("Expected Exec options, got {:?}", other),
+        }
+
+        let missing = "alerts:\n  ticket:\n    type: exec\n";
+        assert!(serde_yaml::from_str::<Config>(missing).is_err());
+    }
+
+    #[test]
+    fn test_rule_severity() {
+        let rule: Rule = serde_yaml::from_str("name: r\ntext: x\nalert: pushover\n").unwrap();
+        assert_eq!(rule.severity, Severity::Error);
+        assert!(!serde_yaml::to_string(&rule).unwrap().contains("severity"));
+
+        let rule: Rule =
+            serde_yaml::from_str("name: r\ntext: x\nalert: pushover\nseverity: critical\n").unwrap();
+        assert_eq!(rule.severity, Severity::Critical);
+        assert!(serde_yaml::to_string(&rule).unwrap().contains("severity: critical"));
+
+        assert!(serde_yaml::from_str::<Rule>("name: r\ntext: x\nalert: a\nseverity: fatal\n").is_err());
+    }
+
+    #[test]
+    fn test_pushover_alert_config() {
+        env::set_var("TEST_PUSHOVER_TOKEN", "app-token");
+        env::set_var("TEST_PUSHOVER_USER", "user-key");
+
+        let yaml = r#"
+alerts:
+  phone:
+    type: pushover
+    token: "${TEST_PUSHOVER_TOKEN}"
+    user: "${TEST_PUSHOVER_USER}"
+rules: []
+"#;
+
+        let mut config: Config = serde_yaml::from_str(yaml).unwrap();
+        config.expand_env_vars();
+
+        let alert = config.alerts.get("phone").unwrap();
+        assert_eq!(alert.alert_type(), AlertType::Pushover);
+        match &alert.options {
+            AlertOptions::Pushover { token, user, priority } => {
+                assert_eq!(token, "app-token");
+                assert_eq!(user, "user-key");
+                assert_eq!(*priority, None);
+            }
+            other => panic!("Expected Pushover options, got {:?}", other),
+        }
+
+        env::remove_var("TEST_PUSHOVER_TOKEN");
+        env::remove_var("TEST_PUSHOVER_USER");
+    }
+
+    #[test]
+    fn test_forward_alert_config() {
+        env::set_var("TEST_FORWARD_HOST", "central.internal");
+
+        let yaml = r#"
+alerts:
+  central:
+    type: forward
+    url: "https://${TEST_FORWARD_HOST}:9400/ingest"
+"#;
+        let mut config: Config = serde_yaml::from_str(yaml).unwrap();
+        config.expand_env_vars();
+
+        let alert = &config.alerts["central"];
+        assert_eq!(alert.alert_type(), AlertType::Forward);
+        match &alert.options {
+            AlertOptions::Forward { url } => assert_eq!(url, "https://central.internal:9400/ingest"),
+            other => panic!("Expected Forward options, got {:?}", other),
+        }
+        assert!(config.validate().is_ok());
+
+        env::remove_var("TEST_FORWARD_HOST");
+    }
+
+    #[test]
+    fn test_rule_expires_at() {
+        let yaml = r#"
+name: incident_1234
+text: "upstream timeout"
+alert: slack
+expires_at: "2025-06-01T00:00:00Z"
+"#;
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        let expires_at = chrono::DateTime::parse_from_rfc3339("2025-06-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        assert_eq!(rule.expires_at, Some(expires_at));
+
+        let before = expires_at - chrono::Duration::seconds(1);
+        let after = expires_at + chrono::Duration::seconds(1);
+        assert!(!rule.is_expired(before));
+        assert!(rule.is_expired(expires_at));
+        assert!(rule.is_expired(after));
+
+        let rule: Rule = serde_yaml::from_str("name: r\ntext: x\nalert: slack\n").unwrap();
+        assert!(!rule.is_expired(after));
+    }
+
+    #[test]
+    fn test_rule_expires_at_invalid() {
+        let yaml = "name: r\ntext: x\nalert: slack\nexpires_at: \"next tuesday\"\n";
+        assert!(serde_yaml::from_str::<Rule>(yaml).is_err());
+    }
+
+    #[test]
+    fn test_windows_eventlog_inputs() {
+        let yaml = r#"
+inputs:
+  windows_eventlog:
+    - log: Application
+      sources: ["Application Error"]
+      level: warning
+    - log: System
+rules: []
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let eventlogs = &config.inputs.windows_eventlog;
+        assert_eq!(eventlogs.len(), 2);
+        assert_eq!(eventlogs[0].log, "Application");
+        assert_eq!(eventlogs[0].sources, vec!["Application Error".to_string()]);
+        assert_eq!(eventlogs[0].level, Some(EventLevel::Warning));
+        assert!(eventlogs[1].sources.is_empty());
+        assert_eq!(eventlogs[1].level, None);
+    }
+
+    #[test]
+    fn test_event_level_ordering() {
+        assert_eq!(EventLevel::from_level(1), EventLevel::Critical);
+        assert_eq!(EventLevel::from_level(4), EventLevel::Information);
+        assert_eq!(EventLevel::from_level(0), EventLevel::Information);
+        assert!(EventLevel::Critical > EventLevel::Error);
+        assert!(EventLevel::Warning > EventLevel::Information);
+    }
+
+    #[test]
+    fn test_rule_alert_route_placeholders() {
+        let yaml = "name: r\npattern: 'service=(?P<service>\\w+)'\nalert: [\"${service}\", oncall]\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_ok());
+        assert_eq!(route_placeholders(&rule.alert[0]), vec!["service"]);
+        assert!(route_placeholders(&rule.alert[1]).is_empty());
+
+        let unknown_group = "name: r\npattern: 'service=(?P<service>\\w+)'\nalert: \"${team}\"\n";
+        let rule: Rule = serde_yaml::from_str(unknown_group).unwrap();
+        assert!(rule.validate().is_err());
+
+        let text_rule = "name: r\ntext: ERROR\nalert: \"${service}\"\n";
+        let rule: Rule = serde_yaml::from_str(text_rule).unwrap();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_resolve_route() {
+        let regex = Regex::new(r"service=(?P<service>\w+)(?: env=(?P<env>\w+))?").unwrap();
+
+        let captures = regex.captures("ERROR service=payments env=prod").unwrap();
+        assert_eq!(resolve_route("${service}", &captures).as_deref(), Some("payments"));
+        assert_eq!(resolve_route("slack-${service}-${env}", &captures).as_deref(), Some("slack-payments-prod"));
+
+        let captures = regex.captures("ERROR service=payments").unwrap();
+        assert_eq!(resolve_route("slack-${env}", &captures), None);
+    }
+
+    #[test]
+    fn test_route_matches() {
+        assert!(route_matches("slack-${service}", "slack-payments"));
+        assert!(!route_matches("slack-${service}", "slack-"));
+        assert!(!route_matches("slack-${service}", "discord-payments"));
+        assert!(route_matches("${service}", "payments"));
+        assert!(route_matches("ops.${team}", "ops.db"));
+        assert!(!route_matches("ops.${team}", "opsXdb"));
+    }
+
+    #[test]
+    fn test_max_watchers_drops_excess_files() {
+        let yaml = r#"
+inputs:
+  files: [/var/log/a.log, /var/log/b.log, /var/log/c.log]
+max_watchers: 2
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let sources = config.watch_sources().unwrap();
+
+        assert_eq!(
+            sources.files,
+            vec![PathBuf::from("/var/log/a.log"), PathBuf::from("/var/log/b.log")]
+        );
+        assert_eq!(sources.dropped, vec!["file /var/log/c.log".to_string()]);
+    }
+
+    #[test]
+    fn test_max_watchers_counts_files_and_containers() {
+        let yaml = r#"
+inputs:
+  files: [/var/log/a.log]
+  containers: [nginx, redis]
+max_watchers: 2
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let sources = config.watch_sources().unwrap();
+
+        assert_eq!(sources.files.len(), 1);
+        assert_eq!(sources.containers, vec!["nginx".to_string()]);
+        assert_eq!(sources.dropped, vec!["container redis".to_string()]);
+    }
+
+    #[test]
+    fn test_max_watchers_unset_watches_everything() {
+        let yaml = "inputs:\n  files: [/var/log/a.log, /var/log/b.log]\n  containers: [nginx]\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let sources = config.watch_sources().unwrap();
+
+        assert_eq!(sources.files.len(), 2);
+        assert_eq!(sources.containers.len(), 1);
+        assert!(sources.dropped.is_empty());
+    }
+
+    #[test]
+    fn test_clock_system_check() {
+        let yaml = r#"
+system_checks:
+  - name: clock
+    type: clock
+    url: "ntp://pool.ntp.org"
+    max_skew: 500ms
+    alert: ops
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let check = &config.system_checks[0];
+        assert_eq!(check.check_type, SystemCheckType::Clock);
+        assert_eq!(check.max_skew(), Duration::from_millis(500));
+        assert!(config.validate().is_ok());
+
+        let config: Config =
+            serde_yaml::from_str(&yaml.replace("ntp://pool.ntp.org", "pool.ntp.org")).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_max_skew_requires_clock_check() {
+        let yaml = "system_checks:\n  - name: api\n    type: http\n    url: http://localhost\n    max_skew: 1s\n    alert: ops\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_ping_system_check() {
+        let yaml = r#"
+system_checks:
+  - name: gateway
+    type: ping
+    url: 10.0.0.1
+    ping_count: 5
+    max_packet_loss: 20
+    ping_method: command
+    alert: ops
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let check = &config.system_checks[0];
+        assert_eq!(check.check_type, SystemCheckType::Ping);
+        let settings = check.ping_settings();
+        assert_eq!(settings.count, 5);
+        assert_eq!(settings.max_loss, 20.0);
+        assert_eq!(settings.method, PingMethod::Command);
+        assert!(config.validate().is_ok());
+
+        let config: Config = serde_yaml::from_str(&yaml.replace("max_packet_loss: 20", "max_packet_loss: 120")).unwrap();
+        assert!(config.validate().is_err());
+        let config: Config = serde_yaml::from_str(&yaml.replace("ping_count: 5", "ping_count: 0")).unwrap();
+        assert!(config.validate().is_err());
+        let config: Config = serde_yaml::from_str(&yaml.replace("10.0.0.1", "http://10.0.0.1")).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_ping_defaults_and_options_require_ping_check() {
+        let yaml = "system_checks:\n  - name: gateway\n    type: ping\n    url: gateway.local\n    alert: ops\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.system_checks[0].ping_settings(), crate::ping::PingSettings::default());
+        assert!(config.validate().is_ok());
+
+        let yaml = "system_checks:\n  - name: api\n    type: http\n    url: http://localhost\n    ping_count: 3\n    alert: ops\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_http_check_expectations() {
+        let yaml = "system_checks:\n  - name: api\n    type: http\n    url: http://localhost/login\n    expected_status: 401\n    expect_body: \"/unauthori[sz]ed/\"\n    alert: ops\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.system_checks[0].expected_status, Some(401));
+        assert_eq!(config.system_checks[0].expect_body.as_deref(), Some("/unauthori[sz]ed/"));
+        assert!(config.validate().is_ok());
+
+        for invalid in [
+            yaml.replace("401", "42"),
+            yaml.replace("/unauthori[sz]ed/", "/unauthori[sz/"),
+            yaml.replace("type: http", "type: tcp").replace("http://localhost/login", "localhost:80"),
+        ] {
+            let config: Config = serde_yaml::from_str(&invalid).unwrap();
+            assert!(config.validate().is_err(), "should be rejected: {}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_exec_system_check() {
+        let yaml = r#"
+system_checks:
+  - name: backup
+    type: exec
+    command: /usr/local/bin/check-backup
+    args: ["--max-age", "1d"]
+    timeout: 30
+    alert: ops
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let check = &config.system_checks[0];
+        assert_eq!(check.check_type, SystemCheckType::Exec);
+        assert_eq!(check.command.as_deref(), Some("/usr/local/bin/check-backup"));
+        assert_eq!(check.args, vec!["--max-age", "1d"]);
+        assert!(check.url.is_empty());
+        assert!(config.validate().is_ok());
+
+        let config: Config = serde_yaml::from_str(&yaml.replace("    command: /usr/local/bin/check-backup\n", "")).unwrap();
+        assert!(config.validate().is_err());
+
+        // Other check types still need a url and can't take a command
+        let yaml = "system_checks:\n  - name: api\n    type: http\n    command: curl\n    alert: ops\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_network_thresholds() {
+        let yaml = "resources:\n  thresholds:\n    network:\n      rx_bytes_per_sec: 10000000\n    alert: ops\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let network = config.resources.unwrap().thresholds.network.unwrap();
+        assert_eq!(network.rx_bytes_per_sec, Some(Limit::new(10_000_000)));
+        assert_eq!(network.tx_bytes_per_sec, None);
+    }
+
+    #[test]
+    fn test_disk_mount_patterns_validated() {
+        let yaml = "resources:\n  thresholds:\n    disk_percent: 90\n    disk_exclude: [/boot, \"/media/*\"]\n    alert: ops\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_ok());
+
+        let config: Config = serde_yaml::from_str(&yaml.replace("/boot", "\"/mnt/[\"")).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_dedup_config() {
+        let yaml = r#"
+dedup:
+  window: 30s
+  normalize:
+    - pattern: 'req-[0-9a-f]+'
+      replacement: 'req-*'
+    - pattern: '\d{4}-\d{2}-\d{2}T[\d:.]+Z?'
+      replacement: '*'
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let dedup = config.dedup.as_ref().unwrap();
+        assert_eq!(dedup.window(), Duration::from_secs(30));
+        assert_eq!(dedup.normalizers().unwrap().len(), 2);
+        assert!(config.validate().is_ok());
+
+        let config: Config = serde_yaml::from_str("dedup:\n  normalize:\n    - pattern: '[unclosed'\n").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_heartbeat_from_file_expands_env_vars() {
+        use tempfile::TempDir;
+
+        env::set_var("TW_TEST_HEARTBEAT_ID", "abc123");
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config.yaml");
+        std::fs::write(
+            &path,
+            "heartbeat:\n  url: \"https://hc-ping.com/${TW_TEST_HEARTBEAT_ID}\"\n  interval: 60\n",
+        )
+        .unwrap();
+
+        let config = Config::from_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(config.heartbeat.len(), 1);
+        assert_eq!(config.heartbeat[0].url, "https://hc-ping.com/abc123");
+        assert_eq!(config.heartbeat[0].interval, 60);
+
+        env::remove_var("TW_TEST_HEARTBEAT_ID");
+    }
+
+    #[test]
+    fn test_time_settings() {
+        let config: Config = serde_yaml::from_str("timezone: '+02:00'\ntime_format: '%d.%m.%Y %H:%M'\n").unwrap();
+        let settings = config.time_settings().unwrap();
+        let noon = chrono::DateTime::parse_from_rfc3339("2024-03-15T12:00:00Z").unwrap().to_utc();
+        assert_eq!(settings.display(noon), "15.03.2024 14:00");
+        assert!(config.validate().is_ok());
+
+        let config: Config = serde_yaml::from_str("").unwrap();
+        assert_eq!(config.time_settings().unwrap(), crate::timestamp::TimeSettings::default());
+
+        let config: Config = serde_yaml::from_str("timezone: Mars/Olympus\n").unwrap();
+        assert!(config.validate().is_err());
+        let config: Config = serde_yaml::from_str("time_format: '%Q'\n").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_notify_recovery_defaults_to_true() {
+        let yaml = r#"
+system_checks:
+  - name: api
+    type: http
+    url: "http://localhost:8080/health"
+    alert: ops
+  - name: batch
+    type: http
+    url: "http://localhost:9090/health"
+    notify_recovery: false
+    alert: ops
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.system_checks[0].notify_recovery);
+        assert!(!config.system_checks[1].notify_recovery);
+    }
+
+    #[test]
+    fn test_rule_resolve_pattern() {
+        let yaml = "name: db\npattern: 'db connection lost'\nresolve: 'db connection restored'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rule.resolve.as_deref(), Some("db connection restored"));
+        assert!(rule.validate().is_ok());
+
+        let yaml = "name: db\npattern: 'db connection lost'\nresolve: '[unclosed'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_rule_exclude() {
+        let yaml = "name: errors\ntext: ERROR\nexclude: 'HealthCheck|/ping'\nexclude_text: '[expected]'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rule.exclude.as_deref(), Some("HealthCheck|/ping"));
+        assert_eq!(rule.exclude_text.as_deref(), Some("[expected]"));
+        assert!(rule.validate().is_ok());
+
+        let yaml = "name: errors\ntext: ERROR\nexclude: '[unclosed'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_rule_once() {
+        let rule: Rule = serde_yaml::from_str("name: restart\ntext: 'service restarted'\nonce: true\nalert: ops\n").unwrap();
+        assert!(rule.once);
+        assert!(rule.validate().is_ok());
+
+        let rule: Rule = serde_yaml::from_str("name: restart\ntext: 'service restarted'\nalert: ops\n").unwrap();
+        assert!(!rule.once);
+
+        let yaml = "name: backup\ntext: 'backup done'\nonce: true\nexpect_within: 1h\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_rule_case_insensitive() {
+        let rule: Rule = serde_yaml::from_str("name: refused\ntext: 'connection refused'\ncase_insensitive: true\nalert: ops\n").unwrap();
+        assert!(rule.case_insensitive);
+        assert!(rule.validate().is_ok());
+
+        let rule: Rule = serde_yaml::from_str("name: refused\ntext: 'connection refused'\nalert: ops\n").unwrap();
+        assert!(!rule.case_insensitive);
+
+        // Patterns use an inline (?i) flag instead
+        let rule: Rule = serde_yaml::from_str("name: fatal\npattern: 'fatal'\ncase_insensitive: true\nalert: ops\n").unwrap();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_rule_multiline() {
+        let yaml = "name: traces\npattern: 'Exception'\nmultiline:\n  start: '^\\d{4}-'\n  timeout: 500ms\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        let multiline = rule.multiline.as_ref().unwrap();
+        assert_eq!(multiline.start, "^\\d{4}-");
+        assert_eq!(multiline.timeout(), Duration::from_millis(500));
+        assert_eq!(multiline.max_lines(), 500);
+        assert!(rule.validate().is_ok());
+
+        let yaml = "name: traces\npattern: 'Exception'\nmultiline:\n  start: '[unclosed'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+
+        let yaml = "name: traces\ntext: 'done'\nexpect_within: 1h\nmultiline:\n  start: '^\\S'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_rule_json_field() {
+        let rule: Rule = serde_yaml::from_str("name: errors\ntext: error\njson_field: error.code\nalert: ops\n").unwrap();
+        assert_eq!(rule.json_field.as_deref(), Some("error.code"));
+        assert!(rule.validate().is_ok());
+
+        for path in ["''", "error.", ".code", "error..code"] {
+            let yaml = format!("name: errors\ntext: error\njson_field: {}\nalert: ops\n", path);
+            let rule: Rule = serde_yaml::from_str(&yaml).unwrap();
+            assert!(rule.validate().is_err(), "json_field {} should be rejected", path);
+        }
+    }
+
+    #[test]
+    fn test_render_message() {
+        let regex = Regex::new(r"(?P<method>[A-Z]+) (\S+) (?P<status>\d+)").unwrap();
+        let captures = regex.captures("GET /api 503");
+        assert_eq!(
+            render_message("${method} ${2} returned ${status}", captures.as_ref()),
+            "GET /api returned 503"
+        );
+        assert_eq!(render_message("${0}!", captures.as_ref()), "GET /api 503!");
+        assert_eq!(render_message("no groups here", None), "no groups here");
+        assert_eq!(render_message("missing ${user}", captures.as_ref()), "missing ");
+    }
+
+    #[test]
+    fn test_rule_message_template_validation() {
+        let yaml = "name: http\npattern: '(?P<status>5\\d\\d) (\\S+)'\nmessage: '${status} on ${2}'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_ok());
+
+        let yaml = "name: oom\ntext: OutOfMemoryError\nmessage: 'JVM ran out of memory'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_ok());
+
+        // Placeholders need a pattern with matching groups
+        let yaml = "name: oom\ntext: OutOfMemoryError\nmessage: 'OOM in ${service}'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+
+        let yaml = "name: http\npattern: '(?P<status>5\\d\\d)'\nmessage: '${code} ${2}'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+
+        let yaml = "name: http\npattern: '(?P<status>5\\d\\d)'\nmessage: '${status} ${2}'\nalert: ops\n";
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        assert!(rule.validate().is_err());
+    }
+
+    #[test]
+    fn test_max_watchers_zero_is_invalid() {
+        let config: Config = serde_yaml::from_str("max_watchers: 0\n").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_sample_rate_config() {
+        let yaml = "inputs:\n  files: [/var/log/app.log]\n  sample_rate:\n    /var/log/app.log: 0.1\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.inputs.sample_rate.get("/var/log/app.log"), Some(&0.1));
+        assert!(config.validate().is_ok());
+
+        let config: Config = serde_yaml::from_str("inputs:\n  sample_rate:\n    nginx: 1.5\n").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_delivery_failures_config() {
+        let yaml = r#"
+alerts:
+  oncall:
+    type: stdout
+delivery_failures:
+  alert: oncall
+  window: 5m
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.delivery_failures.unwrap().window(), Duration::from_secs(300));
+
+        let undefined = "delivery_failures:\n  alert: oncall\n";
+        let config: Config = serde_yaml::from_str(undefined).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_alert_retry_config() {
+        let yaml = r#"
+alerts:
+  team_slack:
+    type: slack
+    url: "https://hooks.slack.com/services/T/B/X"
+    retries: 5
+    retry_backoff: 2s
+  oncall:
+    type: stdout
+alert_retry:
+  retries: 1
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let defaults = config.alert_retry.as_ref();
+        assert_eq!(config.alerts["team_slack"].retry_policy(defaults), (5, Duration::from_secs(2)));
+        assert_eq!(config.alerts["oncall"].retry_policy(defaults), (1, Duration::from_secs(1)));
+        assert_eq!(config.alerts["oncall"].retry_policy(None), (2, Duration::from_secs(1)));
+
+        // Retry options survive a round trip without leaking into the type-specific options
+        let round_trip: Config = serde_yaml::from_str(&serde_yaml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(round_trip.alerts["team_slack"].retry_policy(None), (5, Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_stream_decode_errors_config() {
+        let yaml = r#"
+alerts:
+  oncall:
+    type: stdout
+inputs:
+  streams:
+    - name: events
+      type: websocket
+      url: wss://example.com/logs
+      decode_errors:
+        alert: oncall
+        threshold: "10 in 5m"
+    - type: tcp
+      url: tcp://localhost:9000
+      decode_errors:
+        alert: oncall
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_ok());
+
+        let explicit = config.inputs.streams[0].decode_errors.as_ref().unwrap();
+        assert_eq!(explicit.alert, vec!["oncall"]);
+        assert_eq!(explicit.threshold(), Threshold::parse("10 in 5m").unwrap());
+
+        let default = config.inputs.streams[1].decode_errors.as_ref().unwrap();
+        assert_eq!(default.threshold(), Threshold::parse("5 in 1m").unwrap());
+
+        let undefined = "inputs:\n  streams:\n    - type: tcp\n      url: tcp://localhost:9000\n      decode_errors:\n        alert: missing\n";
+        let config: Config = serde_yaml::from_str(undefined).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_journald_inputs_and_rule_journal_filter() {
+        let yaml = r#"
+alerts:
+  oncall:
+    type: stdout
+inputs:
+  journald:
+    - nginx.service
+    - SYSLOG_IDENTIFIER=cron
+rules:
+  - name: nginx_errors
+    text: error
+    alert: oncall
+    sources:
+      journals: [nginx.service]
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.inputs.journald, vec!["nginx.service", "SYSLOG_IDENTIFIER=cron"]);
+
+        let rule = &config.rules[0];
+        assert!(rule.applies_to_source(&SourceType::Journal("nginx.service".to_string())));
+        assert!(!rule.applies_to_source(&SourceType::Journal("SYSLOG_IDENTIFIER=cron".to_string())));
+        assert!(!rule.applies_to_source(&SourceType::Container("nginx.service".to_string())));
+        assert!(!rule.applies_to_source(&SourceType::File(PathBuf::from("/var/log/nginx.log"))));
+
+        // A filter on other source types doesn't cover journals
+        let yaml = "rules:\n  - name: app\n    text: error\n    alert: []\n    sources:\n      containers: [nginx]\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(!config.rules[0].applies_to_source(&SourceType::Journal("nginx.service".to_string())));
+    }
+
+    #[test]
+    fn test_kubernetes_inputs_and_rule_pod_filter() {
+        let yaml = r#"
+inputs:
+  kubernetes:
+    - namespace: prod
+      selector: app=api
+      container: server
+    - pod: worker-0
+rules:
+  - name: api_errors
+    text: error
+    alert: []
+    sources:
+      pods: [app=api]
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.inputs.kubernetes[0].name(), "app=api");
+        assert_eq!(config.inputs.kubernetes[0].namespace.as_deref(), Some("prod"));
+        assert_eq!(config.inputs.kubernetes[1].name(), "worker-0");
+
+        let rule = &config.rules[0];
+        assert!(rule.applies_to_source(&SourceType::Pod("app=api".to_string())));
+        assert!(!rule.applies_to_source(&SourceType::Pod("worker-0".to_string())));
+        assert!(!rule.applies_to_source(&SourceType::Container("app=api".to_string())));
+        assert!(!rule.applies_to_source(&SourceType::Journal("app=api".to_string())));
+    }
+
+    #[test]
+    fn test_kubernetes_input_needs_pod_or_selector() {
+        for yaml in [
+            "inputs:\n  kubernetes:\n    - namespace: prod\n",
+            "inputs:\n  kubernetes:\n    - pod: worker-0\n      selector: app=worker\n",
+            "inputs:\n  kubernetes:\n    - pod: \"\"\n",
+        ] {
+            let config: Config = serde_yaml::from_str(yaml).unwrap();
+            assert!(config.validate().is_err(), "expected {:?} to be rejected", yaml);
+        }
+    }
+
+    #[test]
+    fn test_container_selector_config() {
+        let yaml = "inputs:\n  container_selector:\n    labels: [\"traefik.enable=true\"]\n    rescan: 10s\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_ok());
+        let selector = config.inputs.container_selector.as_ref().unwrap();
+        assert_eq!(selector.rescan(), Duration::from_secs(10));
+
+        let mut labels = HashMap::new();
+        labels.insert("traefik.enable".to_string(), "true".to_string());
+        assert!(selector.matches(&labels));
+        labels.insert("traefik.enable".to_string(), "false".to_string());
+        assert!(!selector.matches(&labels));
+
+        // No labels selects every container
+        let config: Config = serde_yaml::from_str("inputs:\n  container_selector: {}\n").unwrap();
+        let selector = config.inputs.container_selector.as_ref().unwrap();
+        assert_eq!(selector.rescan(), Duration::from_secs(30));
+        assert!(selector.matches(&HashMap::new()));
+
+        let invalid: Config = serde_yaml::from_str("inputs:\n  container_selector:\n    labels: [\"=true\"]\n").unwrap();
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_container_runtime_commands() {
+        let config: Config = serde_yaml::from_str("inputs:\n  containers: [api]\n").unwrap();
+        assert_eq!(config.inputs.runtime(), ContainerRuntime::Docker);
+
+        for (name, runtime) in [
+            ("docker", ContainerRuntime::Docker),
+            ("podman", ContainerRuntime::Podman),
+            ("nerdctl", ContainerRuntime::Nerdctl),
+        ] {
+            let yaml = format!("inputs:\n  runtime: {}\n  containers: [api]\n", name);
+            let config: Config = serde_yaml::from_str(&yaml).unwrap();
+            assert_eq!(config.inputs.runtime(), runtime);
+            assert_eq!(runtime.binary(), name);
+            assert_eq!(runtime.follow_args("api"), vec!["logs", "-f", "--tail", "0", "api"]);
+            assert_eq!(runtime.tail_args(50, "api"), vec!["logs", "--tail", "50", "api"]);
+            assert_eq!(runtime.ps_args(), vec!["ps", "--format", "{{.Names}}\t{{json .Labels}}"]);
+        }
+
+        assert!(serde_yaml::from_str::<Config>("inputs:\n  runtime: lxc\n").is_err());
+    }
+
+    #[test]
+    fn test_container_runtime_missing_binary_error() {
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        let message = ContainerRuntime::Podman.command_error(missing).to_string();
+        assert!(message.contains("'podman' was not found on PATH"), "unexpected message: {}", message);
+        assert!(message.contains("inputs.runtime"), "unexpected message: {}", message);
+
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let message = ContainerRuntime::Nerdctl.command_error(denied).to_string();
+        assert!(message.starts_with("Failed to run nerdctl"), "unexpected message: {}", message);
+    }
+
+    #[tokio::test]
+    async fn test_container_runtime_missing_binary_is_reported_on_spawn() {
+        // No such binary exists, so spawning fails with NotFound like a missing runtime would
+        let error = tokio::process::Command::new("tinywatcher-no-such-runtime")
+            .output()
+            .await
+            .unwrap_err();
+        let message = ContainerRuntime::Nerdctl.command_error(error).to_string();
+        assert!(message.contains("not found on PATH"), "unexpected message: {}", message);
+    }
+
+    #[test]
+    fn test_tcp_system_check_config() {
+        let yaml = "system_checks:\n  - name: redis\n    type: tcp\n    url: tcp://localhost:6379\n    alert: []\n";
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.system_checks[0].check_type, SystemCheckType::Tcp);
+
+        let missing_port = "system_checks:\n  - name: redis\n    type: tcp\n    url: localhost\n    alert: []\n";
+        let config: Config = serde_yaml::from_str(missing_port).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_include_merges_rules_and_alerts() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("rules.d")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("config.yaml"),
+            "include:\n  - rules.d/*.yaml\nidentity:\n  name: root\nalerts:\n  console:\n    type: stdout\nrules:\n  - name: errors\n    text: ERROR\n    alert: console\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("rules.d").join("nginx.yaml"),
+            "identity:\n  name: ignored\nalerts:\n  console:\n    type: slack\n    url: https://hooks.slack.com/x\n  pager:\n    type: stdout\nrules:\n  - name: nginx_5xx\n    pattern: 'status=5\\d\\d'\n    alert: pager\n",
+        )
+        .unwrap();
+
+        let config = Config::from_file(temp_dir.path().join("config.yaml").to_str().unwrap()).unwrap();
+        let rules: Vec<&str> = config.rules.iter().map(|rule| rule.name.as_str()).collect();
+        assert_eq!(rules, vec!["errors", "nginx_5xx"]);
+        assert_eq!(config.alerts.len(), 2);
+        // The root file wins on clashing names and scalar settings
+        assert_eq!(config.alerts["console"].alert_type(), AlertType::Stdout);
+        assert_eq!(config.identity.name.as_deref(), Some("root"));
+        assert!(config.include.is_empty());
+    }
+
+    #[test]
+    fn test_include_cycle_is_rejected() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.yaml"), "include: [b.yaml]\n").unwrap();
+        std::fs::write(temp_dir.path().join("b.yaml"), "include: [a.yaml]\n").unwrap();
+
+        let err = Config::from_file(temp_dir.path().join("a.yaml").to_str().unwrap()).unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("include cycle"), "{}", message);
+    }
+
+    #[test]
+    fn test_missing_include_fails() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("config.yaml"), "include: [missing.yaml]\n").unwrap();
+
+        let err = Config::from_file(temp_dir.path().join("config.yaml").to_str().unwrap()).unwrap_err();
+        assert!(format!("{:#}", err).contains("missing.yaml"));
+    }
+
+    const EQUIVALENT_YAML: &str = r#"
+identity:
+  name: web-1
+inputs:
+  files:
+    - /var/log/app.log
+alerts:
+  console:
+    type: stdout
+  team:
+    type: slack
+    url: https://hooks.slack.com/services/x
+    retries: 3
+rules:
+  - name: errors
+    text: ERROR
+    alert: console
+    cooldown: 60
+  - name: timeouts
+    pattern: 'timed out after (?P<secs>\d+)s'
+    alert: [console, team]
+    threshold: 5 in 2m
+    condition: secs > 10
+resources:
+  interval: 10
+  cooldown: 5m
+  thresholds:
+    cpu_percent: 90
+    memory_available_min: 2GB
+    alert: team
+system_checks:
+  - name: api
+    type: http
+    url: https://example.com/health
+    alert: team
+    threshold: 3 in 1m
+    timeout: 5
+"#;
+
+    const EQUIVALENT_TOML: &str = r#"
+[identity]
+name = "web-1"
+
+[inputs]
+files = ["/var/log/app.log"]
+
+[alerts.console]
+type = "stdout"
+
+[alerts.team]
+type = "slack"
+url = "https://hooks.slack.com/services/x"
+retries = 3
+
+[[rules]]
+name = "errors"
+text = "ERROR"
+alert = "console"
+cooldown = 60
+
+[[rules]]
+name = "timeouts"
+pattern = 'timed out after (?P<secs>\d+)s'
+alert = ["console", "team"]
+threshold = "5 in 2m"
+condition = "secs > 10"
+
+[resources]
+interval = 10
+cooldown = "5m"
+
+[resources.thresholds]
+cpu_percent = 90
+memory_available_min = "2GB"
+alert = "team"
+
+[[system_checks]]
+name = "api"
+type = "http"
+url = "https://example.com/health"
+alert = "team"
+threshold = "3 in 1m"
+timeout = 5
+"#;
+
+    #[test]
+    fn test_toml_and_yaml_load_identically() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let yaml_path = temp_dir.path().join("config.yaml");
+        let toml_path = temp_dir.path().join("config.toml");
+        std::fs::write(&yaml_path, EQUIVALENT_YAML).unwrap();
+        std::fs::write(&toml_path, EQUIVALENT_TOML).unwrap();
+
+        let from_yaml = Config::from_file(yaml_path.to_str().unwrap()).unwrap();
+        let from_toml = Config::from_file(toml_path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&from_yaml).unwrap(),
+            serde_json::to_value(&from_toml).unwrap()
+        );
+
+        let timeouts = &from_toml.rules[1];
+        assert_eq!(timeouts.alert, vec!["console", "team"]);
+        assert_eq!(timeouts.threshold.as_ref().unwrap().count, 5);
+        assert_eq!(from_toml.rules[0].alert, vec!["console"]);
+        assert!(from_toml.validate().is_ok());
+    }
+
+    #[test]
+    fn test_yaml_can_include_toml() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("config.yml"),
+            "include: [rules.toml]\nalerts:\n  console:\n    type: stdout\n",
+        )
+        .unwrap();
+        std::fs::write(
+            temp_dir.path().join("rules.toml"),
+            "[[rules]]\nname = \"errors\"\ntext = \"ERROR\"\nalert = \"console\"\nthreshold = \"3 in 1m\"\n",
+        )
+        .unwrap();
+
+        let config = Config::from_file(temp_dir.path().join("config.yml").to_str().unwrap()).unwrap();
+        assert_eq!(config.rules.len(), 1);
+        assert_eq!(config.rules[0].threshold.as_ref().unwrap().count, 3);
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_email_address() {
+        let yaml = r#"
+alerts:
+  oncall:
+    type: email
+    from: "TinyWatcher <alerts@example.com>"
+    to: ["ops@example.com", "bob.example.com"]
+rules: []
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(config.address_errors().len(), 1);
+
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("Alert 'oncall': invalid to address 'bob.example.com'"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_collects_bad_urls() {
+        let yaml = r#"
+alerts:
+  team:
+    type: slack
+    url: "hooks.slack.com/services/T000/B000/XXX"
+  hook:
+    type: webhook
+    url: "ftp://example.com/alerts"
+  fine:
+    type: discord
+    url: "https://discord.com/api/webhooks/1/abc"
+system_checks:
+  - name: api
+    type: http
+    url: "not a url"
+    alert: fine
+heartbeat:
+  url: "https://hc-ping.com/uuid"
+rules: []
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        let errors = config.address_errors();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].starts_with("Alert 'hook': invalid url 'ftp://example.com/alerts'"), "{}", errors[0]);
+        assert!(errors[1].starts_with("Alert 'team': invalid url 'hooks.slack.com"), "{}", errors[1]);
+        assert!(errors[2].starts_with("System check 'api': invalid url 'not a url'"), "{}", errors[2]);
+
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("Alert 'hook'") && err.contains("System check 'api'"), "{}", err);
+    }
+
+    #[test]
+    fn test_interval_fields_accept_duration_strings() {
+        let config_with = |value: &str| -> Config {
+            let yaml = format!(
+                r#"
+alerts:
+  console:
+    type: stdout
+inputs:
+  streams:
+    - name: events
+      type: websocket
+      url: "wss://example.com/events"
+      reconnect_delay: {value}
+rules:
+  - name: errors
+    text: ERROR
+    alert: console
+    cooldown: {value}
+resources:
+  interval: {value}
+  thresholds:
+    cpu_percent: 90
+    alert: console
+system_checks:
+  - name: api
+    type: http
+    url: "https://example.com/health"
+    interval: {value}
+    timeout: {value}
+    alert: console
+heartbeat:
+  url: "https://hc-ping.com/uuid"
+  interval: {value}
+"#
+            );
+            serde_yaml::from_str(&yaml).unwrap()
+        };
+
+        for value in ["\"5m\"", "300"] {
+            let config = config_with(value);
+            assert_eq!(config.rules[0].cooldown, 300, "{}", value);
+            assert_eq!(config.resources.as_ref().unwrap().interval, 300, "{}", value);
+            assert_eq!(config.system_checks[0].interval, 300, "{}", value);
+            assert_eq!(config.system_checks[0].timeout, 300, "{}", value);
+            assert_eq!(config.heartbeat[0].interval, 300, "{}", value);
+            assert_eq!(config.inputs.streams[0].reconnect_delay, Some(300), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_interval_rejects_bad_duration() {
+        let yaml = "rules:\n  - name: errors\n    text: ERROR\n    alert: console\n    cooldown: 1500ms\n";
+        assert!(serde_yaml::from_str::<Config>(yaml).is_err());
+
+        let yaml = "rules:\n  - name: errors\n    text: ERROR\n    alert: console\n    cooldown: soon\n";
+        assert!(serde_yaml::from_str::<Config>(yaml).is_err());
+    }
 }
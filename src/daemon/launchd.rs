@@ -1,4 +1,4 @@
-use super::{ServiceManager, ServiceStatus};
+use super::{ServiceManager, ServiceScope, ServiceStatus};
 use anyhow::{Context, Result};
 use std::fs;
 use std::io::Write;
@@ -8,15 +8,32 @@ use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
 pub struct LaunchdManager {
     service_name: String,
+    /// File stem for stdout/stderr logs, e.g. `tinywatcher` or `tinywatcher-web`
+    log_name: String,
 }
 
 impl LaunchdManager {
-    pub fn new() -> Self {
-        Self {
-            service_name: "com.tinywatcher.agent".to_string(),
+    pub fn new(instance: Option<&str>) -> Self {
+        match instance {
+            Some(name) => Self {
+                service_name: format!("com.tinywatcher.agent.{}", name),
+                log_name: format!("tinywatcher-{}", name),
+            },
+            None => Self {
+                service_name: "com.tinywatcher.agent".to_string(),
+                log_name: "tinywatcher".to_string(),
+            },
         }
     }
 
+    fn log_paths(&self, is_daemon: bool) -> (String, String) {
+        let dir = if is_daemon { "/var/log" } else { "/tmp" };
+        (
+            format!("{}/{}.log", dir, self.log_name),
+            format!("{}/{}.err", dir, self.log_name),
+        )
+    }
+
     fn get_plist_path(&self, is_daemon: bool) -> PathBuf {
         if is_daemon {
             // LaunchDaemon - system service running as root
@@ -28,6 +45,15 @@ impl LaunchdManager {
         }
     }
 
+    /// The installed plist that `status` and `status --json` report on
+    fn installed_scope(&self) -> Option<ServiceScope> {
+        super::installed_scope(
+            self.get_plist_path(true).exists(),
+            self.get_plist_path(false).exists(),
+            super::is_elevated(),
+        )
+    }
+
     fn create_plist_content(&self, config_path: Option<PathBuf>, is_daemon: bool) -> Result<String> {
         let exe_path = super::get_executable_path()?;
         let exe_path_str = exe_path.to_str().context("Invalid executable path")?;
@@ -43,11 +69,7 @@ impl LaunchdManager {
         }
         
         // For LaunchDaemons, use /var/log instead of /tmp for logs
-        let (log_path, err_path) = if is_daemon {
-            ("/var/log/tinywatcher.log", "/var/log/tinywatcher.err")
-        } else {
-            ("/tmp/tinywatcher.log", "/tmp/tinywatcher.err")
-        };
+        let (log_path, err_path) = self.log_paths(is_daemon);
         
         let plist = format!(r#"<?xml version="1.0" encoding="UTF-8"?>
 <!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
@@ -92,6 +114,12 @@ impl LaunchdManager {
 }
 
 impl ServiceManager for LaunchdManager {
+    fn preview_install(&self, config_path: Option<PathBuf>, needs_elevation: bool) -> Result<String> {
+        let plist_path = self.get_plist_path(needs_elevation);
+        let plist_content = self.create_plist_content(config_path, needs_elevation)?;
+        Ok(format!("<!-- {} -->\n{}", plist_path.display(), plist_content))
+    }
+
     fn install(&self, config_path: Option<PathBuf>, needs_elevation: bool) -> Result<()> {
         let mut stdout = StandardStream::stdout(ColorChoice::Always);
         
@@ -233,13 +261,9 @@ impl ServiceManager for LaunchdManager {
         writeln!(&mut stdout, "TinyWatcher agent installed and started!")?;
         
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
-        if is_daemon {
-            writeln!(&mut stdout, "  Logs: /var/log/tinywatcher.log")?;
-            writeln!(&mut stdout, "  Errors: /var/log/tinywatcher.err")?;
-        } else {
-            writeln!(&mut stdout, "  Logs: /tmp/tinywatcher.log")?;
-            writeln!(&mut stdout, "  Errors: /tmp/tinywatcher.err")?;
-        }
+        let (log_path, err_path) = self.log_paths(is_daemon);
+        writeln!(&mut stdout, "  Logs: {}", log_path)?;
+        writeln!(&mut stdout, "  Errors: {}", err_path)?;
         stdout.reset()?;
         
         Ok(())
@@ -539,34 +563,29 @@ impl ServiceManager for LaunchdManager {
     }
 
     fn status(&self) -> Result<ServiceStatus> {
-        // Check both LaunchAgent and LaunchDaemon locations
-        let agent_path = self.get_plist_path(false);
-        let daemon_path = self.get_plist_path(true);
-        let running_as_root = super::is_elevated();
-        
-        // Determine which service to check based on what's installed and current privileges
-        let plist_exists = if daemon_path.exists() && agent_path.exists() {
-            // Both exist - check the one that matches current user context
-            true
-        } else if daemon_path.exists() {
-            true
-        } else if agent_path.exists() {
-            true
-        } else {
+        let Some(scope) = self.installed_scope() else {
             return Ok(ServiceStatus::NotInstalled);
         };
         
-        if !plist_exists {
-            return Ok(ServiceStatus::NotInstalled);
-        }
-        
         // Check if service is loaded in the appropriate context
-        let output = if running_as_root && daemon_path.exists() {
+        let output = if scope == ServiceScope::System && super::is_elevated() {
             // Check system-level services
             Command::new("sudo")
                 .args(&["launchctl", "list"])
                 .output()
                 .context("Failed to query launchctl")?
+        } else if scope == ServiceScope::System {
+            // `launchctl list` only shows the user's own jobs; the system domain can be read without root
+            let output = Command::new("launchctl")
+                .arg("print")
+                .arg(format!("system/{}", self.service_name))
+                .output()
+                .context("Failed to query launchctl")?;
+            return Ok(if output.status.success() {
+                ServiceStatus::Running
+            } else {
+                ServiceStatus::Stopped
+            });
         } else {
             // Check user-level services
             Command::new("launchctl")
@@ -577,14 +596,65 @@ impl ServiceManager for LaunchdManager {
         
         let output_str = String::from_utf8_lossy(&output.stdout);
         
-        if output_str.contains(&self.service_name) {
+        // Match the label column exactly so `com.tinywatcher.agent` doesn't match a named instance
+        let loaded = output_str
+            .lines()
+            .any(|line| line.split_whitespace().last() == Some(self.service_name.as_str()));
+        
+        if loaded {
             Ok(ServiceStatus::Running)
         } else {
             Ok(ServiceStatus::Stopped)
         }
     }
 
+    fn service_type(&self) -> Option<&'static str> {
+        self.installed_scope().map(|scope| scope.as_str())
+    }
+
     fn service_name(&self) -> &str {
         &self.service_name
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_matches_agent_plist_content() {
+        let manager = LaunchdManager::new(None);
+        let config = Some(PathBuf::from("/etc/tinywatcher/config.yaml"));
+        let preview = manager.preview_install(config.clone(), false).unwrap();
+        let content = manager.create_plist_content(config, false).unwrap();
+
+        let (header, body) = preview.split_once('\n').unwrap();
+        assert_eq!(header, format!("<!-- {} -->", manager.get_plist_path(false).display()));
+        assert_eq!(body, content);
+        assert!(body.contains("<string>/etc/tinywatcher/config.yaml</string>"));
+        assert!(body.contains("/tmp/tinywatcher.log"));
+    }
+
+    #[test]
+    fn test_preview_matches_daemon_plist_content() {
+        let manager = LaunchdManager::new(None);
+        let preview = manager.preview_install(None, true).unwrap();
+        let content = manager.create_plist_content(None, true).unwrap();
+
+        assert!(preview.starts_with("<!-- /Library/LaunchDaemons/com.tinywatcher.agent.plist -->"));
+        assert!(preview.ends_with(&content));
+        assert!(content.contains("/var/log/tinywatcher.log"));
+    }
+
+    #[test]
+    fn test_named_instance_plist() {
+        let manager = LaunchdManager::new(Some("web"));
+        let preview = manager.preview_install(None, true).unwrap();
+
+        assert_eq!(manager.service_name(), "com.tinywatcher.agent.web");
+        assert!(preview.starts_with("<!-- /Library/LaunchDaemons/com.tinywatcher.agent.web.plist -->"));
+        assert!(preview.contains("<string>com.tinywatcher.agent.web</string>"));
+        assert!(preview.contains("/var/log/tinywatcher-web.log"));
+        assert!(preview.contains("/var/log/tinywatcher-web.err"));
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use serde::Serialize;
 use std::path::PathBuf;
 use std::process::Command;
 
@@ -14,26 +15,51 @@ mod privilege;
 pub use privilege::{is_elevated, any_file_needs_elevation, get_files_needing_elevation};
 
 /// Determine the service manager for the current platform
-pub fn get_service_manager() -> Box<dyn ServiceManager> {
+///
+/// `instance` names a separate service (`tinywatcher-<name>` on systemd) so several
+/// watchers can be installed side by side; `None` manages the default service.
+pub fn get_service_manager(instance: Option<&str>) -> Result<Box<dyn ServiceManager>> {
+    if let Some(name) = instance {
+        validate_instance_name(name)?;
+    }
+
     #[cfg(target_os = "linux")]
-    return Box::new(systemd::SystemdManager::new());
+    return Ok(Box::new(systemd::SystemdManager::new(instance)));
     
     #[cfg(target_os = "macos")]
-    return Box::new(launchd::LaunchdManager::new());
+    return Ok(Box::new(launchd::LaunchdManager::new(instance)));
     
     #[cfg(target_os = "windows")]
-    return Box::new(windows_service::WindowsServiceManager::new());
+    return Ok(Box::new(windows_service::WindowsServiceManager::new(instance)));
     
     #[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
     compile_error!("Unsupported platform for daemon mode");
 }
 
+/// Instance names end up in unit file names, launchd labels and Windows service names,
+/// so only allow characters that are safe in all three
+fn validate_instance_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        anyhow::bail!("Instance name must not be empty");
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        anyhow::bail!(
+            "Invalid instance name '{}': use only letters, digits, '-' and '_'",
+            name
+        );
+    }
+    Ok(())
+}
+
 /// Service manager trait for cross-platform daemon management
 pub trait ServiceManager: Send + Sync {
     /// Install the service
     /// If needs_elevation is true, the service will be installed with elevated privileges
     fn install(&self, config_path: Option<PathBuf>, needs_elevation: bool) -> Result<()>;
     
+    /// Render what `install` would write (unit file, plist or `sc` command) without touching the system
+    fn preview_install(&self, config_path: Option<PathBuf>, needs_elevation: bool) -> Result<String>;
+    
     /// Uninstall the service
     #[allow(dead_code)]
     fn uninstall(&self) -> Result<()>;
@@ -47,6 +73,11 @@ pub trait ServiceManager: Send + Sync {
     /// Get the status of the service
     fn status(&self) -> Result<ServiceStatus>;
     
+    /// Get the type of the installed service ("user" or "system"), if any
+    fn service_type(&self) -> Option<&'static str> {
+        None
+    }
+    
     /// Get the service name
     fn service_name(&self) -> &str {
         "tinywatcher"
@@ -73,6 +104,68 @@ impl std::fmt::Display for ServiceStatus {
     }
 }
 
+impl ServiceStatus {
+    /// Exit code for the `status` command, following LSB init script conventions
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ServiceStatus::Running => 0,
+            ServiceStatus::Stopped => 3,
+            ServiceStatus::NotInstalled => 4,
+            ServiceStatus::Unknown => 1,
+        }
+    }
+}
+
+/// Whether an installed service is per-user or system-wide
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServiceScope {
+    User,
+    System,
+}
+
+#[cfg(unix)]
+impl ServiceScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServiceScope::User => "user",
+            ServiceScope::System => "system",
+        }
+    }
+}
+
+/// The installed service that `status` (and `status --json`) reports on: the system service when
+/// running elevated or when it's the only one installed, otherwise the user service
+#[cfg(unix)]
+pub fn installed_scope(system_exists: bool, user_exists: bool, elevated: bool) -> Option<ServiceScope> {
+    if system_exists && (elevated || !user_exists) {
+        Some(ServiceScope::System)
+    } else if user_exists {
+        Some(ServiceScope::User)
+    } else {
+        None
+    }
+}
+
+/// Machine-readable service status, emitted by `tinywatcher status --json`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatusReport {
+    pub installed: bool,
+    pub running: bool,
+    pub service_type: Option<String>,
+}
+
+impl StatusReport {
+    pub fn new(status: &ServiceStatus, service_type: Option<&str>) -> Self {
+        let installed = *status != ServiceStatus::NotInstalled;
+        Self {
+            installed,
+            running: *status == ServiceStatus::Running,
+            service_type: if installed { service_type.map(str::to_string) } else { None },
+        }
+    }
+}
+
 /// Get the path to the current executable
 pub fn get_executable_path() -> Result<PathBuf> {
     std::env::current_exe().context("Failed to get current executable path")
@@ -103,3 +196,76 @@ pub fn run_command_sudo(command: &str, args: &[&str]) -> Result<bool> {
     
     Ok(output.status.success())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_instance_name() {
+        assert!(validate_instance_name("web").is_ok());
+        assert!(validate_instance_name("db_2-replica").is_ok());
+        assert!(validate_instance_name("").is_err());
+        assert!(validate_instance_name("../etc").is_err());
+        assert!(validate_instance_name("my app").is_err());
+    }
+
+    #[test]
+    fn test_get_service_manager_rejects_invalid_instance() {
+        assert!(get_service_manager(Some("bad/name")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_installed_scope() {
+        assert_eq!(installed_scope(false, false, true), None);
+        assert_eq!(installed_scope(false, true, true), Some(ServiceScope::User));
+        // A system service alone is reported even without root
+        assert_eq!(installed_scope(true, false, false), Some(ServiceScope::System));
+        assert_eq!(installed_scope(true, true, true), Some(ServiceScope::System));
+        assert_eq!(installed_scope(true, true, false), Some(ServiceScope::User));
+    }
+
+    #[test]
+    fn test_status_exit_codes() {
+        assert_eq!(ServiceStatus::Running.exit_code(), 0);
+        assert_eq!(ServiceStatus::Stopped.exit_code(), 3);
+        assert_eq!(ServiceStatus::NotInstalled.exit_code(), 4);
+        assert_eq!(ServiceStatus::Unknown.exit_code(), 1);
+    }
+
+    #[test]
+    fn test_status_report_running() {
+        let report = StatusReport::new(&ServiceStatus::Running, Some("user"));
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({"installed": true, "running": true, "service_type": "user"})
+        );
+    }
+
+    #[test]
+    fn test_status_report_stopped() {
+        let report = StatusReport::new(&ServiceStatus::Stopped, Some("system"));
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({"installed": true, "running": false, "service_type": "system"})
+        );
+    }
+
+    #[test]
+    fn test_status_report_not_installed() {
+        let report = StatusReport::new(&ServiceStatus::NotInstalled, Some("user"));
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({"installed": false, "running": false, "service_type": null})
+        );
+    }
+
+    #[test]
+    fn test_status_report_unknown() {
+        let report = StatusReport::new(&ServiceStatus::Unknown, None);
+        assert!(report.installed);
+        assert!(!report.running);
+        assert_eq!(report.service_type, None);
+    }
+}
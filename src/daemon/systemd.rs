@@ -1,4 +1,4 @@
-use super::{ServiceManager, ServiceStatus};
+use super::{ServiceManager, ServiceScope, ServiceStatus};
 use anyhow::{Context, Result};
 use std::fs;
 use std::io::Write;
@@ -11,10 +11,12 @@ pub struct SystemdManager {
 }
 
 impl SystemdManager {
-    pub fn new() -> Self {
-        Self {
-            service_name: "tinywatcher".to_string(),
-        }
+    pub fn new(instance: Option<&str>) -> Self {
+        let service_name = match instance {
+            Some(name) => format!("tinywatcher-{}", name),
+            None => "tinywatcher".to_string(),
+        };
+        Self { service_name }
     }
 
     fn get_service_path(&self, system_service: bool) -> PathBuf {
@@ -28,6 +30,15 @@ impl SystemdManager {
         }
     }
 
+    /// The installed unit that `status` and `status --json` report on
+    fn installed_scope(&self) -> Option<ServiceScope> {
+        super::installed_scope(
+            self.get_service_path(true).exists(),
+            self.get_service_path(false).exists(),
+            super::is_elevated(),
+        )
+    }
+
     fn create_service_content(&self, config_path: Option<PathBuf>, system_service: bool) -> Result<String> {
         let exe_path = super::get_executable_path()?;
         let exe_path_str = exe_path.to_str().context("Invalid executable path")?;
@@ -44,21 +55,22 @@ impl SystemdManager {
             "default.target"
         };
         
-        // For system services, we might want to add User directive if needed
-        let user_directive = if system_service {
-            // Run as root for system services to access root-owned logs
-            ""
-        } else {
-            ""
+        // System services run as root to access root-owned logs, so no User directive is needed
+        let user_directive = "";
+        
+        let description = match self.service_name.strip_prefix("tinywatcher-") {
+            Some(instance) => format!("TinyWatcher ({})", instance),
+            None => "TinyWatcher".to_string(),
         };
         
         let service_content = format!(r#"[Unit]
-Description=TinyWatcher - Zero-infrastructure observability tool
+Description={} - Zero-infrastructure observability tool
 After=network.target
 
 [Service]
 Type=simple
 ExecStart={}{}
+ExecReload=/bin/kill -HUP $MAINPID
 Restart=always
 RestartSec=10
 StandardOutput=journal
@@ -66,13 +78,19 @@ StandardError=journal
 
 [Install]
 WantedBy={}
-"#, exec_start, user_directive, wanted_by);
+"#, description, exec_start, user_directive, wanted_by);
         
         Ok(service_content)
     }
 }
 
 impl ServiceManager for SystemdManager {
+    fn preview_install(&self, config_path: Option<PathBuf>, needs_elevation: bool) -> Result<String> {
+        let service_path = self.get_service_path(needs_elevation);
+        let service_content = self.create_service_content(config_path, needs_elevation)?;
+        Ok(format!("# {}\n{}", service_path.display(), service_content))
+    }
+
     fn install(&self, config_path: Option<PathBuf>, needs_elevation: bool) -> Result<()> {
         let mut stdout = StandardStream::stdout(ColorChoice::Always);
         
@@ -119,7 +137,7 @@ impl ServiceManager for SystemdManager {
             
             // Use sudo to move the file to system location
             let output = Command::new("sudo")
-                .args(&["mv", temp_path.to_str().unwrap(), service_path.to_str().unwrap()])
+                .args(["mv", temp_path.to_str().unwrap(), service_path.to_str().unwrap()])
                 .output()
                 .context("Failed to install service file. Sudo required.")?;
             
@@ -135,7 +153,7 @@ impl ServiceManager for SystemdManager {
             
             // Reload systemd daemon with sudo
             let output = Command::new("sudo")
-                .args(&["systemctl", "daemon-reload"])
+                .args(["systemctl", "daemon-reload"])
                 .output()
                 .context("Failed to reload systemd daemon")?;
             
@@ -180,12 +198,12 @@ impl ServiceManager for SystemdManager {
         // Enable the service (start on boot)
         let enable_output = if system_service {
             Command::new("sudo")
-                .args(&["systemctl", "enable", &self.service_name])
+                .args(["systemctl", "enable", &self.service_name])
                 .output()
                 .context("Failed to enable service")?
         } else {
             Command::new("systemctl")
-                .args(&["--user", "enable", &self.service_name])
+                .args(["--user", "enable", &self.service_name])
                 .output()
                 .context("Failed to enable service")?
         };
@@ -204,12 +222,12 @@ impl ServiceManager for SystemdManager {
         // Start the service
         let start_output = if system_service {
             Command::new("sudo")
-                .args(&["systemctl", "start", &self.service_name])
+                .args(["systemctl", "start", &self.service_name])
                 .output()
                 .context("Failed to start service")?
         } else {
             Command::new("systemctl")
-                .args(&["--user", "start", &self.service_name])
+                .args(["--user", "start", &self.service_name])
                 .output()
                 .context("Failed to start service")?
         };
@@ -310,34 +328,34 @@ impl ServiceManager for SystemdManager {
         if is_system {
             // System service - needs sudo
             let _ = Command::new("sudo")
-                .args(&["systemctl", "stop", &self.service_name])
+                .args(["systemctl", "stop", &self.service_name])
                 .output();
             
             let _ = Command::new("sudo")
-                .args(&["systemctl", "disable", &self.service_name])
+                .args(["systemctl", "disable", &self.service_name])
                 .output();
             
             let _ = Command::new("sudo")
-                .args(&["rm", service_path.to_str().unwrap()])
+                .args(["rm", service_path.to_str().unwrap()])
                 .output();
             
             let _ = Command::new("sudo")
-                .args(&["systemctl", "daemon-reload"])
+                .args(["systemctl", "daemon-reload"])
                 .output();
         } else {
             // User service
             let _ = Command::new("systemctl")
-                .args(&["--user", "stop", &self.service_name])
+                .args(["--user", "stop", &self.service_name])
                 .output();
             
             let _ = Command::new("systemctl")
-                .args(&["--user", "disable", &self.service_name])
+                .args(["--user", "disable", &self.service_name])
                 .output();
             
             let _ = fs::remove_file(&service_path);
             
             let _ = Command::new("systemctl")
-                .args(&["--user", "daemon-reload"])
+                .args(["--user", "daemon-reload"])
                 .output();
         }
         
@@ -408,12 +426,12 @@ impl ServiceManager for SystemdManager {
         
         let output = if is_system {
             Command::new("sudo")
-                .args(&["systemctl", "start", &self.service_name])
+                .args(["systemctl", "start", &self.service_name])
                 .output()
                 .context("Failed to start service")?
         } else {
             Command::new("systemctl")
-                .args(&["--user", "start", &self.service_name])
+                .args(["--user", "start", &self.service_name])
                 .output()
                 .context("Failed to start service")?
         };
@@ -498,12 +516,12 @@ impl ServiceManager for SystemdManager {
         
         let output = if is_system {
             Command::new("sudo")
-                .args(&["systemctl", "stop", &self.service_name])
+                .args(["systemctl", "stop", &self.service_name])
                 .output()
                 .context("Failed to stop service")?
         } else {
             Command::new("systemctl")
-                .args(&["--user", "stop", &self.service_name])
+                .args(["--user", "stop", &self.service_name])
                 .output()
                 .context("Failed to stop service")?
         };
@@ -528,40 +546,21 @@ impl ServiceManager for SystemdManager {
     }
 
     fn status(&self) -> Result<ServiceStatus> {
-        // Check both user and system service locations
-        let user_service_path = self.get_service_path(false);
-        let system_service_path = self.get_service_path(true);
-        let running_as_root = super::is_elevated();
-        
-        // Determine which service to check based on what's installed and current privileges
-        let service_exists = if system_service_path.exists() && user_service_path.exists() {
-            // Both exist - check the one that matches current user context
-            true
-        } else if system_service_path.exists() {
-            true
-        } else if user_service_path.exists() {
-            true
-        } else {
+        let Some(scope) = self.installed_scope() else {
             return Ok(ServiceStatus::NotInstalled);
         };
         
-        if !service_exists {
-            return Ok(ServiceStatus::NotInstalled);
-        }
-        
         // Check if service is active in the appropriate context
-        let output = if running_as_root && system_service_path.exists() {
-            // Check system service
-            Command::new("systemctl")
-                .args(&["is-active", &self.service_name])
+        let output = match scope {
+            // is-active on a system unit doesn't need root
+            ServiceScope::System => Command::new("systemctl")
+                .args(["is-active", &self.service_name])
                 .output()
-                .context("Failed to check service status")?
-        } else {
-            // Check user service
-            Command::new("systemctl")
-                .args(&["--user", "is-active", &self.service_name])
+                .context("Failed to check service status")?,
+            ServiceScope::User => Command::new("systemctl")
+                .args(["--user", "is-active", &self.service_name])
                 .output()
-                .context("Failed to check service status")?
+                .context("Failed to check service status")?,
         };
         
         let status_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -573,7 +572,56 @@ impl ServiceManager for SystemdManager {
         }
     }
 
+    fn service_type(&self) -> Option<&'static str> {
+        self.installed_scope().map(|scope| scope.as_str())
+    }
+
     fn service_name(&self) -> &str {
         &self.service_name
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_matches_user_service_content() {
+        let manager = SystemdManager::new(None);
+        let config = Some(PathBuf::from("/etc/tinywatcher/config.yaml"));
+        let preview = manager.preview_install(config.clone(), false).unwrap();
+        let content = manager.create_service_content(config, false).unwrap();
+
+        let (header, body) = preview.split_once('\n').unwrap();
+        assert_eq!(header, format!("# {}", manager.get_service_path(false).display()));
+        assert_eq!(body, content);
+        assert!(body.contains("--config /etc/tinywatcher/config.yaml"));
+        assert!(body.contains("WantedBy=default.target"));
+    }
+
+    #[test]
+    fn test_preview_matches_system_service_content() {
+        let manager = SystemdManager::new(None);
+        let preview = manager.preview_install(None, true).unwrap();
+        let content = manager.create_service_content(None, true).unwrap();
+
+        assert_eq!(
+            preview,
+            format!("# /etc/systemd/system/tinywatcher.service\n{}", content)
+        );
+        assert!(content.contains("WantedBy=multi-user.target"));
+    }
+
+    #[test]
+    fn test_named_instance_unit() {
+        let manager = SystemdManager::new(Some("web"));
+        let preview = manager.preview_install(None, true).unwrap();
+
+        assert_eq!(manager.service_name(), "tinywatcher-web");
+        assert!(preview.starts_with("# /etc/systemd/system/tinywatcher-web.service\n"));
+        assert!(preview.contains("Description=TinyWatcher (web) - "));
+        assert!(manager
+            .get_service_path(false)
+            .ends_with(".config/systemd/user/tinywatcher-web.service"));
+    }
+}
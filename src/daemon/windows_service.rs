@@ -7,17 +7,73 @@ use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 
 pub struct WindowsServiceManager {
     service_name: String,
+    display_name: String,
 }
 
 impl WindowsServiceManager {
-    pub fn new() -> Self {
-        Self {
-            service_name: "TinyWatcher".to_string(),
+    pub fn new(instance: Option<&str>) -> Self {
+        match instance {
+            Some(name) => Self {
+                service_name: format!("TinyWatcher-{}", name),
+                display_name: format!("TinyWatcher Agent ({})", name),
+            },
+            None => Self {
+                service_name: "TinyWatcher".to_string(),
+                display_name: "TinyWatcher Agent".to_string(),
+            },
         }
     }
+
+    /// Arguments passed to `sc.exe` to create the service
+    fn create_command_args(&self, config_path: Option<PathBuf>, needs_elevation: bool) -> Result<Vec<String>> {
+        let exe_path = super::get_executable_path()?;
+        let exe_path_str = exe_path.to_str().context("Invalid executable path")?;
+        
+        let mut bin_path = format!("\"{}\" watch", exe_path_str);
+        
+        if let Some(config) = config_path {
+            bin_path.push_str(&format!(" --config \"{}\"", config.to_str().unwrap_or("")));
+        }
+        
+        // Create the service using sc.exe
+        // Windows services run as LocalSystem by default, which has full access
+        // If needs_elevation is true, we explicitly set the service to run as LocalSystem
+        let mut args = vec![
+            "create".to_string(),
+            self.service_name.clone(),
+            "binPath=".to_string(),
+            bin_path,
+            "start=".to_string(),
+            "auto".to_string(),
+            "DisplayName=".to_string(),
+            self.display_name.clone(),
+        ];
+        
+        // Explicitly set to run as LocalSystem if elevated privileges are needed
+        if needs_elevation {
+            args.push("obj=LocalSystem".to_string());
+        }
+        
+        Ok(args)
+    }
 }
 
 impl ServiceManager for WindowsServiceManager {
+    fn preview_install(&self, config_path: Option<PathBuf>, needs_elevation: bool) -> Result<String> {
+        let args = self.create_command_args(config_path, needs_elevation)?;
+        let quoted: Vec<String> = args
+            .iter()
+            .map(|arg| {
+                if arg.contains(' ') {
+                    format!("\"{}\"", arg.replace('"', "\\\""))
+                } else {
+                    arg.clone()
+                }
+            })
+            .collect();
+        Ok(format!("sc {}", quoted.join(" ")))
+    }
+
     fn install(&self, config_path: Option<PathBuf>, needs_elevation: bool) -> Result<()> {
         let mut stdout = StandardStream::stdout(ColorChoice::Always);
         
@@ -41,35 +97,7 @@ impl ServiceManager for WindowsServiceManager {
             writeln!(&mut stdout, " Detected protected log files. Service will run as SYSTEM...")?;
         }
         
-        let exe_path = super::get_executable_path()?;
-        let exe_path_str = exe_path.to_str().context("Invalid executable path")?;
-        
-        let mut bin_path = format!("\"{}\" watch", exe_path_str);
-        
-        if let Some(config) = config_path.clone() {
-            bin_path.push_str(&format!(" --config \"{}\"", config.to_str().unwrap_or("")));
-        }
-        
-        // Create the service using sc.exe
-        // Windows services run as LocalSystem by default, which has full access
-        // If needs_elevation is true, we explicitly set the service to run as LocalSystem
-        let mut args = vec![
-            "create",
-            &self.service_name,
-            "binPath=",
-            &bin_path,
-            "start=",
-            "auto",
-            "DisplayName=",
-            "TinyWatcher Agent",
-        ];
-        
-        // Explicitly set to run as LocalSystem if elevated privileges are needed
-        let obj_param;
-        if needs_elevation {
-            obj_param = "obj=LocalSystem".to_string();
-            args.push(&obj_param);
-        }
+        let args = self.create_command_args(config_path.clone(), needs_elevation)?;
         
         let output = Command::new("sc")
             .args(&args)
@@ -297,7 +325,42 @@ impl ServiceManager for WindowsServiceManager {
         }
     }
 
+    fn service_type(&self) -> Option<&'static str> {
+        // Windows services always run under the service control manager
+        match self.status() {
+            Ok(ServiceStatus::NotInstalled) | Err(_) => None,
+            Ok(_) => Some("system"),
+        }
+    }
+
     fn service_name(&self) -> &str {
         &self.service_name
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_matches_sc_command() {
+        let manager = WindowsServiceManager::new(None);
+        let config = Some(PathBuf::from("C:\\tinywatcher\\config.yaml"));
+        let args = manager.create_command_args(config.clone(), true).unwrap();
+        let preview = manager.preview_install(config, true).unwrap();
+
+        assert!(preview.starts_with("sc create TinyWatcher binPath= "));
+        assert!(preview.ends_with("DisplayName= \"TinyWatcher Agent\" obj=LocalSystem"));
+        assert!(args[3].contains("--config \"C:\\tinywatcher\\config.yaml\""));
+    }
+
+    #[test]
+    fn test_named_instance_sc_command() {
+        let manager = WindowsServiceManager::new(Some("web"));
+        let preview = manager.preview_install(None, false).unwrap();
+
+        assert_eq!(manager.service_name(), "TinyWatcher-web");
+        assert!(preview.starts_with("sc create TinyWatcher-web binPath= "));
+        assert!(preview.ends_with("DisplayName= \"TinyWatcher Agent (web)\""));
+    }
+}
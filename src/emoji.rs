@@ -0,0 +1,61 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether log output may contain emoji (disabled with `--no-emoji` / `TW_NO_EMOJI`)
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Return the emoji prefix for a log line, or an empty string when emoji are disabled
+pub fn icon(emoji: &'static str) -> &'static str {
+    if ENABLED.load(Ordering::Relaxed) {
+        emoji
+    } else {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sources that emit log lines, checked for mis-encoded UTF-8
+    const SOURCES: &[(&str, &str)] = &[
+        ("main.rs", include_str!("main.rs")),
+        ("log_monitor.rs", include_str!("log_monitor.rs")),
+        ("stream_monitor.rs", include_str!("stream_monitor.rs")),
+        ("health_monitor.rs", include_str!("health_monitor.rs")),
+        ("heartbeat_monitor.rs", include_str!("heartbeat_monitor.rs")),
+        ("resource_monitor.rs", include_str!("resource_monitor.rs")),
+        ("alerts/email.rs", include_str!("alerts/email.rs")),
+    ];
+
+    #[test]
+    fn test_icon_respects_toggle() {
+        set_enabled(false);
+        assert_eq!(icon("✅ "), "");
+        set_enabled(true);
+        assert_eq!(icon("✅ "), "✅ ");
+    }
+
+    #[test]
+    fn test_no_mojibake_in_log_strings() {
+        // UTF-8 emoji decoded as Latin-1/Windows-1252 start with these sequences
+        let mojibake = ["\u{FFFD}", "âœ", "â\u{9d}", "ðŸ", "â–", "â¹"];
+
+        for (file, source) in SOURCES {
+            for (number, line) in source.lines().enumerate() {
+                for sequence in mojibake {
+                    assert!(
+                        !line.contains(sequence),
+                        "{}:{} contains mis-encoded text: {}",
+                        file,
+                        number + 1,
+                        line.trim()
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use crate::config::{EventLevel, EventLogConfig};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::Foundation::{ERROR_INSUFFICIENT_BUFFER, ERROR_NO_MORE_ITEMS};
+use windows::Win32::System::EventLog::{
+    EvtClose, EvtFormatMessage, EvtFormatMessageEvent, EvtNext, EvtOpenPublisherMetadata,
+    EvtQuery, EvtQueryChannelPath, EvtQueryForwardDirection, EvtQueryReverseDirection, EvtRender,
+    EvtRenderEventXml, EVT_HANDLE,
+};
+
+/// How often to check the channel for new records
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Number of records fetched per `EvtNext` call
+const BATCH_SIZE: usize = 64;
+
+/// A rendered event record
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRecord {
+    pub record_id: u64,
+    pub provider: String,
+    pub event_id: u32,
+    pub level: EventLevel,
+    pub message: String,
+}
+
+/// Format a record as a single log line
+pub fn format_record(record: &EventRecord) -> String {
+    let message = record.message.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!(
+        "[{}] {} (event {}): {}",
+        record.level, record.provider, record.event_id, message
+    )
+}
+
+/// Whether a record passes the configured source and level filters
+pub fn record_matches(record: &EventRecord, config: &EventLogConfig) -> bool {
+    if let Some(min_level) = config.level {
+        if record.level < min_level {
+            return false;
+        }
+    }
+
+    config.sources.is_empty()
+        || config
+            .sources
+            .iter()
+            .any(|source| source.eq_ignore_ascii_case(&record.provider))
+}
+
+/// Parse the fields we need out of an event's rendered XML
+/// Returns the record and, when present, the raw `<Data>` values as a fallback message
+fn parse_event_xml(xml: &str) -> Option<(EventRecord, String)> {
+    static PATTERNS: OnceLock<[Regex; 5]> = OnceLock::new();
+    let [provider, event_id, level, record_id, data] = PATTERNS.get_or_init(|| {
+        [
+            Regex::new(r#"<Provider Name=['"]([^'"]*)['"]"#).unwrap(),
+            Regex::new(r"<EventID[^>]*>(\d+)</EventID>").unwrap(),
+            Regex::new(r"<Level>(\d+)</Level>").unwrap(),
+            Regex::new(r"<EventRecordID>(\d+)</EventRecordID>").unwrap(),
+            Regex::new(r"<Data(?: [^>]*)?>([^<]*)</Data>").unwrap(),
+        ]
+    });
+
+    let capture = |regex: &Regex| regex.captures(xml).map(|c| c[1].to_string());
+
+    let record = EventRecord {
+        record_id: capture(record_id)?.parse().ok()?,
+        provider: capture(provider).unwrap_or_default(),
+        event_id: capture(event_id)?.parse().ok()?,
+        level: EventLevel::from_level(capture(level).and_then(|l| l.parse().ok()).unwrap_or(0)),
+        message: String::new(),
+    };
+
+    let fallback = data
+        .captures_iter(xml)
+        .map(|c| c[1].trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    Some((record, fallback))
+}
+
+/// Owned event log handle, closed on drop
+struct Handle(EVT_HANDLE);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = EvtClose(self.0);
+        }
+    }
+}
+
+/// Reads new records from one channel
+struct EventLogReader {
+    channel: HSTRING,
+    last_record_id: u64,
+    /// Publisher metadata used to format messages, cached per provider
+    publishers: HashMap<String, Option<Handle>>,
+}
+
+impl EventLogReader {
+    /// Open a channel, starting after its newest existing record
+    fn open(log: &str) -> Result<Self> {
+        let mut reader = Self {
+            channel: HSTRING::from(log),
+            last_record_id: 0,
+            publishers: HashMap::new(),
+        };
+
+        let query = reader
+            .query("*", EvtQueryReverseDirection.0)
+            .with_context(|| format!("Failed to open event log '{}'", log))?;
+        if let Some(event) = next_events(&query, 1)?.into_iter().next() {
+            if let Some((record, _)) = render_xml(&event).as_deref().and_then(parse_event_xml) {
+                reader.last_record_id = record.record_id;
+            }
+        }
+
+        Ok(reader)
+    }
+
+    fn query(&self, xpath: &str, direction: u32) -> Result<Handle> {
+        let handle = unsafe {
+            EvtQuery(
+                EVT_HANDLE::default(),
+                &self.channel,
+                &HSTRING::from(xpath),
+                EvtQueryChannelPath.0 | direction,
+            )
+        }?;
+        Ok(Handle(handle))
+    }
+
+    /// Read records written since the last call
+    fn read_new(&mut self) -> Result<Vec<EventRecord>> {
+        let xpath = format!("*[System[EventRecordID > {}]]", self.last_record_id);
+        let query = self.query(&xpath, EvtQueryForwardDirection.0)?;
+
+        let mut records = Vec::new();
+        loop {
+            let events = next_events(&query, BATCH_SIZE)?;
+            if events.is_empty() {
+                break;
+            }
+
+            for event in events {
+                let Some((mut record, fallback)) = render_xml(&event).as_deref().and_then(parse_event_xml)
+                else {
+                    continue;
+                };
+                record.message = self.format_message(&record.provider, &event).unwrap_or(fallback);
+                self.last_record_id = self.last_record_id.max(record.record_id);
+                records.push(record);
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Format the event's message text using its provider's message table
+    fn format_message(&mut self, provider: &str, event: &Handle) -> Option<String> {
+        let metadata = self
+            .publishers
+            .entry(provider.to_string())
+            .or_insert_with(|| {
+                unsafe {
+                    EvtOpenPublisherMetadata(
+                        EVT_HANDLE::default(),
+                        &HSTRING::from(provider),
+                        PCWSTR::null(),
+                        0,
+                        0,
+                    )
+                }
+                .ok()
+                .map(Handle)
+            })
+            .as_ref()?;
+
+        let mut used = 0u32;
+        let result = unsafe {
+            EvtFormatMessage(metadata.0, event.0, 0, None, EvtFormatMessageEvent.0, None, &mut used)
+        };
+        match result {
+            Err(e) if e.code() == ERROR_INSUFFICIENT_BUFFER.to_hresult() => {}
+            _ => return None,
+        }
+
+        let mut buffer = vec![0u16; used as usize];
+        unsafe {
+            EvtFormatMessage(
+                metadata.0,
+                event.0,
+                0,
+                None,
+                EvtFormatMessageEvent.0,
+                Some(&mut buffer),
+                &mut used,
+            )
+        }
+        .ok()?;
+
+        Some(utf16_to_string(&buffer))
+    }
+}
+
+/// Fetch up to `count` events from a query; empty when there are no more
+fn next_events(query: &Handle, count: usize) -> Result<Vec<Handle>> {
+    let mut raw = vec![0isize; count];
+    let mut returned = 0u32;
+
+    match unsafe { EvtNext(query.0, &mut raw, 0, 0, &mut returned) } {
+        Ok(()) => Ok(raw
+            .into_iter()
+            .take(returned as usize)
+            .map(|handle| Handle(EVT_HANDLE(handle)))
+            .collect()),
+        Err(e) if e.code() == ERROR_NO_MORE_ITEMS.to_hresult() => Ok(Vec::new()),
+        Err(e) => Err(e).context("Failed to read event log records"),
+    }
+}
+
+/// Render an event as XML
+fn render_xml(event: &Handle) -> Option<String> {
+    let mut used = 0u32;
+    let mut count = 0u32;
+    let result = unsafe {
+        EvtRender(EVT_HANDLE::default(), event.0, EvtRenderEventXml.0, 0, None, &mut used, &mut count)
+    };
+    match result {
+        Err(e) if e.code() == ERROR_INSUFFICIENT_BUFFER.to_hresult() => {}
+        _ => return None,
+    }
+
+    let mut buffer = vec![0u16; (used as usize).div_ceil(2)];
+    unsafe {
+        EvtRender(
+            EVT_HANDLE::default(),
+            event.0,
+            EvtRenderEventXml.0,
+            used,
+            Some(buffer.as_mut_ptr().cast()),
+            &mut used,
+            &mut count,
+        )
+    }
+    .ok()?;
+
+    Some(utf16_to_string(&buffer))
+}
+
+fn utf16_to_string(buffer: &[u16]) -> String {
+    let end = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    String::from_utf16_lossy(&buffer[..end])
+}
+
+/// Poll a channel until the receiver is dropped, sending matching records as formatted lines
+/// Blocking; run it on a dedicated thread
+pub fn poll(config: &EventLogConfig, lines: Sender<String>) -> Result<()> {
+    let mut reader = EventLogReader::open(&config.log)?;
+    tracing::info!("Watching Windows event log '{}'", config.log);
+
+    loop {
+        for record in reader.read_new()? {
+            if !record_matches(&record, config) {
+                continue;
+            }
+            if lines.blocking_send(format_record(&record)).is_err() {
+                return Ok(());
+            }
+        }
+
+        if lines.is_closed() {
+            return Ok(());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(provider: &str, level: EventLevel) -> EventRecord {
+        EventRecord {
+            record_id: 42,
+            provider: provider.to_string(),
+            event_id: 1000,
+            level,
+            message: "Faulting application name: app.exe,\r\n  version: 1.0".to_string(),
+        }
+    }
+
+    fn config(sources: &[&str], level: Option<EventLevel>) -> EventLogConfig {
+        EventLogConfig {
+            log: "Application".to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            level,
+        }
+    }
+
+    #[test]
+    fn test_format_record() {
+        assert_eq!(
+            format_record(&record("Application Error", EventLevel::Error)),
+            "[error] Application Error (event 1000): Faulting application name: app.exe, version: 1.0"
+        );
+    }
+
+    #[test]
+    fn test_level_filter() {
+        let errors_and_up = config(&[], Some(EventLevel::Error));
+        assert!(record_matches(&record("App", EventLevel::Critical), &errors_and_up));
+        assert!(record_matches(&record("App", EventLevel::Error), &errors_and_up));
+        assert!(!record_matches(&record("App", EventLevel::Warning), &errors_and_up));
+        assert!(!record_matches(&record("App", EventLevel::Information), &errors_and_up));
+
+        let all = config(&[], None);
+        assert!(record_matches(&record("App", EventLevel::Verbose), &all));
+    }
+
+    #[test]
+    fn test_source_filter() {
+        let filtered = config(&["Application Error", "MSSQLSERVER"], None);
+        assert!(record_matches(&record("application error", EventLevel::Error), &filtered));
+        assert!(!record_matches(&record("Service Control Manager", EventLevel::Error), &filtered));
+    }
+
+    #[test]
+    fn test_parse_event_xml() {
+        let xml = "<Event xmlns='http://schemas.microsoft.com/win/2004/08/events/event'><System>\
+            <Provider Name='Application Error'/><EventID Qualifiers='0'>1000</EventID>\
+            <Level>2</Level><EventRecordID>5123</EventRecordID></System>\
+            <EventData><Data>app.exe</Data><Data>1.0</Data><Data></Data></EventData></Event>";
+
+        let (record, fallback) = parse_event_xml(xml).unwrap();
+        assert_eq!(record.record_id, 5123);
+        assert_eq!(record.provider, "Application Error");
+        assert_eq!(record.event_id, 1000);
+        assert_eq!(record.level, EventLevel::Error);
+        assert_eq!(fallback, "app.exe; 1.0");
+    }
+}
@@ -0,0 +1,127 @@
+use crate::alerts::AlertManager;
+use crate::daemon;
+use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Rule name used for unreadable file alerts
+pub const UNREADABLE_FILES_RULE: &str = "unreadable_files";
+
+/// Configured files the agent doesn't have permission to read
+pub fn unreadable_files(files: &[PathBuf]) -> Vec<PathBuf> {
+    match daemon::get_files_needing_elevation(files) {
+        Ok(unreadable) => unreadable,
+        Err(e) => {
+            tracing::warn!("Failed to check file permissions: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+pub fn describe(unreadable: &[PathBuf]) -> String {
+    let files = unreadable
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!(
+        "Cannot read {} configured file(s) due to permissions: {}",
+        unreadable.len(),
+        files
+    )
+}
+
+/// Startup check; fails if `require_readable` is set and any file is unreadable
+/// Returns the unreadable files otherwise
+pub fn startup_check(files: &[PathBuf], require_readable: bool) -> Result<Vec<PathBuf>> {
+    let unreadable = unreadable_files(files);
+    if require_readable && !unreadable.is_empty() {
+        anyhow::bail!(
+            "{}. Fix the permissions or run with elevated privileges.",
+            describe(&unreadable)
+        );
+    }
+    Ok(unreadable)
+}
+
+/// Alert when the set of unreadable files changes to a non-empty one
+fn should_alert(previous: &[PathBuf], current: &[PathBuf]) -> bool {
+    !current.is_empty() && current != previous
+}
+
+/// Recheck file permissions every `interval`, alerting on newly unreadable files
+/// `alerted` is what the startup check already reported
+pub async fn watch(
+    files: Vec<PathBuf>,
+    alert_names: Vec<String>,
+    alert_manager: Arc<AlertManager>,
+    interval: Duration,
+    mut alerted: Vec<PathBuf>,
+) {
+    let mut interval_timer = tokio::time::interval(interval);
+    interval_timer.tick().await;
+    loop {
+        interval_timer.tick().await;
+
+        let unreadable = unreadable_files(&files);
+        if should_alert(&alerted, &unreadable) {
+            let message = describe(&unreadable);
+            tracing::warn!("{}", message);
+            if let Err(e) = alert_manager
+                .send_alert_multi(&alert_names, UNREADABLE_FILES_RULE, &message, 0)
+                .await
+            {
+                tracing::error!("Failed to send unreadable files alert: {}", e);
+            }
+        } else if unreadable.is_empty() && !alerted.is_empty() {
+            tracing::info!("All configured files are readable again");
+        }
+        alerted = unreadable;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_file_detected() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let readable = dir.path().join("app.log");
+        let locked = dir.path().join("secure.log");
+        std::fs::write(&readable, "ok\n").unwrap();
+        std::fs::write(&locked, "secret\n").unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+
+        let files = vec![readable, locked.clone()];
+        assert_eq!(unreadable_files(&files), vec![locked.clone()]);
+
+        let error = startup_check(&files, true).unwrap_err().to_string();
+        assert!(error.contains(&format!("Cannot read 1 configured file(s) due to permissions: {}", locked.display())));
+        assert_eq!(startup_check(&files, false).unwrap(), vec![locked]);
+    }
+
+    #[test]
+    fn test_readable_files_pass_startup_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "ok\n").unwrap();
+
+        assert!(startup_check(&[path], true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_should_alert_on_change() {
+        let a = vec![PathBuf::from("/var/log/a.log")];
+        let a_and_b = vec![PathBuf::from("/var/log/a.log"), PathBuf::from("/var/log/b.log")];
+
+        assert!(should_alert(&[], &a));
+        assert!(!should_alert(&a, &a));
+        assert!(should_alert(&a, &a_and_b));
+        assert!(!should_alert(&a, &[]));
+    }
+}
@@ -0,0 +1,561 @@
+use crate::state::FileOffset;
+use anyhow::{Context, Result};
+use flate2::read::MultiGzDecoder;
+use notify::{RecursiveMode, Watcher};
+use std::fs::Metadata;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
+use tokio::sync::mpsc::Sender;
+
+/// Longest partial line kept while waiting for its newline; longer ones are emitted as-is
+const MAX_PARTIAL_LINE: usize = 64 * 1024;
+
+/// Most bytes read per `read_lines` call, so catching up on a large backlog doesn't buffer it all
+const READ_CHUNK: usize = 64 * 1024;
+
+/// Identifies the file behind a path, to notice when rotation replaces it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileId {
+    #[cfg(unix)]
+    dev: u64,
+    #[cfg(unix)]
+    ino: u64,
+    /// Creation time, where the platform and filesystem report it; guards against inode reuse
+    created: Option<SystemTime>,
+}
+
+impl FileId {
+    #[cfg(unix)]
+    fn of(metadata: &Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+        Self {
+            dev: metadata.dev(),
+            ino: metadata.ino(),
+            created: metadata.created().ok(),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn of(metadata: &Metadata) -> Self {
+        Self {
+            created: metadata.created().ok(),
+        }
+    }
+
+    /// Stable text form for saved offsets; empty if the file can't be identified
+    fn key(&self) -> String {
+        let created = self
+            .created
+            .and_then(|created| created.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_nanos().to_string())
+            .unwrap_or_default();
+
+        #[cfg(unix)]
+        return format!("{}:{}:{}", self.dev, self.ino, created);
+        #[cfg(not(unix))]
+        return created;
+    }
+}
+
+/// Follows a file by name like `tail -F -n 0`: starts at the end, survives truncation and
+/// rotation, and waits for the file if it doesn't exist yet
+pub struct FileTail {
+    path: PathBuf,
+    file: Option<(File, FileId)>,
+    /// Offset up to which the open file has been read
+    position: u64,
+    /// Bytes after the last newline, waiting for the rest of their line
+    partial: Vec<u8>,
+    /// Reused for each chunk read
+    buffer: Vec<u8>,
+    /// Whether the last read filled a whole chunk, so more may be waiting
+    more: bool,
+}
+
+impl FileTail {
+    /// Start following `path` from a saved offset if it belongs to the same file, else from the end
+    pub async fn open(path: &Path, saved: Option<&FileOffset>) -> Result<Self> {
+        let mut tail = Self {
+            path: path.to_path_buf(),
+            file: None,
+            position: 0,
+            partial: Vec::new(),
+            buffer: vec![0; READ_CHUNK],
+            more: false,
+        };
+
+        match File::open(path).await {
+            Ok(mut file) => {
+                let metadata = file.metadata().await?;
+                let id = FileId::of(&metadata);
+                let key = id.key();
+                let resume = saved.filter(|saved| {
+                    !key.is_empty() && saved.id == key && saved.offset <= metadata.len()
+                });
+                tail.position = match resume {
+                    Some(saved) => {
+                        tracing::info!(
+                            "Resuming {} from offset {} ({} bytes written since)",
+                            path.display(),
+                            saved.offset,
+                            metadata.len() - saved.offset
+                        );
+                        saved.offset
+                    }
+                    None => file.seek(SeekFrom::End(0)).await?,
+                };
+                tail.file = Some((file, id));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                tracing::info!("{} does not exist yet; waiting for it", path.display());
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to open {}", path.display()));
+            }
+        }
+        Ok(tail)
+    }
+
+    /// Read the complete lines appended since the last call, up to `READ_CHUNK` bytes of them
+    /// Call again while `has_more` is true to catch up on the rest
+    pub async fn read_lines(&mut self) -> Result<Vec<String>> {
+        let mut lines = Vec::new();
+
+        let current = match tokio::fs::metadata(&self.path).await {
+            Ok(metadata) => Some(metadata),
+            // Rotated away and not recreated yet; keep reading the old file
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to stat {}", self.path.display()));
+            }
+        };
+
+        if let Some(metadata) = &current {
+            let replaced = self
+                .file
+                .as_ref()
+                .is_none_or(|(_, id)| *id != FileId::of(metadata));
+            if replaced {
+                // Drain what was written to the old file before it was rotated
+                if self.file.is_some() {
+                    self.read_chunk(&mut lines).await?;
+                    if self.more {
+                        return Ok(lines);
+                    }
+                    self.flush_partial(&mut lines);
+                    tracing::info!("{} was replaced; following the new file", self.path.display());
+                }
+                self.reopen().await?;
+            } else if metadata.len() < self.position {
+                tracing::info!("{} was truncated; reading from the start", self.path.display());
+                self.position = 0;
+                self.partial.clear();
+            }
+        }
+
+        self.read_chunk(&mut lines).await?;
+        Ok(lines)
+    }
+
+    /// Whether the last `read_lines` stopped at the chunk limit rather than at the end of the file
+    pub fn has_more(&self) -> bool {
+        self.more
+    }
+
+    /// Offset of the first byte not yet processed, to resume from after a restart
+    /// A partial line is re-read, since it was never processed
+    pub fn checkpoint(&self) -> Option<FileOffset> {
+        let (_, id) = self.file.as_ref()?;
+        Some(FileOffset {
+            id: id.key(),
+            offset: self.position - self.partial.len() as u64,
+        })
+    }
+
+    /// Open the file now at `path` and read it from the start
+    async fn reopen(&mut self) -> Result<()> {
+        let file = File::open(&self.path)
+            .await
+            .with_context(|| format!("Failed to open {}", self.path.display()))?;
+        let metadata = file.metadata().await?;
+        self.file = Some((file, FileId::of(&metadata)));
+        self.position = 0;
+        self.partial.clear();
+        Ok(())
+    }
+
+    /// Read up to one chunk from the current position and split it into lines
+    async fn read_chunk(&mut self, lines: &mut Vec<String>) -> Result<()> {
+        self.more = false;
+        let Some((file, _)) = self.file.as_mut() else {
+            return Ok(());
+        };
+
+        file.seek(SeekFrom::Start(self.position)).await?;
+        let mut read = 0;
+        while read < self.buffer.len() {
+            let n = file
+                .read(&mut self.buffer[read..])
+                .await
+                .with_context(|| format!("Failed to read {}", self.path.display()))?;
+            if n == 0 {
+                break;
+            }
+            read += n;
+        }
+        self.position += read as u64;
+        self.more = read == self.buffer.len();
+
+        let mut rest = &self.buffer[..read];
+        while let Some(end) = rest.iter().position(|&byte| byte == b'\n') {
+            self.partial.extend_from_slice(&rest[..end]);
+            lines.push(String::from_utf8_lossy(&self.partial).into_owned());
+            self.partial.clear();
+            rest = &rest[end + 1..];
+        }
+        self.partial.extend_from_slice(rest);
+        if self.partial.len() >= MAX_PARTIAL_LINE {
+            self.flush_partial(lines);
+        }
+        Ok(())
+    }
+
+    /// Emit a trailing line that will never get its newline
+    fn flush_partial(&mut self, lines: &mut Vec<String>) {
+        if !self.partial.is_empty() {
+            lines.push(String::from_utf8_lossy(&self.partial).into_owned());
+            self.partial.clear();
+        }
+    }
+}
+
+/// How a rotated log file is compressed, going by its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    /// Compressed in a format that can't be read yet (bzip2, xz, zstd)
+    Unsupported,
+}
+
+impl Compression {
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gz" => Some(Compression::Gzip),
+            "bz2" | "xz" | "zst" => Some(Compression::Unsupported),
+            _ => None,
+        }
+    }
+}
+
+/// Decompress a gzipped log and send its lines as they are read
+/// Compressed files don't grow, so this reads once; it blocks, so run it with `spawn_blocking`
+pub fn read_gzip_lines(path: &Path, lines: Sender<String>) -> Result<()> {
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut reader = std::io::BufReader::new(MultiGzDecoder::new(file));
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader
+            .read_until(b'\n', &mut line)
+            .with_context(|| format!("Failed to decompress {}", path.display()))?;
+        if read == 0 {
+            return Ok(());
+        }
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        }
+        // The receiver is gone when the watcher was stopped
+        if lines.blocking_send(String::from_utf8_lossy(&line).into_owned()).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+/// Saved offset marking a file as read to its end, if the file can be identified
+pub fn end_of(metadata: &Metadata) -> Option<FileOffset> {
+    let id = FileId::of(metadata).key();
+    if id.is_empty() {
+        return None;
+    }
+    Some(FileOffset {
+        id,
+        offset: metadata.len(),
+    })
+}
+
+/// Watch the file's directory and signal `changed` whenever the file may have changed
+/// The directory is watched (not the file) so creation and rotation are seen too
+/// Give `changed` a capacity of 1: a signal already waiting covers later events, so a busy file
+/// queues at most one read instead of one per write
+pub fn watch(path: &Path, changed: Sender<()>) -> Result<notify::RecommendedWatcher> {
+    let file_name = path.file_name().map(|name| name.to_os_string());
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let relevant = match &event {
+            Ok(event) => event
+                .paths
+                .iter()
+                .any(|changed| changed.file_name().map(|name| name.to_os_string()) == file_name),
+            // Let the reader find out what went wrong
+            Err(_) => true,
+        };
+        if relevant {
+            let _ = changed.try_send(());
+        }
+    })
+    .context("Failed to create file watcher")?;
+
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("Failed to watch {}", dir.display()))?;
+    Ok(watcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn append(path: &Path, text: &str) {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_starts_at_end_and_reads_appended_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "old line\n");
+
+        let mut tail = FileTail::open(&path, None).await.unwrap();
+        assert!(tail.read_lines().await.unwrap().is_empty());
+
+        append(&path, "first\nsecond\n");
+        assert_eq!(tail.read_lines().await.unwrap(), vec!["first", "second"]);
+        assert!(tail.read_lines().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_partial_line_waits_for_newline() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "");
+
+        let mut tail = FileTail::open(&path, None).await.unwrap();
+        append(&path, "ERROR half");
+        assert!(tail.read_lines().await.unwrap().is_empty());
+
+        append(&path, " written\n");
+        assert_eq!(tail.read_lines().await.unwrap(), vec!["ERROR half written"]);
+    }
+
+    #[tokio::test]
+    async fn test_truncation_reads_from_start() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "a fairly long line before truncation\n");
+
+        let mut tail = FileTail::open(&path, None).await.unwrap();
+        std::fs::write(&path, "").unwrap();
+        append(&path, "after\n");
+        assert_eq!(tail.read_lines().await.unwrap(), vec!["after"]);
+    }
+
+    #[tokio::test]
+    async fn test_rotation_drains_old_file_then_follows_new_one() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "");
+
+        let mut tail = FileTail::open(&path, None).await.unwrap();
+        append(&path, "before rotation\nlast words");
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+
+        // Not recreated yet: the old file is still followed
+        assert_eq!(tail.read_lines().await.unwrap(), vec!["before rotation"]);
+
+        append(&path, "new file\n");
+        assert_eq!(tail.read_lines().await.unwrap(), vec!["last words", "new file"]);
+    }
+
+    /// Read until caught up, checking no single call returns more than a chunk's worth of lines
+    async fn read_all(tail: &mut FileTail) -> Vec<String> {
+        let mut lines = Vec::new();
+        loop {
+            let chunk = tail.read_lines().await.unwrap();
+            assert!(chunk.iter().map(|line| line.len() + 1).sum::<usize>() <= READ_CHUNK + MAX_PARTIAL_LINE);
+            lines.extend(chunk);
+            if !tail.has_more() {
+                return lines;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_large_backlog_is_read_in_chunks() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "");
+        let saved = FileTail::open(&path, None).await.unwrap().checkpoint().unwrap();
+
+        // Written while stopped: far more than one chunk
+        let backlog: String = (0..50_000).map(|i| format!("line {}\n", i)).collect();
+        append(&path, &backlog);
+
+        let mut tail = FileTail::open(&path, Some(&saved)).await.unwrap();
+        let first = tail.read_lines().await.unwrap();
+        assert!(tail.has_more());
+        assert!(first.len() < 50_000);
+
+        let mut lines = first;
+        lines.extend(read_all(&mut tail).await);
+        assert_eq!(lines.len(), 50_000);
+        assert_eq!(lines[0], "line 0");
+        assert_eq!(lines[49_999], "line 49999");
+        assert!(!tail.has_more());
+    }
+
+    #[tokio::test]
+    async fn test_rotation_drains_large_old_file_in_chunks() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "");
+
+        let mut tail = FileTail::open(&path, None).await.unwrap();
+        let backlog: String = (0..50_000).map(|i| format!("old {}\n", i)).collect();
+        append(&path, &backlog);
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        append(&path, "new file\n");
+
+        let lines = read_all(&mut tail).await;
+        assert_eq!(lines.len(), 50_001);
+        assert_eq!(lines[49_999], "old 49999");
+        assert_eq!(lines[50_000], "new file");
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_missing_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("later.log");
+
+        let mut tail = FileTail::open(&path, None).await.unwrap();
+        assert!(tail.read_lines().await.unwrap().is_empty());
+
+        append(&path, "created\n");
+        assert_eq!(tail.read_lines().await.unwrap(), vec!["created"]);
+    }
+
+    #[tokio::test]
+    async fn test_resumes_from_saved_offset_of_same_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "seen\n");
+
+        let mut tail = FileTail::open(&path, None).await.unwrap();
+        append(&path, "processed\nhalf");
+        assert_eq!(tail.read_lines().await.unwrap(), vec!["processed"]);
+        let saved = tail.checkpoint().unwrap();
+        drop(tail);
+
+        // Written while stopped
+        append(&path, " line\nmissed\n");
+        let mut tail = FileTail::open(&path, Some(&saved)).await.unwrap();
+        assert_eq!(tail.read_lines().await.unwrap(), vec!["half line", "missed"]);
+    }
+
+    #[tokio::test]
+    async fn test_saved_offset_of_other_file_starts_at_end() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "old\n");
+        let saved = FileTail::open(&path, None).await.unwrap().checkpoint().unwrap();
+
+        // Rotated while stopped: a different file now has the name
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        append(&path, "new file written while stopped\n");
+
+        let mut tail = FileTail::open(&path, Some(&saved)).await.unwrap();
+        assert!(tail.read_lines().await.unwrap().is_empty());
+        append(&path, "after start\n");
+        assert_eq!(tail.read_lines().await.unwrap(), vec!["after start"]);
+    }
+
+    #[tokio::test]
+    async fn test_saved_offset_past_end_starts_at_end() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "a long line that will be truncated away\n");
+        let saved = FileTail::open(&path, None).await.unwrap().checkpoint().unwrap();
+
+        std::fs::OpenOptions::new().write(true).open(&path).unwrap().set_len(5).unwrap();
+        let mut tail = FileTail::open(&path, Some(&saved)).await.unwrap();
+        assert!(tail.read_lines().await.unwrap().is_empty());
+        assert_eq!(tail.checkpoint().unwrap().offset, 5);
+    }
+
+    #[test]
+    fn test_compression_from_extension() {
+        assert_eq!(Compression::of(Path::new("/var/log/app.log.2.gz")), Some(Compression::Gzip));
+        assert_eq!(Compression::of(Path::new("/var/log/app.log.GZ")), Some(Compression::Gzip));
+        assert_eq!(Compression::of(Path::new("/var/log/app.log.3.bz2")), Some(Compression::Unsupported));
+        assert_eq!(Compression::of(Path::new("/var/log/app.log.1")), None);
+        assert_eq!(Compression::of(Path::new("/var/log/app.log")), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_gzip_lines() {
+        use flate2::write::GzEncoder;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log.1.gz");
+        let mut encoder = GzEncoder::new(std::fs::File::create(&path).unwrap(), flate2::Compression::default());
+        encoder.write_all(b"first\nsecond\nno trailing newline").unwrap();
+        encoder.finish().unwrap();
+
+        let (tx, mut rx) = tokio::sync::mpsc::channel(16);
+        let reader = tokio::task::spawn_blocking(move || read_gzip_lines(&path, tx));
+        let mut lines = Vec::new();
+        while let Some(line) = rx.recv().await {
+            lines.push(line);
+        }
+        reader.await.unwrap().unwrap();
+        assert_eq!(lines, vec!["first", "second", "no trailing newline"]);
+    }
+
+    #[tokio::test]
+    async fn test_read_gzip_lines_rejects_corrupt_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log.1.gz");
+        append(&path, "not actually gzip\n");
+
+        let (tx, _rx) = tokio::sync::mpsc::channel(16);
+        let result = tokio::task::spawn_blocking(move || read_gzip_lines(&path, tx)).await.unwrap();
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_watch_signals_changes_to_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        let _watcher = watch(&path, tx).unwrap();
+
+        append(&path, "hello\n");
+        tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("no change event")
+            .unwrap();
+    }
+}
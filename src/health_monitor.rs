@@ -1,6 +1,9 @@
 use crate::alerts::AlertManager;
-use crate::config::Threshold;
+use crate::clock_skew::{self, ClockReference};
+use crate::config::{SystemCheck, SystemCheckType, Threshold};
+use crate::ping::{self, PingSettings};
 use anyhow::{Context, Result};
+use regex::Regex;
 use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -23,12 +26,107 @@ pub struct HealthCheck {
     pub missed_threshold: u32, // how many failures before alert
     pub alert: Vec<String>,    // alert names to trigger
     pub threshold: Option<Threshold>, // optional rate-based threshold (e.g., "3 in 1m")
+    pub max_skew: Duration,    // clock checks: allowed offset from the reference
+    pub notify_recovery: bool, // alert when the check passes again after alerting
+    pub ping: PingSettings,    // ping checks: echo count, loss tolerance and method
+    pub expected_status: Option<u16>, // HTTP checks: exact status required instead of any 2xx
+    pub expect_body: Option<BodyExpectation>, // HTTP checks: text the response body must contain
+    pub command: Option<String>, // exec checks: program to run
+    pub args: Vec<String>,       // exec checks: its arguments
+}
+
+impl HealthCheck {
+    /// Build a check from its `system_checks` config entry
+    pub fn from_config(check: &SystemCheck) -> Result<Self> {
+        Ok(Self {
+            name: check.name.clone(),
+            check_type: match check.check_type {
+                SystemCheckType::Http => HealthCheckType::Http,
+                SystemCheckType::Tcp => HealthCheckType::Tcp,
+                SystemCheckType::Clock => HealthCheckType::Clock,
+                SystemCheckType::Ping => HealthCheckType::Ping,
+                SystemCheckType::Exec => HealthCheckType::Exec,
+            },
+            url: check.url.clone(),
+            interval: check.interval,
+            timeout_secs: check.timeout,
+            missed_threshold: check.missed_threshold,
+            alert: check.alert.clone(),
+            threshold: check.threshold.clone(),
+            max_skew: check.max_skew(),
+            notify_recovery: check.notify_recovery,
+            ping: check.ping_settings(),
+            expected_status: check.expected_status,
+            expect_body: check
+                .expect_body
+                .as_deref()
+                .map(BodyExpectation::parse)
+                .transpose()
+                .with_context(|| format!("System check '{}'", check.name))?,
+            command: check.command.clone(),
+            args: check.args.clone(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum HealthCheckType {
     Http,
-    // Future: Tcp, Ping, etc.
+    /// Open a TCP connection to check the port accepts connections
+    Tcp,
+    /// Compare the local clock against an NTP server or an HTTP `Date` header
+    Clock,
+    /// Send ICMP echo requests and fail when packet loss exceeds the tolerance
+    Ping,
+    /// Run a command and fail on a non-zero exit code or timeout
+    Exec,
+}
+
+/// What an HTTP check's response body must contain
+#[derive(Debug, Clone)]
+pub enum BodyExpectation {
+    Contains(String),
+    Matches(Regex),
+}
+
+impl BodyExpectation {
+    /// `/.../` is a regex; anything else is a plain substring
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')) {
+            Some(pattern) => Regex::new(pattern)
+                .map(BodyExpectation::Matches)
+                .with_context(|| format!("Invalid expect_body regex '{}'", value)),
+            None => Ok(BodyExpectation::Contains(value.to_string())),
+        }
+    }
+
+    pub fn is_met(&self, body: &str) -> bool {
+        match self {
+            BodyExpectation::Contains(text) => body.contains(text.as_str()),
+            BodyExpectation::Matches(regex) => regex.is_match(body),
+        }
+    }
+}
+
+impl std::fmt::Display for BodyExpectation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BodyExpectation::Contains(text) => write!(f, "{:?}", text),
+            BodyExpectation::Matches(regex) => write!(f, "/{}/", regex.as_str()),
+        }
+    }
+}
+
+/// Longest response body or stderr excerpt quoted in a failure message
+const BODY_SNIPPET_CHARS: usize = 200;
+
+/// The start of a response body or command output on one line, for failure messages
+fn body_snippet(body: &str) -> String {
+    let body = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    match body.char_indices().nth(BODY_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body,
+    }
 }
 
 pub struct HealthMonitor {
@@ -46,10 +144,10 @@ impl HealthMonitor {
         }
     }
 
-    pub async fn start(self) {
+    pub async fn start(&self) {
         let mut tasks = Vec::new();
 
-        for check in self.checks {
+        for check in self.checks.iter().cloned() {
             let alert_manager = self.alert_manager.clone();
             let identity = self.identity.clone();
             
@@ -110,6 +208,9 @@ impl HealthMonitor {
             threshold_info
         );
 
+        // Built on the first tick and then kept, so connections are pooled and kept alive across ticks
+        let mut client = None;
+
         let mut interval_timer = interval(Duration::from_secs(check.interval));
         let mut consecutive_failures = 0u32;
         let mut is_down = false;
@@ -120,7 +221,12 @@ impl HealthMonitor {
         loop {
             interval_timer.tick().await;
 
-            match Self::perform_check(&check).await {
+            let result = match Self::cached_client(&check, &mut client) {
+                Ok(client) => Self::perform_check(&check, client).await,
+                Err(e) => Err(e),
+            };
+            crate::metrics::check_result(&check.name, result.is_ok());
+            match result {
                 Ok(()) => {
                     // Check succeeded
                     if is_down {
@@ -137,8 +243,10 @@ impl HealthMonitor {
                             check.url
                         );
 
-                        if let Err(e) = alert_manager.send_alert_multi(&check.alert, &check.name, &message, 0).await {
-                            tracing::error!("Failed to send recovery alert for '{}': {}", check.name, e);
+                        if check.notify_recovery {
+                            if let Err(e) = alert_manager.send_alert_multi(&check.alert, &check.name, &message, 0).await {
+                                tracing::error!("Failed to send recovery alert for '{}': {}", check.name, e);
+                            }
                         }
                         is_down = false;
                     }
@@ -249,35 +357,155 @@ impl HealthMonitor {
         }
     }
 
-    async fn perform_check(check: &HealthCheck) -> Result<()> {
-        match check.check_type {
-            HealthCheckType::Http => Self::http_check(check).await,
-        }
+    /// The check's client, built now if this is the first tick or building it failed before
+    /// A build failure counts as a failed check, so it is reported and retried on the next tick
+    fn cached_client<'a>(check: &HealthCheck, cached: &'a mut Option<reqwest::Client>) -> Result<&'a reqwest::Client> {
+        let client = match cached.take() {
+            Some(client) => client,
+            None => Self::build_client(check).context("Failed to build HTTP client")?,
+        };
+        Ok(cached.insert(client))
     }
 
-    async fn http_check(check: &HealthCheck) -> Result<()> {
-        let client = reqwest::Client::builder()
+    fn build_client(check: &HealthCheck) -> Result<reqwest::Client> {
+        // Asserting a 3xx status means checking the redirect itself rather than following it
+        let redirects = match check.expected_status {
+            Some(status) if (300..400).contains(&status) => reqwest::redirect::Policy::none(),
+            _ => reqwest::redirect::Policy::default(),
+        };
+
+        reqwest::Client::builder()
             .timeout(Duration::from_secs(check.timeout_secs))
+            .redirect(redirects)
             .connect_timeout(Duration::from_secs(5))
             .tcp_keepalive(Duration::from_secs(30))
             .pool_idle_timeout(Duration::from_secs(90))
             .build()
-            .context("Failed to create HTTP client")?;
+            .context("Failed to create HTTP client")
+    }
 
+    async fn perform_check(check: &HealthCheck, client: &reqwest::Client) -> Result<()> {
+        match check.check_type {
+            HealthCheckType::Http => Self::http_check(check, client).await,
+            HealthCheckType::Tcp => Self::tcp_check(check).await,
+            HealthCheckType::Clock => Self::clock_check(check, client).await,
+            HealthCheckType::Ping => Self::ping_check(check).await,
+            HealthCheckType::Exec => Self::exec_check(check).await,
+        }
+    }
+
+    async fn exec_check(check: &HealthCheck) -> Result<()> {
+        let command = check.command.as_deref().context("Exec check has no command")?;
+        let timeout = Duration::from_secs(check.timeout_secs);
+        let output = tokio::process::Command::new(command)
+            .args(&check.args)
+            .stdin(std::process::Stdio::null())
+            .kill_on_drop(true)
+            .output();
+
+        let output = match tokio::time::timeout(timeout, output).await {
+            Ok(output) => output.with_context(|| format!("Failed to run '{}'", command))?,
+            Err(_) => anyhow::bail!("Command '{}' timed out after {:?}", command, timeout),
+        };
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stderr = body_snippet(&stderr);
+            if stderr.is_empty() {
+                anyhow::bail!("Command '{}' failed ({})", command, output.status);
+            }
+            anyhow::bail!("Command '{}' failed ({}): {}", command, output.status, stderr);
+        }
+        Ok(())
+    }
+
+    async fn ping_check(check: &HealthCheck) -> Result<()> {
+        let outcome = ping::ping(&check.url, &check.ping, Duration::from_secs(check.timeout_secs)).await?;
+        ping::evaluate(outcome, check.ping.max_loss, &check.url)?;
+
+        tracing::debug!("Ping check '{}': {:.0}% packet loss", check.name, outcome.loss_percent());
+        Ok(())
+    }
+
+    async fn clock_check(check: &HealthCheck, client: &reqwest::Client) -> Result<()> {
+        let reference = ClockReference::parse(&check.url)?;
+        let offset =
+            clock_skew::measure(&reference, client, Duration::from_secs(check.timeout_secs)).await?;
+
+        if clock_skew::exceeds(offset, check.max_skew) {
+            anyhow::bail!(
+                "Clock is {} {} (tolerance {:?})",
+                clock_skew::describe(offset),
+                check.url,
+                check.max_skew
+            );
+        }
+
+        tracing::debug!("Clock check '{}': {} {}", check.name, clock_skew::describe(offset), check.url);
+        Ok(())
+    }
+
+    async fn tcp_check(check: &HealthCheck) -> Result<()> {
+        let address = tcp_address(&check.url)?;
+        let timeout = Duration::from_secs(check.timeout_secs);
+        match tokio::time::timeout(timeout, tokio::net::TcpStream::connect(address)).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(e)) => Err(e).with_context(|| format!("TCP connect to {} failed", address)),
+            Err(_) => anyhow::bail!("TCP connect to {} timed out after {:?}", address, timeout),
+        }
+    }
+
+    async fn http_check(check: &HealthCheck, client: &reqwest::Client) -> Result<()> {
         let response = client
             .get(&check.url)
             .send()
             .await
             .context("HTTP request failed")?;
 
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            anyhow::bail!("HTTP status: {}", response.status())
+        let status = response.status();
+        let status_ok = match check.expected_status {
+            Some(expected) => status.as_u16() == expected,
+            None => status.is_success(),
+        };
+        if status_ok && check.expect_body.is_none() {
+            return Ok(());
         }
+
+        let body = response.text().await.unwrap_or_default();
+        if !status_ok {
+            let expected = match check.expected_status {
+                Some(expected) => format!(" (expected {})", expected),
+                None => String::new(),
+            };
+            anyhow::bail!("HTTP status: {}{}, body: {}", status, expected, body_snippet(&body));
+        }
+        if let Some(expectation) = &check.expect_body {
+            if !expectation.is_met(&body) {
+                anyhow::bail!(
+                    "HTTP status: {}, body does not contain {}: {}",
+                    status,
+                    expectation,
+                    body_snippet(&body)
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The `host:port` of a TCP check URL, which may also be written `tcp://host:port`
+pub fn tcp_address(url: &str) -> Result<&str> {
+    let address = url.strip_prefix("tcp://").unwrap_or(url).trim_end_matches('/');
+    let valid_port = address
+        .rsplit_once(':')
+        .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok());
+    if !valid_port {
+        anyhow::bail!("TCP check address '{}' must be host:port", url);
     }
+    Ok(address)
 }
 
 #[cfg(test)]
 #[path = "health_monitor_tests.rs"]
+#[allow(clippy::module_inception)]
 mod tests;
@@ -1,11 +1,13 @@
 #[cfg(test)]
 mod tests {
-    use crate::health_monitor::{HealthCheck, HealthCheckType, HealthMonitor};
+    use crate::health_monitor::{tcp_address, BodyExpectation, HealthCheck, HealthCheckType, HealthMonitor};
     use crate::alerts::{AlertHandler, AlertManager};
+    use crate::ping::PingSettings;
     use anyhow::Result;
     use async_trait::async_trait;
     use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
+    use std::time::Duration;
 
     // Mock alert handler for testing
     struct MockAlertHandler {
@@ -14,6 +16,7 @@ mod tests {
         last_message: Arc<tokio::sync::Mutex<String>>,
     }
 
+    #[allow(dead_code)]
     impl MockAlertHandler {
         fn new(name: &str) -> (Self, Arc<AtomicUsize>, Arc<tokio::sync::Mutex<String>>) {
             let call_count = Arc::new(AtomicUsize::new(0));
@@ -54,6 +57,13 @@ mod tests {
             missed_threshold: 2,
             alert: vec!["slack".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         };
 
         assert_eq!(check.name, "test_api");
@@ -73,6 +83,13 @@ mod tests {
             missed_threshold: 2,
             alert: vec!["slack".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         }];
 
         let alert_manager = Arc::new(AlertManager::new("test-server".to_string()));
@@ -94,6 +111,13 @@ mod tests {
                 missed_threshold: 2,
                 alert: vec!["slack".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
             },
             HealthCheck {
                 name: "database".to_string(),
@@ -104,6 +128,13 @@ mod tests {
                 missed_threshold: 3,
                 alert: vec!["pagerduty".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
             },
         ];
 
@@ -126,6 +157,13 @@ mod tests {
             missed_threshold: 2,
             alert: vec!["slack".to_string(), "pagerduty".to_string(), "discord".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         };
 
         assert_eq!(check.alert.len(), 3);
@@ -146,6 +184,13 @@ mod tests {
             missed_threshold: 1,
             alert: vec!["stdout".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         };
 
         assert_eq!(check.interval, 10);
@@ -165,9 +210,17 @@ mod tests {
             missed_threshold: 1,
             alert: vec!["slack".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         };
 
-        let result = HealthMonitor::perform_check(&check).await;
+        let client = HealthMonitor::build_client(&check).unwrap();
+        let result = HealthMonitor::perform_check(&check, &client).await;
         if result.is_ok() {
             eprintln!("Expected timeout but request succeeded (network issue?)");
         }
@@ -185,9 +238,17 @@ mod tests {
             missed_threshold: 2,
             alert: vec!["slack".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         };
 
-        let result = HealthMonitor::perform_check(&check).await;
+        let client = HealthMonitor::build_client(&check).unwrap();
+        let result = HealthMonitor::perform_check(&check, &client).await;
         if result.is_err() {
             // Network tests can be flaky, just log the error
             eprintln!("HTTP check failed (network issue?): {:?}", result.err());
@@ -206,9 +267,17 @@ mod tests {
             missed_threshold: 2,
             alert: vec!["slack".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         };
 
-        let result = HealthMonitor::perform_check(&check).await;
+        let client = HealthMonitor::build_client(&check).unwrap();
+        let result = HealthMonitor::perform_check(&check, &client).await;
         if result.is_ok() {
             eprintln!("Expected HTTP 500 check to fail, but it succeeded (network issue?)");
         }
@@ -226,9 +295,17 @@ mod tests {
             missed_threshold: 2,
             alert: vec!["slack".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         };
 
-        let result = HealthMonitor::perform_check(&check).await;
+        let client = HealthMonitor::build_client(&check).unwrap();
+        let result = HealthMonitor::perform_check(&check, &client).await;
         if result.is_ok() {
             eprintln!("Expected 404 check to fail, but it succeeded (network issue?)");
         }
@@ -245,9 +322,17 @@ mod tests {
             missed_threshold: 2,
             alert: vec!["slack".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         };
 
-        let result = HealthMonitor::perform_check(&check).await;
+        let client = HealthMonitor::build_client(&check).unwrap();
+        let result = HealthMonitor::perform_check(&check, &client).await;
         assert!(result.is_err());
     }
 
@@ -262,6 +347,13 @@ mod tests {
             missed_threshold: 2,
             alert: vec!["slack".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         };
 
         let cloned = check.clone();
@@ -287,6 +379,13 @@ mod tests {
             missed_threshold: 2,
             alert: vec!["slack".to_string()],
             threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
         };
 
         let debug_str = format!("{:?}", check);
@@ -294,4 +393,449 @@ mod tests {
         assert!(debug_str.contains("Http"));
         assert!(debug_str.contains("localhost:8080"));
     }
+
+    #[tokio::test]
+    async fn test_http_client_built_once_per_check_task() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        // Minimal keep-alive HTTP server that counts connections and requests
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let requests = Arc::new(AtomicUsize::new(0));
+        let (server_connections, server_requests) = (connections.clone(), requests.clone());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                server_connections.fetch_add(1, Ordering::SeqCst);
+                let requests = server_requests.clone();
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    while let Ok(n) = socket.read(&mut buf).await {
+                        if n == 0 {
+                            break;
+                        }
+                        requests.fetch_add(1, Ordering::SeqCst);
+                        let response = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+                        if socket.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        let check = HealthCheck {
+            name: "local_api".to_string(),
+            check_type: HealthCheckType::Http,
+            url: format!("http://{}/health", addr),
+            interval: 1,
+            timeout_secs: 5,
+            missed_threshold: 1,
+            alert: vec![],
+            threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
+        };
+        let alert_manager = Arc::new(AlertManager::new("test-server".to_string()));
+
+        let _ = tokio::time::timeout(
+            std::time::Duration::from_millis(2500),
+            HealthMonitor::run_health_check(check, alert_manager, "test-server".to_string()),
+        )
+        .await;
+
+        assert!(requests.load(Ordering::SeqCst) >= 2, "expected several ticks");
+        // A client built per tick would open a new connection each time
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    /// Answer one SNTP request with a clock running `skew_secs` ahead of ours
+    async fn fake_ntp_server(skew_secs: i64) -> std::net::SocketAddr {
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut request = [0u8; 48];
+            let (_, peer) = socket.recv_from(&mut request).await.unwrap();
+
+            let seconds = (chrono::Utc::now().timestamp() + skew_secs + 2_208_988_800) as u32;
+            let mut response = [0u8; 48];
+            response[0] = 0x1C;
+            response[32..36].copy_from_slice(&seconds.to_be_bytes());
+            response[40..44].copy_from_slice(&seconds.to_be_bytes());
+            socket.send_to(&response, peer).await.unwrap();
+        });
+
+        addr
+    }
+
+    fn clock_check(addr: std::net::SocketAddr) -> HealthCheck {
+        HealthCheck {
+            name: "clock".to_string(),
+            check_type: HealthCheckType::Clock,
+            url: format!("ntp://{}", addr),
+            interval: 60,
+            timeout_secs: 2,
+            missed_threshold: 1,
+            alert: vec![],
+            threshold: None,
+            max_skew: Duration::from_secs(5),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_clock_check_within_tolerance() {
+        let check = clock_check(fake_ntp_server(0).await);
+        let client = HealthMonitor::build_client(&check).unwrap();
+
+        assert!(HealthMonitor::perform_check(&check, &client).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_clock_check_fails_on_skew() {
+        let check = clock_check(fake_ntp_server(30).await);
+        let client = HealthMonitor::build_client(&check).unwrap();
+
+        let error = HealthMonitor::perform_check(&check, &client).await.unwrap_err();
+        assert!(error.to_string().contains("behind"), "unexpected error: {}", error);
+    }
+
+    #[tokio::test]
+    async fn test_configured_failing_check_sends_alert() {
+        // A port with nothing listening on it
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let yaml = format!(
+            r#"
+alerts:
+  oncall:
+    type: stdout
+system_checks:
+  - name: api
+    type: http
+    url: "http://{}/health"
+    interval: 1
+    timeout: 2
+    missed_threshold: 1
+    alert: oncall
+"#,
+            addr
+        );
+        let config: crate::config::Config = serde_yaml::from_str(&yaml).unwrap();
+        let check = HealthCheck::from_config(&config.system_checks[0]).unwrap();
+        assert_eq!(check.check_type, HealthCheckType::Http);
+        assert_eq!(check.missed_threshold, 1);
+        assert_eq!(check.alert, vec!["oncall".to_string()]);
+
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("oncall");
+        alert_manager.register("oncall".to_string(), Arc::new(handler));
+
+        let _ = tokio::time::timeout(
+            Duration::from_millis(1500),
+            HealthMonitor::new(vec![check], Arc::new(alert_manager), "test-server".to_string()).start(),
+        )
+        .await;
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert!(last_message.lock().await.contains("Service 'api' is DOWN"));
+    }
+
+    /// Serve 500s while `failing` is set, 200s otherwise
+    async fn toggling_server(failing: Arc<std::sync::atomic::AtomicBool>) -> std::net::SocketAddr {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let failing = failing.clone();
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 1024];
+                    while let Ok(n) = socket.read(&mut buf).await {
+                        if n == 0 {
+                            break;
+                        }
+                        let status = if failing.load(Ordering::SeqCst) { "500 Internal Server Error" } else { "200 OK" };
+                        let response = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status);
+                        if socket.write_all(response.as_bytes()).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    /// Run a check that fails on its first tick and passes afterwards; returns the alerts sent
+    async fn run_failing_then_recovering(notify_recovery: bool) -> usize {
+        let failing = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let addr = toggling_server(failing.clone()).await;
+
+        let check = HealthCheck {
+            name: "api".to_string(),
+            check_type: HealthCheckType::Http,
+            url: format!("http://{}/health", addr),
+            interval: 1,
+            timeout_secs: 2,
+            missed_threshold: 1,
+            alert: vec!["oncall".to_string()],
+            threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
+        };
+
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("oncall");
+        alert_manager.register("oncall".to_string(), Arc::new(handler));
+
+        let run = HealthMonitor::run_health_check(check, Arc::new(alert_manager), "test-server".to_string());
+        let flip = async {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            failing.store(false, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(1000)).await;
+        };
+        tokio::select! {
+            _ = run => {}
+            _ = flip => {}
+        }
+
+        call_count.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_recovery_alert_sent_by_default() {
+        assert_eq!(run_failing_then_recovering(true).await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_alert_can_be_disabled() {
+        assert_eq!(run_failing_then_recovering(false).await, 1);
+    }
+
+    fn tcp_check(url: String) -> HealthCheck {
+        HealthCheck {
+            name: "postgres".to_string(),
+            check_type: HealthCheckType::Tcp,
+            url,
+            interval: 30,
+            timeout_secs: 2,
+            missed_threshold: 1,
+            alert: vec![],
+            threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: None,
+            args: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tcp_check_succeeds_against_listening_port() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        for url in [addr.to_string(), format!("tcp://{}", addr)] {
+            let check = tcp_check(url);
+            let client = HealthMonitor::build_client(&check).unwrap();
+            assert!(HealthMonitor::perform_check(&check, &client).await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tcp_check_fails_against_unused_port() {
+        // A port with nothing listening on it
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+
+        let check = tcp_check(format!("tcp://{}", addr));
+        let client = HealthMonitor::build_client(&check).unwrap();
+        let error = HealthMonitor::perform_check(&check, &client).await.unwrap_err();
+        assert!(error.to_string().contains("TCP connect to"), "unexpected error: {}", error);
+    }
+
+    #[test]
+    fn test_tcp_address() {
+        assert_eq!(tcp_address("db.internal:5432").unwrap(), "db.internal:5432");
+        assert_eq!(tcp_address("tcp://127.0.0.1:6379/").unwrap(), "127.0.0.1:6379");
+        assert_eq!(tcp_address("[::1]:6379").unwrap(), "[::1]:6379");
+        assert!(tcp_address("db.internal").is_err());
+        assert!(tcp_address("tcp://db.internal:http").is_err());
+        assert!(tcp_address(":5432").is_err());
+    }
+
+    /// Serve every request with the given status line and body
+    async fn http_server(status: &'static str, body: &'static str) -> std::net::SocketAddr {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        addr
+    }
+
+    fn http_check(addr: std::net::SocketAddr, expected_status: Option<u16>, expect_body: Option<&str>) -> HealthCheck {
+        HealthCheck {
+            name: "api".to_string(),
+            check_type: HealthCheckType::Http,
+            url: format!("http://{}/health", addr),
+            interval: 30,
+            timeout_secs: 2,
+            missed_threshold: 1,
+            alert: vec![],
+            threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status,
+            expect_body: expect_body.map(|body| BodyExpectation::parse(body).unwrap()),
+            command: None,
+            args: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_check_reports_status_and_body() {
+        let addr = http_server("418 I'm a teapot", "short and stout").await;
+
+        let check = http_check(addr, None, None);
+        let client = HealthMonitor::build_client(&check).unwrap();
+        let error = HealthMonitor::perform_check(&check, &client).await.unwrap_err().to_string();
+        assert!(error.contains("418"), "unexpected error: {}", error);
+        assert!(error.contains("short and stout"), "unexpected error: {}", error);
+    }
+
+    #[tokio::test]
+    async fn test_http_check_expected_status() {
+        let addr = http_server("418 I'm a teapot", "short and stout").await;
+
+        let check = http_check(addr, Some(418), None);
+        let client = HealthMonitor::build_client(&check).unwrap();
+        assert!(HealthMonitor::perform_check(&check, &client).await.is_ok());
+
+        let check = http_check(addr, Some(200), None);
+        let error = HealthMonitor::perform_check(&check, &client).await.unwrap_err().to_string();
+        assert!(error.contains("418") && error.contains("(expected 200)"), "unexpected error: {}", error);
+    }
+
+    #[tokio::test]
+    async fn test_http_check_expect_body() {
+        let addr = http_server("418 I'm a teapot", r#"{"status": "ok", "teapot": true}"#).await;
+        let client = HealthMonitor::build_client(&http_check(addr, Some(418), None)).unwrap();
+
+        for expected in [r#""status": "ok""#, r#"/"status":\s*"ok"/"#] {
+            let check = http_check(addr, Some(418), Some(expected));
+            assert!(HealthMonitor::perform_check(&check, &client).await.is_ok(), "{} should match", expected);
+        }
+
+        let check = http_check(addr, Some(418), Some("degraded"));
+        let error = HealthMonitor::perform_check(&check, &client).await.unwrap_err().to_string();
+        assert!(error.contains("does not contain \"degraded\""), "unexpected error: {}", error);
+        assert!(error.contains("418") && error.contains("teapot"), "unexpected error: {}", error);
+    }
+
+    #[test]
+    fn test_body_expectation_parse() {
+        assert!(matches!(BodyExpectation::parse("ok").unwrap(), BodyExpectation::Contains(_)));
+        assert!(matches!(BodyExpectation::parse("/^ok$/").unwrap(), BodyExpectation::Matches(_)));
+        assert!(BodyExpectation::parse("/(unclosed/").is_err());
+        // A lone slash is just text
+        assert!(BodyExpectation::parse("/").unwrap().is_met("a/b"));
+    }
+
+    fn exec_check(command: &str, args: &[&str]) -> HealthCheck {
+        HealthCheck {
+            name: "backup".to_string(),
+            check_type: HealthCheckType::Exec,
+            url: String::new(),
+            interval: 30,
+            timeout_secs: 2,
+            missed_threshold: 1,
+            alert: vec![],
+            threshold: None,
+            max_skew: Duration::from_secs(2),
+            notify_recovery: true,
+            ping: PingSettings::default(),
+            expected_status: None,
+            expect_body: None,
+            command: Some(command.to_string()),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_check_maps_exit_code() {
+        let check = exec_check("true", &[]);
+        let client = HealthMonitor::build_client(&check).unwrap();
+        assert!(HealthMonitor::perform_check(&check, &client).await.is_ok());
+
+        let check = exec_check("false", &[]);
+        assert!(HealthMonitor::perform_check(&check, &client).await.is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_check_reports_stderr() {
+        let check = exec_check("sh", &["-c", "echo 'disk full' >&2; exit 3"]);
+        let client = HealthMonitor::build_client(&check).unwrap();
+        let error = HealthMonitor::perform_check(&check, &client).await.unwrap_err().to_string();
+        assert!(error.contains("disk full"), "unexpected error: {}", error);
+        assert!(error.contains('3'), "unexpected error: {}", error);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_exec_check_times_out() {
+        let mut check = exec_check("sleep", &["5"]);
+        check.timeout_secs = 1;
+        let client = HealthMonitor::build_client(&check).unwrap();
+        let error = HealthMonitor::perform_check(&check, &client).await.unwrap_err().to_string();
+        assert!(error.contains("timed out"), "unexpected error: {}", error);
+    }
+
+    #[tokio::test]
+    async fn test_exec_check_missing_command() {
+        let check = exec_check("tinywatcher-no-such-command", &[]);
+        let client = HealthMonitor::build_client(&check).unwrap();
+        let error = HealthMonitor::perform_check(&check, &client).await.unwrap_err().to_string();
+        assert!(error.contains("Failed to run"), "unexpected error: {}", error);
+    }
 }
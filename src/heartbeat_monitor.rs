@@ -1,8 +1,8 @@
+use crate::config::HeartbeatConfig;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use tokio::time::interval;
-use tracing;
 
 /// Response from heartbeat endpoint
 #[derive(Debug, Deserialize, Serialize)]
@@ -20,13 +20,11 @@ pub struct HeartbeatMonitor {
     client: reqwest::Client,
 }
 
-impl HeartbeatMonitor {
-    pub fn new(url: String, interval_secs: u64, identity: String) -> Self {
-        let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(10))
-            .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
+/// How long a single heartbeat request may take
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(10);
 
+impl HeartbeatMonitor {
+    pub fn new(url: String, interval_secs: u64, identity: String, client: reqwest::Client) -> Self {
         Self {
             url,
             interval_secs,
@@ -35,8 +33,16 @@ impl HeartbeatMonitor {
         }
     }
 
+    /// One monitor per configured endpoint
+    pub fn from_configs(configs: &[HeartbeatConfig], identity: &str, client: &reqwest::Client) -> Vec<Self> {
+        configs
+            .iter()
+            .map(|config| Self::new(config.url.clone(), config.interval, identity.to_string(), client.clone()))
+            .collect()
+    }
+
     /// Start the heartbeat monitoring loop
-    pub async fn start(self) {
+    pub async fn start(&self) {
         let mut ticker = interval(Duration::from_secs(self.interval_secs));
         
         tracing::info!(
@@ -60,9 +66,10 @@ impl HeartbeatMonitor {
 
         let response = self.client
             .post(&self.url)
+            .timeout(HEARTBEAT_TIMEOUT)
             .json(&serde_json::json!({
                 "watcher_name": self.identity,
-                "timestamp": chrono::Utc::now().to_rfc3339(),
+                "timestamp": crate::timestamp::now_rfc3339(),
             }))
             .send()
             .await
@@ -133,6 +140,7 @@ mod tests {
             "https://example.com/ping".to_string(),
             60,
             "test-watcher".to_string(),
+            reqwest::Client::new(),
         );
 
         assert_eq!(monitor.url, "https://example.com/ping");
@@ -140,6 +148,30 @@ mod tests {
         assert_eq!(monitor.identity, "test-watcher");
     }
 
+    #[test]
+    fn test_monitor_per_endpoint() {
+        let configs = vec![
+            HeartbeatConfig {
+                url: "https://deadman.example.com/ping".to_string(),
+                interval: 60,
+            },
+            HeartbeatConfig {
+                url: "http://monitor.internal/heartbeat".to_string(),
+                interval: 15,
+            },
+        ];
+
+        let monitors = HeartbeatMonitor::from_configs(&configs, "test-watcher", &reqwest::Client::new());
+        assert_eq!(monitors.len(), 2);
+        assert_eq!(monitors[0].url, "https://deadman.example.com/ping");
+        assert_eq!(monitors[0].interval_secs, 60);
+        assert_eq!(monitors[1].url, "http://monitor.internal/heartbeat");
+        assert_eq!(monitors[1].interval_secs, 15);
+        assert!(monitors.iter().all(|monitor| monitor.identity == "test-watcher"));
+
+        assert!(HeartbeatMonitor::from_configs(&[], "test-watcher", &reqwest::Client::new()).is_empty());
+    }
+
     #[test]
     fn test_heartbeat_response_deserialization() {
         let json = r#"{
@@ -169,4 +201,36 @@ mod tests {
         assert_eq!(response.next_ping_in, None);
         assert_eq!(response.watcher_name, None);
     }
+
+    #[tokio::test]
+    async fn test_configured_heartbeat_pings_endpoint() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (request_tx, mut request_rx) = tokio::sync::mpsc::channel::<String>(1);
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            let response = "HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+            socket.write_all(response.as_bytes()).await.unwrap();
+            let _ = request_tx.send(String::from_utf8_lossy(&buf[..n]).to_string()).await;
+        });
+
+        let yaml = format!("heartbeat:\n  url: \"http://{}/ping/abc123\"\n  interval: 60\n", addr);
+        let config: crate::config::Config = serde_yaml::from_str(&yaml).unwrap();
+        let monitors = HeartbeatMonitor::from_configs(&config.heartbeat, "test-watcher", &reqwest::Client::new());
+        assert_eq!(monitors.len(), 1);
+        for monitor in monitors {
+            tokio::spawn(async move { monitor.start().await });
+        }
+
+        let request = tokio::time::timeout(Duration::from_secs(5), request_rx.recv())
+            .await
+            .expect("no heartbeat received")
+            .unwrap();
+        assert!(request.starts_with("POST /ping/abc123 "), "unexpected request: {}", request);
+        assert!(request.contains("\"watcher_name\":\"test-watcher\""), "unexpected request: {}", request);
+    }
 }
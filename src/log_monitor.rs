@@ -1,13 +1,23 @@
-use crate::alerts::AlertManager;
-use crate::config::{MatchType, Rule, SourceType, Threshold};
+use crate::alerts::{AlertContext, AlertManager, AlertSource, AlertSourceKind};
+use crate::config::{
+    route_cooldown_key, ContainerRuntime, ContainerSelector, DeployAction, DeployConfig, KubernetesConfig, MultilineConfig,
+    Rule, Severity, SourceType, Threshold,
+};
+use crate::file_tail::{self, Compression, FileTail};
+use crate::rule_matcher::{Pattern, RuleMatcher};
+use crate::silent_sources::SilenceWatchdog;
+use crate::state::{Clock, FileOffset, ThresholdState};
+use aho_corasick::AhoCorasick;
 use anyhow::{Context, Result};
-use regex::Regex;
-use std::collections::VecDeque;
+use chrono::{DateTime, SecondsFormat, Utc};
+use regex::{Regex, RegexSet};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::path::PathBuf;
 use std::process::Stdio;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Command;
 use tokio::sync::Mutex;
 
@@ -20,9 +30,342 @@ const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
 /// Maximum retry delay
 const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
 
+/// How often a followed file is checked for new lines when no change event arrives
+const FILE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often watchers check for multiline records that have timed out
+const MULTILINE_FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How many of the last stderr lines of a followed command are kept to explain why it exited
+const STDERR_TAIL_LINES: usize = 5;
+
 pub struct LogMonitor {
-    rules: Vec<CompiledRule>,
+    /// Active rules, shared with cloned monitors and swapped as a whole on config reload
+    rules: Arc<RwLock<Arc<RuleSet>>>,
     alert_manager: Arc<AlertManager>,
+    /// Deploy markers, shared by every watcher of this monitor
+    deploy: Option<Arc<DeployWatch>>,
+    /// Fraction of lines to evaluate for sources matching each pattern
+    sample_rates: Vec<(glob::Pattern, f64)>,
+    /// How far each watched file has been read, so watching resumes there after a retry or restart
+    offsets: Arc<Mutex<HashMap<PathBuf, FileOffset>>>,
+    /// CLI used to follow and discover containers
+    runtime: ContainerRuntime,
+    /// Notes every line so sources that go quiet can be reported
+    silence: Option<Arc<SilenceWatchdog>>,
+}
+
+/// Compiled rules with the prefilter built over them
+struct RuleSet {
+    rules: Vec<CompiledRule>,
+    /// Regex and text rules compiled together, to skip rules that can't match a line
+    prefilter: Option<Prefilter>,
+}
+
+impl RuleSet {
+    fn new(rules: Vec<CompiledRule>) -> Self {
+        let prefilter = Prefilter::build(&rules);
+        Self { rules, prefilter }
+    }
+
+    /// Which rules might match a line, per the prefilter; `None` means all of them
+    fn candidates(&self, line: &str) -> Option<Vec<bool>> {
+        self.prefilter
+            .as_ref()
+            .map(|prefilter| prefilter.candidates(line, self.rules.len()))
+    }
+}
+
+/// Automata over the rules that match raw lines, so one pass over a line tells which
+/// rules might match; only those run their own check (for conditions and captures)
+struct Prefilter {
+    /// Regex rules as a `RegexSet`, with the index into `RuleSet::rules` of each pattern
+    regexes: Option<(RegexSet, Vec<usize>)>,
+    /// Case-sensitive text rules
+    text: Option<Needles>,
+    /// `case_insensitive` text rules, run over the lowercased line
+    text_ignore_case: Option<Needles>,
+}
+
+/// Text rule needles in one Aho-Corasick automaton
+struct Needles {
+    automaton: AhoCorasick,
+    /// Rules using each needle, by pattern id; rules sharing a needle share an entry
+    rules: Vec<Vec<usize>>,
+}
+
+impl Needles {
+    /// Build the automaton, or `None` if fewer than two rules would use it
+    fn build(needles: Vec<(usize, &str)>) -> Option<Self> {
+        if needles.len() < 2 {
+            return None;
+        }
+
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+        let mut patterns: Vec<&str> = Vec::new();
+        let mut rules: Vec<Vec<usize>> = Vec::new();
+        for (index, needle) in needles {
+            let id = *ids.entry(needle).or_insert_with(|| {
+                patterns.push(needle);
+                rules.push(Vec::new());
+                patterns.len() - 1
+            });
+            rules[id].push(index);
+        }
+
+        match AhoCorasick::new(&patterns) {
+            Ok(automaton) => Some(Self { automaton, rules }),
+            Err(e) => {
+                tracing::debug!("Not prefiltering text rules with Aho-Corasick: {}", e);
+                None
+            }
+        }
+    }
+
+    fn members(&self) -> impl Iterator<Item = usize> + '_ {
+        self.rules.iter().flatten().copied()
+    }
+
+    /// Mark the rules whose needle occurs in `line`
+    fn mark(&self, line: &str, candidates: &mut [bool]) {
+        for hit in self.automaton.find_overlapping_iter(line) {
+            for &index in &self.rules[hit.pattern().as_usize()] {
+                candidates[index] = true;
+            }
+        }
+    }
+}
+
+impl Prefilter {
+    /// Build the automata, or `None` if no kind of rule has enough members to benefit
+    fn build(rules: &[CompiledRule]) -> Option<Self> {
+        let plain = || {
+            rules
+                .iter()
+                .enumerate()
+                .filter(|(_, rule)| rule.matcher.json_field.is_none() && rule.multiline.is_none())
+        };
+
+        let (indices, patterns): (Vec<usize>, Vec<&str>) = plain()
+            .filter_map(|(index, rule)| match &rule.matcher.pattern {
+                Pattern::Regex(regex) => Some((index, regex.as_str())),
+                _ => None,
+            })
+            .unzip();
+        let regexes = if patterns.len() < 2 {
+            None
+        } else {
+            match RegexSet::new(&patterns) {
+                Ok(set) => Some((set, indices)),
+                Err(e) => {
+                    // Too large for one automaton; rules are still checked one by one
+                    tracing::debug!("Not prefiltering rules with a regex set: {}", e);
+                    None
+                }
+            }
+        };
+
+        let text = Needles::build(
+            plain()
+                .filter_map(|(index, rule)| match &rule.matcher.pattern {
+                    Pattern::Text(text) => Some((index, text.as_str())),
+                    _ => None,
+                })
+                .collect(),
+        );
+        let text_ignore_case = Needles::build(
+            plain()
+                .filter_map(|(index, rule)| match &rule.matcher.pattern {
+                    Pattern::TextIgnoreCase(text) => Some((index, text.as_str())),
+                    _ => None,
+                })
+                .collect(),
+        );
+
+        if regexes.is_none() && text.is_none() && text_ignore_case.is_none() {
+            return None;
+        }
+        Some(Self {
+            regexes,
+            text,
+            text_ignore_case,
+        })
+    }
+
+    /// Which rules might match the line; rules the prefilter doesn't cover are always candidates
+    fn candidates(&self, line: &str, rule_count: usize) -> Vec<bool> {
+        let mut candidates = vec![true; rule_count];
+
+        if let Some((set, rules)) = &self.regexes {
+            for &index in rules {
+                candidates[index] = false;
+            }
+            for matched in set.matches(line).iter() {
+                candidates[rules[matched]] = true;
+            }
+        }
+
+        if let Some(text) = &self.text {
+            for index in text.members() {
+                candidates[index] = false;
+            }
+            text.mark(line, &mut candidates);
+        }
+
+        if let Some(text) = &self.text_ignore_case {
+            for index in text.members() {
+                candidates[index] = false;
+            }
+            text.mark(&line.to_lowercase(), &mut candidates);
+        }
+
+        candidates
+    }
+}
+
+/// Deterministic per-line sampling: a given line is always either kept or skipped
+fn sampled(line: &str, rate: f64) -> bool {
+    if rate >= 1.0 {
+        return true;
+    }
+
+    // FNV-1a, then a final mix so similar lines spread across the range
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in line.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+
+    (hash as f64 / u64::MAX as f64) < rate
+}
+
+/// Split a line on carriage returns, as rules should see it
+/// CRLF input leaves a trailing `\r` that breaks anchored patterns like `ERROR$`, and some
+/// appliances end lines with a bare `\r`; both are treated as line endings
+pub(crate) fn split_line_endings(line: &str) -> Vec<&str> {
+    if !line.contains('\r') {
+        return vec![line];
+    }
+    line.split('\r').filter(|segment| !segment.is_empty()).collect()
+}
+
+/// Groups continuation lines into records for a `multiline` rule
+struct Multiline {
+    start: Regex,
+    timeout: Duration,
+    max_lines: usize,
+    /// Record being assembled for each source
+    pending: Arc<Mutex<HashMap<SourceType, PendingRecord>>>,
+}
+
+struct PendingRecord {
+    lines: Vec<String>,
+    updated: Instant,
+}
+
+impl Multiline {
+    fn new(config: &MultilineConfig) -> Result<Self> {
+        Ok(Self {
+            start: Regex::new(&config.start).context("Invalid multiline start pattern")?,
+            timeout: config.timeout(),
+            max_lines: config.max_lines(),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Add a line to the source's record and return the record it completes, if any
+    async fn push(&self, line: &str, source: &SourceType, now: Instant) -> Option<String> {
+        let mut pending = self.pending.lock().await;
+
+        // A continuation line with nothing pending (e.g. watching started mid-trace) opens a record
+        if self.start.is_match(line) || !pending.contains_key(source) {
+            let completed = pending.insert(
+                source.clone(),
+                PendingRecord {
+                    lines: vec![line.to_string()],
+                    updated: now,
+                },
+            );
+            return completed.map(|record| record.lines.join("\n"));
+        }
+
+        let record = pending.get_mut(source)?;
+        record.lines.push(line.to_string());
+        record.updated = now;
+        if record.lines.len() >= self.max_lines {
+            return pending.remove(source).map(|record| record.lines.join("\n"));
+        }
+        None
+    }
+
+    /// Take the records that haven't grown within the timeout
+    async fn take_stale(&self, now: Instant) -> Vec<(SourceType, String)> {
+        let mut pending = self.pending.lock().await;
+        let stale: Vec<SourceType> = pending
+            .iter()
+            .filter(|(_, record)| now.saturating_duration_since(record.updated) >= self.timeout)
+            .map(|(source, _)| source.clone())
+            .collect();
+
+        stale
+            .into_iter()
+            .filter_map(|source| {
+                let record = pending.remove(&source)?;
+                Some((source, record.lines.join("\n")))
+            })
+            .collect()
+    }
+
+    /// Take the source's pending record, e.g. when its watcher stops
+    async fn take(&self, source: &SourceType) -> Option<String> {
+        self.pending
+            .lock()
+            .await
+            .remove(source)
+            .map(|record| record.lines.join("\n"))
+    }
+}
+
+/// Tracks whether a deploy is in progress from its marker lines
+struct DeployWatch {
+    start: Regex,
+    end: Option<Regex>,
+    timeout: Duration,
+    action: DeployAction,
+    /// When the current deploy started, if one is in progress
+    started: Mutex<Option<Instant>>,
+}
+
+impl DeployWatch {
+    /// Update the deploy state from a line and return whether a deploy is in progress
+    async fn observe(&self, line: &str, now: Instant) -> bool {
+        let mut started = self.started.lock().await;
+
+        if self.start.is_match(line) {
+            if started.is_none() {
+                tracing::info!(
+                    "Deploy started; quieting log alerts for up to {}",
+                    crate::config::format_duration(self.timeout)
+                );
+            }
+            *started = Some(now);
+        } else if self.end.as_ref().is_some_and(|end| end.is_match(line)) {
+            if started.take().is_some() {
+                tracing::info!("Deploy finished; resuming normal alerting");
+            }
+        } else if started.is_some_and(|at| now.saturating_duration_since(at) >= self.timeout) {
+            *started = None;
+            tracing::warn!(
+                "Deploy still in progress after {}; resuming normal alerting",
+                crate::config::format_duration(self.timeout)
+            );
+        }
+
+        started.is_some()
+    }
 }
 
 struct CompiledRule {
@@ -32,49 +375,344 @@ struct CompiledRule {
     cooldown: u64,
     sources: Option<crate::config::RuleSources>,
     threshold: Option<Threshold>,
-    /// Sliding window of match timestamps for threshold tracking
-    match_history: Arc<Mutex<VecDeque<Instant>>>,
+    severity: Severity,
+    /// Alert if the pattern hasn't been seen within this window
+    expect_within: Option<Duration>,
+    /// Last sighting of the pattern, for `expect_within` tracking
+    last_seen: Arc<Mutex<Sighting>>,
+    /// Sliding window of match timestamps (and lines, if `include_matches` is set) for threshold tracking
+    match_history: Arc<Mutex<VecDeque<ThresholdMatch>>>,
+    /// Number of recent matching lines to include when the threshold fires
+    include_matches: usize,
+    /// Whether the rule has alerted since it was last resolved
+    firing: Arc<AtomicBool>,
+    /// Only alert on the first match for the life of the process
+    once: bool,
+    /// Set once a `once` rule has alerted
+    spent: Arc<AtomicBool>,
+    /// Joins continuation lines into records before matching
+    multiline: Option<Arc<Multiline>>,
 }
 
-enum RuleMatcher {
-    Text(String),
-    Regex(Regex),
+/// A match recorded for threshold tracking, with its line if it may be reported
+pub(crate) type ThresholdMatch = (Instant, Option<String>);
+
+struct Sighting {
+    at: Instant,
+    /// Whether the absence since `at` has already been alerted
+    alerted: bool,
 }
 
-impl LogMonitor {
-    pub fn new(rules: Vec<Rule>, alert_manager: Arc<AlertManager>) -> Result<Self> {
-        let compiled_rules = rules
+/// Running containers with their labels, one `name<TAB>labels` line each
+async fn list_containers(runtime: ContainerRuntime) -> Result<String> {
+    let output = Command::new(runtime.binary())
+        .args(runtime.ps_args())
+        .output()
+        .await
+        .map_err(|e| runtime.command_error(e))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} ps failed: {}",
+            runtime.binary(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse a container's labels as printed by `{{json .Labels}}`: an object (podman) or
+/// a comma-separated `key=value` string (docker, nerdctl)
+fn parse_labels(labels: &str) -> HashMap<String, String> {
+    match serde_json::from_str(labels) {
+        Ok(serde_json::Value::Object(map)) => map
             .into_iter()
-            .map(|rule| {
-                let matcher = match rule.match_type() {
-                    MatchType::Text(text) => RuleMatcher::Text(text),
-                    MatchType::Regex(pattern) => {
-                        let regex = Regex::new(&pattern)
-                            .context(format!("Invalid regex pattern in rule: {}", rule.name))?;
-                        RuleMatcher::Regex(regex)
-                    }
-                };
+            .map(|(key, value)| match value {
+                serde_json::Value::String(value) => (key, value),
+                other => (key, other.to_string()),
+            })
+            .collect(),
+        Ok(serde_json::Value::String(joined)) => parse_joined_labels(&joined),
+        Ok(_) => HashMap::new(),
+        Err(_) => parse_joined_labels(labels),
+    }
+}
 
-                Ok(CompiledRule {
-                    name: rule.name.clone(),
-                    matcher,
-                    alert_names: rule.alert,
-                    cooldown: rule.cooldown,
-                    sources: rule.sources,
-                    threshold: rule.threshold,
-                    match_history: Arc::new(Mutex::new(VecDeque::new())),
-                })
+/// Parse comma-separated `key=value` labels
+/// Values may contain commas, so a piece without `=` continues the previous value
+fn parse_joined_labels(labels: &str) -> HashMap<String, String> {
+    let mut parsed = HashMap::new();
+    let mut last_key: Option<String> = None;
+    for piece in labels.split(',').filter(|piece| !piece.is_empty()) {
+        match (piece.split_once('='), &last_key) {
+            (Some((key, value)), _) => {
+                parsed.insert(key.to_string(), value.to_string());
+                last_key = Some(key.to_string());
+            }
+            (None, Some(key)) => {
+                if let Some(value) = parsed.get_mut(key) {
+                    value.push(',');
+                    value.push_str(piece);
+                }
+            }
+            (None, None) => {
+                parsed.insert(piece.to_string(), String::new());
+            }
+        }
+    }
+    parsed
+}
+
+/// Names of the containers in `ps` output that the selector picks
+fn select_containers(ps_output: &str, selector: &ContainerSelector) -> Vec<String> {
+    ps_output
+        .lines()
+        .filter_map(|line| {
+            let (name, labels) = line.split_once('\t').unwrap_or((line, ""));
+            let name = name.trim();
+            (!name.is_empty() && selector.matches(&parse_labels(labels.trim()))).then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// `journalctl` arguments to follow new entries of a unit, or of a `FIELD=value` match
+/// such as `_SYSTEMD_UNIT=nginx.service` or `SYSLOG_IDENTIFIER=cron`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn journalctl_args(unit: &str) -> Vec<&str> {
+    let mut args = vec!["--follow", "--lines=0", "--output=cat"];
+    if unit.contains('=') {
+        args.push(unit);
+    } else {
+        args.extend(["--unit", unit]);
+    }
+    args
+}
+
+/// Read a followed command's stderr as it is written, so a chatty process never blocks on a full pipe
+/// Resolves to the last `STDERR_TAIL_LINES` lines once the stream closes
+fn drain_stderr<R>(stderr: R) -> tokio::task::JoinHandle<String>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if tail.len() == STDERR_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+        Vec::from(tail).join("\n").trim().to_string()
+    })
+}
+
+/// `kubectl logs` arguments to follow a pod, or every pod matching a selector
+/// With `since`, lines logged after that time are included, e.g. by a restarted container
+fn kubectl_logs_args(config: &KubernetesConfig, since: Option<DateTime<Utc>>) -> Vec<String> {
+    let mut args = vec!["logs".to_string(), "--follow".to_string()];
+    match since {
+        Some(since) => args.push(format!(
+            "--since-time={}",
+            since.to_rfc3339_opts(SecondsFormat::Secs, true)
+        )),
+        None => args.push("--tail=0".to_string()),
+    }
+    if let Some(namespace) = &config.namespace {
+        args.extend(["--namespace".to_string(), namespace.clone()]);
+    }
+    if let Some(pod) = &config.pod {
+        args.push(pod.clone());
+    } else if let Some(selector) = &config.selector {
+        args.extend(["--selector".to_string(), selector.clone()]);
+    }
+    match &config.container {
+        Some(container) => args.extend(["--container".to_string(), container.clone()]),
+        None => args.push("--all-containers".to_string()),
+    }
+    args
+}
+
+/// Describe a log source for structured alert context
+fn alert_source(source: &SourceType) -> Option<AlertSource> {
+    let (kind, name) = match source {
+        SourceType::File(path) => (AlertSourceKind::File, path.display().to_string()),
+        SourceType::Container(name) => (AlertSourceKind::Container, name.clone()),
+        // Streams are handled by the stream monitor, which knows their type and URL
+        SourceType::Stream(_) => return None,
+        #[cfg(windows)]
+        SourceType::EventLog(log) => (AlertSourceKind::EventLog, log.clone()),
+        SourceType::Journal(name) => (AlertSourceKind::Journal, name.clone()),
+        SourceType::Pod(name) => (AlertSourceKind::Pod, name.clone()),
+    };
+
+    Some(AlertSource { kind, name, url: None })
+}
+
+/// Compile config rules, skipping (and reporting) disabled and expired ones
+fn compile_rules(rules: Vec<Rule>) -> Result<Vec<CompiledRule>> {
+    let now = Utc::now();
+    rules
+        .into_iter()
+        .filter(|rule| {
+            if !rule.enabled {
+                tracing::info!("Rule '{}' is disabled; skipping it", rule.name);
+            }
+            rule.enabled
+        })
+        .filter(|rule| {
+            let expired = rule.is_expired(now);
+            if expired {
+                tracing::warn!("Rule '{}' has expired; skipping it", rule.name);
+            }
+            !expired
+        })
+        .map(|rule| {
+            let matcher = RuleMatcher::new(&rule)?;
+            Ok(CompiledRule {
+                name: rule.name.clone(),
+                matcher,
+                alert_names: rule.alert,
+                cooldown: rule.cooldown,
+                sources: rule.sources,
+                threshold: rule.threshold,
+                severity: rule.severity,
+                expect_within: rule.expect_within,
+                last_seen: Arc::new(Mutex::new(Sighting {
+                    at: Instant::now(),
+                    alerted: false,
+                })),
+                match_history: Arc::new(Mutex::new(VecDeque::new())),
+                include_matches: rule.include_matches,
+                firing: Arc::new(AtomicBool::new(false)),
+                once: rule.once,
+                spent: Arc::new(AtomicBool::new(false)),
+                multiline: rule
+                    .multiline
+                    .as_ref()
+                    .map(Multiline::new)
+                    .transpose()
+                    .context(format!("Invalid multiline config in rule: {}", rule.name))?
+                    .map(Arc::new),
             })
-            .collect::<Result<Vec<_>>>()?;
+        })
+        .collect::<Result<Vec<_>>>()
+}
 
+impl LogMonitor {
+    pub fn new(rules: Vec<Rule>, alert_manager: Arc<AlertManager>) -> Result<Self> {
+        let rules = RuleSet::new(compile_rules(rules)?);
         Ok(Self {
-            rules: compiled_rules,
+            rules: Arc::new(RwLock::new(Arc::new(rules))),
             alert_manager,
+            deploy: None,
+            sample_rates: Vec::new(),
+            offsets: Arc::new(Mutex::new(HashMap::new())),
+            runtime: ContainerRuntime::default(),
+            silence: None,
+        })
+    }
+
+    /// The rules in effect right now; a reload swaps in a new set without disturbing callers holding this one
+    fn rule_set(&self) -> Arc<RuleSet> {
+        self.rules.read().unwrap().clone()
+    }
+
+    /// Replace the running rules, e.g. after the config file changed
+    /// Rules keep their threshold windows, `expect_within` timers, firing and `once` state
+    /// across the swap when a rule of the same name existed before
+    pub fn reload_rules(&self, rules: Vec<Rule>) -> Result<()> {
+        let mut compiled = compile_rules(rules)?;
+        let current = self.rule_set();
+        for rule in &mut compiled {
+            if let Some(previous) = current.rules.iter().find(|previous| previous.name == rule.name) {
+                rule.last_seen = previous.last_seen.clone();
+                rule.match_history = previous.match_history.clone();
+                rule.firing = previous.firing.clone();
+                rule.spent = previous.spent.clone();
+            }
+        }
+
+        *self.rules.write().unwrap() = Arc::new(RuleSet::new(compiled));
+        Ok(())
+    }
+
+    /// Evaluate only a fraction of lines from matching sources (file path or glob, container or event log name)
+    pub fn set_sample_rates(&mut self, rates: &std::collections::HashMap<String, f64>) -> Result<()> {
+        self.sample_rates = rates
+            .iter()
+            .map(|(source, rate)| {
+                let pattern = glob::Pattern::new(source)
+                    .with_context(|| format!("Invalid sample_rate source '{}'", source))?;
+                Ok((pattern, *rate))
+            })
+            .collect::<Result<_>>()?;
+        Ok(())
+    }
+
+    /// Sample rate for a source, if one is configured
+    fn sample_rate(&self, source: &SourceType) -> Option<f64> {
+        self.sample_rates.iter().find_map(|(pattern, rate)| {
+            let matches = match source {
+                SourceType::File(path) => pattern.matches_path(path),
+                SourceType::Container(name) => pattern.matches(name),
+                SourceType::Stream(_) => false,
+                #[cfg(windows)]
+                SourceType::EventLog(log) => pattern.matches(log),
+                SourceType::Journal(name) => pattern.matches(name),
+                SourceType::Pod(name) => pattern.matches(name),
+            };
+            matches.then_some(*rate)
         })
     }
 
+    /// Report every line seen to the watchdog that alerts on sources going quiet
+    pub fn set_silence_watchdog(&mut self, watchdog: Arc<SilenceWatchdog>) {
+        self.silence = Some(watchdog);
+    }
+
+    /// Use podman or nerdctl instead of docker for container inputs
+    pub fn set_container_runtime(&mut self, runtime: ContainerRuntime) {
+        self.runtime = runtime;
+    }
+
+    /// Quiet alerts between deploy start and end marker lines
+    pub fn set_deploy_markers(&mut self, config: &DeployConfig) -> Result<()> {
+        let start = Regex::new(&config.start).context("Invalid deploy start pattern")?;
+        let end = config
+            .end
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .context("Invalid deploy end pattern")?;
+
+        self.deploy = Some(Arc::new(DeployWatch {
+            start,
+            end,
+            timeout: config.timeout(),
+            action: config.action,
+            started: Mutex::new(None),
+        }));
+        Ok(())
+    }
+
     /// Watch a file with automatic retry and reconnection
+    /// Compressed files are read once, after which the watcher idles until shutdown rather than being restarted
     pub async fn watch_file(&self, path: PathBuf) -> Result<()> {
+        match Compression::of(&path) {
+            Some(Compression::Gzip) => {
+                self.read_compressed_file(path).await?;
+                return std::future::pending().await;
+            }
+            Some(Compression::Unsupported) => {
+                tracing::warn!(
+                    "Skipping {}: only gzip-compressed logs can be read",
+                    path.display()
+                );
+                return std::future::pending().await;
+            }
+            None => {}
+        }
+
         let mut retry_delay = INITIAL_RETRY_DELAY;
         
         loop {
@@ -103,68 +741,99 @@ impl LogMonitor {
     async fn watch_file_once(&self, path: PathBuf) -> Result<()> {
         tracing::info!("Starting file watch: {}", path.display());
 
-        let mut cmd = Command::new("tail")
-            .arg("-F")  // Follow by name, handles log rotation
-            .arg("-n")
-            .arg("0")
-            .arg(&path)
-            .stdout(Stdio::piped())
-            .spawn()
-            .context("Failed to spawn tail command")?;
-
-        let stdout = cmd.stdout.take().context("Failed to capture stdout")?;
-        let reader = BufReader::new(stdout);
-        let mut lines = reader.lines();
+        let (changed_tx, mut changed) = tokio::sync::mpsc::channel(1);
+        let _watcher = file_tail::watch(&path, changed_tx)?;
+        let saved = self.offsets.lock().await.get(&path).cloned();
+        let mut tail = FileTail::open(&path, saved.as_ref()).await?;
 
         let source = SourceType::File(path.clone());
-        
+        let multiline = self.has_multiline();
+        let mut flush = tokio::time::interval(MULTILINE_FLUSH_INTERVAL);
+        // Change events can be missed (e.g. on network filesystems), so check periodically too
+        let mut poll = tokio::time::interval(FILE_POLL_INTERVAL);
+
         loop {
             tokio::select! {
-                line_result = lines.next_line() => {
-                    match line_result {
-                        Ok(Some(line)) => {
-                            // Enforce line length limit
-                            if line.len() > MAX_LINE_LENGTH {
-                                tracing::warn!(
-                                    "Skipping line longer than {} bytes in {}",
-                                    MAX_LINE_LENGTH,
-                                    path.display()
-                                );
-                                continue;
-                            }
-                            self.process_line(&line, &source).await;
-                        }
-                        Ok(None) => {
-                            tracing::debug!("EOF reached for {}", path.display());
-                            break;
-                        }
-                        Err(e) => {
-                            // Kill child process before returning error
-                            let _ = cmd.kill().await;
-                            return Err(e.into());
-                        }
+                _ = flush.tick(), if multiline => {
+                    self.flush_stale_records().await;
+                    continue;
+                }
+                event = changed.recv() => {
+                    if event.is_none() {
+                        self.flush_records(&source).await;
+                        anyhow::bail!("File watcher stopped for {}", path.display());
                     }
                 }
-                status = cmd.wait() => {
-                    match status {
-                        Ok(exit_status) => {
-                            tracing::warn!(
-                                "tail process exited with status: {} for {}",
-                                exit_status,
-                                path.display()
-                            );
-                            return Err(anyhow::anyhow!("tail process exited: {}", exit_status));
-                        }
-                        Err(e) => {
-                            return Err(e).context("Failed to wait on tail process");
-                        }
+                _ = poll.tick() => {}
+            }
+
+            // Catch up a chunk at a time, so a large backlog is never held in memory at once
+            loop {
+                let lines = match tail.read_lines().await {
+                    Ok(lines) => lines,
+                    Err(e) => {
+                        self.flush_records(&source).await;
+                        return Err(e);
                     }
+                };
+                for line in lines {
+                    // Enforce line length limit
+                    if line.len() > MAX_LINE_LENGTH {
+                        tracing::warn!(
+                            "Skipping line longer than {} bytes in {}",
+                            MAX_LINE_LENGTH,
+                            path.display()
+                        );
+                        continue;
+                    }
+                    self.process_line(&line, &source).await;
+                }
+                if let Some(checkpoint) = tail.checkpoint() {
+                    self.offsets.lock().await.insert(path.clone(), checkpoint);
                 }
+                if !tail.has_more() {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Check every line of a gzipped (rotated) log once; compressed files don't grow
+    async fn read_compressed_file(&self, path: PathBuf) -> Result<()> {
+        let metadata = tokio::fs::metadata(&path)
+            .await
+            .with_context(|| format!("Failed to stat {}", path.display()))?;
+        let done = file_tail::end_of(&metadata);
+        if done.is_some() && self.offsets.lock().await.get(&path) == done.as_ref() {
+            tracing::info!("Already read compressed file: {}", path.display());
+            return Ok(());
+        }
+        tracing::info!("Reading compressed file: {}", path.display());
+
+        let (lines_tx, mut lines) = tokio::sync::mpsc::channel(1024);
+        let reader = {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || file_tail::read_gzip_lines(&path, lines_tx))
+        };
+
+        let source = SourceType::File(path.clone());
+        while let Some(line) = lines.recv().await {
+            if line.len() > MAX_LINE_LENGTH {
+                tracing::warn!(
+                    "Skipping line longer than {} bytes in {}",
+                    MAX_LINE_LENGTH,
+                    path.display()
+                );
+                continue;
             }
+            self.process_line(&line, &source).await;
         }
+        self.flush_records(&source).await;
+        reader.await.context("Decompression task failed")??;
 
-        // Ensure child process is killed
-        let _ = cmd.kill().await;
+        if let Some(done) = done {
+            self.offsets.lock().await.insert(path, done);
+        }
         Ok(())
     }
 
@@ -194,20 +863,64 @@ impl LogMonitor {
         }
     }
 
+    /// Watch every running container the selector picks, starting and stopping watchers as
+    /// containers come and go; `skip` lists containers that are already watched by name
+    pub async fn watch_selected_containers(&self, selector: ContainerSelector, skip: Vec<String>) -> Result<()> {
+        let monitor = Arc::new(self.clone_monitor());
+        // Dropping the set (when this task is aborted) stops every watcher it started
+        let mut watchers = tokio::task::JoinSet::new();
+        let mut watched: HashMap<String, tokio::task::AbortHandle> = HashMap::new();
+        let mut rescan = tokio::time::interval(selector.rescan());
+
+        loop {
+            rescan.tick().await;
+            let selected = match list_containers(self.runtime).await {
+                Ok(output) => select_containers(&output, &selector),
+                Err(e) => {
+                    tracing::error!("Container discovery failed: {}", e);
+                    continue;
+                }
+            };
+
+            watched.retain(|name, watcher| {
+                let running = selected.contains(name);
+                if !running {
+                    tracing::info!("Container {} is gone; no longer watching it", name);
+                    watcher.abort();
+                }
+                running
+            });
+            while watchers.try_join_next().is_some() {}
+
+            for name in selected {
+                if skip.contains(&name) || watched.contains_key(&name) {
+                    continue;
+                }
+                tracing::info!("Discovered container {}; watching it", name);
+                let monitor = monitor.clone();
+                let container = name.clone();
+                let watcher = watchers.spawn(async move {
+                    if let Err(e) = monitor.watch_container(container.clone()).await {
+                        tracing::error!("Error watching container {}: {}", container, e);
+                    }
+                });
+                watched.insert(name, watcher);
+            }
+        }
+    }
+
     /// Watch a container once (internal, no retry)
     async fn watch_container_once(&self, container_name: String) -> Result<()> {
         tracing::info!("Starting container watch: {}", container_name);
 
-        let mut cmd = Command::new("docker")
-            .arg("logs")
-            .arg("-f")
-            .arg("--tail")
-            .arg("0")
-            .arg(&container_name)
+        let runtime = self.runtime;
+        let mut cmd = Command::new(runtime.binary())
+            .args(runtime.follow_args(&container_name))
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
-            .context("Failed to spawn docker logs command")?;
+            .map_err(|e| runtime.command_error(e))?;
 
         // Read both stdout and stderr
         let stdout = cmd.stdout.take().context("Failed to capture stdout")?;
@@ -241,7 +954,7 @@ impl LogMonitor {
         };
 
         let stderr_task = {
-            let monitor = self_clone;
+            let monitor = self_clone.clone();
             let source = source.clone();
             tokio::spawn(async move {
                 let mut lines = stderr_reader.lines();
@@ -260,78 +973,531 @@ impl LogMonitor {
             })
         };
 
+        // Evaluate multiline records that stop growing while the container is quiet
+        let flush_task = self_clone.has_multiline().then(|| {
+            let monitor = self_clone.clone();
+            tokio::spawn(async move {
+                let mut flush = tokio::time::interval(MULTILINE_FLUSH_INTERVAL);
+                loop {
+                    flush.tick().await;
+                    monitor.flush_stale_records().await;
+                }
+            })
+        });
+
         // Wait for both tasks to complete and the process to exit
-        tokio::select! {
+        let result = tokio::select! {
             result = async {
                 tokio::try_join!(stdout_task, stderr_task)
             } => {
                 // Kill process if streams finish
                 let _ = cmd.kill().await;
-                result?;
+                result.map(|_| ()).map_err(anyhow::Error::from)
             }
             status = cmd.wait() => {
-                let exit_status = status.context("Failed to wait on docker logs process")?;
+                status
+                    .with_context(|| format!("Failed to wait on {} logs process", runtime.binary()))
+                    .and_then(|exit_status| {
+                        tracing::warn!(
+                            "{} logs process exited with status: {} for {}",
+                            runtime.binary(),
+                            exit_status,
+                            container_name
+                        );
+                        Err(anyhow::anyhow!("{} logs process exited: {}", runtime.binary(), exit_status))
+                    })
+            }
+        };
+
+        if let Some(flush_task) = flush_task {
+            flush_task.abort();
+        }
+        self_clone.flush_records(&source).await;
+
+        result
+    }
+
+    /// Watch a Windows Event Log channel with automatic retry
+    #[cfg(windows)]
+    pub async fn watch_eventlog(&self, config: crate::config::EventLogConfig) -> Result<()> {
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+
+        loop {
+            match self.watch_eventlog_once(&config).await {
+                Ok(_) => {
+                    tracing::warn!("Event log watcher exited cleanly for: {}", config.log);
+                    retry_delay = INITIAL_RETRY_DELAY;
+                }
+                Err(e) => {
+                    tracing::error!(
+                        "Event log watch failed for {}: {}. Retrying in {:?}...",
+                        config.log,
+                        e,
+                        retry_delay
+                    );
+                }
+            }
+
+            tokio::time::sleep(retry_delay).await;
+            retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+        }
+    }
+
+    /// Watch a Windows Event Log channel once (internal, no retry)
+    /// The Event Log API is blocking, so records are read on a dedicated thread
+    #[cfg(windows)]
+    async fn watch_eventlog_once(&self, config: &crate::config::EventLogConfig) -> Result<()> {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1024);
+        let reader_config = config.clone();
+        let reader = tokio::task::spawn_blocking(move || crate::eventlog::poll(&reader_config, tx));
+
+        let source = SourceType::EventLog(config.log.clone());
+        while let Some(line) = rx.recv().await {
+            if line.len() > MAX_LINE_LENGTH {
                 tracing::warn!(
-                    "docker logs process exited with status: {} for {}",
-                    exit_status,
-                    container_name
+                    "Skipping event longer than {} bytes from {}",
+                    MAX_LINE_LENGTH,
+                    config.log
                 );
-                return Err(anyhow::anyhow!("docker logs process exited: {}", exit_status));
+                continue;
+            }
+            self.process_line(&line, &source).await;
+        }
+
+        reader.await.context("Event log reader panicked")?
+    }
+
+    /// Follow a systemd journal unit with automatic retry and reconnection
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub async fn watch_journal(&self, unit: String) -> Result<()> {
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+
+        loop {
+            match self.watch_journal_once(&unit).await {
+                Ok(_) => {
+                    tracing::warn!("Journal watcher exited cleanly for: {}", unit);
+                    retry_delay = INITIAL_RETRY_DELAY;
+                }
+                Err(e) => {
+                    tracing::error!(
+                        "Journal watch failed for {}: {}. Retrying in {:?}...",
+                        unit,
+                        e,
+                        retry_delay
+                    );
+                }
+            }
+
+            tokio::time::sleep(retry_delay).await;
+            retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+        }
+    }
+
+    /// Follow a systemd journal unit once via `journalctl` (internal, no retry)
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    async fn watch_journal_once(&self, unit: &str) -> Result<()> {
+        tracing::info!("Starting journal watch: {}", unit);
+
+        let mut cmd = Command::new("journalctl")
+            .args(journalctl_args(unit))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to spawn journalctl command")?;
+        let stdout = cmd.stdout.take().context("Failed to capture stdout")?;
+        let stderr = drain_stderr(cmd.stderr.take().context("Failed to capture stderr")?);
+        let mut lines = BufReader::new(stdout).lines();
+
+        let source = SourceType::Journal(unit.to_string());
+        let multiline = self.has_multiline();
+        let mut flush = tokio::time::interval(MULTILINE_FLUSH_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = flush.tick(), if multiline => self.flush_stale_records().await,
+                line = lines.next_line() => match line {
+                    Ok(Some(line)) => {
+                        if line.len() > MAX_LINE_LENGTH {
+                            tracing::warn!(
+                                "Skipping line longer than {} bytes in journal {}",
+                                MAX_LINE_LENGTH,
+                                unit
+                            );
+                            continue;
+                        }
+                        self.process_line(&line, &source).await;
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        self.flush_records(&source).await;
+                        return Err(e).context("Failed to read journalctl output");
+                    }
+                },
+            }
+        }
+        self.flush_records(&source).await;
+
+        // journalctl -f only stops on errors, e.g. no permission to read the journal
+        let status = cmd.wait().await.context("Failed to wait on journalctl process")?;
+        let stderr = stderr.await.unwrap_or_default();
+        anyhow::bail!("journalctl exited with {}: {}", status, stderr)
+    }
+
+    /// Follow Kubernetes pod logs with automatic retry and reconnection
+    /// kubectl exits when a followed container restarts; reconnecting resumes from that moment
+    pub async fn watch_pod(&self, config: KubernetesConfig) -> Result<()> {
+        let mut retry_delay = INITIAL_RETRY_DELAY;
+        let mut since = None;
+
+        loop {
+            match self.watch_pod_once(&config, since).await {
+                Ok(_) => {
+                    tracing::warn!("Pod log stream ended for: {}; reconnecting", config.name());
+                    retry_delay = INITIAL_RETRY_DELAY;
+                }
+                Err(e) => {
+                    tracing::error!(
+                        "Pod watch failed for {}: {}. Retrying in {:?}...",
+                        config.name(),
+                        e,
+                        retry_delay
+                    );
+                }
+            }
+            since = Some(Utc::now());
+
+            tokio::time::sleep(retry_delay).await;
+            retry_delay = (retry_delay * 2).min(MAX_RETRY_DELAY);
+        }
+    }
+
+    /// Follow Kubernetes pod logs once via `kubectl logs` (internal, no retry)
+    async fn watch_pod_once(&self, config: &KubernetesConfig, since: Option<DateTime<Utc>>) -> Result<()> {
+        tracing::info!("Starting pod watch: {}", config.name());
+
+        let mut cmd = Command::new("kubectl")
+            .args(kubectl_logs_args(config, since))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .context("Failed to spawn kubectl logs command")?;
+        let stdout = cmd.stdout.take().context("Failed to capture stdout")?;
+        let stderr = drain_stderr(cmd.stderr.take().context("Failed to capture stderr")?);
+        let mut lines = BufReader::new(stdout).lines();
+
+        let source = SourceType::Pod(config.name().to_string());
+        let multiline = self.has_multiline();
+        let mut flush = tokio::time::interval(MULTILINE_FLUSH_INTERVAL);
+
+        loop {
+            tokio::select! {
+                _ = flush.tick(), if multiline => self.flush_stale_records().await,
+                line = lines.next_line() => match line {
+                    Ok(Some(line)) => {
+                        if line.len() > MAX_LINE_LENGTH {
+                            tracing::warn!(
+                                "Skipping line longer than {} bytes in pod {}",
+                                MAX_LINE_LENGTH,
+                                config.name()
+                            );
+                            continue;
+                        }
+                        self.process_line(&line, &source).await;
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        self.flush_records(&source).await;
+                        return Err(e).context("Failed to read kubectl logs output");
+                    }
+                },
             }
         }
+        self.flush_records(&source).await;
 
+        let status = cmd.wait().await.context("Failed to wait on kubectl logs process")?;
+        let stderr = stderr.await.unwrap_or_default();
+        if !status.success() {
+            anyhow::bail!("kubectl logs exited with {}: {}", status, stderr);
+        }
         Ok(())
     }
 
-    async fn process_line(&self, line: &str, source: &SourceType) {
-        for rule in &self.rules {
+    pub(crate) async fn process_line(&self, line: &str, source: &SourceType) {
+        for line in split_line_endings(line) {
+            self.evaluate_line(line, source).await;
+        }
+    }
+
+    async fn evaluate_line(&self, line: &str, source: &SourceType) {
+        crate::metrics::line_processed(source);
+        if let Some(silence) = &self.silence {
+            silence.line_seen(source);
+        }
+        let now = Utc::now();
+        let deploy_action = match &self.deploy {
+            Some(deploy) if deploy.observe(line, Instant::now()).await => Some(deploy.action),
+            _ => None,
+        };
+
+        let keep = self.is_sampled(line, source);
+        let rules = self.rule_set();
+        let candidates = rules.candidates(line);
+        for (index, rule) in rules.rules.iter().enumerate() {
             // Check if rule applies to this source
-            if !self.rule_applies_to_source(rule, source) {
+            if !self.rule_applies_to_source(rule, source) || rule.matcher.is_expired(now) {
                 continue;
             }
 
-            let matched = match &rule.matcher {
-                RuleMatcher::Text(text) => line.contains(text),
-                RuleMatcher::Regex(regex) => regex.is_match(line),
+            match &rule.multiline {
+                Some(multiline) => {
+                    if let Some(record) = multiline.push(line, source, Instant::now()).await {
+                        self.evaluate_record(rule, &record, source, deploy_action).await;
+                    }
+                }
+                None if keep => {
+                    let may_match = candidates.as_ref().is_none_or(|candidates| candidates[index]);
+                    self.evaluate_rule(rule, line, may_match, source, deploy_action).await;
+                }
+                None => {}
+            }
+        }
+    }
+
+    /// Whether sampling keeps this line (or multiline record) for evaluation
+    fn is_sampled(&self, line: &str, source: &SourceType) -> bool {
+        self.sample_rate(source).is_none_or(|rate| sampled(line, rate))
+    }
+
+    /// Evaluate a completed multiline record against its rule
+    async fn evaluate_record(
+        &self,
+        rule: &CompiledRule,
+        record: &str,
+        source: &SourceType,
+        deploy_action: Option<DeployAction>,
+    ) {
+        if rule.matcher.is_expired(Utc::now()) || !self.is_sampled(record, source) {
+            return;
+        }
+        self.evaluate_rule(rule, record, true, source, deploy_action).await;
+    }
+
+    /// Evaluate multiline records that have stopped growing, from any source
+    pub(crate) async fn flush_stale_records(&self) {
+        let now = Instant::now();
+        let rules = self.rule_set();
+        for rule in &rules.rules {
+            let Some(multiline) = &rule.multiline else {
+                continue;
+            };
+            for (source, record) in multiline.take_stale(now).await {
+                let deploy_action = self.deploy_action().await;
+                self.evaluate_record(rule, &record, &source, deploy_action).await;
+            }
+        }
+    }
+
+    /// Evaluate a source's pending multiline records, e.g. when its watcher stops
+    pub(crate) async fn flush_records(&self, source: &SourceType) {
+        let rules = self.rule_set();
+        for rule in &rules.rules {
+            let Some(multiline) = &rule.multiline else {
+                continue;
+            };
+            if let Some(record) = multiline.take(source).await {
+                let deploy_action = self.deploy_action().await;
+                self.evaluate_record(rule, &record, source, deploy_action).await;
+            }
+        }
+    }
+
+    /// Deploy action in effect right now, without observing a line
+    async fn deploy_action(&self) -> Option<DeployAction> {
+        let deploy = self.deploy.as_ref()?;
+        let started = *deploy.started.lock().await;
+        started
+            .is_some_and(|at| at.elapsed() < deploy.timeout)
+            .then_some(deploy.action)
+    }
+
+    /// Whether any rule groups lines into multiline records
+    fn has_multiline(&self) -> bool {
+        self.rule_set().rules.iter().any(|rule| rule.multiline.is_some())
+    }
+
+    /// Evaluate one rule against a line; `may_match` is false when the prefilter ruled it out
+    async fn evaluate_rule(
+        &self,
+        rule: &CompiledRule,
+        line: &str,
+        may_match: bool,
+        source: &SourceType,
+        deploy_action: Option<DeployAction>,
+    ) {
+        if rule.matcher.resolves(line) {
+            if rule.firing.swap(false, Ordering::SeqCst) {
+                tracing::info!("Rule '{}' resolved by line from {:?}", rule.name, source);
+                self.alert_manager
+                    .send_recovery(&rule.alert_names, &rule.name, line, alert_source(source))
+                    .await;
+            }
+            return;
+        }
+
+        if rule.once && rule.spent.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let matched = may_match && rule.matcher.matches(line);
+
+        if matched && rule.expect_within.is_some() {
+            // Expected lines reset the timer rather than alerting
+            tracing::debug!("Rule '{}' saw expected line from {:?}", rule.name, source);
+            *rule.last_seen.lock().await = Sighting {
+                at: Instant::now(),
+                alerted: false,
+            };
+            return;
+        }
+
+        if matched {
+            tracing::debug!("Rule '{}' matched line from {:?}: {}", rule.name, source, line);
+
+            if deploy_action == Some(DeployAction::Suppress) {
+                tracing::debug!("Suppressing rule '{}' during deploy", rule.name);
+                return;
+            }
+            
+            // Check if we should alert based on threshold
+            let message = if let Some(ref threshold) = rule.threshold {
+                match self.check_threshold(rule, threshold, line).await {
+                    Some(recent) if !recent.is_empty() => Some(format!(
+                        "{}\n\nLast {} matching lines:\n{}",
+                        rule.matcher.alert_message(line),
+                        recent.len(),
+                        recent.join("\n")
+                    )),
+                    Some(_) => Some(rule.matcher.alert_message(line).into_owned()),
+                    None => None,
+                }
+            } else {
+                // No threshold, alert immediately
+                Some(rule.matcher.alert_message(line).into_owned())
             };
 
-            if matched {
-                tracing::debug!("Rule '{}' matched line from {:?}: {}", rule.name, source, line);
-                
-                // Check if we should alert based on threshold
-                let should_alert = if let Some(ref threshold) = rule.threshold {
-                    self.check_threshold(rule, threshold).await
-                } else {
-                    // No threshold, alert immediately
-                    true
+            if let Some(message) = message {
+                let alert_names = rule.matcher.route(&rule.name, &rule.alert_names, line, &self.alert_manager);
+                if alert_names.is_empty() {
+                    return;
+                }
+
+                let severity = match deploy_action {
+                    Some(DeployAction::Downgrade) => Severity::Info,
+                    _ => rule.severity,
+                };
+                let context = AlertContext {
+                    fields: rule.matcher.captured_fields(line),
+                    source: alert_source(source),
+                    severity,
                 };
 
-                if should_alert {
-                    // Send alert to all configured destinations
-                    if let Err(e) = self
-                        .alert_manager
-                        .send_alert_multi(&rule.alert_names, &rule.name, line, rule.cooldown)
-                        .await
-                    {
-                        tracing::error!("Failed to send alert for rule '{}': {}", rule.name, e);
-                    }
+                // Claim the single alert; another source may have matched concurrently
+                if rule.once && rule.spent.swap(true, Ordering::SeqCst) {
+                    return;
+                }
+
+                // Send alert to all configured destinations
+                let sent = self
+                    .alert_manager
+                    .send_routed_alert(
+                        &alert_names,
+                        &rule.name,
+                        &route_cooldown_key(&rule.name, &rule.alert_names, &alert_names),
+                        &message,
+                        rule.cooldown,
+                        &context,
+                    )
+                    .await;
+                match sent {
+                    // Only an alert someone received can be resolved
+                    Ok(true) => rule.firing.store(true, Ordering::SeqCst),
+                    Ok(false) => {}
+                    Err(e) => tracing::error!("Failed to send alert for rule '{}': {}", rule.name, e),
                 }
             }
         }
     }
 
+    /// Periodically alert on `expect_within` rules whose pattern has gone missing
+    /// Keeps running without any such rules, since a config reload may add one
+    pub async fn watch_expected(&self) {
+        let mut interval_timer = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval_timer.tick().await;
+            self.check_expected(Instant::now()).await;
+        }
+    }
+
+    /// Alert once for each `expect_within` rule not seen within its window as of `now`
+    async fn check_expected(&self, now: Instant) {
+        let wall_clock = Utc::now();
+        let rules = self.rule_set();
+        for rule in &rules.rules {
+            let Some(window) = rule.expect_within else {
+                continue;
+            };
+            if rule.matcher.is_expired(wall_clock) {
+                continue;
+            }
+
+            let silent_for = {
+                let mut sighting = rule.last_seen.lock().await;
+                let silent_for = now.saturating_duration_since(sighting.at);
+                if sighting.alerted || silent_for < window {
+                    continue;
+                }
+                sighting.alerted = true;
+                silent_for
+            };
+
+            let message = format!(
+                "Expected log line for rule '{}' not seen in {} (silent for {}s)",
+                rule.name,
+                crate::config::format_duration(window),
+                silent_for.as_secs()
+            );
+
+            tracing::warn!("{}", message);
+            if let Err(e) = self
+                .alert_manager
+                .send_alert_multi(&rule.alert_names, &rule.name, &message, rule.cooldown)
+                .await
+            {
+                tracing::error!("Failed to send alert for rule '{}': {}", rule.name, e);
+            }
+        }
+    }
+
     /// Check if threshold is exceeded and record the match
-    /// Returns true if we should send an alert
-    async fn check_threshold(&self, rule: &CompiledRule, threshold: &Threshold) -> bool {
+    /// Returns the most recent matching lines (up to `include_matches`) if we should send an alert
+    async fn check_threshold(
+        &self,
+        rule: &CompiledRule,
+        threshold: &Threshold,
+        line: &str,
+    ) -> Option<Vec<String>> {
         let now = Instant::now();
         let mut history = rule.match_history.lock().await;
         
-        // Add current match
-        history.push_back(now);
+        // Add current match, keeping the line only if it may be reported
+        let kept_line = (rule.include_matches > 0).then(|| line.to_string());
+        history.push_back((now, kept_line));
         
         // Remove old matches outside the time window
         let cutoff = now - threshold.window;
-        while let Some(&oldest) = history.front() {
+        while let Some(&(oldest, _)) = history.front() {
             if oldest < cutoff {
                 history.pop_front();
             } else {
@@ -348,9 +1514,14 @@ impl LogMonitor {
                 count,
                 threshold.window
             );
-            // Clear history after alerting to avoid repeated alerts
-            history.clear();
-            true
+            let skip = count.saturating_sub(rule.include_matches);
+            let recent = history
+                .drain(..)
+                .skip(skip)
+                .filter_map(|(_, line)| line)
+                .collect();
+            // History is cleared after alerting to avoid repeated alerts
+            Some(recent)
         } else {
             tracing::debug!(
                 "Rule '{}' matched but threshold not reached: {}/{} in {:?}",
@@ -359,10 +1530,95 @@ impl LogMonitor {
                 threshold.count,
                 threshold.window
             );
-            false
+            None
         }
     }
 
+    /// Threshold windows with recorded matches, as timestamps for saving
+    pub async fn export_thresholds(&self, clock: &Clock) -> BTreeMap<String, Vec<ThresholdState>> {
+        let mut exported = BTreeMap::new();
+        let rules = self.rule_set();
+        for rule in &rules.rules {
+            let Some(threshold) = &rule.threshold else {
+                continue;
+            };
+
+            let history = rule.match_history.lock().await;
+            let matches: Vec<_> = history
+                .iter()
+                .filter(|(at, _)| clock.instant.saturating_duration_since(*at) < threshold.window)
+                .map(|(at, line)| ThresholdState {
+                    at: clock.timestamp(*at),
+                    line: line.clone(),
+                })
+                .collect();
+            if !matches.is_empty() {
+                exported.insert(rule.name.clone(), matches);
+            }
+        }
+        exported
+    }
+
+    /// Restore saved threshold windows, dropping matches that have aged out
+    /// Returns the number of matches restored
+    pub async fn restore_thresholds(
+        &self,
+        mut saved: BTreeMap<String, Vec<ThresholdState>>,
+        clock: &Clock,
+    ) -> usize {
+        let mut restored = 0;
+        let rules = self.rule_set();
+        for rule in &rules.rules {
+            let (Some(threshold), Some(matches)) = (&rule.threshold, saved.remove(&rule.name)) else {
+                continue;
+            };
+
+            let mut history = rule.match_history.lock().await;
+            for state in matches {
+                if let Some(at) = clock.instant_for(state.at, threshold.window) {
+                    let line = state.line.filter(|_| rule.include_matches > 0);
+                    history.push_back((at, line));
+                    restored += 1;
+                }
+            }
+            history.make_contiguous().sort_by_key(|(at, _)| *at);
+        }
+        restored
+    }
+
+    /// Read positions of watched files, for saving across restarts
+    pub async fn export_offsets(&self) -> BTreeMap<String, FileOffset> {
+        self.offsets
+            .lock()
+            .await
+            .iter()
+            .map(|(path, offset)| (path.display().to_string(), offset.clone()))
+            .collect()
+    }
+
+    /// Restore saved read positions; each file resumes there if it is still the same file
+    pub async fn restore_offsets(&self, saved: BTreeMap<String, FileOffset>) -> usize {
+        let mut offsets = self.offsets.lock().await;
+        let restored = saved.len();
+        offsets.extend(saved.into_iter().map(|(path, offset)| (PathBuf::from(path), offset)));
+        restored
+    }
+
+    /// Names of the active rules that match a line, ignoring source filters and thresholds
+    pub fn matching_rules(&self, line: &str) -> Vec<String> {
+        let now = Utc::now();
+        let rules = self.rule_set();
+        let candidates = rules.candidates(line);
+        rules
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| candidates.as_ref().is_none_or(|candidates| candidates[*index]))
+            .filter(|(_, rule)| !rule.matcher.is_expired(now) && rule.matcher.matches(line))
+            .map(|(_, rule)| rule.name.clone())
+            .collect()
+    }
+
     fn rule_applies_to_source(&self, rule: &CompiledRule, source: &SourceType) -> bool {
         // If no sources filter is specified, rule applies to all sources
         let Some(ref sources) = rule.sources else {
@@ -388,28 +1644,42 @@ impl LogMonitor {
                 }
                 sources.streams.iter().any(|s| s == name)
             }
+            #[cfg(windows)]
+            SourceType::EventLog(log) => {
+                if sources.windows_eventlog.is_empty() {
+                    return false;
+                }
+                sources.windows_eventlog.iter().any(|l| l.eq_ignore_ascii_case(log))
+            }
+            SourceType::Journal(name) => {
+                if sources.journals.is_empty() {
+                    return false;
+                }
+                sources.journals.iter().any(|j| j == name)
+            }
+            SourceType::Pod(name) => {
+                if sources.pods.is_empty() {
+                    return false;
+                }
+                sources.pods.iter().any(|p| p == name)
+            }
         }
     }
 
     fn clone_monitor(&self) -> Self {
         Self {
-            rules: self.rules.iter().map(|r| CompiledRule {
-                name: r.name.clone(),
-                matcher: match &r.matcher {
-                    RuleMatcher::Text(text) => RuleMatcher::Text(text.clone()),
-                    RuleMatcher::Regex(regex) => RuleMatcher::Regex(regex.clone()),
-                },
-                alert_names: r.alert_names.clone(),
-                cooldown: r.cooldown,
-                sources: r.sources.clone(),
-                threshold: r.threshold.clone(),
-                match_history: r.match_history.clone(),
-            }).collect(),
+            rules: self.rules.clone(),
             alert_manager: self.alert_manager.clone(),
+            deploy: self.deploy.clone(),
+            sample_rates: self.sample_rates.clone(),
+            offsets: self.offsets.clone(),
+            runtime: self.runtime,
+            silence: self.silence.clone(),
         }
     }
 }
 
 #[cfg(test)]
 #[path = "log_monitor_tests.rs"]
+#[allow(clippy::module_inception)]
 mod tests;
@@ -1,8 +1,13 @@
 #[cfg(test)]
 mod tests {
-    use crate::log_monitor::LogMonitor;
-    use crate::alerts::{AlertHandler, AlertManager};
-    use crate::config::{Rule, RuleSources, SourceType};
+    use crate::log_monitor::{
+        alert_source, drain_stderr, journalctl_args, kubectl_logs_args, parse_labels, select_containers,
+        split_line_endings, LogMonitor,
+    };
+    use crate::alerts::{AlertContext, AlertHandler, AlertManager, AlertSource, AlertSourceKind};
+    use crate::config::{
+        ContainerSelector, DeployConfig, KubernetesConfig, MultilineConfig, Rule, RuleSources, Severity, SourceType,
+    };
     use anyhow::Result;
     use async_trait::async_trait;
     use std::path::PathBuf;
@@ -46,6 +51,34 @@ mod tests {
         }
     }
 
+    // Mock alert handler that records the structured context it receives
+    struct ContextAlertHandler {
+        name: String,
+        last_context: Arc<tokio::sync::Mutex<Option<AlertContext>>>,
+    }
+
+    #[async_trait]
+    impl AlertHandler for ContextAlertHandler {
+        async fn send(&self, _identity: &str, _rule_name: &str, _message: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn send_with_context(
+            &self,
+            _identity: &str,
+            _rule_name: &str,
+            _message: &str,
+            context: &AlertContext,
+        ) -> Result<()> {
+            *self.last_context.lock().await = Some(context.clone());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
     fn create_test_monitor() -> (LogMonitor, Arc<AtomicUsize>, Arc<tokio::sync::Mutex<String>>) {
         let mut alert_manager = AlertManager::new("test-server".to_string());
         let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
@@ -59,6 +92,22 @@ mod tests {
             cooldown: 1,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         }];
         
         let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
@@ -76,6 +125,22 @@ mod tests {
             cooldown: 60,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         }];
         
         let result = LogMonitor::new(rules, alert_manager);
@@ -93,6 +158,22 @@ mod tests {
             cooldown: 60,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         }];
         
         let result = LogMonitor::new(rules, alert_manager);
@@ -140,6 +221,22 @@ mod tests {
             cooldown: 1,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         }];
         
         let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
@@ -167,6 +264,22 @@ mod tests {
             cooldown: 1,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         }];
         
         let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
@@ -197,10 +310,27 @@ mod tests {
             cooldown: 60,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         }];
         
         let monitor = LogMonitor::new(rules, alert_manager).unwrap();
-        let rule = &monitor.rules[0];
+        let rules = monitor.rule_set();
+        let rule = &rules.rules[0];
         
         // Should apply to all sources when no filter
         assert!(monitor.rule_applies_to_source(rule, &SourceType::File(PathBuf::from("/var/log/app.log"))));
@@ -221,12 +351,32 @@ mod tests {
                 files: vec![PathBuf::from("/var/log/app.log")],
                 containers: vec![],
                 streams: vec![],
+                windows_eventlog: vec![],
+                journals: vec![],
+                pods: vec![],
             }),
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         }];
         
         let monitor = LogMonitor::new(rules, alert_manager).unwrap();
-        let rule = &monitor.rules[0];
+        let rules = monitor.rule_set();
+        let rule = &rules.rules[0];
         
         // Should match specified file
         assert!(monitor.rule_applies_to_source(rule, &SourceType::File(PathBuf::from("/var/log/app.log"))));
@@ -252,12 +402,32 @@ mod tests {
                 files: vec![],
                 containers: vec!["nginx".to_string(), "api".to_string()],
                 streams: vec![],
+                windows_eventlog: vec![],
+                journals: vec![],
+                pods: vec![],
             }),
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         }];
         
         let monitor = LogMonitor::new(rules, alert_manager).unwrap();
-        let rule = &monitor.rules[0];
+        let rules = monitor.rule_set();
+        let rule = &rules.rules[0];
         
         // Should match specified containers
         assert!(monitor.rule_applies_to_source(rule, &SourceType::Container("nginx".to_string())));
@@ -267,6 +437,173 @@ mod tests {
         assert!(!monitor.rule_applies_to_source(rule, &SourceType::Container("postgres".to_string())));
     }
 
+    #[tokio::test]
+    async fn test_rule_applies_to_source_journal_filter() {
+        let alert_manager = Arc::new(AlertManager::new("test".to_string()));
+        let mut rule = deploy_rules().remove(0);
+        rule.sources = Some(RuleSources {
+            files: vec![],
+            containers: vec![],
+            streams: vec![],
+            windows_eventlog: vec![],
+            journals: vec!["nginx.service".to_string()],
+            pods: vec![],
+        });
+        let monitor = LogMonitor::new(vec![rule], alert_manager).unwrap();
+        let rules = monitor.rule_set();
+        let rule = &rules.rules[0];
+
+        assert!(monitor.rule_applies_to_source(rule, &SourceType::Journal("nginx.service".to_string())));
+        assert!(!monitor.rule_applies_to_source(rule, &SourceType::Journal("sshd.service".to_string())));
+        assert!(!monitor.rule_applies_to_source(rule, &SourceType::Container("nginx.service".to_string())));
+    }
+
+    #[test]
+    fn test_journalctl_args() {
+        assert_eq!(
+            journalctl_args("nginx.service"),
+            vec!["--follow", "--lines=0", "--output=cat", "--unit", "nginx.service"]
+        );
+        assert_eq!(
+            journalctl_args("_SYSTEMD_UNIT=nginx.service"),
+            vec!["--follow", "--lines=0", "--output=cat", "_SYSTEMD_UNIT=nginx.service"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_drain_stderr_keeps_last_lines() {
+        // Far more than a pipe buffer holds, all read while the command is still running
+        let stderr: String = (0..20_000).map(|i| format!("warning {}\n", i)).collect();
+        let tail = drain_stderr(std::io::Cursor::new(stderr.into_bytes())).await.unwrap();
+
+        assert_eq!(
+            tail,
+            "warning 19995\nwarning 19996\nwarning 19997\nwarning 19998\nwarning 19999"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rule_applies_to_source_pod_filter() {
+        let alert_manager = Arc::new(AlertManager::new("test".to_string()));
+        let mut rule = deploy_rules().remove(0);
+        rule.sources = Some(RuleSources {
+            files: vec![],
+            containers: vec!["api".to_string()],
+            streams: vec![],
+            windows_eventlog: vec![],
+            journals: vec![],
+            pods: vec!["api-7d9f".to_string(), "app=worker".to_string()],
+        });
+        let monitor = LogMonitor::new(vec![rule], alert_manager).unwrap();
+        let rules = monitor.rule_set();
+        let rule = &rules.rules[0];
+
+        assert!(monitor.rule_applies_to_source(rule, &SourceType::Pod("api-7d9f".to_string())));
+        assert!(monitor.rule_applies_to_source(rule, &SourceType::Pod("app=worker".to_string())));
+        assert!(!monitor.rule_applies_to_source(rule, &SourceType::Pod("api".to_string())));
+        assert!(monitor.rule_applies_to_source(rule, &SourceType::Container("api".to_string())));
+        assert!(!monitor.rule_applies_to_source(rule, &SourceType::Container("api-7d9f".to_string())));
+
+        let described = alert_source(&SourceType::Pod("api-7d9f".to_string())).unwrap();
+        assert_eq!(described.kind, AlertSourceKind::Pod);
+        assert_eq!(described.name, "api-7d9f");
+    }
+
+    const DOCKER_PS: &str = "traefik\t\"com.docker.compose.service=traefik,traefik.enable=false\"\n\
+api\t\"traefik.enable=true,com.docker.compose.service=api,traefik.http.routers.api.rule=Host(`a`,`b`)\"\n\
+web\t\"traefik.enable=true,tier=frontend\"\n\
+db\t\"\"\n";
+
+    const PODMAN_PS: &str = "api\t{\"traefik.enable\":\"true\",\"tier\":\"backend\"}\n\
+web\t{\"traefik.enable\":\"true\",\"tier\":\"frontend\"}\n\
+db\tnull\n";
+
+    fn selector(labels: &[&str]) -> ContainerSelector {
+        ContainerSelector {
+            labels: labels.iter().map(|label| label.to_string()).collect(),
+            rescan: None,
+        }
+    }
+
+    #[test]
+    fn test_select_containers_by_label() {
+        assert_eq!(select_containers(DOCKER_PS, &selector(&[])), vec!["traefik", "api", "web", "db"]);
+        assert_eq!(select_containers(DOCKER_PS, &selector(&["traefik.enable=true"])), vec!["api", "web"]);
+        assert_eq!(
+            select_containers(DOCKER_PS, &selector(&["traefik.enable=true", "tier=frontend"])),
+            vec!["web"]
+        );
+        // A bare key only requires the label to be present
+        assert_eq!(
+            select_containers(DOCKER_PS, &selector(&["com.docker.compose.service"])),
+            vec!["traefik", "api"]
+        );
+        assert!(select_containers(DOCKER_PS, &selector(&["tier=backend"])).is_empty());
+        assert!(select_containers("", &selector(&[])).is_empty());
+    }
+
+    #[test]
+    fn test_select_containers_from_podman_labels() {
+        assert_eq!(select_containers(PODMAN_PS, &selector(&["traefik.enable=true"])), vec!["api", "web"]);
+        assert_eq!(select_containers(PODMAN_PS, &selector(&["tier=frontend"])), vec!["web"]);
+        assert_eq!(select_containers(PODMAN_PS, &selector(&[])), vec!["api", "web", "db"]);
+    }
+
+    #[test]
+    fn test_parse_labels_keeps_commas_in_values() {
+        let labels = parse_labels("traefik.enable=true,traefik.http.routers.api.rule=Host(`a`,`b`),empty=");
+        assert_eq!(labels["traefik.enable"], "true");
+        assert_eq!(labels["traefik.http.routers.api.rule"], "Host(`a`,`b`)");
+        assert_eq!(labels["empty"], "");
+        assert!(parse_labels("").is_empty());
+    }
+
+    #[test]
+    fn test_kubectl_logs_args() {
+        let pod = KubernetesConfig {
+            namespace: Some("prod".to_string()),
+            pod: Some("api-7d9f".to_string()),
+            selector: None,
+            container: Some("server".to_string()),
+        };
+        assert_eq!(
+            kubectl_logs_args(&pod, None),
+            vec!["logs", "--follow", "--tail=0", "--namespace", "prod", "api-7d9f", "--container", "server"]
+        );
+
+        let selector = KubernetesConfig {
+            namespace: None,
+            pod: None,
+            selector: Some("app=api".to_string()),
+            container: None,
+        };
+        assert_eq!(
+            kubectl_logs_args(&selector, None),
+            vec!["logs", "--follow", "--tail=0", "--selector", "app=api", "--all-containers"]
+        );
+
+        // Reconnecting after a restart picks up from when the previous stream ended
+        let since = chrono::DateTime::parse_from_rfc3339("2024-03-15T12:00:00.750Z").unwrap().to_utc();
+        assert_eq!(kubectl_logs_args(&selector, Some(since))[2], "--since-time=2024-03-15T12:00:00Z");
+    }
+
+    #[tokio::test]
+    async fn test_journal_lines_alert_with_journal_source() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let monitor = LogMonitor::new(deploy_rules(), Arc::new(alert_manager)).unwrap();
+
+        let source = SourceType::Journal("nginx.service".to_string());
+        monitor.process_line("ERROR upstream timed out", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, "ERROR upstream timed out");
+
+        let described = alert_source(&source).unwrap();
+        assert_eq!(described.kind, AlertSourceKind::Journal);
+        assert_eq!(described.name, "nginx.service");
+    }
+
     #[tokio::test]
     async fn test_multiple_rules() {
         let mut alert_manager = AlertManager::new("test-server".to_string());
@@ -284,6 +621,22 @@ mod tests {
                 cooldown: 1,
                 sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
             },
             Rule {
                 name: "warn_rule".to_string(),
@@ -293,6 +646,22 @@ mod tests {
                 cooldown: 1,
                 sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
             },
         ];
         
@@ -337,6 +706,22 @@ mod tests {
             cooldown: 1,
             sources: None,
             threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
         }];
         
         let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
@@ -349,4 +734,1795 @@ mod tests {
         assert_eq!(count1.load(Ordering::SeqCst), 1);
         assert_eq!(count2.load(Ordering::SeqCst), 1);
     }
+
+    #[tokio::test]
+    async fn test_captured_fields_in_context() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let last_context = Arc::new(tokio::sync::Mutex::new(None));
+        alert_manager.register(
+            "test-alert".to_string(),
+            Arc::new(ContextAlertHandler {
+                name: "test-alert".to_string(),
+                last_context: last_context.clone(),
+            }),
+        );
+
+        let rules = vec![Rule {
+            name: "http_error".to_string(),
+            text: None,
+            pattern: Some(r"status=(?P<status>\d+) path=(?P<path>\S+)(?: user=(?P<user>\w+))?".to_string()),
+            alert: vec!["test-alert".to_string()],
+            cooldown: 0,
+            sources: None,
+            threshold: None,
+            fields: vec!["status".to_string(), "user".to_string()],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        }];
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        let source = SourceType::Container("test".to_string());
+
+        monitor.process_line("status=500 path=/api/orders user=alice", &source).await;
+        let context = last_context.lock().await.take().unwrap();
+        assert_eq!(context.fields.len(), 2);
+        assert_eq!(context.fields["status"], "500");
+        assert_eq!(context.fields["user"], "alice");
+        // `path` is captured by the pattern but not requested
+        assert!(!context.fields.contains_key("path"));
+
+        // Missing optional captures are omitted rather than sent empty
+        monitor.process_line("status=502 path=/api/orders", &source).await;
+        let context = last_context.lock().await.take().unwrap();
+        assert_eq!(context.fields.len(), 1);
+        assert_eq!(context.fields["status"], "502");
+        assert!(!context.fields.contains_key("user"));
+    }
+
+    #[tokio::test]
+    async fn test_captured_fields_rendered_as_text() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let rules = vec![Rule {
+            name: "http_error".to_string(),
+            text: None,
+            pattern: Some(r"status=(?P<status>\d+) user=(?P<user>\w+)".to_string()),
+            alert: vec!["test-alert".to_string()],
+            cooldown: 0,
+            sources: None,
+            threshold: None,
+            fields: vec!["status".to_string(), "user".to_string()],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        }];
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        let source = SourceType::Container("test".to_string());
+
+        monitor.process_line("GET /api status=500 user=alice took=30ms", &source).await;
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, "status: 500\nuser: alice");
+    }
+
+    #[tokio::test]
+    async fn test_container_source_in_context() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let last_context = Arc::new(tokio::sync::Mutex::new(None));
+        alert_manager.register(
+            "test-alert".to_string(),
+            Arc::new(ContextAlertHandler {
+                name: "test-alert".to_string(),
+                last_context: last_context.clone(),
+            }),
+        );
+
+        let rules = vec![Rule {
+            name: "error_rule".to_string(),
+            text: Some("ERROR".to_string()),
+            pattern: None,
+            alert: vec!["test-alert".to_string()],
+            cooldown: 0,
+            sources: None,
+            threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        }];
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+
+        monitor.process_line("ERROR boom", &SourceType::Container("nginx".to_string())).await;
+        let context = last_context.lock().await.take().unwrap();
+        assert_eq!(
+            context.source,
+            Some(AlertSource {
+                kind: AlertSourceKind::Container,
+                name: "nginx".to_string(),
+                url: None,
+            })
+        );
+
+        monitor.process_line("ERROR boom", &SourceType::File(PathBuf::from("/var/log/app.log"))).await;
+        let context = last_context.lock().await.take().unwrap();
+        let source = context.source.unwrap();
+        assert_eq!(source.kind, AlertSourceKind::File);
+        assert_eq!(source.name, "/var/log/app.log");
+    }
+
+    #[tokio::test]
+    async fn test_numeric_condition() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let rules = vec![Rule {
+            name: "slow_request".to_string(),
+            text: None,
+            pattern: Some(r"duration=(?P<ms>\d+)ms".to_string()),
+            alert: vec!["test-alert".to_string()],
+            cooldown: 0,
+            sources: None,
+            threshold: None,
+            fields: vec![],
+            condition: Some(crate::config::Condition::parse("ms > 1000").unwrap()),
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        }];
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        // Below and at the limit don't fire
+        monitor.process_line("GET /api duration=123ms", &source).await;
+        monitor.process_line("GET /api duration=1000ms", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        monitor.process_line("GET /api duration=1500ms", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, "GET /api duration=1500ms");
+    }
+
+    fn expected_line_monitor(window: Duration) -> (LogMonitor, Arc<AtomicUsize>, Arc<tokio::sync::Mutex<String>>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let rules = vec![Rule {
+            name: "backup_completed".to_string(),
+            text: Some("backup completed".to_string()),
+            pattern: None,
+            alert: vec!["test-alert".to_string()],
+            cooldown: 0,
+            sources: None,
+            threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: Some(window),
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        }];
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count, last_message)
+    }
+
+    #[tokio::test]
+    async fn test_expected_line_alerts_after_silence() {
+        let (monitor, call_count, last_message) = expected_line_monitor(Duration::from_millis(200));
+        let source = SourceType::File(PathBuf::from("/var/log/cron.log"));
+
+        // Seeing the line resets the timer and never alerts by itself
+        monitor.process_line("nightly backup completed", &source).await;
+        monitor.check_expected(std::time::Instant::now()).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        monitor.check_expected(std::time::Instant::now()).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert!(last_message.lock().await.contains("not seen in 200ms"));
+
+        // Only one alert per silence
+        monitor.check_expected(std::time::Instant::now()).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        // A new sighting re-arms the alert
+        monitor.process_line("nightly backup completed", &source).await;
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        monitor.check_expected(std::time::Instant::now()).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_expected_line_seen_regularly_does_not_alert() {
+        let (monitor, call_count, _) = expected_line_monitor(Duration::from_millis(200));
+        let source = SourceType::File(PathBuf::from("/var/log/cron.log"));
+
+        for _ in 0..5 {
+            tokio::time::sleep(Duration::from_millis(80)).await;
+            monitor.process_line("nightly backup completed", &source).await;
+            monitor.check_expected(std::time::Instant::now()).await;
+        }
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_expected_line_alerts_if_never_seen_after_startup() {
+        let (monitor, call_count, _) = expected_line_monitor(Duration::from_millis(100));
+
+        monitor.check_expected(std::time::Instant::now()).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        monitor
+            .check_expected(std::time::Instant::now() + Duration::from_millis(150))
+            .await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    fn threshold_monitor(include_matches: usize) -> (LogMonitor, Arc<AtomicUsize>, Arc<tokio::sync::Mutex<String>>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let rules = vec![Rule {
+            name: "error_burst".to_string(),
+            text: Some("ERROR".to_string()),
+            pattern: None,
+            alert: vec!["test-alert".to_string()],
+            cooldown: 0,
+            sources: None,
+            threshold: Some(crate::config::Threshold::parse("3 in 10s").unwrap()),
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        }];
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count, last_message)
+    }
+
+    #[tokio::test]
+    async fn test_threshold_alert_includes_recent_matches() {
+        let (monitor, call_count, last_message) = threshold_monitor(2);
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR first", &source).await;
+        monitor.process_line("INFO unrelated", &source).await;
+        monitor.process_line("ERROR second", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        monitor.process_line("ERROR third", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        let message = last_message.lock().await.clone();
+        assert_eq!(message, "ERROR third\n\nLast 2 matching lines:\nERROR second\nERROR third");
+    }
+
+    #[tokio::test]
+    async fn test_threshold_alert_without_include_matches() {
+        let (monitor, call_count, last_message) = threshold_monitor(0);
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        for line in ["ERROR first", "ERROR second", "ERROR third"] {
+            monitor.process_line(line, &source).await;
+        }
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, "ERROR third");
+    }
+
+    #[tokio::test]
+    async fn test_threshold_include_matches_larger_than_count() {
+        let (monitor, _, last_message) = threshold_monitor(10);
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        for line in ["ERROR first", "ERROR second", "ERROR third"] {
+            monitor.process_line(line, &source).await;
+        }
+
+        assert!(last_message
+            .lock()
+            .await
+            .ends_with("Last 3 matching lines:\nERROR first\nERROR second\nERROR third"));
+    }
+
+    fn threshold_rule_monitor(threshold: &str) -> (LogMonitor, Arc<AtomicUsize>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].threshold = Some(crate::config::Threshold::parse(threshold).unwrap());
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count)
+    }
+
+    #[tokio::test]
+    async fn test_threshold_fifth_match_alerts_once() {
+        let (monitor, call_count) = threshold_rule_monitor("5 in 2s");
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        for _ in 0..4 {
+            monitor.process_line("ERROR timeout", &source).await;
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        monitor.process_line("ERROR timeout", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        // The window starts over after alerting
+        monitor.process_line("ERROR timeout", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_threshold_matches_outside_window_expire() {
+        let (monitor, call_count) = threshold_rule_monitor("5 in 200ms");
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        for _ in 0..4 {
+            monitor.process_line("ERROR timeout", &source).await;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+
+        monitor.process_line("ERROR timeout", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+    }
+
+    fn expiring_monitor(expires_at: &str) -> (LogMonitor, Arc<AtomicUsize>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let rules = vec![Rule {
+            name: "incident_rule".to_string(),
+            text: Some("ERROR".to_string()),
+            pattern: None,
+            alert: vec!["test-alert".to_string()],
+            cooldown: 0,
+            sources: None,
+            threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: Some(chrono::DateTime::parse_from_rfc3339(expires_at).unwrap().into()),
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        }];
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count)
+    }
+
+    #[tokio::test]
+    async fn test_expired_rule_is_skipped() {
+        let (monitor, call_count) = expiring_monitor("2000-01-01T00:00:00Z");
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR boom", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_rule_before_expiry_is_active() {
+        let (monitor, call_count) = expiring_monitor("2999-01-01T00:00:00Z");
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR boom", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_rule_never_fires() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            "- name: muted\n  text: ERROR\n  alert: test-alert\n  cooldown: 0\n  enabled: false\n\
+             - name: panics\n  text: PANIC\n  alert: test-alert\n  cooldown: 0\n",
+        )
+        .unwrap();
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        assert_eq!(monitor.rule_set().rules.len(), 1);
+        monitor.process_line("ERROR boom", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        monitor.process_line("PANIC boom", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_capture_based_routing() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (payments, payments_count, payments_message) = MockAlertHandler::new("payments");
+        let (orders, orders_count, _) = MockAlertHandler::new("orders");
+        alert_manager.register("payments".to_string(), Arc::new(payments));
+        alert_manager.register("orders".to_string(), Arc::new(orders));
+
+        let rules = vec![Rule {
+            name: "service_errors".to_string(),
+            text: None,
+            pattern: Some(r"ERROR service=(?P<service>\w+)".to_string()),
+            alert: vec!["${service}".to_string()],
+            cooldown: 0,
+            sources: None,
+            threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        }];
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR service=payments charge declined", &source).await;
+        assert_eq!(payments_count.load(Ordering::SeqCst), 1);
+        assert_eq!(orders_count.load(Ordering::SeqCst), 0);
+        assert_eq!(*payments_message.lock().await, "ERROR service=payments charge declined");
+
+        // Routes that resolve to no registered alert are dropped
+        monitor.process_line("ERROR service=billing timeout", &source).await;
+        assert_eq!(payments_count.load(Ordering::SeqCst), 1);
+        assert_eq!(orders_count.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_routed_cooldown_is_per_destination() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (payments, payments_count, _) = MockAlertHandler::new("slack-payments");
+        let (orders, orders_count, _) = MockAlertHandler::new("slack-orders");
+        alert_manager.register("slack-payments".to_string(), Arc::new(payments));
+        alert_manager.register("slack-orders".to_string(), Arc::new(orders));
+
+        let rules: Vec<Rule> = serde_yaml::from_str(
+            "- name: service_errors\n  pattern: 'ERROR service=(?P<service>\\w+)'\n  alert: slack-${service}\n  cooldown: 300\n",
+        )
+        .unwrap();
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR service=payments charge declined", &source).await;
+        monitor.process_line("ERROR service=orders out of stock", &source).await;
+        assert_eq!(payments_count.load(Ordering::SeqCst), 1);
+        assert_eq!(orders_count.load(Ordering::SeqCst), 1);
+
+        // Each destination is still held back by its own cooldown
+        monitor.process_line("ERROR service=payments card expired", &source).await;
+        assert_eq!(payments_count.load(Ordering::SeqCst), 1);
+    }
+
+    fn deploy_rules() -> Vec<Rule> {
+        vec![Rule {
+            name: "errors".to_string(),
+            text: Some("ERROR".to_string()),
+            pattern: None,
+            alert: vec!["test-alert".to_string()],
+            cooldown: 0,
+            sources: None,
+            threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        }]
+    }
+
+    fn deploy_monitor(deploy_yaml: &str) -> (LogMonitor, Arc<AtomicUsize>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut monitor = LogMonitor::new(deploy_rules(), Arc::new(alert_manager)).unwrap();
+        let deploy: DeployConfig = serde_yaml::from_str(deploy_yaml).unwrap();
+        monitor.set_deploy_markers(&deploy).unwrap();
+        (monitor, call_count)
+    }
+
+    #[tokio::test]
+    async fn test_alerts_suppressed_during_deploy() {
+        let (monitor, call_count) = deploy_monitor("start: 'Deploy started'\nend: 'Deploy (finished|complete)'\n");
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR before deploy", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        monitor.process_line("Deploy started: v1.2.3", &source).await;
+        monitor.process_line("ERROR connection reset during restart", &source).await;
+        monitor.process_line("ERROR upstream unavailable", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        monitor.process_line("Deploy complete: v1.2.3", &source).await;
+        monitor.process_line("ERROR after deploy", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_deploy_suppression_times_out() {
+        let (monitor, call_count) = deploy_monitor("start: 'Deploy started'\ntimeout: 100ms\n");
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("Deploy started", &source).await;
+        monitor.process_line("ERROR during deploy", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        monitor.process_line("ERROR well after deploy", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_deploy_downgrades_severity() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let last_context = Arc::new(tokio::sync::Mutex::new(None));
+        alert_manager.register(
+            "test-alert".to_string(),
+            Arc::new(ContextAlertHandler {
+                name: "test-alert".to_string(),
+                last_context: last_context.clone(),
+            }),
+        );
+
+        let mut monitor = LogMonitor::new(deploy_rules(), Arc::new(alert_manager)).unwrap();
+        let deploy: DeployConfig =
+            serde_yaml::from_str("start: 'Deploy started'\nend: 'Deploy finished'\naction: downgrade\n").unwrap();
+        monitor.set_deploy_markers(&deploy).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("Deploy started", &source).await;
+        monitor.process_line("ERROR during deploy", &source).await;
+        assert_eq!(last_context.lock().await.take().unwrap().severity, Severity::Info);
+
+        monitor.process_line("Deploy finished", &source).await;
+        monitor.process_line("ERROR after deploy", &source).await;
+        assert_eq!(last_context.lock().await.take().unwrap().severity, Severity::Error);
+    }
+
+    #[tokio::test]
+    async fn test_threshold_window_survives_restart() {
+        use crate::state::Clock;
+
+        let source = SourceType::Container("test".to_string());
+        let (before, _, _) = threshold_monitor(0);
+        before.process_line("ERROR one", &source).await;
+        before.process_line("ERROR two", &source).await;
+        let saved = before.export_thresholds(&Clock::now()).await;
+
+        let (after, call_count, _) = threshold_monitor(0);
+        assert_eq!(after.restore_thresholds(saved, &Clock::now()).await, 2);
+
+        // The third match completes the threshold started before the restart
+        after.process_line("ERROR three", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_threshold_matches_are_dropped() {
+        use crate::state::{Clock, ThresholdState};
+
+        let clock = Clock::now();
+        let stale = ThresholdState {
+            at: clock.utc - chrono::Duration::hours(1),
+            line: None,
+        };
+        let mut saved = std::collections::BTreeMap::new();
+        saved.insert("error_burst".to_string(), vec![stale.clone(), stale]);
+
+        let (monitor, call_count, _) = threshold_monitor(0);
+        assert_eq!(monitor.restore_thresholds(saved, &clock).await, 0);
+
+        monitor
+            .process_line("ERROR one", &SourceType::Container("test".to_string()))
+            .await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+    }
+
+    fn sampled_monitor(rates: &[(&str, f64)]) -> (LogMonitor, Arc<AtomicUsize>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut monitor = LogMonitor::new(deploy_rules(), Arc::new(alert_manager)).unwrap();
+        let rates = rates.iter().map(|(source, rate)| (source.to_string(), *rate)).collect();
+        monitor.set_sample_rates(&rates).unwrap();
+        (monitor, call_count)
+    }
+
+    #[tokio::test]
+    async fn test_sampling_evaluates_configured_fraction() {
+        let (monitor, call_count) = sampled_monitor(&[("/var/log/*.log", 0.25)]);
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        for i in 0..10_000 {
+            monitor.process_line(&format!("ERROR request {} failed", i), &source).await;
+        }
+
+        let evaluated = call_count.load(Ordering::SeqCst);
+        assert!((2250..=2750).contains(&evaluated), "evaluated {} of 10000 lines", evaluated);
+    }
+
+    #[tokio::test]
+    async fn test_sampling_is_deterministic_per_line() {
+        let (monitor, call_count) = sampled_monitor(&[("api", 0.5)]);
+        let source = SourceType::Container("api".to_string());
+
+        let lines: Vec<String> = (0..50).map(|i| format!("ERROR job {}", i)).collect();
+        for line in &lines {
+            monitor.process_line(line, &source).await;
+        }
+        let first_pass = call_count.load(Ordering::SeqCst);
+        for line in &lines {
+            monitor.process_line(line, &source).await;
+        }
+
+        assert_eq!(call_count.load(Ordering::SeqCst), first_pass * 2);
+    }
+
+    #[tokio::test]
+    async fn test_sampling_only_applies_to_matching_sources() {
+        let (monitor, call_count) = sampled_monitor(&[("/var/log/noisy.log", 0.0)]);
+
+        monitor
+            .process_line("ERROR dropped", &SourceType::File(PathBuf::from("/var/log/noisy.log")))
+            .await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        monitor
+            .process_line("ERROR kept", &SourceType::File(PathBuf::from("/var/log/app.log")))
+            .await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    fn resolving_monitor() -> (LogMonitor, Arc<AtomicUsize>, Arc<tokio::sync::Mutex<String>>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].text = None;
+        rules[0].pattern = Some("connection to db lost".to_string());
+        rules[0].resolve = Some("connection to db (restored|established)".to_string());
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count, last_message)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_line_sends_recovery() {
+        let (monitor, call_count, last_message) = resolving_monitor();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("WARN connection to db lost", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        monitor.process_line("INFO connection to db restored", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            *last_message.lock().await,
+            "Rule 'errors' resolved\nINFO connection to db restored"
+        );
+
+        // Already resolved: no second recovery
+        monitor.process_line("INFO connection to db restored", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_does_not_restart_cooldown() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let mut rules = deploy_rules();
+        rules[0].text = None;
+        rules[0].pattern = Some("connection to db lost".to_string());
+        rules[0].resolve = Some("connection to db restored".to_string());
+        rules[0].cooldown = 1;
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("WARN connection to db lost", &source).await;
+        tokio::time::sleep(Duration::from_millis(700)).await;
+        monitor.process_line("INFO connection to db restored", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+
+        // The first alert's cooldown is over, even though the recovery was sent within the last second
+        tokio::time::sleep(Duration::from_millis(700)).await;
+        monitor.process_line("WARN connection to db lost", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 3);
+        assert_eq!(*last_message.lock().await, "WARN connection to db lost");
+    }
+
+    #[tokio::test]
+    async fn test_suppressed_alert_is_not_resolved() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let mut rules = deploy_rules();
+        rules[0].text = None;
+        rules[0].pattern = Some("connection to db lost".to_string());
+        rules[0].resolve = Some("connection to db restored".to_string());
+        rules[0].cooldown = 3600;
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("WARN connection to db lost", &source).await;
+        monitor.process_line("INFO connection to db restored", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+
+        // Held back by the cooldown, so nobody heard about it and there is nothing to recover from
+        monitor.process_line("WARN connection to db lost", &source).await;
+        monitor.process_line("INFO connection to db restored", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_without_prior_alert_is_silent() {
+        let (monitor, call_count, _) = resolving_monitor();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("INFO connection to db established", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+    }
+
+    fn once_monitor() -> (LogMonitor, Arc<AtomicUsize>, Arc<tokio::sync::Mutex<String>>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].text = Some("service restarted".to_string());
+        rules[0].once = true;
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count, last_message)
+    }
+
+    #[tokio::test]
+    async fn test_once_rule_alerts_a_single_time() {
+        let (monitor, call_count, last_message) = once_monitor();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        for i in 0..50 {
+            monitor.process_line(&format!("INFO service restarted (#{})", i), &source).await;
+        }
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, "INFO service restarted (#0)");
+    }
+
+    #[tokio::test]
+    async fn test_once_rule_is_shared_across_cloned_monitors() {
+        let (monitor, call_count, _) = once_monitor();
+        let clone = monitor.clone_monitor();
+
+        monitor
+            .process_line("service restarted", &SourceType::File(PathBuf::from("/var/log/a.log")))
+            .await;
+        clone
+            .process_line("service restarted", &SourceType::Container("web".to_string()))
+            .await;
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_once_rule_waits_for_threshold() {
+        let (monitor, call_count, _) = {
+            let mut alert_manager = AlertManager::new("test-server".to_string());
+            let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+            alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+            let mut rules = deploy_rules();
+            rules[0].once = true;
+            rules[0].threshold = Some(crate::config::Threshold::parse("3 in 1m").unwrap());
+            (LogMonitor::new(rules, Arc::new(alert_manager)).unwrap(), call_count, last_message)
+        };
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        for _ in 0..2 {
+            monitor.process_line("ERROR boom", &source).await;
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        for _ in 0..10 {
+            monitor.process_line("ERROR boom", &source).await;
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    fn anchored_monitor() -> (LogMonitor, Arc<AtomicUsize>, Arc<tokio::sync::Mutex<String>>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].text = None;
+        rules[0].pattern = Some("ERROR$".to_string());
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count, last_message)
+    }
+
+    #[test]
+    fn test_split_line_endings() {
+        assert_eq!(split_line_endings("plain line"), vec!["plain line"]);
+        assert_eq!(split_line_endings(""), vec![""]);
+        assert_eq!(split_line_endings("crlf line\r"), vec!["crlf line"]);
+        assert_eq!(split_line_endings("first\rsecond\r"), vec!["first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn test_crlf_line_matches_anchored_pattern() {
+        let (monitor, call_count, last_message) = anchored_monitor();
+        let source = SourceType::File(PathBuf::from("C:\\logs\\app.log"));
+
+        monitor.process_line("disk check ERROR\r", &source).await;
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, "disk check ERROR");
+    }
+
+    #[tokio::test]
+    async fn test_bare_cr_separates_lines() {
+        let (monitor, call_count, last_message) = anchored_monitor();
+        let source = SourceType::File(PathBuf::from("/var/log/appliance.log"));
+
+        monitor.process_line("fan ERROR\rINFO ok\rpsu ERROR\r", &source).await;
+
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+        assert_eq!(*last_message.lock().await, "psu ERROR");
+    }
+
+    fn excluding_monitor(exclude: Option<&str>, exclude_text: Option<&str>) -> (LogMonitor, Arc<AtomicUsize>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].exclude = exclude.map(str::to_string);
+        rules[0].exclude_text = exclude_text.map(str::to_string);
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count)
+    }
+
+    #[tokio::test]
+    async fn test_exclude_pattern_suppresses_match() {
+        let (monitor, call_count) = excluding_monitor(Some("HealthCheck|/ping"), None);
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR HealthCheck endpoint timed out", &source).await;
+        monitor.process_line("ERROR GET /ping 503", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        monitor.process_line("ERROR payment failed", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_exclude_text_suppresses_match() {
+        let (monitor, call_count) = excluding_monitor(None, Some("[expected]"));
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR retrying [expected]", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        // Regex metacharacters in exclude_text are literal
+        monitor.process_line("ERROR retrying e", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_without_exclude_behavior_is_unchanged() {
+        let (monitor, call_count) = excluding_monitor(None, None);
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR HealthCheck endpoint timed out", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_invalid_exclude_pattern_is_rejected() {
+        let mut rules = deploy_rules();
+        rules[0].exclude = Some("[unclosed".to_string());
+        let alert_manager = Arc::new(AlertManager::new("test-server".to_string()));
+        assert!(LogMonitor::new(rules, alert_manager).is_err());
+    }
+
+    fn text_monitor(text: &str, case_insensitive: bool) -> (LogMonitor, Arc<AtomicUsize>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].text = Some(text.to_string());
+        rules[0].case_insensitive = case_insensitive;
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count)
+    }
+
+    #[tokio::test]
+    async fn test_text_rule_is_case_sensitive_by_default() {
+        let (monitor, call_count) = text_monitor("error", false);
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("This is an ERROR message", &source).await;
+        monitor.process_line("This is an Error message", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        monitor.process_line("This is an error message", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_case_insensitive_text_rule() {
+        let (monitor, call_count) = text_monitor("Connection Refused", true);
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR connection refused", &source).await;
+        monitor.process_line("ERROR CONNECTION REFUSED", &source).await;
+        monitor.process_line("ERROR Connection Refused", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 3);
+
+        monitor.process_line("ERROR connection reset", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_case_insensitive_survives_clone() {
+        let (monitor, call_count) = text_monitor("timeout", true);
+        let clone = monitor.clone_monitor();
+
+        clone
+            .process_line("UPSTREAM TIMEOUT", &SourceType::Container("web".to_string()))
+            .await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pattern_rule_inline_case_insensitive_flag() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].text = None;
+        rules[0].pattern = Some("(?i)fatal".to_string());
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+
+        monitor
+            .process_line("FATAL: out of memory", &SourceType::File(PathBuf::from("/var/log/app.log")))
+            .await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    const STACK_TRACE: [&str; 5] = [
+        "2024-05-01 12:00:00 ERROR Payment failed",
+        "java.lang.NullPointerException: card is null",
+        "\tat com.acme.PaymentService.charge(PaymentService.java:42)",
+        "\tat com.acme.CheckoutController.submit(CheckoutController.java:17)",
+        "Caused by: java.io.IOException: gateway closed",
+    ];
+
+    fn multiline_monitor(
+        max_lines: Option<usize>,
+    ) -> (LogMonitor, Arc<AtomicUsize>, Arc<tokio::sync::Mutex<String>>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].name = "npe_in_payments".to_string();
+        rules[0].text = None;
+        rules[0].pattern = Some(r"NullPointerException[^\n]*\n\s+at com\.acme\.PaymentService".to_string());
+        rules[0].multiline = Some(MultilineConfig {
+            start: r"^\d{4}-\d{2}-\d{2} ".to_string(),
+            timeout: Some(Duration::from_millis(50)),
+            max_lines,
+        });
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count, last_message)
+    }
+
+    #[tokio::test]
+    async fn test_multiline_record_matches_pattern_spanning_lines() {
+        let (monitor, call_count, last_message) = multiline_monitor(None);
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        for line in STACK_TRACE {
+            monitor.process_line(line, &source).await;
+        }
+        // The record is still open until the next record starts
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        monitor.process_line("2024-05-01 12:00:01 INFO retrying", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, STACK_TRACE.join("\n"));
+    }
+
+    #[tokio::test]
+    async fn test_multiline_record_flushes_after_timeout() {
+        let (monitor, call_count, _) = multiline_monitor(None);
+        let source = SourceType::Container("payments".to_string());
+
+        for line in STACK_TRACE {
+            monitor.process_line(line, &source).await;
+        }
+        monitor.flush_stale_records().await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        monitor.flush_stale_records().await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        // Nothing left to flush
+        monitor.flush_stale_records().await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_multiline_records_are_kept_per_source() {
+        let (monitor, call_count, _) = multiline_monitor(None);
+        let app = SourceType::File(PathBuf::from("/var/log/app.log"));
+        let other = SourceType::File(PathBuf::from("/var/log/other.log"));
+
+        monitor.process_line(STACK_TRACE[0], &app).await;
+        monitor.process_line(STACK_TRACE[1], &app).await;
+        // A new record in another file doesn't cut this one short
+        monitor.process_line("2024-05-01 12:00:00 INFO started", &other).await;
+        monitor.process_line(STACK_TRACE[2], &app).await;
+
+        monitor.flush_records(&app).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_multiline_record_is_cut_at_max_lines() {
+        let (monitor, call_count, last_message) = multiline_monitor(Some(3));
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        for line in STACK_TRACE {
+            monitor.process_line(line, &source).await;
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, STACK_TRACE[..3].join("\n"));
+    }
+
+    #[tokio::test]
+    async fn test_single_line_rules_ignore_multiline_grouping() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].text = Some("IOException".to_string());
+        let mut npe = rules[0].clone();
+        npe.name = "npe".to_string();
+        npe.text = Some("NullPointerException".to_string());
+        npe.multiline = Some(MultilineConfig {
+            start: r"^\d{4}-".to_string(),
+            timeout: None,
+            max_lines: None,
+        });
+        rules.push(npe);
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        for line in STACK_TRACE {
+            monitor.process_line(line, &source).await;
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, STACK_TRACE[4]);
+    }
+
+    fn json_field_monitor(
+        json_field: &str,
+        text: Option<&str>,
+        pattern: Option<&str>,
+    ) -> (LogMonitor, Arc<AtomicUsize>, Arc<tokio::sync::Mutex<String>>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].json_field = Some(json_field.to_string());
+        rules[0].text = text.map(str::to_string);
+        rules[0].pattern = pattern.map(str::to_string);
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count, last_message)
+    }
+
+    #[tokio::test]
+    async fn test_json_field_text_matches_only_that_field() {
+        let (monitor, call_count, last_message) = json_field_monitor("level", Some("ERROR"), None);
+        let source = SourceType::File(PathBuf::from("/var/log/app.json"));
+
+        // "ERROR" in the message doesn't count, only in `level`
+        monitor
+            .process_line(r#"{"level":"INFO","msg":"retrying after ERROR"}"#, &source)
+            .await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        let line = r#"{"level":"ERROR","msg":"payment failed"}"#;
+        monitor.process_line(line, &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        // The alert still carries the whole line
+        assert_eq!(*last_message.lock().await, line);
+    }
+
+    #[tokio::test]
+    async fn test_json_field_pattern_on_nested_path() {
+        let (monitor, call_count, _) = json_field_monitor("error.code", None, Some("^E5\\d\\d$"));
+        let source = SourceType::File(PathBuf::from("/var/log/app.json"));
+
+        monitor
+            .process_line(r#"{"msg":"E500 mentioned","error":{"code":"E404"}}"#, &source)
+            .await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        monitor.process_line(r#"{"error":{"code":"E503"}}"#, &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_json_field_skips_non_json_and_missing_fields() {
+        let (monitor, call_count, _) = json_field_monitor("level", Some("ERROR"), None);
+        let source = SourceType::File(PathBuf::from("/var/log/app.json"));
+
+        monitor.process_line("ERROR plain text line", &source).await;
+        monitor.process_line(r#"{"msg":"ERROR without level"}"#, &source).await;
+        monitor.process_line(r#"{"level":"ERROR""#, &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_json_field_condition_and_fields_use_field_value() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let last_context = Arc::new(tokio::sync::Mutex::new(None));
+        alert_manager.register(
+            "test-alert".to_string(),
+            Arc::new(ContextAlertHandler {
+                name: "test-alert".to_string(),
+                last_context: last_context.clone(),
+            }),
+        );
+
+        let mut rules = deploy_rules();
+        rules[0].json_field = Some("duration_ms".to_string());
+        rules[0].text = None;
+        rules[0].pattern = Some("^(?P<ms>\\d+)$".to_string());
+        rules[0].condition = Some(crate::config::Condition::parse("ms > 1000").unwrap());
+        rules[0].fields = vec!["ms".to_string()];
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.json"));
+
+        monitor.process_line(r#"{"path":"/a","duration_ms":250}"#, &source).await;
+        assert!(last_context.lock().await.is_none());
+
+        monitor.process_line(r#"{"path":"/b","duration_ms":4200}"#, &source).await;
+        let context = last_context.lock().await.take().unwrap();
+        assert_eq!(context.fields.get("ms").map(String::as_str), Some("4200"));
+    }
+
+    fn message_monitor(
+        text: Option<&str>,
+        pattern: Option<&str>,
+        message: &str,
+    ) -> (LogMonitor, Arc<AtomicUsize>, Arc<tokio::sync::Mutex<String>>) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+
+        let mut rules = deploy_rules();
+        rules[0].text = text.map(str::to_string);
+        rules[0].pattern = pattern.map(str::to_string);
+        rules[0].message = Some(message.to_string());
+
+        let monitor = LogMonitor::new(rules, Arc::new(alert_manager)).unwrap();
+        (monitor, call_count, last_message)
+    }
+
+    #[tokio::test]
+    async fn test_message_template_with_named_groups() {
+        let (monitor, _, last_message) = message_monitor(
+            None,
+            Some(r"status=(?P<status>\d{3}) user=(?P<user>\w+)"),
+            "HTTP ${status} for user ${user}",
+        );
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("GET /checkout status=502 user=alice took 3s", &source).await;
+        assert_eq!(*last_message.lock().await, "HTTP 502 for user alice");
+    }
+
+    #[tokio::test]
+    async fn test_message_template_with_numbered_groups() {
+        let (monitor, _, last_message) = message_monitor(
+            None,
+            Some(r"(\w+) failed after (\d+) retries(?: \((\w+)\))?"),
+            "${1} gave up (${2} retries) ${3}",
+        );
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        // ${3} didn't participate, so it renders empty
+        monitor.process_line("ERROR sync failed after 5 retries", &source).await;
+        assert_eq!(*last_message.lock().await, "sync gave up (5 retries) ");
+
+        monitor.process_line("ERROR upload failed after 3 retries (timeout)", &source).await;
+        assert_eq!(*last_message.lock().await, "upload gave up (3 retries) timeout");
+    }
+
+    #[tokio::test]
+    async fn test_message_template_without_groups() {
+        let (monitor, call_count, last_message) = message_monitor(Some("OutOfMemoryError"), None, "JVM ran out of memory");
+        let source = SourceType::Container("api".to_string());
+
+        monitor.process_line("java.lang.OutOfMemoryError: Java heap space", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, "JVM ran out of memory");
+    }
+
+    #[tokio::test]
+    async fn test_message_template_heads_threshold_summary() {
+        let (monitor, _, last_message) = message_monitor(None, Some(r"disk (?P<disk>\w+) error"), "I/O errors on ${disk}");
+        {
+            let mut rules = monitor.rules.write().unwrap();
+            let rule = &mut Arc::get_mut(&mut rules).unwrap().rules[0];
+            rule.threshold = Some(crate::config::Threshold::parse("2 in 1m").unwrap());
+            rule.include_matches = 2;
+        }
+        let source = SourceType::File(PathBuf::from("/var/log/kern.log"));
+
+        monitor.process_line("disk sda error 1", &source).await;
+        monitor.process_line("disk sda error 2", &source).await;
+        assert_eq!(
+            *last_message.lock().await,
+            "I/O errors on sda\n\nLast 2 matching lines:\ndisk sda error 1\ndisk sda error 2"
+        );
+    }
+
+    fn prefilter_rules() -> Vec<Rule> {
+        let base = deploy_rules().remove(0);
+        let rule = |name: &str, pattern: &str| Rule {
+            name: name.to_string(),
+            text: None,
+            pattern: Some(pattern.to_string()),
+            ..base.clone()
+        };
+
+        let mut slow = rule("slow_request", r"took (?P<ms>\d+)ms");
+        slow.condition = Some(crate::config::Condition::parse("ms > 1000").unwrap());
+        let mut errors = rule("errors", r"\bERROR\b");
+        errors.exclude = Some("HealthCheck".to_string());
+        let mut text = base.clone();
+        text.name = "panic_text".to_string();
+        text.text = Some("panic:".to_string());
+        let mut json = rule("json_level", "^fatal$");
+        json.json_field = Some("level".to_string());
+
+        vec![
+            errors,
+            rule("http_5xx", r"status=5\d\d"),
+            rule("anchored", r"^WARN "),
+            rule("case_insensitive", r"(?i)timeout"),
+            rule("alternation", r"disk (full|quota exceeded)"),
+            slow,
+            text,
+            json,
+            rule("never", r"^\z unmatched"),
+        ]
+    }
+
+    #[test]
+    fn test_prefilter_matches_naive_rule_loop() {
+        let alert_manager = Arc::new(AlertManager::new("test-server".to_string()));
+        let monitor = LogMonitor::new(prefilter_rules(), alert_manager).unwrap();
+        assert!(monitor.rule_set().prefilter.is_some());
+
+        let lines = [
+            "ERROR payment failed",
+            "ERROR HealthCheck failed",
+            "INFO all good",
+            "GET /api status=503 took 1500ms",
+            "GET /api status=200 took 20ms",
+            "WARN disk full",
+            "prefix WARN not anchored",
+            "upstream TIMEOUT after 30s",
+            "disk quota exceeded on /home",
+            "thread 'main' panicked at 'panic: boom'",
+            r#"{"level":"fatal","msg":"ERROR"}"#,
+            "",
+        ];
+        let rules = monitor.rule_set();
+        for line in lines {
+            let naive: Vec<&str> = rules
+                .rules
+                .iter()
+                .filter(|rule| rule.matcher.matches(line))
+                .map(|rule| rule.name.as_str())
+                .collect();
+            assert_eq!(monitor.matching_rules(line), naive, "line: {}", line);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prefiltered_rules_still_alert() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let monitor = LogMonitor::new(prefilter_rules(), Arc::new(alert_manager)).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("GET /api status=200 took 20ms", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        // Matches http_5xx and slow_request
+        monitor.process_line("GET /api status=502 took 2500ms", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+        assert_eq!(*last_message.lock().await, "GET /api status=502 took 2500ms");
+    }
+
+    #[test]
+    fn test_prefilter_skipped_for_single_regex_rule() {
+        let alert_manager = Arc::new(AlertManager::new("test-server".to_string()));
+        let mut rules = deploy_rules();
+        rules[0].text = None;
+        rules[0].pattern = Some("ERROR".to_string());
+        let monitor = LogMonitor::new(rules, alert_manager).unwrap();
+        assert!(monitor.rule_set().prefilter.is_none());
+    }
+
+    fn text_rule(name: &str, text: &str, case_insensitive: bool) -> Rule {
+        let mut rule = deploy_rules().remove(0);
+        rule.name = name.to_string();
+        rule.text = Some(text.to_string());
+        rule.case_insensitive = case_insensitive;
+        rule
+    }
+
+    fn needle_rules() -> Vec<Rule> {
+        let mut excluded = text_rule("excluded", "failed", false);
+        excluded.exclude_text = Some("HealthCheck".to_string());
+        vec![
+            text_rule("error", "ERROR", false),
+            // Overlaps "ERROR" and shares a needle with the next rule
+            text_rule("rr", "RR", false),
+            text_rule("rr_again", "RR", false),
+            text_rule("disk", "disk", false),
+            text_rule("disk_full", "disk full", false),
+            text_rule("timeout_ci", "Timeout", true),
+            text_rule("straße_ci", "STRASSE", true),
+            text_rule("überlauf_ci", "ÜBERLAUF", true),
+            excluded,
+        ]
+    }
+
+    #[test]
+    fn test_text_prefilter_matches_naive_rule_loop() {
+        let alert_manager = Arc::new(AlertManager::new("test-server".to_string()));
+        let monitor = LogMonitor::new(needle_rules(), alert_manager).unwrap();
+        assert!(monitor.rule_set().prefilter.is_some());
+
+        let lines = [
+            "ERROR disk full on /var",
+            "error in lowercase",
+            "upstream TIMEOUT",
+            "timeout waiting for lock",
+            "Überlauf im Puffer",
+            "strasse closed",
+            "payment failed",
+            "HealthCheck failed",
+            "INFO all good",
+            "",
+        ];
+        let rules = monitor.rule_set();
+        for line in lines {
+            let naive: Vec<&str> = rules
+                .rules
+                .iter()
+                .filter(|rule| rule.matcher.matches(line))
+                .map(|rule| rule.name.as_str())
+                .collect();
+            assert_eq!(monitor.matching_rules(line), naive, "line: {}", line);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_all_text_rules_fire_on_line_with_every_needle() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let monitor = LogMonitor::new(needle_rules(), Arc::new(alert_manager)).unwrap();
+
+        let line = "ERROR disk full: TIMEOUT writing überlauf to Strasse, backup failed";
+        monitor
+            .process_line(line, &SourceType::File(PathBuf::from("/var/log/app.log")))
+            .await;
+        assert_eq!(call_count.load(Ordering::SeqCst), needle_rules().len());
+    }
+
+    /// Compare the prefiltered and naive rule loops over many rules
+    /// Run with `cargo test --release bench_prefilter -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_prefilter_vs_naive_rule_loop() {
+        let mut rules: Vec<Rule> = (0..200)
+            .map(|i| text_rule(&format!("text_{}", i), &format!("E{:04} failure", i), i % 4 == 0))
+            .collect();
+        rules.extend((0..50).map(|i| {
+            let mut rule = text_rule(&format!("regex_{}", i), "", false);
+            rule.text = None;
+            rule.pattern = Some(format!(r"code=R{}\d+ took \d+ms", i));
+            rule
+        }));
+        let monitor = LogMonitor::new(rules, Arc::new(AlertManager::new("bench".to_string()))).unwrap();
+
+        let lines: Vec<String> = (0..20_000)
+            .map(|i| match i % 10 {
+                0 => format!("ERROR E{:04} failure in worker {}", i % 200, i),
+                1 => format!("WARN code=R{}7 took {}ms", i % 50, i),
+                _ => format!("INFO request {} served in {}ms", i, i % 300),
+            })
+            .collect();
+
+        let started = std::time::Instant::now();
+        let naive: usize = lines
+            .iter()
+            .map(|line| monitor.rule_set().rules.iter().filter(|rule| rule.matcher.matches(line)).count())
+            .sum();
+        let naive_elapsed = started.elapsed();
+
+        let started = std::time::Instant::now();
+        let prefiltered: usize = lines.iter().map(|line| monitor.matching_rules(line).len()).sum();
+        let prefiltered_elapsed = started.elapsed();
+
+        assert_eq!(naive, prefiltered);
+        println!(
+            "{} lines x {} rules: naive {:?}, prefiltered {:?}",
+            lines.len(),
+            monitor.rule_set().rules.len(),
+            naive_elapsed,
+            prefiltered_elapsed
+        );
+    }
+
+    /// Wait up to a few seconds for the alert count to reach `expected`
+    async fn wait_for_alerts(call_count: &AtomicUsize, expected: usize) {
+        for _ in 0..100 {
+            if call_count.load(Ordering::SeqCst) >= expected {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), expected);
+    }
+
+    #[tokio::test]
+    async fn test_watch_file_processes_appended_lines_and_truncation() {
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "ERROR already there before watching\n").unwrap();
+
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let monitor = Arc::new(LogMonitor::new(deploy_rules(), Arc::new(alert_manager)).unwrap());
+
+        let watcher = {
+            let monitor = monitor.clone();
+            let path = path.clone();
+            tokio::spawn(async move { monitor.watch_file_once(path).await })
+        };
+        // Let the watcher open the file and seek to its end
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let append = |text: &str| {
+            let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        };
+
+        append("INFO fine\nERROR disk failure\n");
+        wait_for_alerts(&call_count, 1).await;
+        assert_eq!(*last_message.lock().await, "ERROR disk failure");
+
+        std::fs::write(&path, "").unwrap();
+        append("ERROR after truncate\n");
+        wait_for_alerts(&call_count, 2).await;
+        assert_eq!(*last_message.lock().await, "ERROR after truncate");
+
+        watcher.abort();
+    }
+
+    #[tokio::test]
+    async fn test_watch_file_resumes_from_saved_offset_after_restart() {
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let state_dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "").unwrap();
+        let append = |text: &str| {
+            let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+        };
+
+        let start = |monitor: Arc<LogMonitor>| {
+            let path = path.clone();
+            tokio::spawn(async move { monitor.watch_file_once(path).await })
+        };
+
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let monitor = Arc::new(LogMonitor::new(deploy_rules(), Arc::new(alert_manager)).unwrap());
+        let watcher = start(monitor.clone());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        append("ERROR before stop\n");
+        wait_for_alerts(&call_count, 1).await;
+        watcher.abort();
+        let state = crate::state::PersistedState {
+            offsets: monitor.export_offsets().await,
+            ..Default::default()
+        };
+        crate::state::save(state_dir.path(), &state).unwrap();
+
+        // Written while tinywatcher was not running
+        append("ERROR while stopped\n");
+
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let monitor = Arc::new(LogMonitor::new(deploy_rules(), Arc::new(alert_manager)).unwrap());
+        let saved = crate::state::load(state_dir.path()).unwrap();
+        assert_eq!(monitor.restore_offsets(saved.offsets).await, 1);
+        let watcher = start(monitor.clone());
+
+        wait_for_alerts(&call_count, 1).await;
+        assert_eq!(*last_message.lock().await, "ERROR while stopped");
+
+        append("ERROR after restart\n");
+        wait_for_alerts(&call_count, 2).await;
+        assert_eq!(*last_message.lock().await, "ERROR after restart");
+
+        watcher.abort();
+    }
+
+    #[tokio::test]
+    async fn test_watch_file_reads_gzipped_rotated_log_once() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("app.log.2.gz");
+        let mut encoder = GzEncoder::new(std::fs::File::create(&path).unwrap(), flate2::Compression::default());
+        encoder
+            .write_all(b"INFO started\nERROR disk failure\nINFO fine\nERROR out of memory\n")
+            .unwrap();
+        encoder.finish().unwrap();
+
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, last_message) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let monitor = LogMonitor::new(deploy_rules(), Arc::new(alert_manager)).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), monitor.read_compressed_file(path.clone()))
+            .await
+            .expect("compressed file should be read once, not followed")
+            .unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+        assert_eq!(*last_message.lock().await, "ERROR out of memory");
+
+        // Already read to the end, so a second read (e.g. after a restart with state) is a no-op
+        monitor.read_compressed_file(path).await.unwrap();
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_supervised_compressed_file_watch_is_not_restarted() {
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("app.log.2.gz");
+        let mut encoder = GzEncoder::new(std::fs::File::create(&path).unwrap(), flate2::Compression::default());
+        encoder.write_all(b"ERROR disk failure\n").unwrap();
+        encoder.finish().unwrap();
+
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let alert_manager = Arc::new(alert_manager);
+        let monitor = Arc::new(LogMonitor::new(deploy_rules(), alert_manager.clone()).unwrap());
+
+        let shutdown = tokio_util::sync::CancellationToken::new();
+        let runs = Arc::new(AtomicUsize::new(0));
+        let task = {
+            let runs = runs.clone();
+            crate::shutdown::spawn("app.log.2.gz", &shutdown, &alert_manager, move || {
+                runs.fetch_add(1, Ordering::SeqCst);
+                let monitor = monitor.clone();
+                let path = path.clone();
+                async move { monitor.watch_file(path).await }
+            })
+        };
+
+        wait_for_alerts(&call_count, 1).await;
+        // Longer than the supervisor's first restart delay
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("watcher did not stop")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reload_rules_changes_matching() {
+        let (monitor, call_count, last_message) = message_monitor(Some("ERROR"), None, "error seen");
+        let clone = monitor.clone_monitor();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("PANIC boom", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        let mut rules = deploy_rules();
+        rules[0].text = Some("PANIC".to_string());
+        rules[0].message = Some("panic seen".to_string());
+        monitor.reload_rules(rules).unwrap();
+
+        monitor.process_line("ERROR boom", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 0);
+
+        // Watchers running on a cloned monitor pick up the new rules too
+        clone.process_line("PANIC boom", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+        assert_eq!(*last_message.lock().await, "panic seen");
+    }
+
+    #[tokio::test]
+    async fn test_reload_rules_keeps_threshold_window() {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let (handler, call_count, _) = MockAlertHandler::new("test-alert");
+        alert_manager.register("test-alert".to_string(), Arc::new(handler));
+        let mut rules = deploy_rules();
+        rules[0].threshold = Some(crate::config::Threshold::parse("2 in 1m").unwrap());
+        let monitor = LogMonitor::new(rules.clone(), Arc::new(alert_manager)).unwrap();
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+
+        monitor.process_line("ERROR one", &source).await;
+        monitor.reload_rules(rules.clone()).unwrap();
+        monitor.process_line("ERROR two", &source).await;
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+
+        // An invalid rule set is rejected and the running rules stay in place
+        rules[0].text = None;
+        rules[0].pattern = Some("(unclosed".to_string());
+        assert!(monitor.reload_rules(rules).is_err());
+        assert_eq!(monitor.matching_rules("ERROR three"), vec!["errors"]);
+    }
 }
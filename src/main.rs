@@ -1,19 +1,34 @@
 mod alerts;
+mod bench;
 mod cli;
+mod clock_skew;
 mod config;
 mod daemon;
+mod emoji;
+mod file_access;
+mod file_tail;
+#[cfg(windows)]
+mod eventlog;
 mod health_monitor;
 mod heartbeat_monitor;
 mod log_monitor;
+mod metrics;
+mod ping;
+mod reload;
 mod resource_monitor;
+mod rule_matcher;
+mod shutdown;
+mod silent_sources;
+mod state;
 mod stream_monitor;
+mod timestamp;
 
 use alerts::AlertManager;
 use anyhow::{Context, Result};
 use clap::Parser;
-use cli::{Cli, Commands};
+use cli::{Cli, Commands, LogFormat};
 use config::Config;
-use health_monitor::{HealthCheck, HealthCheckType, HealthMonitor};
+use health_monitor::{HealthCheck, HealthMonitor};
 use log_monitor::LogMonitor;
 use regex::Regex;
 use resource_monitor::ResourceMonitor;
@@ -26,6 +41,7 @@ use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    emoji::set_enabled(!cli.no_emoji);
 
     // Initialize tracing
     let filter = if cli.verbose {
@@ -34,17 +50,24 @@ async fn main() -> Result<()> {
         EnvFilter::new("info")
     };
 
-    tracing_subscriber::registry()
-        .with(fmt::layer())
-        .with(filter)
-        .init();
+    let registry = tracing_subscriber::registry();
+    match cli.log_format {
+        LogFormat::Text => registry.with(fmt::layer()).with(filter).init(),
+        LogFormat::Json => registry.with(json_log_layer(std::io::stdout)).with(filter).init(),
+    }
 
     match cli.command {
         Commands::Watch {
             config,
             no_resources,
+            max_alerts,
+            max_alerts_window,
+            require_readable,
         } => {
-            handle_watch(config, no_resources).await?;
+            let alert_limit = max_alerts.map(|max| {
+                (max, std::time::Duration::from_secs(max_alerts_window * 60))
+            });
+            handle_watch(config, no_resources, alert_limit, require_readable).await?;
         }
         Commands::Test { config } => {
             handle_test(config).await?;
@@ -54,20 +77,38 @@ async fn main() -> Result<()> {
             lines,
             file,
             container,
+            timeout,
         } => {
-            handle_check(config, lines, file, container).await?;
+            handle_check(config, lines, file, container, std::time::Duration::from_secs(timeout)).await?;
+        }
+        Commands::Bench { config, lines, input } => {
+            handle_bench(config, lines, input).await?;
+        }
+        Commands::Mute { config, duration } => {
+            handle_mute(config, duration)?;
+        }
+        Commands::Unmute { config } => {
+            handle_unmute(config)?;
         }
-        Commands::Start { config } => {
-            handle_start(config)?;
+        Commands::Start { config, dry_run, name } => {
+            if dry_run {
+                handle_start_dry_run(config, name.as_deref())?;
+            } else {
+                handle_start(config, name.as_deref())?;
+            }
         }
-        Commands::Stop => {
-            handle_stop()?;
+        Commands::Stop { name } => {
+            handle_stop(name.as_deref())?;
         }
-        Commands::Restart => {
-            handle_restart()?;
+        Commands::Restart { name } => {
+            handle_restart(name.as_deref())?;
         }
-        Commands::Status => {
-            handle_status()?;
+        Commands::Status { json, name } => {
+            let status = handle_status(json, name.as_deref())?;
+            let exit_code = status.exit_code();
+            if exit_code != 0 {
+                std::process::exit(exit_code);
+            }
         }
     }
 
@@ -77,10 +118,15 @@ async fn main() -> Result<()> {
 async fn handle_watch(
     config_path: std::path::PathBuf,
     no_resources: bool,
+    alert_limit: Option<(usize, std::time::Duration)>,
+    require_readable: bool,
 ) -> Result<()> {
     // Load config
     let mut config = Config::from_file(config_path.to_str().context("Invalid config path")?)?;
 
+    config.validate()?;
+    timestamp::set(config.time_settings()?);
+
     // Disable resource monitoring if requested
     if no_resources {
         config.resources = None;
@@ -89,11 +135,15 @@ async fn handle_watch(
     // Check if we have anything to watch
     if config.inputs.files.is_empty()
         && config.inputs.containers.is_empty()
+        && config.inputs.container_selector.is_none()
         && config.inputs.streams.is_empty()
+        && config.inputs.windows_eventlog.is_empty()
+        && config.inputs.journald.is_empty()
+        && config.inputs.kubernetes.is_empty()
         && config.resources.is_none()
         && config.system_checks.is_empty()
     {
-        anyhow::bail!("Nothing to watch! Configure files, containers, streams, resources, or system_checks in your config file.");
+        anyhow::bail!("Nothing to watch! Configure files, containers, streams, journald units, kubernetes pods, resources, or system_checks in your config file.");
     }
 
     let identity = config.identity.get_name();
@@ -101,207 +151,429 @@ async fn handle_watch(
 
     // Create alert manager and register handlers
     let mut alert_manager = AlertManager::new(identity.clone());
+    let http_client = alert_manager.http_client().clone();
+    alert_manager.set_container_identity(config.identity.include_container);
+    alert_manager.set_cooldown_mode(config.cooldown_mode);
+    if let Some((max_alerts, window)) = alert_limit {
+        alert_manager.set_alert_limit(max_alerts, window);
+    }
+    for rule in config.rules.iter().filter(|rule| rule.burst > 0) {
+        alert_manager.set_burst(&rule.name, rule.burst);
+    }
+    if let Some(dedup) = &config.dedup {
+        alert_manager.set_dedup(dedup.window(), dedup.normalizers()?);
+    }
+    if let Some(dir) = &config.state_dir {
+        alert_manager.set_mute_dir(dir.clone());
+    }
+    if let Some(delivery_failures) = &config.delivery_failures {
+        alert_manager.set_failure_alert(delivery_failures.alert.clone(), delivery_failures.window());
+    }
     
     for (name, alert) in &config.alerts {
-        use crate::config::{AlertOptions, AlertType};
+        use crate::config::AlertOptions;
         
-        let handler: Arc<dyn alerts::AlertHandler> = match alert.alert_type {
-            AlertType::Stdout => Arc::new(alerts::StdoutAlert::new(name.clone())),
-            AlertType::Slack => {
-                if let AlertOptions::Slack { url } = &alert.options {
-                    Arc::new(alerts::SlackAlert::new(name.clone(), url.clone()))
-                } else {
-                    tracing::error!("Invalid Slack alert configuration for '{}'", name);
-                    continue;
-                }
-            }
-            AlertType::Webhook => {
-                if let AlertOptions::Webhook { url } = &alert.options {
-                    Arc::new(alerts::WebhookAlert::new(name.clone(), url.clone()))
-                } else {
-                    tracing::error!("Invalid Webhook alert configuration for '{}'", name);
-                    continue;
+        let handler: Arc<dyn alerts::AlertHandler> = match &alert.options {
+            AlertOptions::Stdout {} => Arc::new(alerts::StdoutAlert::new(name.clone())),
+            AlertOptions::Desktop {} => {
+                #[cfg(feature = "desktop")]
+                {
+                    Arc::new(alerts::DesktopAlert::new(name.clone()))
                 }
-            }
-            AlertType::Discord => {
-                if let AlertOptions::Discord { url } = &alert.options {
-                    Arc::new(alerts::DiscordAlert::new(name.clone(), url.clone()))
-                } else {
-                    tracing::error!("Invalid Discord alert configuration for '{}'", name);
+
+                #[cfg(not(feature = "desktop"))]
+                {
+                    tracing::error!(
+                        "Desktop alert '{}' needs a build with the 'desktop' feature (cargo install tinywatcher --features desktop); skipping it",
+                        name
+                    );
                     continue;
                 }
             }
-            AlertType::Telegram => {
-                if let AlertOptions::Telegram { bot_token, chat_id } = &alert.options {
-                    Arc::new(alerts::TelegramAlert::new(name.clone(), bot_token.clone(), chat_id.clone()))
-                } else {
-                    tracing::error!("Invalid Telegram alert configuration for '{}'", name);
-                    continue;
-                }
+            AlertOptions::Slack { url, format } => {
+                Arc::new(alerts::SlackAlert::new(name.clone(), url.clone(), *format, http_client.clone()))
             }
-            AlertType::PagerDuty => {
-                if let AlertOptions::PagerDuty { routing_key } = &alert.options {
-                    Arc::new(alerts::PagerDutyAlert::new(name.clone(), routing_key.clone()))
-                } else {
-                    tracing::error!("Invalid PagerDuty alert configuration for '{}'", name);
-                    continue;
-                }
-            }
-            AlertType::Ntfy => {
-                if let AlertOptions::Ntfy { topic, server } = &alert.options {
-                    Arc::new(alerts::NtfyAlert::new(name.clone(), topic.clone(), server.clone()))
-                } else {
-                    tracing::error!("Invalid Ntfy alert configuration for '{}'", name);
-                    continue;
+            AlertOptions::Webhook {
+                url, compress_above, template, headers, method, content_type, secret, signature_header,
+            } => {
+                let webhook = alerts::WebhookAlert::new(name.clone(), url.clone(), *compress_above, http_client.clone())
+                    .with_request(method.as_deref(), headers.as_ref(), template.clone(), content_type.clone())
+                    .and_then(|webhook| webhook.with_signature(secret.clone(), signature_header.as_deref()));
+                match webhook {
+                    Ok(handler) => Arc::new(handler),
+                    Err(e) => {
+                        tracing::error!("Invalid Webhook alert configuration for '{}': {}", name, e);
+                        continue;
+                    }
                 }
             }
-            AlertType::Email => {
-                #[cfg(unix)]
-                {
-                    if let AlertOptions::Email { from, to, smtp_server: _ } = &alert.options {
-                        Arc::new(alerts::EmailAlert::new(name.clone(), from.clone(), to.clone()))
-                    } else {
-                        tracing::error!("Invalid Email alert configuration for '{}'", name);
+            AlertOptions::Discord { url, thread_id, mentions } => {
+                match alerts::DiscordAlert::new(name.clone(), url.clone(), thread_id.clone(), mentions, http_client.clone()) {
+                    Ok(handler) => Arc::new(handler),
+                    Err(e) => {
+                        tracing::error!("Invalid Discord alert configuration for '{}': {}", name, e);
                         continue;
                     }
                 }
-                
-                #[cfg(not(unix))]
-                {
-                    if let AlertOptions::Email { from, to, smtp_server } = &alert.options {
-                        Arc::new(alerts::EmailAlert::new(name.clone(), from.clone(), to.clone(), smtp_server.clone()))
-                    } else {
-                        tracing::error!("Invalid Email alert configuration for '{}'", name);
+            }
+            AlertOptions::Telegram { bot_token, chat_id } => {
+                Arc::new(alerts::TelegramAlert::new(name.clone(), bot_token.clone(), chat_id.clone(), http_client.clone()))
+            }
+            AlertOptions::PagerDuty { routing_key } => {
+                Arc::new(alerts::PagerDutyAlert::new(name.clone(), routing_key.clone(), http_client.clone()))
+            }
+            AlertOptions::Opsgenie { api_key, priority } => {
+                Arc::new(alerts::OpsgenieAlert::new(name.clone(), api_key.clone(), *priority, http_client.clone()))
+            }
+            AlertOptions::Ntfy { topic, server } => {
+                Arc::new(alerts::NtfyAlert::new(name.clone(), topic.clone(), server.clone(), http_client.clone()))
+            }
+            AlertOptions::Pushover { token, user, priority } => {
+                Arc::new(alerts::PushoverAlert::new(name.clone(), token.clone(), user.clone(), *priority, http_client.clone()))
+            }
+            #[cfg(unix)]
+            AlertOptions::LocalSyslog { facility } => {
+                match alerts::LocalSyslogAlert::new(name.clone(), facility.clone()) {
+                    Ok(handler) => Arc::new(handler),
+                    Err(e) => {
+                        tracing::error!("Invalid LocalSyslog alert configuration for '{}': {}", name, e);
                         continue;
                     }
                 }
             }
-            AlertType::SendGrid => {
-                if let AlertOptions::SendGrid { api_key, from, to } = &alert.options {
-                    Arc::new(alerts::SendGridAlert::new(name.clone(), api_key.clone(), from.clone(), to.clone()))
-                } else {
-                    tracing::error!("Invalid SendGrid alert configuration for '{}'", name);
-                    continue;
-                }
+            #[cfg(not(unix))]
+            AlertOptions::LocalSyslog { .. } => {
+                tracing::error!("LocalSyslog alert '{}' is only supported on Unix; skipping it", name);
+                continue;
+            }
+            AlertOptions::Gotify { url, token, priority } => {
+                Arc::new(alerts::GotifyAlert::new(name.clone(), url.clone(), token.clone(), *priority, http_client.clone()))
+            }
+            #[cfg(unix)]
+            AlertOptions::Email { from, to, .. } => {
+                Arc::new(alerts::EmailAlert::new(name.clone(), from.clone(), to.clone()))
+            }
+            #[cfg(not(unix))]
+            AlertOptions::Email { from, to, smtp_server } => {
+                Arc::new(alerts::EmailAlert::new(name.clone(), from.clone(), to.clone(), smtp_server.clone()))
+            }
+            AlertOptions::SendGrid { api_key, from, to } => {
+                Arc::new(alerts::SendGridAlert::new(name.clone(), api_key.clone(), from.clone(), to.clone(), http_client.clone()))
+            }
+            AlertOptions::Forward { url } => {
+                Arc::new(alerts::ForwardAlert::new(name.clone(), url.clone(), http_client.clone()))
+            }
+            AlertOptions::File { path, format } => {
+                Arc::new(alerts::FileAlert::new(name.clone(), path.clone(), *format))
+            }
+            AlertOptions::Exec { command, args, timeout } => {
+                Arc::new(alerts::ExecAlert::new(name.clone(), command.clone(), args.clone(), *timeout))
             }
         };
         
+        let (retries, backoff) = alert.retry_policy(config.alert_retry.as_ref());
+        let handler: Arc<dyn alerts::AlertHandler> = if retries > 0 {
+            Arc::new(alerts::RetryingHandler::new(handler, retries, backoff))
+        } else {
+            handler
+        };
         alert_manager.register(name.clone(), handler);
         tracing::debug!("Registered alert handler: {}", name);
     }
     
     let alert_manager = Arc::new(alert_manager);
 
-    // Spawn log monitoring tasks
+    // Cooldowns and threshold windows saved by a previous run
+    let mut saved_state = match &config.state_dir {
+        Some(dir) => match state::load(dir) {
+            Ok(saved) => Some(saved),
+            Err(e) => {
+                tracing::warn!("Ignoring saved state: {:#}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    let mut persisted_monitor = None;
+
+    // Spawn supervised monitoring tasks, restarted if they stop and stopped when shutdown_token is cancelled
+    let shutdown_token = tokio_util::sync::CancellationToken::new();
     let mut tasks = Vec::new();
+    let mut reloader = reload::Reloader::new(config_path.clone(), shutdown_token.clone(), alert_manager.clone());
+
+    if config.delivery_failures.is_some() {
+        let manager = alert_manager.clone();
+        tasks.push(shutdown::spawn("delivery failures", &shutdown_token, &alert_manager, move || {
+            let manager = manager.clone();
+            async move {
+                manager.watch_delivery_failures().await;
+                Ok(())
+            }
+        }));
+    }
+
+    // Report log sources that stop producing lines
+    let silence = match &config.silent_sources {
+        Some(silent_sources) => {
+            let watchdog = Arc::new(silent_sources::SilenceWatchdog::new(silent_sources, alert_manager.clone())?);
+            let task_watchdog = watchdog.clone();
+            tasks.push(shutdown::spawn("silent sources", &shutdown_token, &alert_manager, move || {
+                let watchdog = task_watchdog.clone();
+                async move {
+                    watchdog.run().await;
+                    Ok(())
+                }
+            }));
+            Some(watchdog)
+        }
+        None => None,
+    };
+    let watch_silence = |source: config::SourceType| {
+        if let Some(silence) = &silence {
+            silence.watch(source);
+        }
+    };
 
     if !config.rules.is_empty() {
-        let log_monitor = Arc::new(
-            LogMonitor::new(config.rules.clone(), alert_manager.clone())
-                .context("Failed to create log monitor")?,
-        );
+        let mut log_monitor = LogMonitor::new(config.rules.clone(), alert_manager.clone())
+            .context("Failed to create log monitor")?;
+        if let Some(silence) = &silence {
+            log_monitor.set_silence_watchdog(silence.clone());
+            reloader.set_silence_watchdog(silence.clone());
+        }
+        if let Some(deploy) = &config.deploy {
+            log_monitor.set_deploy_markers(deploy)?;
+        }
+        log_monitor.set_sample_rates(&config.inputs.sample_rate)?;
+        log_monitor.set_container_runtime(config.inputs.runtime());
+        let log_monitor = Arc::new(log_monitor);
+
+        if let Some(saved) = saved_state.take() {
+            state::restore(saved, &alert_manager, Some(&log_monitor)).await;
+        }
+        persisted_monitor = Some(log_monitor.clone());
+        reloader.set_log_monitor(log_monitor.clone());
+
+        // Alert on expected lines that stop appearing, including rules added by a reload
+        let monitor = log_monitor.clone();
+        tasks.push(shutdown::spawn("expected lines", &shutdown_token, &alert_manager, move || {
+            let monitor = monitor.clone();
+            async move {
+                monitor.watch_expected().await;
+                Ok(())
+            }
+        }));
 
-        // Expand glob patterns in file paths
-        let expanded_files = config.expand_file_globs()
+        // Expand glob patterns in file paths and apply max_watchers
+        let sources = config.watch_sources()
             .context("Failed to expand file glob patterns")?;
+        if !sources.dropped.is_empty() {
+            tracing::warn!(
+                "max_watchers ({}) reached; not watching {} source(s): {}",
+                config.max_watchers.unwrap_or_default(),
+                sources.dropped.len(),
+                sources.dropped.join(", ")
+            );
+        }
 
-        // Watch files
-        for file in expanded_files {
-            let monitor = log_monitor.clone();
-            let file_clone = file.clone();
-            tasks.push(tokio::spawn(async move {
-                if let Err(e) = monitor.watch_file(file_clone.clone()).await {
-                    tracing::error!("Error watching file {}: {}", file_clone.display(), e);
+        // Surface permission problems now rather than as endless retries
+        let unreadable = file_access::startup_check(&sources.files, require_readable)?;
+        if !unreadable.is_empty() {
+            let message = file_access::describe(&unreadable);
+            tracing::warn!("{}", message);
+            if let Some(unreadable_config) = &config.unreadable_files {
+                if let Err(e) = alert_manager
+                    .send_alert_multi(&unreadable_config.alert, file_access::UNREADABLE_FILES_RULE, &message, 0)
+                    .await
+                {
+                    tracing::error!("Failed to send unreadable files alert: {}", e);
+                }
+            }
+        }
+        if let Some(unreadable_config) = &config.unreadable_files {
+            let files = sources.files.clone();
+            let alert_names = unreadable_config.alert.clone();
+            let interval = unreadable_config.interval();
+            let manager = alert_manager.clone();
+            tasks.push(shutdown::spawn("unreadable files", &shutdown_token, &alert_manager, move || {
+                let (files, alert_names, manager, unreadable) =
+                    (files.clone(), alert_names.clone(), manager.clone(), unreadable.clone());
+                async move {
+                    file_access::watch(files, alert_names, manager, interval, unreadable).await;
+                    Ok(())
                 }
             }));
         }
 
+        // Watch files; the reloader owns these so a reload can add and remove them
+        tasks.extend(sources.files.into_iter().filter_map(|file| reloader.watch_file(file)));
+
         // Watch containers
-        for container in config.inputs.containers {
+        for container in sources.containers {
+            watch_silence(config::SourceType::Container(container.clone()));
             let monitor = log_monitor.clone();
-            let container_clone = container.clone();
-            tasks.push(tokio::spawn(async move {
-                if let Err(e) = monitor.watch_container(container_clone.clone()).await {
-                    tracing::error!("Error watching container {}: {}", container_clone, e);
-                }
+            let name = format!("container {}", container);
+            tasks.push(shutdown::spawn(name, &shutdown_token, &alert_manager, move || {
+                let (monitor, container) = (monitor.clone(), container.clone());
+                async move { monitor.watch_container(container).await }
+            }));
+        }
+
+        // Watch Windows Event Log channels
+        #[cfg(windows)]
+        for eventlog_config in config.inputs.windows_eventlog.clone() {
+            watch_silence(config::SourceType::EventLog(eventlog_config.log.clone()));
+            let monitor = log_monitor.clone();
+            let name = format!("event log {}", eventlog_config.log);
+            tasks.push(shutdown::spawn(name, &shutdown_token, &alert_manager, move || {
+                let (monitor, eventlog_config) = (monitor.clone(), eventlog_config.clone());
+                async move { monitor.watch_eventlog(eventlog_config).await }
+            }));
+        }
+        #[cfg(not(windows))]
+        if !config.inputs.windows_eventlog.is_empty() {
+            tracing::warn!("windows_eventlog inputs are only supported on Windows; ignoring them");
+        }
+
+        // Watch containers found by label, as they start and stop
+        if let Some(selector) = config.inputs.container_selector.clone() {
+            let monitor = log_monitor.clone();
+            let named = config.inputs.containers.clone();
+            tasks.push(shutdown::spawn("container discovery", &shutdown_token, &alert_manager, move || {
+                let (monitor, selector, named) = (monitor.clone(), selector.clone(), named.clone());
+                async move { monitor.watch_selected_containers(selector, named).await }
             }));
         }
 
+        // Follow Kubernetes pods
+        for kubernetes in config.inputs.kubernetes.clone() {
+            watch_silence(config::SourceType::Pod(kubernetes.name().to_string()));
+            let monitor = log_monitor.clone();
+            let name = format!("pod {}", kubernetes.name());
+            tasks.push(shutdown::spawn(name, &shutdown_token, &alert_manager, move || {
+                let (monitor, kubernetes) = (monitor.clone(), kubernetes.clone());
+                async move { monitor.watch_pod(kubernetes).await }
+            }));
+        }
+
+        // Follow systemd journal units
+        #[cfg(target_os = "linux")]
+        for unit in config.inputs.journald.clone() {
+            watch_silence(config::SourceType::Journal(unit.clone()));
+            let monitor = log_monitor.clone();
+            let name = format!("journal {}", unit);
+            tasks.push(shutdown::spawn(name, &shutdown_token, &alert_manager, move || {
+                let (monitor, unit) = (monitor.clone(), unit.clone());
+                async move { monitor.watch_journal(unit).await }
+            }));
+        }
+        #[cfg(not(target_os = "linux"))]
+        if !config.inputs.journald.is_empty() {
+            tracing::warn!("journald inputs are only supported on Linux; ignoring them");
+        }
+
         // Watch streams
         for stream_config in config.inputs.streams.clone() {
-            let stream_monitor = Arc::new(
-                StreamMonitor::new(config.rules.clone(), alert_manager.clone())
-                    .context("Failed to create stream monitor")?,
-            );
-            tasks.push(tokio::spawn(async move {
-                if let Err(e) = stream_monitor.watch_stream(stream_config.clone()).await {
-                    tracing::error!("Error watching stream {}: {}", stream_config.get_name(), e);
-                }
+            let mut stream_monitor = StreamMonitor::new(config.rules.clone(), alert_manager.clone())
+                .context("Failed to create stream monitor")?;
+            if let Some(silence) = &silence {
+                stream_monitor.set_silence_watchdog(silence.clone());
+            }
+            watch_silence(config::SourceType::Stream(stream_config.get_name()));
+            let stream_monitor = Arc::new(stream_monitor);
+            reloader.add_stream_monitor(stream_monitor.clone());
+            let name = format!("stream {}", stream_config.get_name());
+            tasks.push(shutdown::spawn(name, &shutdown_token, &alert_manager, move || {
+                let (stream_monitor, stream_config) = (stream_monitor.clone(), stream_config.clone());
+                async move { stream_monitor.watch_stream(stream_config).await }
             }));
         }
-    } else if !config.inputs.files.is_empty() || !config.inputs.containers.is_empty() || !config.inputs.streams.is_empty() {
+    } else if !config.inputs.files.is_empty()
+        || !config.inputs.containers.is_empty()
+        || config.inputs.container_selector.is_some()
+        || !config.inputs.streams.is_empty()
+        || !config.inputs.windows_eventlog.is_empty()
+        || !config.inputs.journald.is_empty()
+        || !config.inputs.kubernetes.is_empty()
+    {
         tracing::warn!("Log sources configured but no rules defined!");
         tracing::info!("Tip: Add a --config file with rules, or the logs will be monitored but no alerts will be triggered.");
     }
 
+    if let Some(saved) = saved_state.take() {
+        state::restore(saved, &alert_manager, None).await;
+    }
+    let final_monitor = persisted_monitor.clone();
+    if let Some(dir) = config.state_dir.clone() {
+        let manager = alert_manager.clone();
+        tasks.push(shutdown::spawn("state persistence", &shutdown_token, &alert_manager, move || {
+            let (dir, manager, persisted_monitor) = (dir.clone(), manager.clone(), persisted_monitor.clone());
+            async move {
+                state::persist_periodically(dir, manager, persisted_monitor).await;
+                Ok(())
+            }
+        }));
+    }
+
     // Start resource monitoring
     if !no_resources {
         if let Some(resource_config) = config.resources {
-            let resource_monitor = ResourceMonitor::new(resource_config, alert_manager.clone());
-            tasks.push(tokio::spawn(async move {
-                resource_monitor.start().await;
+            let resource_monitor = Arc::new(ResourceMonitor::new(resource_config, alert_manager.clone()));
+            tasks.push(shutdown::spawn("resources", &shutdown_token, &alert_manager, move || {
+                let resource_monitor = resource_monitor.clone();
+                async move {
+                    resource_monitor.start().await;
+                    Ok(())
+                }
             }));
         }
     }
 
     // Start health check monitoring
     if !config.system_checks.is_empty() {
-        // Convert config checks to health checks
-        let health_checks: Vec<HealthCheck> = config
+        let health_checks = config
             .system_checks
             .iter()
-            .map(|sc| HealthCheck {
-                name: sc.name.clone(),
-                check_type: match sc.check_type {
-                    config::SystemCheckType::Http => HealthCheckType::Http,
-                },
-                url: sc.url.clone(),
-                interval: sc.interval,
-                timeout_secs: sc.timeout,
-                missed_threshold: sc.missed_threshold,
-                alert: sc.alert.clone(),
-                threshold: sc.threshold.clone(),
+            .filter(|check| {
+                if !check.enabled {
+                    tracing::info!("System check '{}' is disabled; skipping it", check.name);
+                }
+                check.enabled
             })
-            .collect();
+            .map(HealthCheck::from_config)
+            .collect::<anyhow::Result<Vec<HealthCheck>>>()?;
+        let names: Vec<&str> = health_checks.iter().map(|check| check.name.as_str()).collect();
+        tracing::info!("Health checks enabled ({}): {}", health_checks.len(), names.join(", "));
 
-        let health_monitor = HealthMonitor::new(
+        let health_monitor = Arc::new(HealthMonitor::new(
             health_checks,
             alert_manager.clone(),
             identity.clone(),
-        );
+        ));
         
-        tasks.push(tokio::spawn(async move {
-            health_monitor.start().await;
+        tasks.push(shutdown::spawn("health checks", &shutdown_token, &alert_manager, move || {
+            let health_monitor = health_monitor.clone();
+            async move {
+                health_monitor.start().await;
+                Ok(())
+            }
         }));
     }
 
-    // Start heartbeat monitoring
-    if let Some(heartbeat_config) = config.heartbeat {
-        tracing::info!(
-            "Heartbeat monitoring enabled (interval: {}s)", 
-            heartbeat_config.interval
-        );
-        
-        let heartbeat_monitor = heartbeat_monitor::HeartbeatMonitor::new(
-            heartbeat_config.url,
-            heartbeat_config.interval,
-            identity.clone(),
-        );
-        
-        tasks.push(tokio::spawn(async move {
-            heartbeat_monitor.start().await;
+    // Start heartbeat monitoring, one task per endpoint
+    for heartbeat_monitor in heartbeat_monitor::HeartbeatMonitor::from_configs(&config.heartbeat, &identity, alert_manager.http_client()) {
+        let heartbeat_monitor = Arc::new(heartbeat_monitor);
+        tasks.push(shutdown::spawn("heartbeat", &shutdown_token, &alert_manager, move || {
+            let heartbeat_monitor = heartbeat_monitor.clone();
+            async move {
+                heartbeat_monitor.start().await;
+                Ok(())
+            }
         }));
     }
+    if !config.heartbeat.is_empty() {
+        tracing::info!("Heartbeat monitoring enabled ({} endpoint(s))", config.heartbeat.len());
+    }
 
     // Wait for all tasks
     if tasks.is_empty() {
@@ -314,18 +586,50 @@ async fn handle_watch(
         anyhow::bail!("Nothing to monitor");
     }
 
-    tracing::info!("▶ TinyWatcher is running. Press Ctrl+C to stop.");
+    if let Some(metrics_config) = &config.metrics {
+        let listener = Arc::new(
+            tokio::net::TcpListener::bind(metrics_config.bind)
+                .await
+                .with_context(|| format!("Failed to listen for metrics on {}", metrics_config.bind))?,
+        );
+        metrics::enable();
+        tasks.push(shutdown::spawn("metrics", &shutdown_token, &alert_manager, move || {
+            metrics::serve(listener.clone())
+        }));
+        tracing::info!("Serving metrics on http://{}/metrics", metrics_config.bind);
+    }
 
-    // Wait for Ctrl+C signal
-    tokio::signal::ctrl_c()
-        .await
-        .context("Failed to listen for Ctrl+C")?;
+    tracing::info!("{}TinyWatcher is running. Press Ctrl+C to stop.", emoji::icon("▶ "));
+
+    // Wait for Ctrl+C or SIGTERM, reloading rules and files on SIGHUP meanwhile
+    let mut reload_signal = reload::ReloadSignal::new()?;
+    let mut terminate_signal = shutdown::TerminateSignal::new()?;
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        tokio::select! {
+            result = &mut ctrl_c => {
+                result.context("Failed to listen for Ctrl+C")?;
+                break;
+            }
+            _ = terminate_signal.recv() => {
+                tracing::info!("Received SIGTERM");
+                break;
+            }
+            _ = reload_signal.recv() => tasks.extend(reloader.reload()),
+        }
+    }
 
-    tracing::info!("⏹ Received shutdown signal, stopping all monitors...");
+    tracing::info!("{}Shutting down gracefully...", emoji::icon("⏹ "));
+    shutdown_token.cancel();
+    shutdown::wait_for(tasks).await;
 
-    // Abort all tasks
-    for task in tasks {
-        task.abort();
+    // Save state once more so nothing since the last flush is lost
+    if let Some(dir) = &config.state_dir {
+        let state = state::snapshot(&alert_manager, final_monitor.as_deref()).await;
+        if let Err(e) = state::save(dir, &state) {
+            tracing::error!("Failed to save state: {:#}", e);
+        }
     }
 
     tracing::info!(" All monitors stopped.");
@@ -344,6 +648,7 @@ async fn handle_test(config_path: std::path::PathBuf) -> Result<()> {
 
 fn validate_config(config: &Config) -> Result<()> {
     let mut stdout = StandardStream::stdout(ColorChoice::Always);
+    config.time_settings()?;
 
     // Show identity
     stdout.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)).set_bold(true))?;
@@ -397,6 +702,28 @@ fn validate_config(config: &Config) -> Result<()> {
         writeln!(&mut stdout, "    • {}", container)?;
         stdout.reset()?;
     }
+
+    if config.inputs.runtime.is_some() {
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+        write!(&mut stdout, "  Container runtime: ")?;
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+        writeln!(&mut stdout, "{}", config.inputs.runtime().binary())?;
+        stdout.reset()?;
+    }
+
+    if let Some(selector) = &config.inputs.container_selector {
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+        write!(&mut stdout, "  Container selector: ")?;
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+        if selector.labels.is_empty() {
+            writeln!(&mut stdout, "all running containers")?;
+        } else {
+            writeln!(&mut stdout, "labels [{}]", selector.labels.join(", "))?;
+        }
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+        writeln!(&mut stdout, "    Rescan: every {:?}", selector.rescan())?;
+        stdout.reset()?;
+    }
     
     stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
     write!(&mut stdout, "  Streams: ")?;
@@ -415,6 +742,79 @@ fn validate_config(config: &Config) -> Result<()> {
         stdout.reset()?;
     }
 
+    if !config.inputs.windows_eventlog.is_empty() {
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+        write!(&mut stdout, "  Windows event logs: ")?;
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+        writeln!(&mut stdout, "{}", config.inputs.windows_eventlog.len())?;
+        stdout.reset()?;
+
+        for eventlog in &config.inputs.windows_eventlog {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            writeln!(&mut stdout, "    • {}", eventlog.log)?;
+            if !eventlog.sources.is_empty() {
+                writeln!(&mut stdout, "      Sources: [{}]", eventlog.sources.join(", "))?;
+            }
+            if let Some(level) = eventlog.level {
+                writeln!(&mut stdout, "      Minimum level: {}", level)?;
+            }
+            stdout.reset()?;
+        }
+
+        if !cfg!(windows) {
+            write!(&mut stdout, "    ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))?;
+            write!(&mut stdout, "[WARNING]")?;
+            stdout.reset()?;
+            writeln!(&mut stdout, " Windows event logs are only watched on Windows")?;
+        }
+    }
+
+    if !config.inputs.kubernetes.is_empty() {
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+        write!(&mut stdout, "  Kubernetes pods: ")?;
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+        writeln!(&mut stdout, "{}", config.inputs.kubernetes.len())?;
+        stdout.reset()?;
+
+        for kubernetes in &config.inputs.kubernetes {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            match &kubernetes.selector {
+                Some(selector) => writeln!(&mut stdout, "    • selector {}", selector)?,
+                None => writeln!(&mut stdout, "    • {}", kubernetes.name())?,
+            }
+            if let Some(namespace) = &kubernetes.namespace {
+                writeln!(&mut stdout, "      Namespace: {}", namespace)?;
+            }
+            if let Some(container) = &kubernetes.container {
+                writeln!(&mut stdout, "      Container: {}", container)?;
+            }
+            stdout.reset()?;
+        }
+    }
+
+    if !config.inputs.journald.is_empty() {
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+        write!(&mut stdout, "  Journal units: ")?;
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+        writeln!(&mut stdout, "{}", config.inputs.journald.len())?;
+        stdout.reset()?;
+
+        for unit in &config.inputs.journald {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            writeln!(&mut stdout, "    • {}", unit)?;
+            stdout.reset()?;
+        }
+
+        if !cfg!(target_os = "linux") {
+            write!(&mut stdout, "    ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))?;
+            write!(&mut stdout, "[WARNING]")?;
+            stdout.reset()?;
+            writeln!(&mut stdout, " Journal units are only watched on Linux")?;
+        }
+    }
+
     // Validate alerts
     stdout.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)).set_bold(true))?;
     writeln!(&mut stdout, "\nALERTS")?;
@@ -426,27 +826,56 @@ fn validate_config(config: &Config) -> Result<()> {
     writeln!(&mut stdout, "{}", config.alerts.len())?;
     stdout.reset()?;
     
+    // Bad addresses are reported where they appear and fail the test at the end, so all of them show up
+    let mut address_errors = 0;
     for (name, alert) in &config.alerts {
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))?;
         write!(&mut stdout, "  {}", name)?;
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
-        write!(&mut stdout, " ({:?})", alert.alert_type)?;
+        write!(&mut stdout, " ({:?})", alert.alert_type())?;
         stdout.reset()?;
         
         match &alert.options {
-            crate::config::AlertOptions::Slack { url } => {
+            crate::config::AlertOptions::Slack { url, .. } => {
                 stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
                 writeln!(&mut stdout, " → {}...", &url.chars().take(30).collect::<String>())?;
                 stdout.reset()?;
             }
-            crate::config::AlertOptions::Webhook { url } => {
+            crate::config::AlertOptions::Webhook { url, compress_above, template, headers, method, secret, signature_header, .. } => {
                 stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
                 writeln!(&mut stdout, " → {}...", &url.chars().take(30).collect::<String>())?;
+                if let Some(bytes) = compress_above {
+                    writeln!(&mut stdout, "      Gzip bodies over: {} bytes", bytes)?;
+                }
+                if let Some(method) = method {
+                    writeln!(&mut stdout, "      Method: {}", method.to_uppercase())?;
+                }
+                if let Some(headers) = headers {
+                    let mut names: Vec<&str> = headers.keys().map(String::as_str).collect();
+                    names.sort();
+                    writeln!(&mut stdout, "      Headers: {}", names.join(", "))?;
+                }
+                if template.is_some() {
+                    writeln!(&mut stdout, "      Body: custom template")?;
+                }
+                if secret.is_some() {
+                    writeln!(
+                        &mut stdout,
+                        "      Signed: HMAC-SHA256 in {}",
+                        signature_header.as_deref().unwrap_or(alerts::DEFAULT_SIGNATURE_HEADER)
+                    )?;
+                }
                 stdout.reset()?;
             }
-            crate::config::AlertOptions::Discord { url } => {
+            crate::config::AlertOptions::Discord { url, thread_id, mentions } => {
                 stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
                 writeln!(&mut stdout, " → {}...", &url.chars().take(30).collect::<String>())?;
+                if let Some(thread_id) = thread_id {
+                    writeln!(&mut stdout, "      Thread: {}", thread_id)?;
+                }
+                if !mentions.is_empty() {
+                    writeln!(&mut stdout, "      Mentions: {}", mentions.join(", "))?;
+                }
                 stdout.reset()?;
             }
             crate::config::AlertOptions::Telegram { bot_token, chat_id } => {
@@ -461,6 +890,15 @@ fn validate_config(config: &Config) -> Result<()> {
                 writeln!(&mut stdout, " → {}...", &routing_key.chars().take(15).collect::<String>())?;
                 stdout.reset()?;
             }
+            crate::config::AlertOptions::Opsgenie { api_key, priority } => {
+                writeln!(&mut stdout)?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                writeln!(&mut stdout, "      API key: {}...", &api_key.chars().take(6).collect::<String>())?;
+                if let Some(priority) = priority {
+                    writeln!(&mut stdout, "      Priority: {:?}", priority)?;
+                }
+                stdout.reset()?;
+            }
             crate::config::AlertOptions::Ntfy { topic, server } => {
                 writeln!(&mut stdout)?;
                 stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
@@ -472,6 +910,32 @@ fn validate_config(config: &Config) -> Result<()> {
                 }
                 stdout.reset()?;
             }
+            crate::config::AlertOptions::Pushover { token, user, priority } => {
+                writeln!(&mut stdout)?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                writeln!(&mut stdout, "      User: {}...", &user.chars().take(6).collect::<String>())?;
+                writeln!(&mut stdout, "      Token: {}...", &token.chars().take(6).collect::<String>())?;
+                if let Some(priority) = priority {
+                    writeln!(&mut stdout, "      Priority: {}", priority)?;
+                }
+                stdout.reset()?;
+            }
+            crate::config::AlertOptions::LocalSyslog { facility } => {
+                writeln!(&mut stdout)?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                writeln!(&mut stdout, "      Facility: {}", facility.as_deref().unwrap_or("user"))?;
+                stdout.reset()?;
+            }
+            crate::config::AlertOptions::Gotify { url, token, priority } => {
+                writeln!(&mut stdout)?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                writeln!(&mut stdout, "      Server: {}", url)?;
+                writeln!(&mut stdout, "      Token: {}...", &token.chars().take(6).collect::<String>())?;
+                if let Some(priority) = priority {
+                    writeln!(&mut stdout, "      Priority: {}", priority)?;
+                }
+                stdout.reset()?;
+            }
             crate::config::AlertOptions::Email { from, to, smtp_server } => {
                 writeln!(&mut stdout)?;
                 stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
@@ -490,9 +954,45 @@ fn validate_config(config: &Config) -> Result<()> {
                 writeln!(&mut stdout, "      API Key: {}...", &api_key.chars().take(15).collect::<String>())?;
                 stdout.reset()?;
             }
+            crate::config::AlertOptions::Forward { url } => {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                writeln!(&mut stdout, " → {}", url)?;
+                stdout.reset()?;
+            }
+            crate::config::AlertOptions::File { path, format } => {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                writeln!(&mut stdout, " → {} ({:?})", path.display(), format)?;
+                stdout.reset()?;
+            }
+            crate::config::AlertOptions::Exec { command, args, .. } => {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                writeln!(&mut stdout, " → {} {}", command, args.join(" "))?;
+                stdout.reset()?;
+            }
             crate::config::AlertOptions::Stdout {} => {
                 writeln!(&mut stdout)?;
             }
+            crate::config::AlertOptions::Desktop {} => {
+                writeln!(&mut stdout)?;
+                if !cfg!(feature = "desktop") {
+                    stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)))?;
+                    writeln!(&mut stdout, "      Not available: built without the 'desktop' feature")?;
+                    stdout.reset()?;
+                }
+            }
+        }
+
+        if let Err(e) = alert.validate(name) {
+            write!(&mut stdout, "    ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true))?;
+            write!(&mut stdout, "[ERROR]")?;
+            stdout.reset()?;
+            writeln!(&mut stdout, " {}", e)?;
+            return Err(e);
+        }
+        for error in alert.address_errors(name) {
+            write_address_error(&mut stdout, "    ", &error)?;
+            address_errors += 1;
         }
     }
 
@@ -509,20 +1009,63 @@ fn validate_config(config: &Config) -> Result<()> {
     
     for rule in &config.rules {
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::Magenta)).set_bold(true))?;
-        writeln!(&mut stdout, "  {}", rule.name)?;
+        write!(&mut stdout, "  {}", rule.name)?;
+        write_disabled(&mut stdout, rule.enabled)?;
         stdout.reset()?;
         
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
         if let Some(text) = &rule.text {
             write!(&mut stdout, "    Text: ")?;
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
-            writeln!(&mut stdout, "{}", text)?;
+            if rule.case_insensitive {
+                writeln!(&mut stdout, "{} (case-insensitive)", text)?;
+            } else {
+                writeln!(&mut stdout, "{}", text)?;
+            }
         } else if let Some(pattern) = &rule.pattern {
             write!(&mut stdout, "    Pattern: ")?;
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
             writeln!(&mut stdout, "{}", pattern)?;
         }
         stdout.reset()?;
+
+        if let Some(template) = &rule.message {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            write!(&mut stdout, "    Message: ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(&mut stdout, "{}", template)?;
+            stdout.reset()?;
+        }
+
+        if let Some(path) = &rule.json_field {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            write!(&mut stdout, "    JSON field: ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(&mut stdout, "{}", path)?;
+            stdout.reset()?;
+        }
+
+        let excludes: Vec<&str> = rule.exclude.iter().chain(&rule.exclude_text).map(String::as_str).collect();
+        if !excludes.is_empty() {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            write!(&mut stdout, "    Exclude: ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(&mut stdout, "{}", excludes.join(", "))?;
+            stdout.reset()?;
+        }
+
+        if let Some(multiline) = &rule.multiline {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            write!(&mut stdout, "    Multiline: ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(
+                &mut stdout,
+                "records start at {} (timeout {})",
+                multiline.start,
+                config::format_duration(multiline.timeout())
+            )?;
+            stdout.reset()?;
+        }
         
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
         if rule.alert.len() == 1 {
@@ -539,9 +1082,61 @@ fn validate_config(config: &Config) -> Result<()> {
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
         write!(&mut stdout, "    Cooldown: ")?;
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
-        writeln!(&mut stdout, "{}s", rule.cooldown)?;
+        if rule.once {
+            writeln!(&mut stdout, "first match only")?;
+        } else if rule.burst > 0 {
+            writeln!(&mut stdout, "{}s (first {} alerts sent immediately)", rule.cooldown, rule.burst)?;
+        } else {
+            writeln!(&mut stdout, "{}s", rule.cooldown)?;
+        }
+        stdout.reset()?;
+
+        if !rule.fields.is_empty() {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            write!(&mut stdout, "    Fields: ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(&mut stdout, "[{}]", rule.fields.join(", "))?;
+            stdout.reset()?;
+        }
+
+        if let Some(ref condition) = rule.condition {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            write!(&mut stdout, "    Condition: ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(&mut stdout, "{}", condition)?;
+            stdout.reset()?;
+        }
+
+        if let Some(window) = rule.expect_within {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            write!(&mut stdout, "    Expect within: ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(&mut stdout, "{}", config::format_duration(window))?;
+            stdout.reset()?;
+        }
+
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+        write!(&mut stdout, "    Severity: ")?;
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+        writeln!(&mut stdout, "{}", rule.severity)?;
         stdout.reset()?;
 
+        if let Some(expires_at) = rule.expires_at {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            write!(&mut stdout, "    Expires: ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(&mut stdout, "{}", expires_at.to_rfc3339())?;
+            stdout.reset()?;
+
+            if rule.is_expired(chrono::Utc::now()) {
+                write!(&mut stdout, "    ")?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_bold(true))?;
+                write!(&mut stdout, "[WARNING]")?;
+                stdout.reset()?;
+                writeln!(&mut stdout, " Rule has expired and will be skipped; consider removing it")?;
+            }
+        }
+
         // Show source filtering
         if let Some(sources) = &rule.sources {
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
@@ -558,6 +1153,12 @@ fn validate_config(config: &Config) -> Result<()> {
             if !sources.streams.is_empty() {
                 writeln!(&mut stdout, "      Streams: [{}]", sources.streams.join(", "))?;
             }
+            if !sources.journals.is_empty() {
+                writeln!(&mut stdout, "      Journals: [{}]", sources.journals.join(", "))?;
+            }
+            if !sources.pods.is_empty() {
+                writeln!(&mut stdout, "      Pods: [{}]", sources.pods.join(", "))?;
+            }
             stdout.reset()?;
         } else {
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
@@ -565,8 +1166,20 @@ fn validate_config(config: &Config) -> Result<()> {
             stdout.reset()?;
         }
 
-        // Check if all alerts exist
+        // Check if all alerts exist; capture-routed alerts must fit at least one
         for alert_name in &rule.alert {
+            if !crate::config::route_placeholders(alert_name).is_empty() {
+                if !config.alerts.keys().any(|name| crate::config::route_matches(alert_name, name)) {
+                    write!(&mut stdout, "    ")?;
+                    stdout.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true))?;
+                    write!(&mut stdout, "[ERROR]")?;
+                    stdout.reset()?;
+                    writeln!(&mut stdout, " Alert route '{}' matches no alert in configuration", alert_name)?;
+                    anyhow::bail!("Rule '{}' alert route '{}' matches no configured alert", rule.name, alert_name);
+                }
+                continue;
+            }
+
             if !config.alerts.contains_key(alert_name) {
                 write!(&mut stdout, "    ")?;
                 stdout.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true))?;
@@ -627,6 +1240,12 @@ fn validate_config(config: &Config) -> Result<()> {
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
         writeln!(&mut stdout, "{}s", resources.interval)?;
         stdout.reset()?;
+
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+        write!(&mut stdout, "  Cooldown: ")?;
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+        writeln!(&mut stdout, "{}s", resources.cooldown_secs())?;
+        stdout.reset()?;
         
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
         writeln!(&mut stdout, "  Thresholds:")?;
@@ -636,23 +1255,87 @@ fn validate_config(config: &Config) -> Result<()> {
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
             write!(&mut stdout, "    CPU: ")?;
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
-            writeln!(&mut stdout, "{}%", cpu)?;
+            writeln!(&mut stdout, "{}%{}", cpu.above, cpu.sustain_label())?;
             stdout.reset()?;
         }
         if let Some(memory) = resources.thresholds.memory_percent {
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
             write!(&mut stdout, "    Memory: ")?;
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
-            writeln!(&mut stdout, "{}%", memory)?;
+            writeln!(&mut stdout, "{}%{}", memory.above, memory.sustain_label())?;
             stdout.reset()?;
         }
         if let Some(disk) = resources.thresholds.disk_percent {
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
             write!(&mut stdout, "    Disk: ")?;
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
-            writeln!(&mut stdout, "{}%", disk)?;
+            writeln!(&mut stdout, "{}%{}", disk.above, disk.sustain_label())?;
             stdout.reset()?;
         }
+        for (label, minimum) in [
+            ("Memory Available", resources.thresholds.memory_available_min),
+            ("Disk Available", resources.thresholds.disk_available_min),
+        ] {
+            if let Some(minimum) = minimum {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                write!(&mut stdout, "    {}: ", label)?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                writeln!(&mut stdout, "at least {}", config::format_size(minimum))?;
+                stdout.reset()?;
+            }
+        }
+        let mut disk_mounts: Vec<_> = resources.thresholds.disk_mounts.iter().collect();
+        disk_mounts.sort_by(|a, b| a.0.cmp(b.0));
+        for (mount_point, limit) in disk_mounts {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            write!(&mut stdout, "    Disk {}: ", mount_point)?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(&mut stdout, "{}%{}", limit.above, limit.sustain_label())?;
+            stdout.reset()?;
+        }
+        for (label, patterns) in [
+            ("Disk Include", &resources.thresholds.disk_include),
+            ("Disk Exclude", &resources.thresholds.disk_exclude),
+        ] {
+            if !patterns.is_empty() {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                write!(&mut stdout, "    {}: ", label)?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                writeln!(&mut stdout, "{}", patterns.join(", "))?;
+                stdout.reset()?;
+            }
+        }
+        if let Some(inodes) = resources.thresholds.inode_percent {
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+            write!(&mut stdout, "    Inodes: ")?;
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+            writeln!(&mut stdout, "{}%{}", inodes.above, inodes.sustain_label())?;
+            stdout.reset()?;
+        }
+        for (label, limit) in [
+            ("Load (1m)", resources.thresholds.load1),
+            ("Load (5m)", resources.thresholds.load5),
+            ("Load (15m)", resources.thresholds.load15),
+        ] {
+            if let Some(limit) = limit {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                write!(&mut stdout, "    {}: ", label)?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                writeln!(&mut stdout, "{}{}", limit.above, limit.sustain_label())?;
+                stdout.reset()?;
+            }
+        }
+        if let Some(network) = &resources.thresholds.network {
+            for (label, limit) in [("Network RX", network.rx_bytes_per_sec), ("Network TX", network.tx_bytes_per_sec)] {
+                if let Some(limit) = limit {
+                    stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                    write!(&mut stdout, "    {}: ", label)?;
+                    stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                    writeln!(&mut stdout, "{} bytes/s{}", limit.above, limit.sustain_label())?;
+                    stdout.reset()?;
+                }
+            }
+        }
         
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
         write!(&mut stdout, "    Alerts: ")?;
@@ -691,7 +1374,8 @@ fn validate_config(config: &Config) -> Result<()> {
     if !config.system_checks.is_empty() {
         for check in &config.system_checks {
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::Magenta)).set_bold(true))?;
-            writeln!(&mut stdout, "  {}", check.name)?;
+            write!(&mut stdout, "  {}", check.name)?;
+            write_disabled(&mut stdout, check.enabled)?;
             stdout.reset()?;
             
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
@@ -699,10 +1383,17 @@ fn validate_config(config: &Config) -> Result<()> {
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
             writeln!(&mut stdout, "{:?}", check.check_type)?;
             
-            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
-            write!(&mut stdout, "    URL: ")?;
-            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
-            writeln!(&mut stdout, "{}", check.url)?;
+            if let Some(command) = &check.command {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                write!(&mut stdout, "    Command: ")?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                writeln!(&mut stdout, "{} {}", command, check.args.join(" "))?;
+            } else {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                write!(&mut stdout, "    URL: ")?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                writeln!(&mut stdout, "{}", check.url)?;
+            }
             
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
             write!(&mut stdout, "    Interval: ")?;
@@ -713,6 +1404,35 @@ fn validate_config(config: &Config) -> Result<()> {
             write!(&mut stdout, "    Timeout: ")?;
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
             writeln!(&mut stdout, "{}s", check.timeout)?;
+
+            if check.check_type == config::SystemCheckType::Clock {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                write!(&mut stdout, "    Max Skew: ")?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                writeln!(&mut stdout, "{:?}", check.max_skew())?;
+            }
+
+            if let Some(status) = check.expected_status {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                write!(&mut stdout, "    Expected Status: ")?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                writeln!(&mut stdout, "{}", status)?;
+            }
+
+            if let Some(body) = &check.expect_body {
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                write!(&mut stdout, "    Expect Body: ")?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                writeln!(&mut stdout, "{}", body)?;
+            }
+
+            if check.check_type == config::SystemCheckType::Ping {
+                let ping = check.ping_settings();
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
+                write!(&mut stdout, "    Ping: ")?;
+                stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+                writeln!(&mut stdout, "{} packets, up to {}% loss ({:?})", ping.count, ping.max_loss, ping.method)?;
+            }
             
             stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
             write!(&mut stdout, "    Missed Threshold: ")?;
@@ -725,6 +1445,11 @@ fn validate_config(config: &Config) -> Result<()> {
             writeln!(&mut stdout, "{}", check.alert.join(", "))?;
             stdout.reset()?;
             
+            if let Some(error) = check.url_error() {
+                write_address_error(&mut stdout, "    ", &error)?;
+                address_errors += 1;
+            }
+            
             // Check if alerts exist
             for alert_name in &check.alert {
                 if !config.alerts.contains_key(alert_name) {
@@ -748,7 +1473,7 @@ fn validate_config(config: &Config) -> Result<()> {
     writeln!(&mut stdout, "\nHEARTBEAT MONITORING")?;
     stdout.reset()?;
     
-    if let Some(heartbeat) = &config.heartbeat {
+    for heartbeat in &config.heartbeat {
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
         write!(&mut stdout, "  URL: ")?;
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
@@ -772,13 +1497,10 @@ fn validate_config(config: &Config) -> Result<()> {
         }
         
         // Validate URL is a valid HTTP/HTTPS URL
-        if !heartbeat.url.starts_with("http://") && !heartbeat.url.starts_with("https://") {
-            write!(&mut stdout, "  ")?;
-            stdout.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true))?;
-            write!(&mut stdout, "[ERROR]")?;
-            stdout.reset()?;
-            writeln!(&mut stdout, " Heartbeat URL must start with http:// or https://")?;
-            anyhow::bail!("Invalid heartbeat URL format: must be HTTP or HTTPS");
+        if let Some(error) = heartbeat.url_error() {
+            write_address_error(&mut stdout, "  ", &error)?;
+            address_errors += 1;
+            continue;
         }
         
         // Validate interval is reasonable
@@ -804,12 +1526,17 @@ fn validate_config(config: &Config) -> Result<()> {
         write!(&mut stdout, "[OK]")?;
         stdout.reset()?;
         writeln!(&mut stdout, " Heartbeat configuration is valid")?;
-    } else {
+    }
+    if config.heartbeat.is_empty() {
         stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)).set_dimmed(true))?;
         writeln!(&mut stdout, "  Not configured")?;
         stdout.reset()?;
     }
 
+    if address_errors > 0 {
+        anyhow::bail!("Configuration has {} invalid URL(s) or email address(es)", address_errors);
+    }
+
     // Final success message
     writeln!(&mut stdout)?;
     stdout.set_color(ColorSpec::new().set_fg(Some(Color::Green)).set_bold(true))?;
@@ -819,16 +1546,94 @@ fn validate_config(config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// End a rule or check heading, marking it when `enabled: false` keeps it from running
+fn write_disabled(stdout: &mut impl WriteColor, enabled: bool) -> Result<()> {
+    if !enabled {
+        stdout.set_color(ColorSpec::new().set_fg(Some(Color::Yellow)).set_dimmed(true))?;
+        write!(stdout, " (disabled)")?;
+    }
+    writeln!(stdout)?;
+    Ok(())
+}
+
+fn write_address_error(stdout: &mut StandardStream, indent: &str, error: &str) -> Result<()> {
+    write!(stdout, "{}", indent)?;
+    stdout.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true))?;
+    write!(stdout, "[ERROR]")?;
+    stdout.reset()?;
+    writeln!(stdout, " {}", error)?;
+    Ok(())
+}
+
+/// One JSON object per log line, with `timestamp`, `level`, `target`, `message` and any event fields at the top level
+fn json_log_layer<S, W>(writer: W) -> impl tracing_subscriber::Layer<S>
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    W: for<'w> fmt::MakeWriter<'w> + Send + Sync + 'static,
+{
+    fmt::layer().json().flatten_event(true).with_writer(writer)
+}
+
 enum CheckRuleMatcher {
     Text(String),
     Regex(Regex),
 }
 
+async fn handle_bench(
+    config_path: std::path::PathBuf,
+    lines: usize,
+    input: Option<std::path::PathBuf>,
+) -> Result<()> {
+    let config = Config::from_file(config_path.to_str().context("Invalid config path")?)?;
+    if config.rules.is_empty() {
+        anyhow::bail!("No rules defined in configuration!");
+    }
+    for rule in &config.rules {
+        rule.validate()?;
+    }
+
+    let input_lines = match &input {
+        Some(path) => bench::read_lines(path, lines)?,
+        None => bench::generate_lines(lines),
+    };
+
+    println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    match &input {
+        Some(path) => println!(" Benchmarking {} rules over {} lines from {}\n", config.rules.len(), input_lines.len(), path.display()),
+        None => println!(" Benchmarking {} rules over {} generated lines\n", config.rules.len(), input_lines.len()),
+    }
+
+    let report = bench::run(&config, &input_lines).await?;
+
+    let mut stdout = StandardStream::stdout(ColorChoice::Always);
+    stdout.set_color(ColorSpec::new().set_bold(true))?;
+    writeln!(
+        &mut stdout,
+        " {:.0} lines/sec ({} lines in {:.3}s)",
+        report.lines_per_sec(),
+        report.lines,
+        report.elapsed.as_secs_f64()
+    )?;
+    stdout.reset()?;
+
+    writeln!(&mut stdout, "\n Matches per rule:")?;
+    for (rule, count) in &report.matches {
+        write!(&mut stdout, "   {}: ", rule)?;
+        stdout.set_color(ColorSpec::new().set_fg(Some(if *count > 0 { Color::Yellow } else { Color::White })).set_dimmed(*count == 0))?;
+        writeln!(&mut stdout, "{}", count)?;
+        stdout.reset()?;
+    }
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+
+    Ok(())
+}
+
 async fn handle_check(
     config_path: std::path::PathBuf,
     lines: usize,
     cli_files: Vec<std::path::PathBuf>,
     cli_containers: Vec<String>,
+    command_timeout: std::time::Duration,
 ) -> Result<()> {
     use tokio::process::Command;
 
@@ -844,6 +1649,7 @@ async fn handle_check(
 
     // First, validate the configuration
     validate_config(&config)?;
+    timestamp::set(config.time_settings()?);
 
     // Only need rules if we have log inputs to check
     let has_log_inputs = !config.inputs.files.is_empty() || !config.inputs.containers.is_empty();
@@ -872,6 +1678,7 @@ async fn handle_check(
     let compiled_rules: Vec<(String, CheckRuleMatcher)> = config
         .rules
         .iter()
+        .filter(|rule| rule.enabled)
         .map(|rule| {
             let matcher = match rule.match_type() {
                 MatchType::Text(text) => CheckRuleMatcher::Text(text),
@@ -894,13 +1701,16 @@ async fn handle_check(
             continue;
         }
 
-        let output = Command::new("tail")
-            .arg("-n")
-            .arg(lines.to_string())
-            .arg(file)
-            .output()
+        let mut command = Command::new("tail");
+        command.arg("-n").arg(lines.to_string()).arg(file);
+
+        let Some(output) = command_output_with_timeout(&mut command, command_timeout)
             .await
-            .context(format!("Failed to tail file: {}", file.display()))?;
+            .context(format!("Failed to tail file: {}", file.display()))?
+        else {
+            println!("    Timed out after {:?}, skipping...\n", command_timeout);
+            continue;
+        };
 
         let log_content = String::from_utf8_lossy(&output.stdout);
         let matches = check_logs_for_rules(&log_content, &compiled_rules);
@@ -912,16 +1722,16 @@ async fn handle_check(
     for container in &config.inputs.containers {
         println!(" Checking container: {}", container);
 
-        let output = Command::new("docker")
-            .arg("logs")
-            .arg("--tail")
-            .arg(lines.to_string())
-            .arg(container)
-            .output()
-            .await;
+        let runtime = config.inputs.runtime();
+        let mut command = Command::new(runtime.binary());
+        command.args(runtime.tail_args(lines, container));
 
-        match output {
-            Ok(output) => {
+        match command_output_with_timeout(&mut command, command_timeout).await {
+            Ok(None) => {
+                println!("    Timed out after {:?}, skipping...\n", command_timeout);
+                continue;
+            }
+            Ok(Some(output)) => {
                 // Check both stdout and stderr
                 let stdout_content = String::from_utf8_lossy(&output.stdout);
                 let stderr_content = String::from_utf8_lossy(&output.stderr);
@@ -931,7 +1741,7 @@ async fn handle_check(
                 total_matches += matches;
             }
             Err(e) => {
-                println!("    Failed to get logs: {}\n", e);
+                println!("    Failed to get logs: {}\n", runtime.command_error(e));
                 continue;
             }
         }
@@ -948,6 +1758,19 @@ async fn handle_check(
     Ok(())
 }
 
+/// Run an external command, giving up after `deadline`
+/// Returns `Ok(None)` on timeout; the child process is killed when dropped
+async fn command_output_with_timeout(
+    command: &mut tokio::process::Command,
+    deadline: std::time::Duration,
+) -> std::io::Result<Option<std::process::Output>> {
+    command.kill_on_drop(true);
+    match tokio::time::timeout(deadline, command.output()).await {
+        Ok(output) => output.map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
 fn check_logs_for_rules(log_content: &str, rules: &[(String, CheckRuleMatcher)]) -> usize {
     let mut match_count = 0;
 
@@ -987,9 +1810,59 @@ fn check_logs_for_rules(log_content: &str, rules: &[(String, CheckRuleMatcher)])
     match_count
 }
 
-fn handle_start(config_path: Option<std::path::PathBuf>) -> Result<()> {
+/// State directory from the config, which `mute` and `unmute` need to reach running watchers
+fn mute_dir(config_path: &std::path::Path) -> Result<std::path::PathBuf> {
+    let config = Config::from_file(config_path.to_str().context("Invalid config path")?)?;
+    config.state_dir.context(
+        "Muting needs state_dir set in the config, so running watchers can see the mute marker",
+    )
+}
+
+fn handle_mute(config_path: std::path::PathBuf, duration: std::time::Duration) -> Result<()> {
+    let dir = mute_dir(&config_path)?;
+    let until = chrono::Utc::now() + chrono::Duration::from_std(duration).context("Mute duration is too long")?;
+    state::save_mute(&dir, until)?;
+
+    println!(
+        "{}Alerts muted until {} ({})",
+        emoji::icon("🔇 "),
+        until.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        config::format_duration(duration)
+    );
+    Ok(())
+}
+
+fn handle_unmute(config_path: std::path::PathBuf) -> Result<()> {
+    let dir = mute_dir(&config_path)?;
+    if state::clear_mute(&dir)? {
+        println!("{}Alerts unmuted", emoji::icon("🔔 "));
+    } else {
+        println!("Alerts were not muted");
+    }
+    Ok(())
+}
+
+fn handle_start_dry_run(config_path: Option<std::path::PathBuf>, instance: Option<&str>) -> Result<()> {
+    let manager = daemon::get_service_manager(instance)?;
+    
+    // Use the same privilege decision as a first-time install
+    let needs_elevation = if daemon::is_elevated() {
+        true
+    } else if let Some(config) = &config_path {
+        let cfg = Config::from_file(config.to_str().context("Invalid config path")?)?;
+        !cfg.inputs.files.is_empty()
+            && daemon::any_file_needs_elevation(&cfg.inputs.files).unwrap_or(false)
+    } else {
+        false
+    };
+    
+    println!("{}", manager.preview_install(config_path, needs_elevation)?);
+    Ok(())
+}
+
+fn handle_start(config_path: Option<std::path::PathBuf>, instance: Option<&str>) -> Result<()> {
     let mut stdout = StandardStream::stdout(ColorChoice::Always);
-    let manager = daemon::get_service_manager();
+    let manager = daemon::get_service_manager(instance)?;
     
     // Check if we're running with elevated privileges
     let running_as_root = daemon::is_elevated();
@@ -999,8 +1872,9 @@ fn handle_start(config_path: Option<std::path::PathBuf>) -> Result<()> {
     let (user_service_installed, system_service_installed) = {
         use std::path::PathBuf;
         let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
-        let agent_path = PathBuf::from(&home).join("Library/LaunchAgents/com.tinywatcher.agent.plist");
-        let daemon_path = PathBuf::from("/Library/LaunchDaemons/com.tinywatcher.agent.plist");
+        let plist = format!("{}.plist", manager.service_name());
+        let agent_path = PathBuf::from(&home).join("Library/LaunchAgents").join(&plist);
+        let daemon_path = PathBuf::from("/Library/LaunchDaemons").join(&plist);
         (agent_path.exists(), daemon_path.exists())
     };
     
@@ -1008,8 +1882,9 @@ fn handle_start(config_path: Option<std::path::PathBuf>) -> Result<()> {
     let (user_service_installed, system_service_installed) = {
         use std::path::PathBuf;
         let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
-        let user_path = PathBuf::from(&home).join(".config/systemd/user/tinywatcher.service");
-        let system_path = PathBuf::from("/etc/systemd/system/tinywatcher.service");
+        let unit = format!("{}.service", manager.service_name());
+        let user_path = PathBuf::from(&home).join(".config/systemd/user").join(&unit);
+        let system_path = PathBuf::from("/etc/systemd/system").join(&unit);
         (user_path.exists(), system_path.exists())
     };
     
@@ -1125,7 +2000,7 @@ fn handle_start(config_path: Option<std::path::PathBuf>) -> Result<()> {
             // Service is installed but not running
             
             // If a new config is provided, reinstall with the new config
-            if config_path.is_some() {
+            if let Some(config) = &config_path {
                 stdout.set_color(ColorSpec::new().set_fg(Some(Color::Cyan)))?;
                 write!(&mut stdout, "ℹ")?;
                 stdout.reset()?;
@@ -1133,7 +2008,6 @@ fn handle_start(config_path: Option<std::path::PathBuf>) -> Result<()> {
                 writeln!(&mut stdout)?;
                 
                 // Load and validate the new config
-                let config = config_path.as_ref().unwrap();
                 if !config.exists() {
                     anyhow::bail!("Configuration file not found: {}", config.display());
                 }
@@ -1239,9 +2113,9 @@ fn handle_start(config_path: Option<std::path::PathBuf>) -> Result<()> {
     }
 }
 
-fn handle_stop() -> Result<()> {
+fn handle_stop(instance: Option<&str>) -> Result<()> {
     let mut stdout = StandardStream::stdout(ColorChoice::Always);
-    let manager = daemon::get_service_manager();
+    let manager = daemon::get_service_manager(instance)?;
     let status = manager.status()?;
     
     match status {
@@ -1266,9 +2140,9 @@ fn handle_stop() -> Result<()> {
     }
 }
 
-fn handle_restart() -> Result<()> {
+fn handle_restart(instance: Option<&str>) -> Result<()> {
     let mut stdout = StandardStream::stdout(ColorChoice::Always);
-    let manager = daemon::get_service_manager();
+    let manager = daemon::get_service_manager(instance)?;
     let status = manager.status()?;
     
     match status {
@@ -1298,11 +2172,17 @@ fn handle_restart() -> Result<()> {
     }
 }
 
-fn handle_status() -> Result<()> {
+fn handle_status(json: bool, instance: Option<&str>) -> Result<daemon::ServiceStatus> {
     let mut stdout = StandardStream::stdout(ColorChoice::Always);
-    let manager = daemon::get_service_manager();
+    let manager = daemon::get_service_manager(instance)?;
     let status = manager.status()?;
     
+    if json {
+        let report = daemon::StatusReport::new(&status, manager.service_type());
+        println!("{}", serde_json::to_string(&report)?);
+        return Ok(status);
+    }
+    
     let platform = if cfg!(target_os = "macos") {
         "launchd"
     } else if cfg!(target_os = "linux") {
@@ -1393,5 +2273,182 @@ fn handle_status() -> Result<()> {
         }
     }
     
-    Ok(())
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_command_timeout_skips_slow_command() {
+        let started = Instant::now();
+        let mut command = tokio::process::Command::new("sleep");
+        command.arg("10");
+
+        let output = command_output_with_timeout(&mut command, Duration::from_millis(200))
+            .await
+            .unwrap();
+
+        assert!(output.is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_command_timeout_proceeds_to_next_source() {
+        let mut slow = tokio::process::Command::new("sleep");
+        slow.arg("10");
+        let mut fast = tokio::process::Command::new("echo");
+        fast.arg("ERROR after slow source");
+
+        let timeout = Duration::from_millis(200);
+        assert!(command_output_with_timeout(&mut slow, timeout).await.unwrap().is_none());
+
+        let output = command_output_with_timeout(&mut fast, timeout).await.unwrap().unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "ERROR after slow source");
+    }
+
+    #[test]
+    fn test_validate_config_with_every_alert_type() {
+        use crate::config::AlertType;
+
+        let yaml = r#"
+alerts:
+  console:
+    type: stdout
+  slack:
+    type: slack
+    url: "https://hooks.slack.com/services/T000/B000/XXXX"
+  hook:
+    type: webhook
+    url: "https://example.com/alert"
+  discord:
+    type: discord
+    url: "https://discord.com/api/webhooks/123/token"
+  telegram:
+    type: telegram
+    bot_token: "123456:ABC"
+    chat_id: "-100123"
+  pagerduty:
+    type: pagerduty
+    routing_key: "R0UTINGKEY"
+  ntfy:
+    type: ntfy
+    topic: tinywatcher-alerts
+  sendgrid:
+    type: sendgrid
+    api_key: "SG.key"
+    from: "alerts@example.com"
+    to: ["oncall@example.com"]
+rules:
+  - name: errors
+    text: ERROR
+    alert: [console, slack, hook, discord, telegram, pagerduty, ntfy, sendgrid]
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(config.alerts["discord"].alert_type(), AlertType::Discord);
+        assert_eq!(config.alerts["telegram"].alert_type(), AlertType::Telegram);
+        assert_eq!(config.alerts["pagerduty"].alert_type(), AlertType::PagerDuty);
+        assert_eq!(config.alerts["ntfy"].alert_type(), AlertType::Ntfy);
+        assert_eq!(config.alerts["sendgrid"].alert_type(), AlertType::SendGrid);
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_config_with_system_check() {
+        let yaml = r#"
+alerts:
+  console:
+    type: stdout
+system_checks:
+  - name: api
+    type: http
+    url: "http://localhost:8080/health"
+    interval: 30
+    alert: console
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+
+        assert!(validate_config(&config).is_ok());
+    }
+
+    #[test]
+    fn test_validate_config_rejects_system_check_with_undefined_alert() {
+        let yaml = r#"
+alerts:
+  console:
+    type: stdout
+system_checks:
+  - name: api
+    type: http
+    url: "http://localhost:8080/health"
+    alert: pager
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+
+        let err = validate_config(&config).unwrap_err().to_string();
+        assert!(err.contains("System check 'api' references undefined alert 'pager'"));
+    }
+
+    #[test]
+    fn test_validate_config_reports_disabled_rule() {
+        let yaml = r#"
+alerts:
+  console:
+    type: stdout
+rules:
+  - name: noisy
+    text: DEBUG
+    alert: console
+    enabled: false
+"#;
+        let config: Config = serde_yaml::from_str(yaml).unwrap();
+        assert!(!config.rules[0].enabled);
+        assert!(validate_config(&config).is_ok());
+
+        let mut heading = termcolor::Buffer::no_color();
+        write!(&mut heading, "  {}", config.rules[0].name).unwrap();
+        write_disabled(&mut heading, config.rules[0].enabled).unwrap();
+        assert_eq!(String::from_utf8(heading.into_inner()).unwrap(), "  noisy (disabled)\n");
+
+        let mut heading = termcolor::Buffer::no_color();
+        write_disabled(&mut heading, true).unwrap();
+        assert_eq!(String::from_utf8(heading.into_inner()).unwrap(), "\n");
+    }
+
+    /// Collects everything a log layer writes
+    struct Captured(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_log_format() {
+        let output = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let writer = {
+            let output = output.clone();
+            move || Captured(output.clone())
+        };
+        let subscriber = tracing_subscriber::registry().with(json_log_layer(writer));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(rule = "errors", "Failed to send alert to 'slack'");
+        });
+
+        let output = String::from_utf8(output.lock().unwrap().clone()).unwrap();
+        let line: serde_json::Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["message"], "Failed to send alert to 'slack'");
+        assert_eq!(line["rule"], "errors");
+        assert!(line["timestamp"].is_string());
+    }
 }
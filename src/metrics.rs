@@ -0,0 +1,232 @@
+use crate::config::SourceType;
+use anyhow::{Context, Result};
+use http_body_util::Full;
+use hyper::body::{Bytes, Incoming};
+use hyper::{Method, Request, Response, StatusCode};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+/// Set once the `metrics` endpoint is configured; nothing is recorded before that
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry::new());
+
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+fn record(update: impl FnOnce(&mut Registry)) {
+    if ENABLED.load(Ordering::Relaxed) {
+        update(&mut REGISTRY.lock().unwrap());
+    }
+}
+
+/// Count a log line read from `source`, whether or not it matched
+pub fn line_processed(source: &SourceType) {
+    record(|registry| {
+        *registry.lines.entry(source_labels(source)).or_default() += 1;
+    });
+}
+
+pub fn alert_sent(alert_name: &str) {
+    record(|registry| {
+        *registry.alerts_sent.entry(alert_name.to_string()).or_default() += 1;
+    });
+}
+
+pub fn alert_failed(alert_name: &str) {
+    record(|registry| {
+        *registry.alert_failures.entry(alert_name.to_string()).or_default() += 1;
+    });
+}
+
+/// Result of the latest run of a system check
+pub fn check_result(check_name: &str, up: bool) {
+    record(|registry| {
+        registry.checks_up.insert(check_name.to_string(), up);
+    });
+}
+
+/// Latest resource reading, e.g. `cpu_percent`, or `disk_percent` for one mount
+pub fn resource_reading(metric: &'static str, mount: Option<&str>, value: f64) {
+    record(|registry| {
+        registry
+            .resources
+            .insert((metric, mount.unwrap_or_default().to_string()), value);
+    });
+}
+
+fn source_labels(source: &SourceType) -> (&'static str, String) {
+    (source.kind(), source.name())
+}
+
+/// Everything recorded since startup, rendered on each scrape
+struct Registry {
+    /// Lines by source type and name
+    lines: BTreeMap<(&'static str, String), u64>,
+    alerts_sent: BTreeMap<String, u64>,
+    alert_failures: BTreeMap<String, u64>,
+    checks_up: BTreeMap<String, bool>,
+    /// Readings by metric and mount point (empty for host-wide metrics)
+    resources: BTreeMap<(&'static str, String), f64>,
+}
+
+impl Registry {
+    const fn new() -> Self {
+        Self {
+            lines: BTreeMap::new(),
+            alerts_sent: BTreeMap::new(),
+            alert_failures: BTreeMap::new(),
+            checks_up: BTreeMap::new(),
+            resources: BTreeMap::new(),
+        }
+    }
+
+    /// Render in the Prometheus text exposition format
+    fn render(&self) -> String {
+        let mut out = String::new();
+
+        header(&mut out, "tinywatcher_lines_processed_total", "counter", "Log lines processed, by source");
+        for ((kind, name), count) in &self.lines {
+            let _ = writeln!(
+                out,
+                "tinywatcher_lines_processed_total{{type=\"{}\",source=\"{}\"}} {}",
+                kind,
+                escape(name),
+                count
+            );
+        }
+
+        header(&mut out, "tinywatcher_alerts_sent_total", "counter", "Alerts delivered, by alert");
+        for (alert, count) in &self.alerts_sent {
+            let _ = writeln!(out, "tinywatcher_alerts_sent_total{{alert=\"{}\"}} {}", escape(alert), count);
+        }
+
+        header(&mut out, "tinywatcher_alert_failures_total", "counter", "Failed alert deliveries, by alert");
+        for (alert, count) in &self.alert_failures {
+            let _ = writeln!(out, "tinywatcher_alert_failures_total{{alert=\"{}\"}} {}", escape(alert), count);
+        }
+
+        header(&mut out, "tinywatcher_health_check_up", "gauge", "Whether the latest run of a system check passed");
+        for (check, up) in &self.checks_up {
+            let _ = writeln!(out, "tinywatcher_health_check_up{{check=\"{}\"}} {}", escape(check), u8::from(*up));
+        }
+
+        let mut metric = None;
+        for ((name, mount), value) in &self.resources {
+            if metric != Some(*name) {
+                header(&mut out, &format!("tinywatcher_{}", name), "gauge", "Latest resource reading");
+                metric = Some(*name);
+            }
+            if mount.is_empty() {
+                let _ = writeln!(out, "tinywatcher_{} {}", name, value);
+            } else {
+                let _ = writeln!(out, "tinywatcher_{}{{mount=\"{}\"}} {}", name, escape(mount), value);
+            }
+        }
+
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a label value: backslash, double quote and newline
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+pub fn render() -> String {
+    REGISTRY.lock().unwrap().render()
+}
+
+/// Serve `GET /metrics` on an already bound listener until the task is stopped
+/// Bound by the caller, so a port already in use fails at startup rather than in the background
+pub async fn serve(listener: Arc<TcpListener>) -> Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await.context("Failed to accept metrics connection")?;
+        tokio::spawn(async move {
+            let service = hyper::service::service_fn(|request| async move {
+                Ok::<_, std::convert::Infallible>(respond(&request))
+            });
+            if let Err(e) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(hyper_util::rt::TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!("Metrics connection closed: {}", e);
+            }
+        });
+    }
+}
+
+fn respond(request: &Request<Incoming>) -> Response<Full<Bytes>> {
+    let (status, content_type, body) = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => (StatusCode::OK, "text/plain; version=0.0.4", render()),
+        _ => (StatusCode::NOT_FOUND, "text/plain", "Not found\n".to_string()),
+    };
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(hyper::header::CONTENT_TYPE, hyper::header::HeaderValue::from_static(content_type));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_render_metric_names() {
+        let mut registry = Registry::new();
+        *registry
+            .lines
+            .entry(source_labels(&SourceType::File(PathBuf::from("/var/log/app.log"))))
+            .or_default() += 3;
+        registry.alerts_sent.insert("slack".to_string(), 2);
+        registry.alert_failures.insert("slack".to_string(), 1);
+        registry.checks_up.insert("api".to_string(), false);
+        registry.resources.insert(("cpu_percent", String::new()), 12.5);
+        registry.resources.insert(("disk_percent", "/".to_string()), 70.0);
+
+        let rendered = registry.render();
+        for line in [
+            "# TYPE tinywatcher_lines_processed_total counter",
+            "tinywatcher_lines_processed_total{type=\"file\",source=\"/var/log/app.log\"} 3",
+            "tinywatcher_alerts_sent_total{alert=\"slack\"} 2",
+            "tinywatcher_alert_failures_total{alert=\"slack\"} 1",
+            "# TYPE tinywatcher_health_check_up gauge",
+            "tinywatcher_health_check_up{check=\"api\"} 0",
+            "tinywatcher_cpu_percent 12.5",
+            "tinywatcher_disk_percent{mount=\"/\"} 70",
+        ] {
+            assert!(rendered.lines().any(|rendered| rendered == line), "missing '{}' in:\n{}", line, rendered);
+        }
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        assert_eq!(escape("C:\\logs\\\"app\"\n"), "C:\\\\logs\\\\\\\"app\\\"\\n");
+    }
+
+    #[tokio::test]
+    async fn test_serves_metrics_endpoint() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve(Arc::new(listener)));
+
+        let response = reqwest::get(format!("http://{}/metrics", addr)).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(response.text().await.unwrap().contains("# TYPE tinywatcher_alerts_sent_total counter"));
+
+        let response = reqwest::get(format!("http://{}/other", addr)).await.unwrap();
+        assert_eq!(response.status(), 404);
+        server.abort();
+    }
+}
@@ -0,0 +1,312 @@
+use crate::config::PingMethod;
+use anyhow::{Context, Result};
+use regex::Regex;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+const ICMPV4_ECHO_REQUEST: u8 = 8;
+const ICMPV4_ECHO_REPLY: u8 = 0;
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+const ICMPV6_ECHO_REPLY: u8 = 129;
+
+/// Echo payload, also used to recognise our replies on a raw socket
+const PAYLOAD: &[u8] = b"tinywatcher ping";
+
+/// How a ping check sends its echo requests and judges the result
+#[derive(Debug, Clone, PartialEq)]
+pub struct PingSettings {
+    pub count: u32,
+    /// Highest tolerated packet loss, in percent
+    pub max_loss: f64,
+    pub method: PingMethod,
+}
+
+impl Default for PingSettings {
+    fn default() -> Self {
+        Self {
+            count: 3,
+            max_loss: 50.0,
+            method: PingMethod::Socket,
+        }
+    }
+}
+
+/// Echo requests sent and replies received
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PingOutcome {
+    pub sent: u32,
+    pub received: u32,
+}
+
+impl PingOutcome {
+    pub fn loss_percent(&self) -> f64 {
+        if self.sent == 0 {
+            return 100.0;
+        }
+        100.0 * f64::from(self.sent.saturating_sub(self.received)) / f64::from(self.sent)
+    }
+}
+
+/// Fail when more packets were lost than the settings tolerate
+pub fn evaluate(outcome: PingOutcome, max_loss: f64, host: &str) -> Result<()> {
+    let loss = outcome.loss_percent();
+    if outcome.sent == 0 || loss > max_loss {
+        anyhow::bail!(
+            "{:.0}% packet loss to {} ({} of {} replies received, tolerance {}%)",
+            loss,
+            host,
+            outcome.received,
+            outcome.sent,
+            max_loss
+        );
+    }
+    Ok(())
+}
+
+/// Ping `host`, waiting up to `timeout` for each reply
+pub async fn ping(host: &str, settings: &PingSettings, timeout: Duration) -> Result<PingOutcome> {
+    match settings.method {
+        PingMethod::Socket => {
+            let addr = resolve(host).await?;
+            let count = settings.count;
+            tokio::task::spawn_blocking(move || ping_socket(addr, count, timeout))
+                .await
+                .context("Ping task failed")?
+                .map_err(socket_error)
+        }
+        PingMethod::Command => ping_command(host, settings.count, timeout).await,
+    }
+}
+
+async fn resolve(host: &str) -> Result<IpAddr> {
+    if let Ok(addr) = host.parse() {
+        return Ok(addr);
+    }
+    tokio::net::lookup_host((host, 0))
+        .await
+        .with_context(|| format!("Failed to resolve {}", host))?
+        .next()
+        .map(|addr| addr.ip())
+        .with_context(|| format!("{} has no addresses", host))
+}
+
+/// Explain socket failures, pointing at the `ping` command when ICMP sockets aren't permitted
+fn socket_error(error: std::io::Error) -> anyhow::Error {
+    if error.kind() == std::io::ErrorKind::PermissionDenied {
+        anyhow::anyhow!(
+            "Not permitted to open an ICMP socket ({}); run as root, grant CAP_NET_RAW or widen \
+             net.ipv4.ping_group_range, or set `ping_method: command` to use the system ping binary",
+            error
+        )
+    } else {
+        anyhow::Error::new(error).context("ICMP ping failed")
+    }
+}
+
+/// Send `count` echo requests from an ICMP socket; blocking, so run it with `spawn_blocking`
+fn ping_socket(addr: IpAddr, count: u32, timeout: Duration) -> std::io::Result<PingOutcome> {
+    let (domain, protocol, request, reply) = match addr {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4, ICMPV4_ECHO_REQUEST, ICMPV4_ECHO_REPLY),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6, ICMPV6_ECHO_REQUEST, ICMPV6_ECHO_REPLY),
+    };
+    // Unprivileged ICMP sockets where the OS allows them, raw sockets otherwise
+    let socket = Socket::new(domain, Type::DGRAM, Some(protocol))
+        .or_else(|_| Socket::new(domain, Type::RAW, Some(protocol)))?;
+    socket.connect(&SocketAddr::new(addr, 0).into())?;
+
+    let identifier = std::process::id() as u16;
+    let mut received = 0;
+    let mut buf = [0u8; 1500];
+    for sequence in 0..count {
+        let sequence = sequence as u16;
+        socket.send(&echo_request(request, identifier, sequence))?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            socket.set_read_timeout(Some(remaining))?;
+            let n = match (&socket).read(&mut buf) {
+                Ok(n) => n,
+                Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => break,
+                Err(e) => return Err(e),
+            };
+            if is_echo_reply(&buf[..n], reply, sequence) {
+                received += 1;
+                break;
+            }
+        }
+    }
+    Ok(PingOutcome { sent: count, received })
+}
+
+fn echo_request(kind: u8, identifier: u16, sequence: u16) -> Vec<u8> {
+    let mut packet = vec![kind, 0, 0, 0];
+    packet.extend_from_slice(&identifier.to_be_bytes());
+    packet.extend_from_slice(&sequence.to_be_bytes());
+    packet.extend_from_slice(PAYLOAD);
+    // The kernel fills in ICMPv6 checksums; ICMPv4 ones are ours to compute
+    let checksum = checksum(&packet);
+    packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+    packet
+}
+
+/// Internet checksum (RFC 1071)
+fn checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|pair| u32::from(u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)])))
+        .sum();
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Whether a received packet is the reply to our request `sequence`
+/// Raw IPv4 sockets deliver the IP header too; unprivileged sockets rewrite the identifier,
+/// so replies are matched on sequence and payload instead
+fn is_echo_reply(packet: &[u8], reply: u8, sequence: u16) -> bool {
+    let icmp = match packet.first() {
+        Some(first) if first >> 4 == 4 => packet.get(usize::from(first & 0x0f) * 4..).unwrap_or_default(),
+        _ => packet,
+    };
+    icmp.len() >= 8 + PAYLOAD.len()
+        && icmp[0] == reply
+        && icmp[6..8] == sequence.to_be_bytes()
+        && icmp[8..].starts_with(PAYLOAD)
+}
+
+/// Ping with the system `ping` binary, which is setuid or has the capability where needed
+async fn ping_command(host: &str, count: u32, timeout: Duration) -> Result<PingOutcome> {
+    let output = Command::new("ping")
+        .args(ping_command_args(host, count, timeout))
+        .output()
+        .await
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => anyhow::anyhow!("The ping command was not found on PATH"),
+            _ => anyhow::Error::new(e).context("Failed to run ping"),
+        })?;
+
+    parse_ping_summary(&String::from_utf8_lossy(&output.stdout)).with_context(|| {
+        format!(
+            "ping {} failed: {}",
+            host,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+    })
+}
+
+fn ping_command_args(host: &str, count: u32, timeout: Duration) -> Vec<String> {
+    let count = count.to_string();
+    if cfg!(windows) {
+        // Reply timeout in milliseconds
+        vec!["-n".into(), count, "-w".into(), timeout.as_millis().to_string(), host.into()]
+    } else if cfg!(target_os = "macos") {
+        vec!["-c".into(), count, "-W".into(), timeout.as_millis().to_string(), host.into()]
+    } else {
+        // iputils and busybox take whole seconds
+        let seconds = timeout.as_secs().max(1).to_string();
+        vec!["-c".into(), count, "-W".into(), seconds, host.into()]
+    }
+}
+
+/// Read sent and received counts from the summary `ping` prints on Linux, macOS, BSD or Windows
+fn parse_ping_summary(output: &str) -> Option<PingOutcome> {
+    static SUMMARY: OnceLock<Regex> = OnceLock::new();
+    let summary = SUMMARY.get_or_init(|| {
+        Regex::new(r"(\d+) packets transmitted, (\d+) (?:packets )?received|Sent = (\d+), Received = (\d+)")
+            .expect("ping summary regex is valid")
+    });
+
+    let captures = summary.captures(output)?;
+    let number = |first: usize, second: usize| {
+        captures
+            .get(first)
+            .or_else(|| captures.get(second))
+            .and_then(|m| m.as_str().parse().ok())
+    };
+    Some(PingOutcome {
+        sent: number(1, 3)?,
+        received: number(2, 4)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_loss_against_tolerance() {
+        let all = PingOutcome { sent: 4, received: 4 };
+        let half = PingOutcome { sent: 4, received: 2 };
+        let none = PingOutcome { sent: 4, received: 0 };
+
+        assert!(evaluate(all, 0.0, "gateway").is_ok());
+        assert!(evaluate(half, 50.0, "gateway").is_ok());
+        assert!(evaluate(half, 25.0, "gateway").is_err());
+
+        let error = evaluate(none, 50.0, "gateway").unwrap_err().to_string();
+        assert_eq!(error, "100% packet loss to gateway (0 of 4 replies received, tolerance 50%)");
+
+        // Nothing sent can't count as reachable
+        assert!(evaluate(PingOutcome { sent: 0, received: 0 }, 100.0, "gateway").is_err());
+    }
+
+    #[test]
+    fn test_parse_ping_summary() {
+        let linux = "--- 10.0.0.1 ping statistics ---\n3 packets transmitted, 2 received, 33.3333% packet loss, time 2003ms\n";
+        assert_eq!(parse_ping_summary(linux), Some(PingOutcome { sent: 3, received: 2 }));
+
+        let macos = "--- 10.0.0.1 ping statistics ---\n3 packets transmitted, 3 packets received, 0.0% packet loss\n";
+        assert_eq!(parse_ping_summary(macos), Some(PingOutcome { sent: 3, received: 3 }));
+
+        let windows = "Ping statistics for 10.0.0.1:\r\n    Packets: Sent = 3, Received = 0, Lost = 3 (100% loss),\r\n";
+        assert_eq!(parse_ping_summary(windows), Some(PingOutcome { sent: 3, received: 0 }));
+
+        assert_eq!(parse_ping_summary("ping: unknown host nowhere\n"), None);
+    }
+
+    #[test]
+    fn test_permission_denied_suggests_command_method() {
+        let error = socket_error(std::io::Error::from(std::io::ErrorKind::PermissionDenied)).to_string();
+        assert!(error.contains("ping_method: command"), "unexpected error: {}", error);
+
+        let error = socket_error(std::io::Error::from(std::io::ErrorKind::NetworkUnreachable)).to_string();
+        assert!(!error.contains("ping_method"), "unexpected error: {}", error);
+    }
+
+    #[test]
+    fn test_echo_request_and_reply_matching() {
+        let request = echo_request(ICMPV4_ECHO_REQUEST, 0x1234, 7);
+        // A packet with its checksum filled in sums to zero
+        assert_eq!(checksum(&request), 0);
+
+        let mut reply = request.clone();
+        reply[0] = ICMPV4_ECHO_REPLY;
+        assert!(is_echo_reply(&reply, ICMPV4_ECHO_REPLY, 7));
+        assert!(!is_echo_reply(&reply, ICMPV4_ECHO_REPLY, 8));
+        assert!(!is_echo_reply(&request, ICMPV4_ECHO_REPLY, 7));
+
+        // Raw IPv4 sockets prefix a 20-byte IP header
+        let mut with_header = vec![0x45];
+        with_header.extend_from_slice(&[0; 19]);
+        with_header.extend_from_slice(&reply);
+        assert!(is_echo_reply(&with_header, ICMPV4_ECHO_REPLY, 7));
+    }
+
+    #[tokio::test]
+    async fn test_socket_ping_loopback_or_explains_permissions() {
+        let settings = PingSettings::default();
+        match ping("127.0.0.1", &settings, Duration::from_secs(1)).await {
+            Ok(outcome) => assert_eq!(outcome, PingOutcome { sent: 3, received: 3 }),
+            Err(e) => assert!(e.to_string().contains("ping_method: command"), "unexpected error: {}", e),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::alerts::AlertManager;
+use crate::config::{Config, SourceType};
+use crate::log_monitor::LogMonitor;
+use crate::shutdown;
+use crate::silent_sources::SilenceWatchdog;
+use crate::stream_monitor::StreamMonitor;
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// Resolves each time a config reload is requested with SIGHUP; never on platforms without it
+pub struct ReloadSignal {
+    #[cfg(unix)]
+    hangup: tokio::signal::unix::Signal,
+}
+
+impl ReloadSignal {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            hangup: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+                .context("Failed to listen for SIGHUP")?,
+        })
+    }
+
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        self.hangup.recv().await;
+
+        #[cfg(not(unix))]
+        std::future::pending::<()>().await;
+    }
+}
+
+/// The parts of a running watcher a config reload can change: the rules and the set of watched files
+/// Everything else (alerts, containers, streams, resources, checks) applies after a restart
+pub struct Reloader {
+    config_path: PathBuf,
+    log_monitor: Option<Arc<LogMonitor>>,
+    stream_monitors: Vec<Arc<StreamMonitor>>,
+    /// Watched files, each with a token that stops only its watcher
+    files: HashMap<PathBuf, CancellationToken>,
+    shutdown: CancellationToken,
+    alert_manager: Arc<AlertManager>,
+    /// Starts and stops the silence clock of files as they are added and removed
+    silence: Option<Arc<SilenceWatchdog>>,
+}
+
+impl Reloader {
+    pub fn new(config_path: PathBuf, shutdown: CancellationToken, alert_manager: Arc<AlertManager>) -> Self {
+        Self {
+            config_path,
+            log_monitor: None,
+            stream_monitors: Vec::new(),
+            files: HashMap::new(),
+            shutdown,
+            alert_manager,
+            silence: None,
+        }
+    }
+
+    pub fn set_log_monitor(&mut self, log_monitor: Arc<LogMonitor>) {
+        self.log_monitor = Some(log_monitor);
+    }
+
+    pub fn set_silence_watchdog(&mut self, watchdog: Arc<SilenceWatchdog>) {
+        self.silence = Some(watchdog);
+    }
+
+    pub fn add_stream_monitor(&mut self, stream_monitor: Arc<StreamMonitor>) {
+        self.stream_monitors.push(stream_monitor);
+    }
+
+    /// Spawn a supervised watcher for a file, which a reload can stop again if the file leaves the config
+    pub fn watch_file(&mut self, file: PathBuf) -> Option<JoinHandle<()>> {
+        let monitor = self.log_monitor.clone()?;
+        let token = self.shutdown.child_token();
+        let name = format!("file {}", file.display());
+        let task = {
+            let file = file.clone();
+            shutdown::spawn(name, &token, &self.alert_manager, move || {
+                let (monitor, file) = (monitor.clone(), file.clone());
+                async move { monitor.watch_file(file).await }
+            })
+        };
+        if let Some(silence) = &self.silence {
+            silence.watch(SourceType::File(file.clone()));
+        }
+        self.files.insert(file, token);
+        Some(task)
+    }
+
+    /// Re-read the config file and apply its rules and files; an invalid config is logged and ignored
+    /// Returns watchers started for newly added files
+    pub fn reload(&mut self) -> Vec<JoinHandle<()>> {
+        tracing::info!("Reloading config from {}", self.config_path.display());
+        match self.try_reload() {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                tracing::error!("Config reload failed, keeping the running config: {:#}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn try_reload(&mut self) -> Result<Vec<JoinHandle<()>>> {
+        let config = Config::from_file(self.config_path.to_str().context("Invalid config path")?)?;
+        config.validate()?;
+
+        let Some(log_monitor) = self.log_monitor.clone() else {
+            anyhow::bail!("No rules were configured at startup; restart TinyWatcher to start watching logs");
+        };
+        let files = config
+            .watch_sources()
+            .context("Failed to expand file glob patterns")?
+            .files;
+
+        // Every monitor compiles the same rules, so a bad pattern fails before anything is swapped
+        log_monitor.reload_rules(config.rules.clone())?;
+        for stream_monitor in &self.stream_monitors {
+            stream_monitor.reload_rules(config.rules.clone())?;
+        }
+
+        self.files.retain(|file, token| {
+            let keep = files.contains(file);
+            if !keep {
+                tracing::info!("{} is no longer in the config; no longer watching it", file.display());
+                token.cancel();
+                if let Some(silence) = &self.silence {
+                    silence.forget(&SourceType::File(file.clone()));
+                }
+            }
+            keep
+        });
+        let added: Vec<PathBuf> = files.into_iter().filter(|file| !self.files.contains_key(file)).collect();
+        let tasks = added
+            .into_iter()
+            .filter_map(|file| {
+                tracing::info!("Watching newly configured file {}", file.display());
+                self.watch_file(file)
+            })
+            .collect();
+
+        tracing::info!("Config reloaded: {} rule(s), {} file(s)", config.rules.len(), self.files.len());
+        Ok(tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(file: &tempfile::NamedTempFile, pattern: &str) {
+        let yaml = format!(
+            "alerts:\n  console:\n    type: stdout\nrules:\n  - name: watched\n    pattern: \"{}\"\n    alert: console\n",
+            pattern
+        );
+        std::fs::write(file.path(), yaml).unwrap();
+    }
+
+    fn reloader(file: &tempfile::NamedTempFile) -> (Reloader, Arc<LogMonitor>) {
+        let config = Config::from_file(file.path().to_str().unwrap()).unwrap();
+        let alert_manager = Arc::new(AlertManager::new("test-server".to_string()));
+        let log_monitor = Arc::new(LogMonitor::new(config.rules, alert_manager.clone()).unwrap());
+        let mut reloader = Reloader::new(file.path().to_path_buf(), CancellationToken::new(), alert_manager);
+        reloader.set_log_monitor(log_monitor.clone());
+        (reloader, log_monitor)
+    }
+
+    #[tokio::test]
+    async fn test_reload_swaps_rules() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        write_config(&file, "ERROR");
+        let (mut reloader, log_monitor) = reloader(&file);
+        assert_eq!(log_monitor.matching_rules("PANIC now"), Vec::<String>::new());
+
+        write_config(&file, "PANIC");
+        reloader.reload();
+        assert_eq!(log_monitor.matching_rules("PANIC now"), vec!["watched"]);
+        assert_eq!(log_monitor.matching_rules("ERROR now"), Vec::<String>::new());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_reload_keeps_running_rules() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        write_config(&file, "ERROR");
+        let (mut reloader, log_monitor) = reloader(&file);
+
+        write_config(&file, "(unclosed");
+        reloader.reload();
+        assert_eq!(log_monitor.matching_rules("ERROR now"), vec!["watched"]);
+
+        std::fs::write(file.path(), "rules: [not valid").unwrap();
+        reloader.reload();
+        assert_eq!(log_monitor.matching_rules("ERROR now"), vec!["watched"]);
+    }
+}
@@ -1,12 +1,15 @@
 use crate::alerts::AlertManager;
-use crate::config::ResourceConfig;
-use std::sync::Arc;
-use sysinfo::{System, Disks};
-use tokio::time::{interval, Duration};
+use crate::config::{format_size, Limit, NetworkThresholds, ResourceConfig, ResourceThresholds};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use sysinfo::{System, Disks, LoadAvg, Networks};
+use tokio::time::{interval, Duration, Instant};
 
 pub struct ResourceMonitor {
     config: ResourceConfig,
     alert_manager: Arc<AlertManager>,
+    metrics: Mutex<MetricTracker>,
 }
 
 impl ResourceMonitor {
@@ -14,12 +17,15 @@ impl ResourceMonitor {
         Self {
             config,
             alert_manager,
+            metrics: Mutex::new(MetricTracker::default()),
         }
     }
 
     pub async fn start(&self) {
         let mut interval = interval(Duration::from_secs(self.config.interval));
         let mut sys = System::new_all();
+        let mut networks = Networks::new();
+        let mut previous_network: Option<NetworkSample> = None;
         
         tracing::info!(
             "Starting resource monitoring (interval: {}s)",
@@ -29,103 +35,518 @@ impl ResourceMonitor {
         loop {
             interval.tick().await;
             sys.refresh_all();
+            record_readings(&sys);
 
             self.check_cpu(&sys).await;
             self.check_memory(&sys).await;
+            self.check_load(&sys).await;
             self.check_disk().await;
+            self.check_inodes().await;
+            self.check_network(&mut networks, &mut previous_network).await;
+        }
+    }
+
+    /// Alert once `metric` has been over its limit long enough, and once more when it drops back
+    /// `kind` names the rule (`cpu_threshold`, `cpu_recovered`) and appears in error logs
+    async fn report(
+        &self,
+        kind: &str,
+        metric: &str,
+        exceeded: bool,
+        sustain: Option<Duration>,
+        alert: String,
+        recovery: String,
+    ) {
+        let event = self
+            .metrics
+            .lock()
+            .unwrap()
+            .observe(metric, exceeded, sustain, Instant::now());
+
+        let (rule, message, cooldown) = match event {
+            MetricEvent::Breached => (format!("{}_threshold", kind), alert, self.config.cooldown_secs()),
+            MetricEvent::Recovered if self.config.thresholds.notify_recovery => {
+                tracing::info!("Resource {} recovered: {}", kind, recovery);
+                (format!("{}_recovered", kind), recovery, 0)
+            }
+            _ => return,
+        };
+
+        if let Err(e) = self
+            .alert_manager
+            .send_alert_multi(&self.config.thresholds.alert, &rule, &message, cooldown)
+            .await
+        {
+            tracing::error!("Failed to send {} alert: {}", kind, e);
         }
     }
 
     async fn check_cpu(&self, sys: &System) {
-        if let Some(threshold) = self.config.thresholds.cpu_percent {
+        if let Some(limit) = self.config.thresholds.cpu_percent {
             let cpu_usage = sys.global_cpu_usage();
-            
-            if cpu_usage > threshold {
-                let message = format!(
-                    "CPU usage is {}% (threshold: {}%)",
-                    cpu_usage, threshold
-                );
-                
-                if let Err(e) = self
-                    .alert_manager
-                    .send_alert_multi(
-                        &self.config.thresholds.alert,
-                        "cpu_threshold",
-                        &message,
-                        self.config.interval * 6, // 6x interval cooldown
-                    )
-                    .await
-                {
-                    tracing::error!("Failed to send CPU alert: {}", e);
-                }
-            }
+
+            self.report(
+                "cpu",
+                "cpu",
+                cpu_usage > limit.above,
+                limit.sustain,
+                format!("CPU usage is {}% (threshold: {}%{})", cpu_usage, limit.above, limit.sustain_label()),
+                format!("CPU back to {:.1}% (threshold: {}%)", cpu_usage, limit.above),
+            )
+            .await;
         }
     }
 
     async fn check_memory(&self, sys: &System) {
-        if let Some(threshold) = self.config.thresholds.memory_percent {
+        if let Some(limit) = self.config.thresholds.memory_percent {
             let total_memory = sys.total_memory();
             let used_memory = sys.used_memory();
             let memory_percent = (used_memory as f32 / total_memory as f32) * 100.0;
-            
-            if memory_percent > threshold {
-                let message = format!(
-                    "Memory usage is {:.1}% (threshold: {}%)",
-                    memory_percent, threshold
-                );
-                
-                if let Err(e) = self
-                    .alert_manager
-                    .send_alert_multi(
-                        &self.config.thresholds.alert,
-                        "memory_threshold",
-                        &message,
-                        self.config.interval * 6,
-                    )
-                    .await
-                {
-                    tracing::error!("Failed to send memory alert: {}", e);
-                }
-            }
+
+            self.report(
+                "memory",
+                "memory",
+                memory_percent > limit.above,
+                limit.sustain,
+                format!(
+                    "Memory usage is {:.1}% (threshold: {}%{})",
+                    memory_percent, limit.above, limit.sustain_label()
+                ),
+                format!("Memory back to {:.1}% (threshold: {}%)", memory_percent, limit.above),
+            )
+            .await;
+        }
+
+        if let Some(minimum) = self.config.thresholds.memory_available_min {
+            let available = sys.available_memory();
+
+            self.report(
+                "memory_available",
+                "memory_available",
+                available < minimum,
+                None,
+                format!(
+                    "Available memory is {} (minimum: {})",
+                    format_bytes(available),
+                    format_size(minimum)
+                ),
+                format!(
+                    "Available memory back to {} (minimum: {})",
+                    format_bytes(available),
+                    format_size(minimum)
+                ),
+            )
+            .await;
+        }
+    }
+
+    async fn check_load(&self, sys: &System) {
+        let thresholds = &self.config.thresholds;
+        if thresholds.load1.is_none() && thresholds.load5.is_none() && thresholds.load15.is_none() {
+            return;
+        }
+
+        let cores = sys.cpus().len();
+        for (window, load, limit) in load_limits(&System::load_average(), thresholds) {
+            self.report(
+                "load",
+                &format!("load:{}", window),
+                load > limit.above,
+                limit.sustain,
+                format!(
+                    "Load average ({}) is {:.2} on {} CPU cores (threshold: {}{})",
+                    window, load, cores, limit.above, limit.sustain_label()
+                ),
+                format!(
+                    "Load average ({}) back to {:.2} on {} CPU cores (threshold: {})",
+                    window, load, cores, limit.above
+                ),
+            )
+            .await;
         }
     }
 
     async fn check_disk(&self) {
-        if let Some(threshold) = self.config.thresholds.disk_percent {
+        let thresholds = &self.config.thresholds;
+        if thresholds.disk_percent.is_none()
+            && thresholds.disk_mounts.is_empty()
+            && thresholds.disk_available_min.is_none()
+        {
+            return;
+        }
+
+        let disks: Vec<DiskUsage> = Disks::new_with_refreshed_list()
+            .iter()
+            .map(|disk| DiskUsage {
+                mount_point: disk.mount_point().display().to_string(),
+                total: disk.total_space(),
+                available: disk.available_space(),
+            })
+            .collect();
+
+        for (mount_point, used_percent, limit) in disk_readings(&disks, thresholds) {
+            crate::metrics::resource_reading("disk_percent", Some(&mount_point), used_percent as f64);
+            self.report(
+                "disk",
+                &format!("disk:{}", mount_point),
+                used_percent > limit.above,
+                limit.sustain,
+                format!(
+                    "Disk usage on {} is {:.1}% (threshold: {}%{})",
+                    mount_point, used_percent, limit.above, limit.sustain_label()
+                ),
+                format!(
+                    "Disk usage on {} back to {:.1}% (threshold: {}%)",
+                    mount_point, used_percent, limit.above
+                ),
+            )
+            .await;
+        }
+
+        if let Some(minimum) = thresholds.disk_available_min {
+            for disk in disks.iter().filter(|disk| disk.total > 0 && disk_selected(&disk.mount_point, thresholds)) {
+                self.report(
+                    "disk_available",
+                    &format!("disk_available:{}", disk.mount_point),
+                    disk.available < minimum,
+                    None,
+                    format!(
+                        "Available space on {} is {} (minimum: {})",
+                        disk.mount_point,
+                        format_bytes(disk.available),
+                        format_size(minimum)
+                    ),
+                    format!(
+                        "Available space on {} back to {} (minimum: {})",
+                        disk.mount_point,
+                        format_bytes(disk.available),
+                        format_size(minimum)
+                    ),
+                )
+                .await;
+            }
+        }
+    }
+
+    async fn check_inodes(&self) {
+        if let Some(limit) = self.config.thresholds.inode_percent {
             let disks = Disks::new_with_refreshed_list();
-            
+
             for disk in &disks {
-                let total_space = disk.total_space();
-                let available_space = disk.available_space();
-                
-                if total_space == 0 {
+                let mount_point = disk.mount_point();
+                // Unsupported platforms and filesystems without fixed inode tables are skipped
+                let Some((total, free)) = inode_counts(mount_point) else {
+                    continue;
+                };
+                let Some(used_percent) = inode_used_percent(total, free) else {
                     continue;
-                }
-                
-                let used_percent = ((total_space - available_space) as f32 / total_space as f32) * 100.0;
-                
-                if used_percent > threshold {
-                    let message = format!(
-                        "Disk usage on {} is {:.1}% (threshold: {}%)",
-                        disk.mount_point().display(),
+                };
+
+                self.report(
+                    "inode",
+                    &format!("inode:{}", mount_point.display()),
+                    used_percent > limit.above,
+                    limit.sustain,
+                    format!(
+                        "Inode usage on {} is {:.1}% (threshold: {}%{})",
+                        mount_point.display(),
+                        used_percent,
+                        limit.above,
+                        limit.sustain_label()
+                    ),
+                    format!(
+                        "Inode usage on {} back to {:.1}% (threshold: {}%)",
+                        mount_point.display(),
                         used_percent,
-                        threshold
-                    );
-                    
-                    if let Err(e) = self
-                        .alert_manager
-                        .send_alert_multi(
-                            &self.config.thresholds.alert,
-                            "disk_threshold",
-                            &message,
-                            self.config.interval * 6,
-                        )
-                        .await
-                    {
-                        tracing::error!("Failed to send disk alert: {}", e);
-                    }
-                }
+                        limit.above
+                    ),
+                )
+                .await;
             }
         }
     }
+
+    async fn check_network(&self, networks: &mut Networks, previous: &mut Option<NetworkSample>) {
+        let Some(thresholds) = &self.config.thresholds.network else {
+            return;
+        };
+
+        networks.refresh_list();
+        let sample = NetworkSample::from_networks(networks);
+        // The first tick only records totals to measure the next interval against
+        let Some(previous) = previous.replace(sample.clone()) else {
+            return;
+        };
+
+        for (interface, rate) in network_rates(&previous, &sample) {
+            for (direction, rate, limit) in rate.limits(thresholds) {
+                self.report(
+                    "network",
+                    &format!("network:{}:{}", interface, direction),
+                    rate > limit.above as f64,
+                    limit.sustain,
+                    format!(
+                        "Network {} on {} is {} (threshold: {}{})",
+                        direction,
+                        interface,
+                        format_rate(rate),
+                        format_rate(limit.above as f64),
+                        limit.sustain_label()
+                    ),
+                    format!(
+                        "Network {} on {} back to {} (threshold: {})",
+                        direction,
+                        interface,
+                        format_rate(rate),
+                        format_rate(limit.above as f64)
+                    ),
+                )
+                .await;
+            }
+        }
+    }
+}
+
+/// Publish host-wide readings for the `/metrics` endpoint, whether or not they have thresholds
+fn record_readings(sys: &System) {
+    crate::metrics::resource_reading("cpu_percent", None, sys.global_cpu_usage() as f64);
+    if sys.total_memory() > 0 {
+        let memory_percent = sys.used_memory() as f64 / sys.total_memory() as f64 * 100.0;
+        crate::metrics::resource_reading("memory_percent", None, memory_percent);
+    }
+    crate::metrics::resource_reading("load1", None, System::load_average().one);
+}
+
+/// Space on one mounted filesystem
+#[derive(Debug, Clone)]
+struct DiskUsage {
+    mount_point: String,
+    total: u64,
+    available: u64,
+}
+
+/// Whether the include/exclude globs let a mount be checked; mounts in `disk_mounts` are always included
+fn disk_selected(mount_point: &str, thresholds: &ResourceThresholds) -> bool {
+    let matches = |patterns: &[String]| {
+        patterns
+            .iter()
+            .any(|pattern| glob::Pattern::new(pattern).is_ok_and(|pattern| pattern.matches(mount_point)))
+    };
+
+    !matches(&thresholds.disk_exclude)
+        && (thresholds.disk_include.is_empty()
+            || thresholds.disk_mounts.contains_key(mount_point)
+            || matches(&thresholds.disk_include))
+}
+
+/// The limit a mount is checked against: its `disk_mounts` entry, else `disk_percent`
+fn disk_limit(mount_point: &str, thresholds: &ResourceThresholds) -> Option<Limit<f32>> {
+    if !disk_selected(mount_point, thresholds) {
+        return None;
+    }
+    thresholds.disk_mounts.get(mount_point).copied().or(thresholds.disk_percent)
+}
+
+/// Used percentage and limit of every mount that should be checked
+fn disk_readings(disks: &[DiskUsage], thresholds: &ResourceThresholds) -> Vec<(String, f32, Limit<f32>)> {
+    disks
+        .iter()
+        .filter(|disk| disk.total > 0)
+        .filter_map(|disk| {
+            let limit = disk_limit(&disk.mount_point, thresholds)?;
+            let used_percent = (disk.total.saturating_sub(disk.available) as f32 / disk.total as f32) * 100.0;
+            Some((disk.mount_point.clone(), used_percent, limit))
+        })
+        .collect()
 }
+
+/// Load averages with a configured limit, with the window, load and limit
+fn load_limits(load: &LoadAvg, thresholds: &ResourceThresholds) -> Vec<(&'static str, f64, Limit<f64>)> {
+    [
+        ("1m", load.one, thresholds.load1),
+        ("5m", load.five, thresholds.load5),
+        ("15m", load.fifteen, thresholds.load15),
+    ]
+    .into_iter()
+    .filter_map(|(window, load, limit)| limit.map(|limit| (window, load, limit)))
+    .collect()
+}
+
+/// What a tick's reading of a metric calls for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MetricEvent {
+    /// Over the limit for at least the sustain duration
+    Breached,
+    /// Back under the limit after a breach was reported
+    Recovered,
+    Quiet,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MetricState {
+    /// When the metric went over its limit, so `for` durations span ticks
+    since: Instant,
+    /// Whether a breach has been reported and a recovery is owed
+    firing: bool,
+}
+
+/// Breach and recovery state of every metric, keyed like `cpu` or `disk:/var`
+#[derive(Debug, Default)]
+struct MetricTracker {
+    states: HashMap<String, MetricState>,
+}
+
+impl MetricTracker {
+    /// Record whether `metric` is over its limit at `now`
+    fn observe(&mut self, metric: &str, exceeded: bool, sustain: Option<Duration>, now: Instant) -> MetricEvent {
+        if !exceeded {
+            return match self.states.remove(metric) {
+                Some(state) if state.firing => MetricEvent::Recovered,
+                _ => MetricEvent::Quiet,
+            };
+        }
+
+        let state = self
+            .states
+            .entry(metric.to_string())
+            .or_insert(MetricState { since: now, firing: false });
+        if sustain.is_none_or(|sustain| now.saturating_duration_since(state.since) >= sustain) {
+            state.firing = true;
+            MetricEvent::Breached
+        } else {
+            MetricEvent::Quiet
+        }
+    }
+}
+
+/// Cumulative bytes received and transmitted per interface at one point in time
+#[derive(Debug, Clone)]
+struct NetworkSample {
+    at: Instant,
+    totals: HashMap<String, (u64, u64)>,
+}
+
+impl NetworkSample {
+    fn from_networks(networks: &Networks) -> Self {
+        Self {
+            at: Instant::now(),
+            totals: networks
+                .list()
+                .iter()
+                .map(|(name, data)| (name.clone(), (data.total_received(), data.total_transmitted())))
+                .collect(),
+        }
+    }
+}
+
+/// Average receive and transmit rates of one interface, in bytes per second
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct NetworkRate {
+    rx: f64,
+    tx: f64,
+}
+
+impl NetworkRate {
+    /// Directions with a configured limit, with their rate and limit
+    fn limits(&self, thresholds: &NetworkThresholds) -> Vec<(&'static str, f64, Limit<u64>)> {
+        [("receive", self.rx, thresholds.rx_bytes_per_sec), ("transmit", self.tx, thresholds.tx_bytes_per_sec)]
+            .into_iter()
+            .filter_map(|(direction, rate, limit)| limit.map(|limit| (direction, rate, limit)))
+            .collect()
+    }
+}
+
+/// Per-interface rates between two samples, sorted by interface name
+/// Interfaces missing from either sample, or whose counters went backwards (reset), are skipped
+fn network_rates(previous: &NetworkSample, current: &NetworkSample) -> Vec<(String, NetworkRate)> {
+    let elapsed = current.at.saturating_duration_since(previous.at).as_secs_f64();
+    if elapsed <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut rates: Vec<(String, NetworkRate)> = current
+        .totals
+        .iter()
+        .filter_map(|(name, &(rx, tx))| {
+            let &(previous_rx, previous_tx) = previous.totals.get(name)?;
+            let rx = rx.checked_sub(previous_rx)?;
+            let tx = tx.checked_sub(previous_tx)?;
+            Some((
+                name.clone(),
+                NetworkRate {
+                    rx: rx as f64 / elapsed,
+                    tx: tx as f64 / elapsed,
+                },
+            ))
+        })
+        .collect();
+    rates.sort_by(|a, b| a.0.cmp(&b.0));
+    rates
+}
+
+/// An amount of memory or disk space with a binary unit, e.g. `1.5 GB`
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// A byte rate with a decimal unit, e.g. `12.5 MB/s`
+fn format_rate(bytes_per_sec: f64) -> String {
+    const UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut value = bytes_per_sec;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Percentage of inodes in use, or None when the filesystem reports no inode limit
+fn inode_used_percent(total: u64, free: u64) -> Option<f32> {
+    if total == 0 {
+        return None;
+    }
+    let used = total.saturating_sub(free);
+    Some((used as f64 / total as f64 * 100.0) as f32)
+}
+
+/// Total and free inode counts for the filesystem containing `path`
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)] // statvfs field widths vary by platform
+fn inode_counts(path: &Path) -> Option<(u64, u64)> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return None;
+    }
+
+    Some((stat.f_files as u64, stat.f_ffree as u64))
+}
+
+#[cfg(not(unix))]
+fn inode_counts(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+#[path = "resource_monitor_tests.rs"]
+#[allow(clippy::module_inception)]
+mod tests;
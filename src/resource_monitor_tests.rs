@@ -0,0 +1,308 @@
+#[cfg(test)]
+mod tests {
+    use crate::alerts::{AlertHandler, AlertManager};
+    use crate::config::{Limit, NetworkThresholds, ResourceConfig, ResourceThresholds};
+    use crate::resource_monitor::{
+        disk_readings, disk_selected, format_bytes, format_rate, DiskUsage, ResourceMonitor, inode_used_percent, load_limits, network_rates, NetworkRate, MetricEvent, MetricTracker, NetworkSample,
+    };
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::time::{Duration, Instant};
+
+    #[test]
+    fn test_inode_used_percent() {
+        assert_eq!(inode_used_percent(1000, 1000), Some(0.0));
+        assert_eq!(inode_used_percent(1000, 250), Some(75.0));
+        assert_eq!(inode_used_percent(1000, 0), Some(100.0));
+    }
+
+    #[test]
+    fn test_inode_used_percent_without_inode_limit() {
+        // Filesystems like btrfs report zero total inodes
+        assert_eq!(inode_used_percent(0, 0), None);
+    }
+
+    #[test]
+    fn test_inode_used_percent_free_exceeds_total() {
+        assert_eq!(inode_used_percent(100, 150), Some(0.0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_inode_counts_for_root() {
+        let (total, free) = crate::resource_monitor::inode_counts(std::path::Path::new("/")).unwrap();
+        assert!(free <= total || total == 0);
+    }
+
+    fn sample(at: Instant, totals: &[(&str, u64, u64)]) -> NetworkSample {
+        NetworkSample {
+            at,
+            totals: totals
+                .iter()
+                .map(|(name, rx, tx)| (name.to_string(), (*rx, *tx)))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn test_network_rates_between_samples() {
+        let start = Instant::now();
+        let previous = sample(start, &[("eth0", 1_000, 500), ("lo", 0, 0)]);
+        let current = sample(
+            start + Duration::from_secs(10),
+            &[("eth0", 101_000, 20_500), ("lo", 50, 50), ("wg0", 1_000, 1_000)],
+        );
+
+        let rates = network_rates(&previous, &current);
+        // wg0 appeared since the last sample, so it has no rate yet
+        assert_eq!(
+            rates,
+            vec![
+                ("eth0".to_string(), NetworkRate { rx: 10_000.0, tx: 2_000.0 }),
+                ("lo".to_string(), NetworkRate { rx: 5.0, tx: 5.0 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_network_rates_skip_counter_resets() {
+        let start = Instant::now();
+        let previous = sample(start, &[("eth0", 5_000, 5_000)]);
+        let current = sample(start + Duration::from_secs(1), &[("eth0", 100, 6_000)]);
+        assert!(network_rates(&previous, &current).is_empty());
+
+        // No time elapsed, no rate
+        assert!(network_rates(&previous, &previous).is_empty());
+    }
+
+    #[test]
+    fn test_network_rate_limits() {
+        let thresholds = NetworkThresholds {
+            rx_bytes_per_sec: Some(Limit::new(1_000)),
+            tx_bytes_per_sec: None,
+        };
+        // Only configured directions are compared
+        let rate = NetworkRate { rx: 1_500.0, tx: 1_000_000.0 };
+        assert_eq!(rate.limits(&thresholds), vec![("receive", 1_500.0, Limit::new(1_000))]);
+    }
+
+    #[test]
+    fn test_format_rate() {
+        assert_eq!(format_rate(512.0), "512 B/s");
+        assert_eq!(format_rate(12_500_000.0), "12.5 MB/s");
+        assert_eq!(format_rate(2_000_000_000_000.0), "2000.0 GB/s");
+    }
+
+    #[test]
+    fn test_load_limits() {
+        let mut thresholds: ResourceThresholds = serde_yaml::from_str("alert: ops").unwrap();
+        let load = sysinfo::LoadAvg { one: 9.5, five: 6.0, fifteen: 3.0 };
+
+        // No load thresholds configured, nothing to compare
+        assert!(load_limits(&load, &thresholds).is_empty());
+
+        thresholds.load1 = Some(Limit::new(8.0));
+        thresholds.load15 = Some(Limit::new(2.0));
+        let exceeded: Vec<_> = load_limits(&load, &thresholds)
+            .into_iter()
+            .filter(|(_, load, limit)| *load > limit.above)
+            .map(|(window, _, _)| window)
+            .collect();
+        assert_eq!(exceeded, vec!["1m", "15m"]);
+
+        thresholds.load5 = Some(Limit::new(6.0));
+        assert_eq!(load_limits(&load, &thresholds)[1], ("5m", 6.0, Limit::new(6.0)));
+    }
+
+    #[test]
+    fn test_brief_spike_is_suppressed() {
+        let mut tracker = MetricTracker::default();
+        let start = Instant::now();
+        let sustain = Some(Duration::from_secs(300));
+        let tick = |n: u64| start + Duration::from_secs(10 * n);
+
+        assert_eq!(tracker.observe("cpu", true, sustain, tick(0)), MetricEvent::Quiet);
+        assert_eq!(tracker.observe("cpu", true, sustain, tick(1)), MetricEvent::Quiet);
+        // Dropping below the limit resets the clock, and nothing was reported so nothing recovers
+        assert_eq!(tracker.observe("cpu", false, sustain, tick(2)), MetricEvent::Quiet);
+        assert_eq!(tracker.observe("cpu", true, sustain, tick(31)), MetricEvent::Quiet);
+    }
+
+    #[test]
+    fn test_sustained_breach_alerts() {
+        let mut tracker = MetricTracker::default();
+        let start = Instant::now();
+        let sustain = Some(Duration::from_secs(300));
+
+        assert_eq!(tracker.observe("cpu", true, sustain, start), MetricEvent::Quiet);
+        assert_eq!(tracker.observe("cpu", true, sustain, start + Duration::from_secs(290)), MetricEvent::Quiet);
+        assert_eq!(tracker.observe("cpu", true, sustain, start + Duration::from_secs(300)), MetricEvent::Breached);
+        assert_eq!(tracker.observe("cpu", true, sustain, start + Duration::from_secs(310)), MetricEvent::Breached);
+
+        // Metrics are tracked independently, and limits without `for` alert at once
+        assert_eq!(tracker.observe("memory", true, sustain, start + Duration::from_secs(310)), MetricEvent::Quiet);
+        assert_eq!(tracker.observe("disk:/", true, None, start), MetricEvent::Breached);
+    }
+
+    #[test]
+    fn test_recovery_emitted_once_after_breach_clears() {
+        let mut tracker = MetricTracker::default();
+        let start = Instant::now();
+        let readings = [true, true, false, false, false, true, false];
+
+        let events: Vec<MetricEvent> = readings
+            .iter()
+            .enumerate()
+            .map(|(n, &exceeded)| tracker.observe("cpu", exceeded, None, start + Duration::from_secs(10 * n as u64)))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                MetricEvent::Breached,
+                MetricEvent::Breached,
+                MetricEvent::Recovered,
+                MetricEvent::Quiet,
+                MetricEvent::Quiet,
+                MetricEvent::Breached,
+                MetricEvent::Recovered,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_limit_accepts_value_or_sustained_form() {
+        let yaml = "cpu_percent: 85\nmemory_percent: { above: 90, for: 5m }\nalert: ops\n";
+        let thresholds: ResourceThresholds = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(thresholds.cpu_percent, Some(Limit::new(85.0)));
+        assert_eq!(
+            thresholds.memory_percent,
+            Some(Limit { above: 90.0, sustain: Some(Duration::from_secs(300)) })
+        );
+        assert_eq!(thresholds.memory_percent.unwrap().sustain_label(), " for 5m");
+
+        // Round-trips in the same shape it was written
+        let yaml = serde_yaml::to_string(&thresholds).unwrap();
+        assert!(yaml.contains("cpu_percent: 85"), "unexpected yaml: {}", yaml);
+        assert!(yaml.contains("for: 5m"), "unexpected yaml: {}", yaml);
+
+        assert!(serde_yaml::from_str::<ResourceThresholds>("cpu_percent: { above: 85, for: soon }\nalert: ops\n").is_err());
+    }
+
+    fn disks() -> Vec<DiskUsage> {
+        [("/", 100, 5), ("/boot", 100, 2), ("/data", 100, 15), ("/media/usb", 100, 0), ("/proc", 0, 0)]
+            .into_iter()
+            .map(|(mount_point, total, available)| DiskUsage {
+                mount_point: mount_point.to_string(),
+                total,
+                available,
+            })
+            .collect()
+    }
+
+    fn checked(thresholds: &ResourceThresholds) -> Vec<(String, f32)> {
+        disk_readings(&disks(), thresholds)
+            .into_iter()
+            .map(|(mount_point, _, limit)| (mount_point, limit.above))
+            .collect()
+    }
+
+    #[test]
+    fn test_disk_mount_overrides_and_exclude() {
+        let yaml = "disk_percent: 90\ndisk_mounts:\n  /data: { above: 80, for: 10m }\ndisk_exclude: [/boot, /media/*]\nalert: ops\n";
+        let thresholds: ResourceThresholds = serde_yaml::from_str(yaml).unwrap();
+
+        // Zero-sized pseudo filesystems are skipped too
+        assert_eq!(
+            checked(&thresholds),
+            vec![("/".to_string(), 90.0), ("/data".to_string(), 80.0)]
+        );
+        let readings = disk_readings(&disks(), &thresholds);
+        assert_eq!(readings[1].1, 85.0);
+        assert_eq!(readings[1].2.sustain, Some(Duration::from_secs(600)));
+    }
+
+    #[test]
+    fn test_disk_include_limits_checked_mounts() {
+        let yaml = "disk_percent: 90\ndisk_mounts:\n  /boot: 99\ndisk_include: [/]\nalert: ops\n";
+        let thresholds: ResourceThresholds = serde_yaml::from_str(yaml).unwrap();
+        // Mounts with their own threshold are always checked
+        assert_eq!(
+            checked(&thresholds),
+            vec![("/".to_string(), 90.0), ("/boot".to_string(), 99.0)]
+        );
+
+        // Without disk_percent only the listed mounts are checked
+        let thresholds: ResourceThresholds = serde_yaml::from_str("disk_mounts:\n  /data: 80\nalert: ops\n").unwrap();
+        assert_eq!(checked(&thresholds), vec![("/data".to_string(), 80.0)]);
+    }
+
+    #[test]
+    fn test_disk_selected_without_percent_threshold() {
+        // disk_available_min alone still honours the mount filters
+        let thresholds: ResourceThresholds =
+            serde_yaml::from_str("disk_available_min: 10GB\ndisk_exclude: [/boot]\nalert: ops\n").unwrap();
+        assert!(disk_selected("/", &thresholds));
+        assert!(!disk_selected("/boot", &thresholds));
+        assert!(checked(&thresholds).is_empty());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536 << 20), "1.5 GB");
+        assert_eq!(format_bytes(2 << 40), "2.0 TB");
+    }
+
+    struct CountingHandler(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl AlertHandler for CountingHandler {
+        async fn send(&self, _identity: &str, _rule_name: &str, _message: &str) -> Result<()> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "ops"
+        }
+    }
+
+    /// Alerts sent for two consecutive CPU breaches under the given resources config
+    async fn alerts_for_two_breaches(yaml: &str) -> usize {
+        let config: ResourceConfig = serde_yaml::from_str(yaml).unwrap();
+        let sent = Arc::new(AtomicUsize::new(0));
+        let mut alert_manager = AlertManager::new("test-host".to_string());
+        alert_manager.register("ops".to_string(), Arc::new(CountingHandler(sent.clone())));
+        let monitor = ResourceMonitor::new(config, Arc::new(alert_manager));
+
+        for usage in [95, 97] {
+            let message = format!("CPU usage is {}%", usage);
+            monitor.report("cpu", "cpu", true, None, message, String::new()).await;
+        }
+        sent.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_resource_cooldown_is_configurable() {
+        let thresholds = "thresholds:\n  cpu_percent: 90\n  alert: ops\n";
+
+        // Default cooldown is 6x the interval, so the second breach is held back
+        assert_eq!(alerts_for_two_breaches(thresholds).await, 1);
+        assert_eq!(alerts_for_two_breaches(&format!("cooldown: 0\n{}", thresholds)).await, 2);
+    }
+
+    #[test]
+    fn test_resource_cooldown_secs() {
+        let config: ResourceConfig = serde_yaml::from_str("interval: 10\nthresholds:\n  alert: ops\n").unwrap();
+        assert_eq!(config.cooldown_secs(), 60);
+
+        for cooldown in ["300", "5m"] {
+            let yaml = format!("interval: 10\ncooldown: {}\nthresholds:\n  alert: ops\n", cooldown);
+            let config: ResourceConfig = serde_yaml::from_str(&yaml).unwrap();
+            assert_eq!(config.cooldown_secs(), 300);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use crate::alerts::AlertManager;
+use crate::config::{render_message, resolve_routes, Condition, MatchType, Rule};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// What a rule looks for in a line
+pub enum Pattern {
+    Text(String),
+    /// `case_insensitive` text, with the needle already lowercased
+    TextIgnoreCase(String),
+    Regex(Regex),
+}
+
+/// The matching part of a compiled rule, shared by the log and stream monitors
+/// Decides whether a line matches and what its alert says and where it goes
+pub struct RuleMatcher {
+    pub pattern: Pattern,
+    /// Dotted path of the JSON field to match instead of the whole line
+    pub json_field: Option<String>,
+    /// Alert message template filled from the pattern's capture groups
+    pub message: Option<String>,
+    /// Lines matching this are ignored even if the rule matches
+    exclude: Option<Regex>,
+    exclude_text: Option<String>,
+    /// Named capture groups to include in alerts as structured fields
+    fields: Vec<String>,
+    /// Numeric condition on a named capture group that must hold for a match
+    condition: Option<Condition>,
+    /// Time after which the rule no longer applies
+    expires_at: Option<DateTime<Utc>>,
+    /// Line that resolves the rule once it has alerted
+    resolve: Option<Regex>,
+}
+
+/// Look up a dotted path (`level`, `error.code`) in a JSON line and return its value as text
+/// Non-JSON lines, missing fields and null, object or array values give `None`
+pub fn json_field_value(line: &str, path: &str) -> Option<String> {
+    let line = line.trim();
+    if !line.starts_with('{') {
+        return None;
+    }
+
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    match path.split('.').try_fold(&value, |value, key| value.get(key))? {
+        serde_json::Value::String(text) => Some(text.clone()),
+        serde_json::Value::Number(number) => Some(number.to_string()),
+        serde_json::Value::Bool(flag) => Some(flag.to_string()),
+        _ => None,
+    }
+}
+
+impl RuleMatcher {
+    /// Compile a rule's pattern and filters
+    pub fn new(rule: &Rule) -> Result<Self> {
+        let pattern = match rule.match_type() {
+            MatchType::Text(text) if rule.case_insensitive => Pattern::TextIgnoreCase(text.to_lowercase()),
+            MatchType::Text(text) => Pattern::Text(text),
+            MatchType::Regex(pattern) => Pattern::Regex(
+                Regex::new(&pattern).with_context(|| format!("Invalid regex pattern in rule: {}", rule.name))?,
+            ),
+        };
+
+        Ok(Self {
+            pattern,
+            json_field: rule.json_field.clone(),
+            message: rule.message.clone(),
+            exclude: rule
+                .exclude
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .with_context(|| format!("Invalid exclude pattern in rule: {}", rule.name))?,
+            exclude_text: rule.exclude_text.clone(),
+            fields: rule.fields.clone(),
+            condition: rule.condition.clone(),
+            expires_at: rule.expires_at,
+            resolve: rule
+                .resolve
+                .as_deref()
+                .map(Regex::new)
+                .transpose()
+                .with_context(|| format!("Invalid resolve pattern in rule: {}", rule.name))?,
+        })
+    }
+
+    /// Whether the rule's `expires_at` has passed as of `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        crate::config::is_expired(self.expires_at, now)
+    }
+
+    /// Whether the line is the rule's `resolve` line
+    pub fn resolves(&self, line: &str) -> bool {
+        self.resolve.as_ref().is_some_and(|resolve| resolve.is_match(line))
+    }
+
+    /// Whether the line hits one of the rule's `exclude` / `exclude_text` filters
+    fn is_excluded(&self, line: &str) -> bool {
+        self.exclude.as_ref().is_some_and(|exclude| exclude.is_match(line))
+            || self.exclude_text.as_ref().is_some_and(|text| line.contains(text.as_str()))
+    }
+
+    /// The text the pattern runs against: the line, or its `json_field` value
+    /// `None` when the rule targets a JSON field the line doesn't have
+    fn subject<'a>(&self, line: &'a str) -> Option<Cow<'a, str>> {
+        match &self.json_field {
+            None => Some(Cow::Borrowed(line)),
+            Some(path) => json_field_value(line, path).map(Cow::Owned),
+        }
+    }
+
+    /// Check whether a line matches this rule, including any numeric condition
+    pub fn matches(&self, line: &str) -> bool {
+        if self.is_excluded(line) {
+            return false;
+        }
+        let Some(subject) = self.subject(line) else {
+            return false;
+        };
+
+        match &self.pattern {
+            Pattern::Text(text) => subject.contains(text.as_str()),
+            Pattern::TextIgnoreCase(text) => subject.to_lowercase().contains(text.as_str()),
+            Pattern::Regex(regex) => match &self.condition {
+                None => regex.is_match(&subject),
+                Some(condition) => regex
+                    .captures(&subject)
+                    .and_then(|captures| captures.name(&condition.field))
+                    .is_some_and(|value| condition.is_met(value.as_str())),
+            },
+        }
+    }
+
+    /// The alert text for a matching line: the rendered `message` template, or the line itself
+    pub fn alert_message<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let Some(template) = &self.message else {
+            return Cow::Borrowed(line);
+        };
+
+        let subject = self.subject(line);
+        let captures = match (&self.pattern, subject.as_deref()) {
+            (Pattern::Regex(regex), Some(subject)) => regex.captures(subject),
+            _ => None,
+        };
+        Cow::Owned(render_message(template, captures.as_ref()))
+    }
+
+    /// Extract the configured named capture groups from a matching line
+    /// Groups that did not participate in the match are omitted
+    pub fn captured_fields(&self, line: &str) -> BTreeMap<String, String> {
+        let Pattern::Regex(regex) = &self.pattern else {
+            return BTreeMap::new();
+        };
+        if self.fields.is_empty() {
+            return BTreeMap::new();
+        }
+
+        let Some(subject) = self.subject(line) else {
+            return BTreeMap::new();
+        };
+        let Some(captures) = regex.captures(&subject) else {
+            return BTreeMap::new();
+        };
+
+        self.fields
+            .iter()
+            .filter_map(|field| {
+                captures
+                    .name(field)
+                    .map(|value| (field.clone(), value.as_str().to_string()))
+            })
+            .collect()
+    }
+
+    /// Resolve a rule's alert names for a matching line
+    /// `${group}` placeholders are filled from the line's named captures; routes that
+    /// can't be resolved to a registered alert are dropped
+    pub fn route(
+        &self,
+        rule_name: &str,
+        alert_names: &[String],
+        line: &str,
+        alert_manager: &AlertManager,
+    ) -> Vec<String> {
+        let subject = self.subject(line);
+        let captures = || match &self.pattern {
+            Pattern::Regex(regex) => subject.as_deref().and_then(|subject| regex.captures(subject)),
+            _ => None,
+        };
+        resolve_routes(rule_name, alert_names, captures, |alert| alert_manager.has_handler(alert))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(yaml: &str) -> RuleMatcher {
+        let rule: Rule = serde_yaml::from_str(yaml).unwrap();
+        RuleMatcher::new(&rule).unwrap()
+    }
+
+    #[test]
+    fn test_json_field_value() {
+        let line = r#"{"level":"error","status":503,"retry":false,"error":{"code":"E42","detail":null}}"#;
+        assert_eq!(json_field_value(line, "level").as_deref(), Some("error"));
+        assert_eq!(json_field_value(line, "status").as_deref(), Some("503"));
+        assert_eq!(json_field_value(line, "retry").as_deref(), Some("false"));
+        assert_eq!(json_field_value(line, "error.code").as_deref(), Some("E42"));
+        assert_eq!(json_field_value(line, "error.detail"), None);
+        assert_eq!(json_field_value(line, "error"), None);
+        assert_eq!(json_field_value(line, "missing"), None);
+        assert_eq!(json_field_value("level=error", "level"), None);
+        assert_eq!(json_field_value("{not json", "level"), None);
+    }
+
+    #[test]
+    fn test_condition_and_exclude() {
+        let matcher = matcher(
+            "name: slow\npattern: 'took (?P<ms>\\d+)ms'\ncondition: 'ms > 1000'\nexclude_text: healthz\nalert: [slack]",
+        );
+
+        assert!(matcher.matches("GET /api took 1500ms"));
+        assert!(!matcher.matches("GET /api took 20ms"));
+        assert!(!matcher.matches("GET /healthz took 1500ms"));
+    }
+
+    #[test]
+    fn test_json_field_subject() {
+        let matcher = matcher(
+            "name: errors\npattern: '^(?P<level>error)$'\njson_field: level\nfields: [level]\nmessage: 'level ${level}'\nalert: [slack]",
+        );
+        let line = r#"{"level":"error","msg":"boom"}"#;
+
+        assert!(matcher.matches(line));
+        assert!(!matcher.matches(r#"{"level":"info","msg":"error"}"#));
+        assert_eq!(matcher.alert_message(line), "level error");
+        assert_eq!(matcher.captured_fields(line).get("level").map(String::as_str), Some("error"));
+    }
+}
@@ -0,0 +1,234 @@
+use crate::alerts::AlertManager;
+#[cfg(unix)]
+use anyhow::Context;
+use anyhow::Result;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// How long alert deliveries already in progress get to finish once shutdown starts
+pub const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Delay before restarting a monitor that stopped, doubled after each quick failure
+const INITIAL_RESTART_DELAY: Duration = Duration::from_secs(1);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(60);
+/// A monitor that ran at least this long before stopping restarts with the initial delay again
+const HEALTHY_RUN: Duration = Duration::from_secs(60);
+
+/// Spawn a supervised monitor task that runs until `shutdown` is cancelled
+/// `monitor` builds a fresh run each time; a run that returns, fails or panics is logged and restarted with backoff
+/// A monitor in the middle of delivering an alert keeps running until deliveries finish, up to `GRACE_PERIOD`
+pub fn spawn<F, Fut>(
+    name: impl Into<String>,
+    shutdown: &CancellationToken,
+    alert_manager: &Arc<AlertManager>,
+    monitor: F,
+) -> JoinHandle<()>
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    let name = name.into();
+    let shutdown = shutdown.clone();
+    let alert_manager = alert_manager.clone();
+    tokio::spawn(async move {
+        let mut restart_delay = INITIAL_RESTART_DELAY;
+        loop {
+            let started = Instant::now();
+            let mut run = tokio::spawn(monitor());
+            let outcome = tokio::select! {
+                outcome = &mut run => outcome,
+                _ = shutdown.cancelled() => {
+                    tokio::select! {
+                        _ = &mut run => {}
+                        _ = tokio::time::timeout(GRACE_PERIOD, alert_manager.idle()) => {}
+                    }
+                    run.abort();
+                    return;
+                }
+            };
+
+            if started.elapsed() >= HEALTHY_RUN {
+                restart_delay = INITIAL_RESTART_DELAY;
+            }
+            match outcome {
+                Ok(Ok(())) => tracing::warn!("Monitor '{}' stopped; restarting in {:?}", name, restart_delay),
+                Ok(Err(e)) => tracing::error!("Monitor '{}' failed: {:#}; restarting in {:?}", name, e, restart_delay),
+                Err(e) => tracing::error!("Monitor '{}' panicked: {}; restarting in {:?}", name, e, restart_delay),
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(restart_delay) => {}
+                _ = shutdown.cancelled() => return,
+            }
+            restart_delay = (restart_delay * 2).min(MAX_RESTART_DELAY);
+        }
+    })
+}
+
+/// Resolves each time SIGTERM arrives, which is how systemd stops the service; never on platforms without it
+pub struct TerminateSignal {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl TerminateSignal {
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            #[cfg(unix)]
+            terminate: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+                .context("Failed to listen for SIGTERM")?,
+        })
+    }
+
+    pub async fn recv(&mut self) {
+        #[cfg(unix)]
+        self.terminate.recv().await;
+
+        #[cfg(not(unix))]
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Wait for monitor tasks to stop after shutdown, aborting any still running past the grace period
+pub async fn wait_for(tasks: Vec<JoinHandle<()>>) {
+    let abort_handles: Vec<_> = tasks.iter().map(|task| task.abort_handle()).collect();
+    let stopped = futures::future::join_all(tasks);
+    if tokio::time::timeout(GRACE_PERIOD + Duration::from_secs(1), stopped).await.is_err() {
+        tracing::warn!("Some monitors did not stop in time; aborting them");
+        for handle in abort_handles {
+            handle.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::AlertHandler;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_cancelling_token_stops_monitor() {
+        let shutdown = CancellationToken::new();
+        let alert_manager = Arc::new(AlertManager::new("test-host".to_string()));
+        let ticks = Arc::new(AtomicUsize::new(0));
+
+        let counter = ticks.clone();
+        let task = spawn("ticker", &shutdown, &alert_manager, move || {
+            let counter = counter.clone();
+            async move {
+                let mut interval = tokio::time::interval(Duration::from_millis(10));
+                loop {
+                    interval.tick().await;
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("monitor did not stop")
+            .unwrap();
+
+        let stopped_at = ticks.load(Ordering::SeqCst);
+        assert!(stopped_at > 0);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(ticks.load(Ordering::SeqCst), stopped_at);
+    }
+
+    struct SlowHandler(Arc<AtomicBool>);
+
+    #[async_trait]
+    impl AlertHandler for SlowHandler {
+        async fn send(&self, _identity: &str, _rule_name: &str, _message: &str) -> Result<()> {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            self.0.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "slow"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_alert_finishes_before_stop() {
+        let delivered = Arc::new(AtomicBool::new(false));
+        let mut alert_manager = AlertManager::new("test-host".to_string());
+        alert_manager.register("slow".to_string(), Arc::new(SlowHandler(delivered.clone())));
+        let alert_manager = Arc::new(alert_manager);
+        let shutdown = CancellationToken::new();
+
+        let manager = alert_manager.clone();
+        let task = spawn("alerting", &shutdown, &alert_manager, move || {
+            let manager = manager.clone();
+            async move {
+                let _ = manager
+                    .send_alert_multi(&["slow".to_string()], "errors", "ERROR boom", 0)
+                    .await;
+                std::future::pending::<Result<()>>().await
+            }
+        });
+
+        // Cancel while the delivery is still sleeping
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        shutdown.cancel();
+        wait_for(vec![task]).await;
+        assert!(delivered.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_failed_monitor_is_restarted() {
+        let shutdown = CancellationToken::new();
+        let alert_manager = Arc::new(AlertManager::new("test-host".to_string()));
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let counter = runs.clone();
+        let task = spawn("flaky", &shutdown, &alert_manager, move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                anyhow::bail!("connection lost")
+            }
+        });
+
+        // The first restart comes after INITIAL_RESTART_DELAY, the next one twice that
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        shutdown.cancel();
+        task.await.unwrap();
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_panicking_monitor_is_restarted() {
+        let shutdown = CancellationToken::new();
+        let alert_manager = Arc::new(AlertManager::new("test-host".to_string()));
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let counter = runs.clone();
+        let task = spawn("panicky", &shutdown, &alert_manager, move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    panic!("first run panics");
+                }
+                std::future::pending::<Result<()>>().await
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        shutdown.cancel();
+        task.await.unwrap();
+    }
+}
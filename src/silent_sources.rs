@@ -0,0 +1,199 @@
+use crate::alerts::AlertManager;
+use crate::config::{format_duration, SilentSourcesConfig, SourceType};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Rule name used for silent source alerts and their recoveries
+pub const SILENT_SOURCE_RULE: &str = "silent_source";
+
+struct SourceState {
+    last_line: Instant,
+    /// Alerted about this silence; cleared once lines resume
+    alerted: bool,
+}
+
+/// Tracks when each log source last produced a line and alerts when one goes quiet
+pub struct SilenceWatchdog {
+    alert_names: Vec<String>,
+    timeout: Duration,
+    source_timeouts: Vec<(glob::Pattern, Duration)>,
+    sources: Mutex<HashMap<SourceType, SourceState>>,
+    alert_manager: Arc<AlertManager>,
+}
+
+impl SilenceWatchdog {
+    pub fn new(config: &SilentSourcesConfig, alert_manager: Arc<AlertManager>) -> Result<Self> {
+        Ok(Self {
+            alert_names: config.alert.clone(),
+            timeout: config.timeout(),
+            source_timeouts: config.source_timeouts()?,
+            sources: Mutex::new(HashMap::new()),
+            alert_manager,
+        })
+    }
+
+    /// Start the clock for a source, so one that never logs anything is reported too
+    /// Watching a source again (e.g. after its watcher restarted) keeps its current state
+    pub fn watch(&self, source: SourceType) {
+        self.sources.lock().unwrap().entry(source).or_insert_with(|| SourceState {
+            last_line: Instant::now(),
+            alerted: false,
+        });
+    }
+
+    /// Stop tracking a source that is no longer watched
+    pub fn forget(&self, source: &SourceType) {
+        self.sources.lock().unwrap().remove(source);
+    }
+
+    /// Record a line; sources not watched yet (such as discovered containers) are tracked from here on
+    pub fn line_seen(&self, source: &SourceType) {
+        let now = Instant::now();
+        let mut sources = self.sources.lock().unwrap();
+        match sources.get_mut(source) {
+            Some(state) => state.last_line = now,
+            None => {
+                sources.insert(source.clone(), SourceState { last_line: now, alerted: false });
+            }
+        }
+    }
+
+    fn timeout_for(&self, source: &SourceType) -> Duration {
+        self.source_timeouts
+            .iter()
+            .find(|(pattern, _)| match source {
+                SourceType::File(path) => pattern.matches_path(path),
+                other => pattern.matches(&other.name()),
+            })
+            .map(|(_, timeout)| *timeout)
+            .unwrap_or(self.timeout)
+    }
+
+    /// Check for silent sources every second
+    pub async fn run(&self) {
+        let mut interval_timer = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval_timer.tick().await;
+            self.check(Instant::now()).await;
+        }
+    }
+
+    /// Alert once for each source silent past its timeout as of `now`, and once more when it logs again
+    async fn check(&self, now: Instant) {
+        let mut messages = Vec::new();
+        {
+            let mut sources = self.sources.lock().unwrap();
+            for (source, state) in sources.iter_mut() {
+                let timeout = self.timeout_for(source);
+                let silent = now.saturating_duration_since(state.last_line) >= timeout;
+                if silent && !state.alerted {
+                    state.alerted = true;
+                    messages.push(format!(
+                        "No logs from {} {} for {}",
+                        source.kind(),
+                        source.name(),
+                        format_duration(timeout)
+                    ));
+                } else if !silent && state.alerted {
+                    state.alerted = false;
+                    messages.push(format!("Logs from {} {} resumed", source.kind(), source.name()));
+                }
+            }
+        }
+        messages.sort();
+
+        for message in messages {
+            tracing::warn!("{}", message);
+            if let Err(e) = self
+                .alert_manager
+                .send_alert_multi(&self.alert_names, SILENT_SOURCE_RULE, &message, 0)
+                .await
+            {
+                tracing::error!("Failed to send silent source alert: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::AlertHandler;
+    use async_trait::async_trait;
+    use std::path::PathBuf;
+
+    struct RecordingHandler(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl AlertHandler for RecordingHandler {
+        async fn send(&self, _identity: &str, _rule_name: &str, message: &str) -> Result<()> {
+            self.0.lock().unwrap().push(message.to_string());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "ops"
+        }
+    }
+
+    fn watchdog(yaml: &str) -> (SilenceWatchdog, Arc<Mutex<Vec<String>>>) {
+        let config: SilentSourcesConfig = serde_yaml::from_str(yaml).unwrap();
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let mut alert_manager = AlertManager::new("test-host".to_string());
+        alert_manager.register("ops".to_string(), Arc::new(RecordingHandler(sent.clone())));
+        (SilenceWatchdog::new(&config, Arc::new(alert_manager)).unwrap(), sent)
+    }
+
+    #[tokio::test]
+    async fn test_silent_source_alerts_once_and_clears() {
+        let (watchdog, sent) = watchdog("alert: ops\ntimeout: 5m\n");
+        let source = SourceType::File(PathBuf::from("/var/log/app.log"));
+        watchdog.watch(source.clone());
+
+        watchdog.check(Instant::now() + Duration::from_secs(60)).await;
+        assert!(sent.lock().unwrap().is_empty());
+
+        let later = Instant::now() + Duration::from_secs(301);
+        watchdog.check(later).await;
+        watchdog.check(later + Duration::from_secs(60)).await;
+        assert_eq!(*sent.lock().unwrap(), vec!["No logs from file /var/log/app.log for 5m"]);
+
+        watchdog.line_seen(&source);
+        watchdog.check(Instant::now()).await;
+        assert_eq!(sent.lock().unwrap().len(), 2);
+        assert_eq!(sent.lock().unwrap()[1], "Logs from file /var/log/app.log resumed");
+
+        // Lines keep arriving: nothing more to report
+        watchdog.line_seen(&source);
+        watchdog.check(Instant::now()).await;
+        assert_eq!(sent.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_per_source_timeout() {
+        let (watchdog, sent) = watchdog("alert: ops\ntimeout: 1h\nsources:\n  \"/var/log/*.log\": 30s\n  nginx: 2m\n");
+        watchdog.watch(SourceType::File(PathBuf::from("/var/log/app.log")));
+        watchdog.watch(SourceType::Container("nginx".to_string()));
+        watchdog.watch(SourceType::Container("redis".to_string()));
+
+        watchdog.check(Instant::now() + Duration::from_secs(31)).await;
+        assert_eq!(*sent.lock().unwrap(), vec!["No logs from file /var/log/app.log for 30s"]);
+
+        watchdog.check(Instant::now() + Duration::from_secs(121)).await;
+        assert_eq!(sent.lock().unwrap().len(), 2);
+        assert_eq!(sent.lock().unwrap()[1], "No logs from container nginx for 2m");
+    }
+
+    #[tokio::test]
+    async fn test_forgotten_source_is_not_reported() {
+        let (watchdog, sent) = watchdog("alert: ops\ntimeout: 10s\n");
+        let source = SourceType::Stream("events".to_string());
+        watchdog.watch(source.clone());
+        watchdog.forget(&source);
+
+        watchdog.check(Instant::now() + Duration::from_secs(60)).await;
+        assert!(sent.lock().unwrap().is_empty());
+    }
+}
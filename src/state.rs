@@ -0,0 +1,290 @@
+use crate::alerts::AlertManager;
+use crate::log_monitor::LogMonitor;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often state is written to disk
+const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// File name inside the state directory
+const STATE_FILE: &str = "state.json";
+
+/// Marker written by `tinywatcher mute` inside the state directory
+const MUTE_FILE: &str = "mute.json";
+
+/// Cooldown, threshold and file offset state that survives restarts
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct PersistedState {
+    #[serde(default)]
+    pub cooldowns: BTreeMap<String, CooldownState>,
+    #[serde(default)]
+    pub thresholds: BTreeMap<String, Vec<ThresholdState>>,
+    /// How far each watched file has been read, by path
+    #[serde(default)]
+    pub offsets: BTreeMap<String, FileOffset>,
+}
+
+/// When alerts set by `tinywatcher mute` resume
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MuteMarker {
+    pub until: DateTime<Utc>,
+}
+
+/// Last alert for a rule and how long its cooldown lasts
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct CooldownState {
+    pub last_alert: DateTime<Utc>,
+    pub window_secs: u64,
+}
+
+/// A match recorded in a rule's threshold window
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ThresholdState {
+    pub at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<String>,
+}
+
+/// Read position in a watched file, and which file it belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct FileOffset {
+    /// Identity of the file (device, inode and creation time where available)
+    pub id: String,
+    pub offset: u64,
+}
+
+/// Pairs the monotonic and wall clocks so instants can be saved as timestamps
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    pub instant: Instant,
+    pub utc: DateTime<Utc>,
+}
+
+impl Clock {
+    pub fn now() -> Self {
+        Self {
+            instant: Instant::now(),
+            utc: Utc::now(),
+        }
+    }
+
+    pub fn timestamp(&self, instant: Instant) -> DateTime<Utc> {
+        let age = self.instant.saturating_duration_since(instant);
+        self.utc - chrono::Duration::from_std(age).unwrap_or_default()
+    }
+
+    /// Age of a saved timestamp, or None if it is in the future
+    pub fn age(&self, at: DateTime<Utc>) -> Option<Duration> {
+        (self.utc - at).to_std().ok()
+    }
+
+    /// Instant for a future timestamp, or None if it has already passed
+    pub fn instant_until(&self, at: DateTime<Utc>) -> Option<Instant> {
+        let remaining = (at - self.utc).to_std().ok()?;
+        self.instant.checked_add(remaining)
+    }
+
+    /// Instant for a saved timestamp, or None if it is older than this window (or than the monotonic clock)
+    pub fn instant_for(&self, at: DateTime<Utc>, window: Duration) -> Option<Instant> {
+        let age = self.age(at)?;
+        if age >= window {
+            return None;
+        }
+        self.instant.checked_sub(age)
+    }
+}
+
+fn state_path(dir: &Path) -> PathBuf {
+    dir.join(STATE_FILE)
+}
+
+/// Load saved state; a missing file is an empty state
+pub fn load(dir: &Path) -> Result<PersistedState> {
+    let path = state_path(dir);
+    if !path.exists() {
+        return Ok(PersistedState::default());
+    }
+
+    let contents = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read state file {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse state file {}", path.display()))
+}
+
+/// Write state atomically, so a crash mid-write can't leave a truncated file
+pub fn save(dir: &Path, state: &PersistedState) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create state directory {}", dir.display()))?;
+
+    let path = state_path(dir);
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(state)?)
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, &path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+/// Mute alerts from every watcher using `dir` until `until`
+pub fn save_mute(dir: &Path, until: DateTime<Utc>) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create state directory {}", dir.display()))?;
+
+    let path = dir.join(MUTE_FILE);
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec_pretty(&MuteMarker { until })?)
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    std::fs::rename(&tmp, &path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+/// When a saved mute ends, or None if nothing is muted (an expired marker still loads)
+pub fn load_mute(dir: &Path) -> Result<Option<DateTime<Utc>>> {
+    let path = dir.join(MUTE_FILE);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read mute marker {}", path.display())),
+    };
+    let marker: MuteMarker = serde_json::from_str(&contents)
+        .with_context(|| format!("Failed to parse mute marker {}", path.display()))?;
+    Ok(Some(marker.until))
+}
+
+/// When the mute marker was last written, or None if there is none
+pub async fn mute_modified(dir: &Path) -> Option<SystemTime> {
+    tokio::fs::metadata(dir.join(MUTE_FILE))
+        .await
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Remove the mute marker; returns whether there was one
+pub fn clear_mute(dir: &Path) -> Result<bool> {
+    let path = dir.join(MUTE_FILE);
+    match std::fs::remove_file(&path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e).with_context(|| format!("Failed to remove mute marker {}", path.display())),
+    }
+}
+
+/// Capture the current cooldown and threshold state
+pub async fn snapshot(alert_manager: &AlertManager, log_monitor: Option<&LogMonitor>) -> PersistedState {
+    let clock = Clock::now();
+    PersistedState {
+        cooldowns: alert_manager.export_cooldowns(&clock).await,
+        thresholds: match log_monitor {
+            Some(monitor) => monitor.export_thresholds(&clock).await,
+            None => BTreeMap::new(),
+        },
+        offsets: match log_monitor {
+            Some(monitor) => monitor.export_offsets().await,
+            None => BTreeMap::new(),
+        },
+    }
+}
+
+/// Restore saved state, discarding entries older than their window
+pub async fn restore(state: PersistedState, alert_manager: &AlertManager, log_monitor: Option<&LogMonitor>) {
+    let clock = Clock::now();
+    let cooldowns = alert_manager.restore_cooldowns(state.cooldowns, &clock).await;
+    let thresholds = match log_monitor {
+        Some(monitor) => monitor.restore_thresholds(state.thresholds, &clock).await,
+        None => 0,
+    };
+    let offsets = match log_monitor {
+        Some(monitor) => monitor.restore_offsets(state.offsets).await,
+        None => 0,
+    };
+    tracing::info!(
+        "Restored {} cooldown(s), {} threshold match(es) and {} file offset(s) from saved state",
+        cooldowns,
+        thresholds,
+        offsets
+    );
+}
+
+/// Periodically write state to `dir`
+pub async fn persist_periodically(dir: PathBuf, alert_manager: Arc<AlertManager>, log_monitor: Option<Arc<LogMonitor>>) {
+    let mut interval_timer = tokio::time::interval(FLUSH_INTERVAL);
+    interval_timer.tick().await;
+    loop {
+        interval_timer.tick().await;
+        let state = snapshot(&alert_manager, log_monitor.as_deref()).await;
+        if let Err(e) = save(&dir, &state) {
+            tracing::error!("Failed to save state: {:#}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = PersistedState::default();
+        state.cooldowns.insert(
+            "errors".to_string(),
+            CooldownState {
+                last_alert: "2025-01-01T12:00:00Z".parse().unwrap(),
+                window_secs: 60,
+            },
+        );
+        state.thresholds.insert(
+            "timeouts".to_string(),
+            vec![ThresholdState {
+                at: "2025-01-01T12:00:05Z".parse().unwrap(),
+                line: Some("upstream timed out".to_string()),
+            }],
+        );
+
+        state.offsets.insert(
+            "/var/log/app.log".to_string(),
+            FileOffset {
+                id: "2049:131".to_string(),
+                offset: 4096,
+            },
+        );
+
+        save(dir.path(), &state).unwrap();
+        assert_eq!(load(dir.path()).unwrap(), state);
+    }
+
+    #[test]
+    fn test_missing_state_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load(dir.path()).unwrap(), PersistedState::default());
+    }
+
+    #[test]
+    fn test_clock_drops_stale_entries() {
+        let clock = Clock::now();
+        let ten_secs_ago = clock.utc - chrono::Duration::seconds(10);
+
+        assert!(clock.instant_for(ten_secs_ago, Duration::from_secs(60)).is_some());
+        assert!(clock.instant_for(ten_secs_ago, Duration::from_secs(5)).is_none());
+    }
+
+    #[test]
+    fn test_mute_marker_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(load_mute(dir.path()).unwrap(), None);
+
+        let until: DateTime<Utc> = "2025-01-01T12:30:00Z".parse().unwrap();
+        save_mute(dir.path(), until).unwrap();
+        assert_eq!(load_mute(dir.path()).unwrap(), Some(until));
+
+        assert!(clear_mute(dir.path()).unwrap());
+        assert!(!clear_mute(dir.path()).unwrap());
+        assert_eq!(load_mute(dir.path()).unwrap(), None);
+    }
+}
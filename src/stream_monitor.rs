@@ -1,17 +1,33 @@
-use crate::alerts::AlertManager;
-use crate::config::{MatchType, Rule, SourceType, StreamConfig, StreamType};
+use crate::alerts::{AlertContext, AlertManager, AlertSource, AlertSourceKind};
+use crate::config::{route_cooldown_key, Rule, Severity, SourceType, StreamConfig, StreamType, Threshold};
+use crate::log_monitor::{split_line_endings, ThresholdMatch};
+use crate::rule_matcher::RuleMatcher;
+use crate::silent_sources::SilenceWatchdog;
 use anyhow::{Context, Result};
-use regex::Regex;
-use std::sync::Arc;
-use std::time::Duration;
+use chrono::Utc;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::Mutex;
 
 /// Maximum line length to prevent regex DoS
 const MAX_LINE_LENGTH: usize = 10_000;
 
+/// Rule name used for undecodable-data alerts
+pub const DECODE_ERRORS_RULE: &str = "stream_decode_errors";
+
 pub struct StreamMonitor {
-    rules: Vec<CompiledRule>,
+    /// Active rules, swapped as a whole on config reload
+    rules: RwLock<Arc<Vec<CompiledRule>>>,
     alert_manager: Arc<AlertManager>,
+    /// Recent frames or lines that weren't valid UTF-8, for `decode_errors` tracking
+    decode_failures: Mutex<VecDeque<Instant>>,
+    /// Notes every line so a stream that goes quiet can be reported
+    silence: Option<Arc<SilenceWatchdog>>,
+    /// ID of the last Server-Sent Event received, sent as `Last-Event-ID` when reconnecting
+    last_event_id: Mutex<Option<String>>,
 }
 
 struct CompiledRule {
@@ -20,52 +36,186 @@ struct CompiledRule {
     alert_names: Vec<String>,
     cooldown: u64,
     sources: Option<crate::config::RuleSources>,
+    threshold: Option<Threshold>,
+    /// Sliding window of match timestamps (and lines, if `include_matches` is set) for threshold tracking
+    match_history: Arc<Mutex<VecDeque<ThresholdMatch>>>,
+    /// Number of recent matching lines to include when the threshold fires
+    include_matches: usize,
+    severity: Severity,
+    /// Whether the rule has alerted since it was last resolved
+    firing: Arc<AtomicBool>,
+    /// Only alert on the first match for the life of the process
+    once: bool,
+    /// Set once a `once` rule has alerted
+    spent: Arc<AtomicBool>,
 }
 
-enum RuleMatcher {
-    Text(String),
-    Regex(Regex),
+/// A dispatched Server-Sent Event
+#[derive(Debug, PartialEq)]
+struct SseEvent {
+    event: Option<String>,
+    id: Option<String>,
+    /// `data:` lines of the event, joined with newlines
+    data: String,
 }
 
-impl StreamMonitor {
-    pub fn new(rules: Vec<Rule>, alert_manager: Arc<AlertManager>) -> Result<Self> {
-        let compiled_rules = rules
-            .into_iter()
-            .map(|rule| {
-                let matcher = match rule.match_type() {
-                    MatchType::Text(text) => RuleMatcher::Text(text),
-                    MatchType::Regex(pattern) => {
-                        let regex = Regex::new(&pattern)
-                            .with_context(|| format!("Invalid regex pattern in rule: {}", rule.name))?;
-                        RuleMatcher::Regex(regex)
-                    }
-                };
+/// Collects the fields of a Server-Sent Events stream line by line; a blank line ends an event
+/// `retry:` is ignored in favour of the stream's `reconnect_delay`
+#[derive(Default)]
+struct SseParser {
+    event: Option<String>,
+    /// Kept across events, as the last event ID is
+    id: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feed one line without its line ending; returns the event it completes, if any
+    fn feed_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            let event = self.event.take();
+            if self.data.is_empty() {
+                return None;
+            }
+            return Some(SseEvent {
+                event,
+                id: self.id.clone(),
+                data: std::mem::take(&mut self.data).join("\n"),
+            });
+        }
+        if line.starts_with(':') {
+            // Comment, commonly sent as a keep-alive
+            return None;
+        }
 
-                Ok(CompiledRule {
-                    name: rule.name.clone(),
-                    matcher,
-                    alert_names: rule.alert,
-                    cooldown: rule.cooldown,
-                    sources: rule.sources,
-                })
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "data" => self.data.push(value.to_string()),
+            "event" => self.event = Some(value.to_string()),
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            _ => {}
+        }
+        None
+    }
+}
+
+impl CompiledRule {
+    /// Record a match and report whether the rule's threshold (if any) is now reached,
+    /// with the most recent matching lines (up to `include_matches`) to report
+    /// The window starts over after it fires, like the log monitor
+    async fn threshold_reached(&self, line: &str) -> Option<Vec<String>> {
+        let Some(threshold) = &self.threshold else {
+            return Some(Vec::new());
+        };
+
+        let now = Instant::now();
+        let mut history = self.match_history.lock().await;
+        let kept_line = (self.include_matches > 0).then(|| line.to_string());
+        history.push_back((now, kept_line));
+
+        let cutoff = now - threshold.window;
+        while history.front().is_some_and(|&(oldest, _)| oldest < cutoff) {
+            history.pop_front();
+        }
+
+        let count = history.len();
+        if count < threshold.count as usize {
+            return None;
+        }
+        let skip = count.saturating_sub(self.include_matches);
+        Some(history.drain(..).skip(skip).filter_map(|(_, line)| line).collect())
+    }
+}
+
+/// Describe a stream for structured alert context
+fn stream_source(config: &StreamConfig) -> AlertSource {
+    let kind = match config.stream_type {
+        StreamType::Websocket => AlertSourceKind::Websocket,
+        StreamType::Http => AlertSourceKind::Http,
+        StreamType::Tcp => AlertSourceKind::Tcp,
+        StreamType::Sse => AlertSourceKind::Sse,
+    };
+
+    AlertSource {
+        kind,
+        name: config.get_name(),
+        url: Some(config.url.clone()),
+    }
+}
+
+/// Compile config rules for stream matching
+fn compile_rules(rules: Vec<Rule>) -> Result<Vec<CompiledRule>> {
+    let now = Utc::now();
+    rules
+        .into_iter()
+        // Disabled and expired rules are already reported by the log monitor
+        .filter(|rule| rule.enabled && !rule.is_expired(now))
+        // `expect_within` rules are tracked by the log monitor
+        .filter(|rule| rule.expect_within.is_none())
+        .map(|rule| {
+            let matcher = RuleMatcher::new(&rule)?;
+            Ok(CompiledRule {
+                name: rule.name.clone(),
+                matcher,
+                alert_names: rule.alert,
+                cooldown: rule.cooldown,
+                sources: rule.sources,
+                threshold: rule.threshold,
+                match_history: Arc::new(Mutex::new(VecDeque::new())),
+                include_matches: rule.include_matches,
+                severity: rule.severity,
+                firing: Arc::new(AtomicBool::new(false)),
+                once: rule.once,
+                spent: Arc::new(AtomicBool::new(false)),
             })
-            .collect::<Result<Vec<_>>>()?;
+        })
+        .collect::<Result<Vec<_>>>()
+}
 
+impl StreamMonitor {
+    pub fn new(rules: Vec<Rule>, alert_manager: Arc<AlertManager>) -> Result<Self> {
         Ok(Self {
-            rules: compiled_rules,
+            rules: RwLock::new(Arc::new(compile_rules(rules)?)),
             alert_manager,
+            decode_failures: Mutex::new(VecDeque::new()),
+            silence: None,
+            last_event_id: Mutex::new(None),
         })
     }
 
+    pub fn set_silence_watchdog(&mut self, watchdog: Arc<SilenceWatchdog>) {
+        self.silence = Some(watchdog);
+    }
+
+    /// Replace the running rules, keeping threshold windows, firing and `once` state of rules that kept their name
+    pub fn reload_rules(&self, rules: Vec<Rule>) -> Result<()> {
+        let mut compiled = compile_rules(rules)?;
+        let current = self.rules.read().unwrap().clone();
+        for rule in &mut compiled {
+            if let Some(previous) = current.iter().find(|previous| previous.name == rule.name) {
+                rule.match_history = previous.match_history.clone();
+                rule.firing = previous.firing.clone();
+                rule.spent = previous.spent.clone();
+            }
+        }
+
+        *self.rules.write().unwrap() = Arc::new(compiled);
+        Ok(())
+    }
+
     pub async fn watch_stream(&self, stream_config: StreamConfig) -> Result<()> {
         let stream_name = stream_config.get_name();
-        tracing::info!(" Starting stream monitoring: {}", stream_name);
+        tracing::info!("Starting stream monitoring: {}", stream_name);
 
         loop {
             let result = match stream_config.stream_type {
                 StreamType::Websocket => self.watch_websocket(&stream_config).await,
                 StreamType::Http => self.watch_http(&stream_config).await,
                 StreamType::Tcp => self.watch_tcp(&stream_config).await,
+                StreamType::Sse => self.watch_sse(&stream_config).await,
             };
 
             if let Err(e) = result {
@@ -92,14 +242,13 @@ impl StreamMonitor {
             .await
             .context("Failed to connect to WebSocket")?;
 
-        tracing::info!(" Connected to WebSocket: {}", config.url);
+        tracing::info!("Connected to WebSocket: {}", config.url);
 
         let (_, mut read) = ws_stream.split();
 
         while let Some(message) = read.next().await {
             match message {
                 Ok(Message::Text(text)) => {
-                    let source = SourceType::Stream(config.get_name());
                     for line in text.lines() {
                         if line.len() > MAX_LINE_LENGTH {
                             tracing::warn!(
@@ -109,24 +258,11 @@ impl StreamMonitor {
                             );
                             continue;
                         }
-                        self.process_line(line, &source).await;
+                        self.process_line(line, config).await;
                     }
                 }
                 Ok(Message::Binary(data)) => {
-                    if let Ok(text) = String::from_utf8(data) {
-                        let source = SourceType::Stream(config.get_name());
-                        for line in text.lines() {
-                            if line.len() > MAX_LINE_LENGTH {
-                                tracing::warn!(
-                                    "Skipping line longer than {} bytes in stream {}",
-                                    MAX_LINE_LENGTH,
-                                    config.get_name()
-                                );
-                                continue;
-                            }
-                            self.process_line(line, &source).await;
-                        }
-                    }
+                    self.process_binary_frame(data, config).await;
                 }
                 Ok(Message::Close(_)) => {
                     tracing::warn!("WebSocket closed by server");
@@ -172,7 +308,7 @@ impl StreamMonitor {
             ));
         }
 
-        tracing::info!("✅ Connected to HTTP stream: {}", config.url);
+        tracing::info!("Connected to HTTP stream: {}", config.url);
 
         let mut stream = response.bytes_stream();
         let mut buffer = Vec::new();
@@ -187,20 +323,21 @@ impl StreamMonitor {
             // Process complete lines
             while let Some(newline_pos) = buffer.iter().position(|&b| b == b'\n') {
                 let line_bytes = buffer.drain(..=newline_pos).collect::<Vec<_>>();
-                if let Ok(line) = String::from_utf8(line_bytes) {
-                    let line = line.trim();
-                    if !line.is_empty() {
-                        if line.len() > MAX_LINE_LENGTH {
-                            tracing::warn!(
-                                "Skipping line longer than {} bytes in stream {}",
-                                MAX_LINE_LENGTH,
-                                config.get_name()
-                            );
-                            continue;
-                        }
-                        let source = SourceType::Stream(config.get_name());
-                        self.process_line(line, &source).await;
+                let Ok(line) = String::from_utf8(line_bytes) else {
+                    self.record_decode_failure(config).await;
+                    continue;
+                };
+                let line = line.trim();
+                if !line.is_empty() {
+                    if line.len() > MAX_LINE_LENGTH {
+                        tracing::warn!(
+                            "Skipping line longer than {} bytes in stream {}",
+                            MAX_LINE_LENGTH,
+                            config.get_name()
+                        );
+                        continue;
                     }
+                    self.process_line(line, config).await;
                 }
             }
         }
@@ -224,13 +361,17 @@ impl StreamMonitor {
             .await
             .context("Failed to connect to TCP stream")?;
 
-        tracing::info!("✅ Connected to TCP stream: {}", addr);
+        tracing::info!("Connected to TCP stream: {}", addr);
 
         let reader = BufReader::new(stream);
-        let mut lines = reader.lines();
+        // Split on raw bytes so a non-UTF-8 line is counted instead of ending the connection
+        let mut lines = reader.split(b'\n');
 
-        let source = SourceType::Stream(config.get_name());
-        while let Some(line) = lines.next_line().await? {
+        while let Some(bytes) = lines.next_segment().await? {
+            let Ok(line) = String::from_utf8(bytes) else {
+                self.record_decode_failure(config).await;
+                continue;
+            };
             if line.len() > MAX_LINE_LENGTH {
                 tracing::warn!(
                     "Skipping line longer than {} bytes in stream {}",
@@ -239,49 +380,251 @@ impl StreamMonitor {
                 );
                 continue;
             }
-            self.process_line(&line, &source).await;
+            self.process_line(&line, config).await;
         }
 
         Err(anyhow::anyhow!("TCP stream ended"))
     }
 
-    async fn process_line(&self, line: &str, source: &SourceType) {
-        for rule in &self.rules {
+    async fn watch_sse(&self, config: &StreamConfig) -> Result<()> {
+        use futures_util::StreamExt;
+        use reqwest::header::ACCEPT;
+
+        tracing::debug!("Connecting to SSE stream: {}", config.url);
+
+        let mut request = reqwest::Client::new()
+            .get(&config.url)
+            .header(ACCEPT, "text/event-stream");
+        if let Some(headers) = &config.headers {
+            for (key, value) in headers {
+                request = request.header(key, value);
+            }
+        }
+        // Lets servers that keep a backlog replay events missed while disconnected
+        if let Some(id) = self.last_event_id.lock().await.clone() {
+            request = request.header("Last-Event-ID", id);
+        }
+
+        let response = request
+            .send()
+            .await
+            .context("Failed to connect to SSE stream")?;
+
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "SSE stream returned error: {}",
+                response.status()
+            ));
+        }
+
+        tracing::info!("Connected to SSE stream: {}", config.url);
+
+        let mut stream = response.bytes_stream();
+        let mut buffer = Vec::new();
+        let mut parser = SseParser::default();
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.context("Failed to read SSE stream chunk")?;
+            buffer.extend_from_slice(&chunk);
+
+            while let Some(newline_pos) = buffer.iter().position(|&b| b == b'\n') {
+                let line_bytes = buffer.drain(..=newline_pos).collect::<Vec<_>>();
+                let Ok(line) = String::from_utf8(line_bytes) else {
+                    self.record_decode_failure(config).await;
+                    continue;
+                };
+                let Some(event) = parser.feed_line(line.trim_end_matches(['\r', '\n'])) else {
+                    continue;
+                };
+
+                tracing::debug!(
+                    "SSE event {} (id {}) from stream {}",
+                    event.event.as_deref().unwrap_or("message"),
+                    event.id.as_deref().unwrap_or("none"),
+                    config.get_name()
+                );
+                if event.id.is_some() {
+                    *self.last_event_id.lock().await = event.id;
+                }
+                for line in event.data.lines() {
+                    if line.len() > MAX_LINE_LENGTH {
+                        tracing::warn!(
+                            "Skipping line longer than {} bytes in stream {}",
+                            MAX_LINE_LENGTH,
+                            config.get_name()
+                        );
+                        continue;
+                    }
+                    self.process_line(line, config).await;
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!("SSE stream ended"))
+    }
+
+    /// Process a binary WebSocket frame as text, counting it as a decode failure if it isn't UTF-8
+    async fn process_binary_frame(&self, data: Vec<u8>, config: &StreamConfig) {
+        let Ok(text) = String::from_utf8(data) else {
+            self.record_decode_failure(config).await;
+            return;
+        };
+
+        for line in text.lines() {
+            if line.len() > MAX_LINE_LENGTH {
+                tracing::warn!(
+                    "Skipping line longer than {} bytes in stream {}",
+                    MAX_LINE_LENGTH,
+                    config.get_name()
+                );
+                continue;
+            }
+            self.process_line(line, config).await;
+        }
+    }
+
+    /// Count undecodable data and alert once the stream's `decode_errors` threshold is reached
+    async fn record_decode_failure(&self, config: &StreamConfig) {
+        tracing::debug!("Dropping data that isn't valid UTF-8 from stream {}", config.get_name());
+
+        let Some(decode_errors) = &config.decode_errors else {
+            return;
+        };
+        let threshold = decode_errors.threshold();
+
+        let now = Instant::now();
+        let mut failures = self.decode_failures.lock().await;
+        failures.push_back(now);
+        let cutoff = now - threshold.window;
+        while failures.front().is_some_and(|&oldest| oldest < cutoff) {
+            failures.pop_front();
+        }
+        if failures.len() < threshold.count as usize {
+            return;
+        }
+        let count = failures.len();
+        failures.clear();
+        drop(failures);
+
+        let message = format!(
+            "Stream '{}' sent {} frames or lines that are not valid UTF-8 within {:?}; check the endpoint URL and stream type",
+            config.get_name(),
+            count,
+            threshold.window
+        );
+        tracing::warn!("{}", message);
+
+        let context = AlertContext {
+            source: Some(stream_source(config)),
+            severity: Severity::Warning,
+            ..Default::default()
+        };
+        if let Err(e) = self
+            .alert_manager
+            .send_alert_multi_with_context(&decode_errors.alert, DECODE_ERRORS_RULE, &message, 0, &context)
+            .await
+        {
+            tracing::error!("Failed to send decode error alert for stream '{}': {}", config.get_name(), e);
+        }
+    }
+
+    async fn process_line(&self, line: &str, config: &StreamConfig) {
+        for line in split_line_endings(line) {
+            self.evaluate_line(line, config).await;
+        }
+    }
+
+    async fn evaluate_line(&self, line: &str, config: &StreamConfig) {
+        let source_name = config.get_name();
+        let source = SourceType::Stream(source_name.clone());
+        crate::metrics::line_processed(&source);
+        if let Some(silence) = &self.silence {
+            silence.line_seen(&source);
+        }
+        let now = Utc::now();
+
+        let rules = self.rules.read().unwrap().clone();
+        for rule in rules.iter() {
             // Check if rule applies to this source
-            if !self.rule_applies_to_source(rule, source) {
+            if !self.rule_applies_to_source(rule, &source) || rule.matcher.is_expired(now) {
                 continue;
             }
 
-            let matched = match &rule.matcher {
-                RuleMatcher::Text(text) => line.contains(text),
-                RuleMatcher::Regex(regex) => regex.is_match(line),
-            };
+            if rule.matcher.resolves(line) {
+                if rule.firing.swap(false, Ordering::SeqCst) {
+                    tracing::info!("Rule '{}' resolved in stream '{}'", rule.name, source_name);
+                    self.alert_manager
+                        .send_recovery(&rule.alert_names, &rule.name, line, Some(stream_source(config)))
+                        .await;
+                }
+                continue;
+            }
 
-            if matched {
-                let source_name = match source {
-                    SourceType::Stream(name) => name.clone(),
-                    _ => format!("{:?}", source),
-                };
+            let matched = rule.matcher.matches(line);
 
+            if matched {
                 tracing::info!(
-                    "🔔 Rule '{}' matched in stream '{}': {}",
+                    "Rule '{}' matched in stream '{}': {}",
                     rule.name,
                     source_name,
                     line
                 );
 
-                let message = format!(
-                    "Rule '{}' triggered\nStream: {}\nLine: {}",
-                    rule.name, source_name, line
-                );
+                let Some(recent) = rule.threshold_reached(line).await else {
+                    continue;
+                };
+
+                let alert_names = rule.matcher.route(&rule.name, &rule.alert_names, line, &self.alert_manager);
+                if alert_names.is_empty() {
+                    continue;
+                }
+
+                if rule.once && rule.spent.swap(true, Ordering::SeqCst) {
+                    continue;
+                }
+
+                let mut message = match rule.matcher.message {
+                    Some(_) => format!(
+                        "Rule '{}' triggered\nStream: {}\n{}",
+                        rule.name,
+                        source_name,
+                        rule.matcher.alert_message(line)
+                    ),
+                    None => format!(
+                        "Rule '{}' triggered\nStream: {}\nLine: {}",
+                        rule.name, source_name, line
+                    ),
+                };
+                if !recent.is_empty() {
+                    message.push_str(&format!(
+                        "\n\nLast {} matching lines:\n{}",
+                        recent.len(),
+                        recent.join("\n")
+                    ));
+                }
+
+                let context = AlertContext {
+                    fields: rule.matcher.captured_fields(line),
+                    source: Some(stream_source(config)),
+                    severity: rule.severity,
+                };
 
                 // Send alert to all configured handlers
-                if let Err(e) = self
+                let sent = self
                     .alert_manager
-                    .send_alert_multi(&rule.alert_names, &rule.name, &message, rule.cooldown)
-                    .await
-                {
-                    tracing::error!("Failed to send alert for rule '{}': {}", rule.name, e);
+                    .send_routed_alert(
+                        &alert_names,
+                        &rule.name,
+                        &route_cooldown_key(&rule.name, &rule.alert_names, &alert_names),
+                        &message,
+                        rule.cooldown,
+                        &context,
+                    )
+                    .await;
+                match sent {
+                    Ok(true) => rule.firing.store(true, Ordering::SeqCst),
+                    Ok(false) => {}
+                    Err(e) => tracing::error!("Failed to send alert for rule '{}': {}", rule.name, e),
                 }
             }
         }
@@ -312,6 +655,30 @@ impl StreamMonitor {
                 }
                 sources.streams.iter().any(|s| s == name)
             }
+            #[cfg(windows)]
+            SourceType::EventLog(log) => {
+                if sources.windows_eventlog.is_empty() {
+                    return false;
+                }
+                sources.windows_eventlog.iter().any(|l| l.eq_ignore_ascii_case(log))
+            }
+            SourceType::Journal(name) => {
+                if sources.journals.is_empty() {
+                    return false;
+                }
+                sources.journals.iter().any(|j| j == name)
+            }
+            SourceType::Pod(name) => {
+                if sources.pods.is_empty() {
+                    return false;
+                }
+                sources.pods.iter().any(|p| p == name)
+            }
         }
     }
 }
+
+#[cfg(test)]
+#[path = "stream_monitor_tests.rs"]
+#[allow(clippy::module_inception)]
+mod tests;
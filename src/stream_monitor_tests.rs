@@ -0,0 +1,531 @@
+#[cfg(test)]
+mod tests {
+    use crate::alerts::{AlertContext, AlertHandler, AlertManager, AlertSource, AlertSourceKind};
+    use crate::config::{DecodeErrorsConfig, Rule, Severity, StreamConfig, StreamType, Threshold};
+    use crate::stream_monitor::{SseEvent, SseParser, StreamMonitor};
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use std::sync::Arc;
+
+    type LastAlert = Arc<tokio::sync::Mutex<Option<(String, AlertContext)>>>;
+
+    // Mock alert handler that records the message and context it receives
+    struct ContextAlertHandler {
+        name: String,
+        last_alert: LastAlert,
+    }
+
+    #[async_trait]
+    impl AlertHandler for ContextAlertHandler {
+        async fn send(&self, _identity: &str, _rule_name: &str, _message: &str) -> Result<()> {
+            Ok(())
+        }
+
+        async fn send_with_context(
+            &self,
+            _identity: &str,
+            _rule_name: &str,
+            message: &str,
+            context: &AlertContext,
+        ) -> Result<()> {
+            *self.last_alert.lock().await = Some((message.to_string(), context.clone()));
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            &self.name
+        }
+    }
+
+    fn create_test_monitor() -> (StreamMonitor, LastAlert) {
+        monitor_for(test_rule())
+    }
+
+    fn test_rule() -> Rule {
+        Rule {
+            name: "error_rule".to_string(),
+            text: Some("ERROR".to_string()),
+            pattern: None,
+            alert: vec!["test-alert".to_string()],
+            cooldown: 0,
+            sources: None,
+            threshold: None,
+            fields: vec![],
+            condition: None,
+            expect_within: None,
+            include_matches: 0,
+            severity: Severity::Error,
+            expires_at: None,
+            enabled: true,
+            burst: 0,
+            resolve: None,
+            once: false,
+            exclude: None,
+            exclude_text: None,
+            case_insensitive: false,
+            multiline: None,
+            json_field: None,
+            message: None,
+        }
+    }
+
+    fn monitor_for(rule: Rule) -> (StreamMonitor, LastAlert) {
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        let last_alert = Arc::new(tokio::sync::Mutex::new(None));
+        alert_manager.register(
+            "test-alert".to_string(),
+            Arc::new(ContextAlertHandler {
+                name: "test-alert".to_string(),
+                last_alert: last_alert.clone(),
+            }),
+        );
+
+        let monitor = StreamMonitor::new(vec![rule], Arc::new(alert_manager)).unwrap();
+        (monitor, last_alert)
+    }
+
+    fn stream_config(name: &str, stream_type: StreamType, url: &str) -> StreamConfig {
+        StreamConfig {
+            name: Some(name.to_string()),
+            stream_type,
+            url: url.to_string(),
+            headers: None,
+            reconnect_delay: None,
+            decode_errors: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream_source_in_context() {
+        let (monitor, last_alert) = create_test_monitor();
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        monitor.process_line("ERROR upstream timeout", &config).await;
+
+        let (message, context) = last_alert.lock().await.take().unwrap();
+        assert!(message.contains("Stream: app_events"));
+        assert_eq!(
+            context.source,
+            Some(AlertSource {
+                kind: AlertSourceKind::Websocket,
+                name: "app_events".to_string(),
+                url: Some("wss://example.com/logs".to_string()),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_source_type_follows_config() {
+        let (monitor, last_alert) = create_test_monitor();
+
+        monitor
+            .process_line("ERROR", &stream_config("ingest", StreamType::Tcp, "tcp://localhost:9000"))
+            .await;
+        let (_, context) = last_alert.lock().await.take().unwrap();
+        assert_eq!(context.source.unwrap().kind, AlertSourceKind::Tcp);
+
+        monitor
+            .process_line("ERROR", &stream_config("feed", StreamType::Http, "https://example.com/feed"))
+            .await;
+        let (_, context) = last_alert.lock().await.take().unwrap();
+        assert_eq!(context.source.unwrap().kind, AlertSourceKind::Http);
+    }
+
+    #[tokio::test]
+    async fn test_stream_no_match_no_alert() {
+        let (monitor, last_alert) = create_test_monitor();
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        monitor.process_line("INFO all good", &config).await;
+        assert!(last_alert.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_text_rule_matches_literally() {
+        let mut rule = test_rule();
+        rule.text = Some("disk [full]".to_string());
+        let (monitor, last_alert) = monitor_for(rule);
+        let config = stream_config("ingest", StreamType::Tcp, "tcp://localhost:9000");
+
+        // Regex metacharacters in `text` are not interpreted
+        monitor.process_line("disk f", &config).await;
+        assert!(last_alert.lock().await.is_none());
+
+        monitor.process_line("WARN disk [full] on /var", &config).await;
+        assert!(last_alert.lock().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_pattern_rule_uses_regex() {
+        let mut rule = test_rule();
+        rule.text = None;
+        rule.pattern = Some(r"status=5\d\d".to_string());
+        let (monitor, last_alert) = monitor_for(rule);
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        monitor.process_line("GET / status=200", &config).await;
+        assert!(last_alert.lock().await.is_none());
+
+        monitor.process_line("GET / status=503", &config).await;
+        assert!(last_alert.lock().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_on_stream_rule_sends_recovery() {
+        let mut rule = test_rule();
+        rule.text = Some("connection to db lost".to_string());
+        rule.resolve = Some("connection to db restored".to_string());
+        let (monitor, last_alert) = monitor_for(rule);
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        // Nothing to recover from yet
+        monitor.process_line("INFO connection to db restored", &config).await;
+        assert!(last_alert.lock().await.is_none());
+
+        monitor.process_line("WARN connection to db lost", &config).await;
+        assert!(last_alert.lock().await.take().is_some());
+
+        monitor.process_line("INFO connection to db restored", &config).await;
+        let (message, context) = last_alert.lock().await.take().unwrap();
+        assert_eq!(message, "Rule 'error_rule' resolved\nINFO connection to db restored");
+        assert_eq!(context.severity, Severity::Info);
+        assert_eq!(context.source.unwrap().name, "app_events");
+
+        // Already resolved: no second recovery
+        monitor.process_line("INFO connection to db restored", &config).await;
+        assert!(last_alert.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_threshold_on_stream_rule() {
+        let mut rule = test_rule();
+        rule.threshold = Some(crate::config::Threshold::parse("5 in 2s").unwrap());
+        let (monitor, last_alert) = monitor_for(rule);
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        for _ in 0..4 {
+            monitor.process_line("ERROR upstream timeout", &config).await;
+            assert!(last_alert.lock().await.is_none());
+        }
+
+        monitor.process_line("ERROR upstream timeout", &config).await;
+        assert!(last_alert.lock().await.take().is_some());
+
+        // The window starts over after alerting
+        monitor.process_line("ERROR upstream timeout", &config).await;
+        assert!(last_alert.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_threshold_on_stream_rule_includes_matches() {
+        let mut rule = test_rule();
+        rule.threshold = Some(crate::config::Threshold::parse("3 in 10s").unwrap());
+        rule.include_matches = 2;
+        let (monitor, last_alert) = monitor_for(rule);
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        for i in 1..=3 {
+            monitor.process_line(&format!("ERROR timeout #{}", i), &config).await;
+        }
+
+        let (message, _) = last_alert.lock().await.take().unwrap();
+        assert_eq!(
+            message,
+            "Rule 'error_rule' triggered\nStream: app_events\nLine: ERROR timeout #3\n\n\
+             Last 2 matching lines:\nERROR timeout #2\nERROR timeout #3"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_case_insensitive_text_on_stream_rule() {
+        let mut rule = test_rule();
+        rule.case_insensitive = true;
+        let (monitor, last_alert) = monitor_for(rule);
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        monitor.process_line("upstream error: timeout", &config).await;
+        assert!(last_alert.lock().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_json_field_on_stream_rule() {
+        let mut rule = test_rule();
+        rule.json_field = Some("level".to_string());
+        let (monitor, last_alert) = monitor_for(rule);
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        monitor.process_line(r#"{"level":"INFO","msg":"ERROR in a message"}"#, &config).await;
+        assert!(last_alert.lock().await.is_none());
+
+        monitor.process_line(r#"{"level":"ERROR","msg":"boom"}"#, &config).await;
+        assert!(last_alert.lock().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_message_template_on_stream_rule() {
+        let mut rule = test_rule();
+        rule.text = None;
+        rule.pattern = Some(r"ERROR code=(?P<code>\d+)".to_string());
+        rule.message = Some("upstream failed with ${code}".to_string());
+        let (monitor, last_alert) = monitor_for(rule);
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        monitor.process_line("ERROR code=504 from gateway", &config).await;
+        let (message, _) = last_alert.lock().await.take().unwrap();
+        assert_eq!(message, "Rule 'error_rule' triggered\nStream: app_events\nupstream failed with 504");
+    }
+
+    /// Serve `payload` to the first connection on a local port, then close it
+    async fn serve_once(payload: impl AsRef<[u8]> + Send + 'static) -> std::net::SocketAddr {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let _ = tokio::time::timeout(std::time::Duration::from_millis(100), socket.read(&mut buf)).await;
+            socket.write_all(payload.as_ref()).await.unwrap();
+        });
+        addr
+    }
+
+    fn anchored_monitor() -> (StreamMonitor, LastAlert) {
+        let mut rule = test_rule();
+        rule.text = None;
+        rule.pattern = Some("ERROR$".to_string());
+        monitor_for(rule)
+    }
+
+    #[tokio::test]
+    async fn test_tcp_crlf_lines_match_anchored_pattern() {
+        let (monitor, last_alert) = anchored_monitor();
+        let addr = serve_once("INFO ok\r\ndisk ERROR\r\n").await;
+
+        let config = stream_config("ingest", StreamType::Tcp, &format!("tcp://{}", addr));
+        assert!(monitor.watch_tcp(&config).await.is_err());
+
+        let (message, _) = last_alert.lock().await.take().unwrap();
+        assert!(message.ends_with("Line: disk ERROR"), "unexpected message: {}", message);
+    }
+
+    #[tokio::test]
+    async fn test_http_bare_cr_lines_match_anchored_pattern() {
+        let (monitor, last_alert) = anchored_monitor();
+        let addr = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\nfan ERROR\rINFO ok\rpsu ERROR\r\n",
+        )
+        .await;
+
+        let config = stream_config("feed", StreamType::Http, &format!("http://{}/logs", addr));
+        assert!(monitor.watch_http(&config).await.is_err());
+
+        let (message, _) = last_alert.lock().await.take().unwrap();
+        assert!(message.ends_with("Line: psu ERROR"), "unexpected message: {}", message);
+    }
+
+    #[tokio::test]
+    async fn test_exclude_on_stream_rule() {
+        let mut rule = test_rule();
+        rule.exclude = Some("HealthCheck".to_string());
+        let (monitor, last_alert) = monitor_for(rule);
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        monitor.process_line("ERROR HealthCheck failed", &config).await;
+        assert!(last_alert.lock().await.is_none());
+
+        monitor.process_line("ERROR payment failed", &config).await;
+        assert!(last_alert.lock().await.is_some());
+    }
+
+    fn decode_errors_config(name: &str, stream_type: StreamType, url: &str) -> StreamConfig {
+        let mut config = stream_config(name, stream_type, url);
+        config.decode_errors = Some(DecodeErrorsConfig {
+            alert: vec!["test-alert".to_string()],
+            threshold: Some(Threshold::parse("3 in 1m").unwrap()),
+        });
+        config
+    }
+
+    #[tokio::test]
+    async fn test_binary_frames_alert_after_threshold() {
+        let (monitor, last_alert) = create_test_monitor();
+        let config = decode_errors_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        monitor.process_binary_frame(vec![0xff, 0xfe, 0x00], &config).await;
+        monitor.process_binary_frame(vec![0xc3, 0x28], &config).await;
+        assert!(last_alert.lock().await.is_none());
+
+        monitor.process_binary_frame(vec![0x80, 0x81, 0x82], &config).await;
+        let (message, context) = last_alert.lock().await.take().unwrap();
+        assert!(message.contains("Stream 'app_events' sent 3"), "unexpected message: {}", message);
+        assert_eq!(context.severity, Severity::Warning);
+        assert_eq!(context.source.unwrap().kind, AlertSourceKind::Websocket);
+
+        // The count starts over after alerting
+        monitor.process_binary_frame(vec![0xff], &config).await;
+        assert!(last_alert.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_valid_binary_frames_are_processed_as_text() {
+        let (monitor, last_alert) = create_test_monitor();
+        let config = decode_errors_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        monitor.process_binary_frame(b"INFO ok\nERROR boom".to_vec(), &config).await;
+        let (message, _) = last_alert.lock().await.take().unwrap();
+        assert!(message.ends_with("Line: ERROR boom"), "unexpected message: {}", message);
+    }
+
+    #[tokio::test]
+    async fn test_binary_frames_without_decode_errors_config_are_dropped() {
+        let (monitor, last_alert) = create_test_monitor();
+        let config = stream_config("app_events", StreamType::Websocket, "wss://example.com/logs");
+
+        for _ in 0..10 {
+            monitor.process_binary_frame(vec![0xff, 0xfe], &config).await;
+        }
+        assert!(last_alert.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_invalid_utf8_lines_do_not_end_connection() {
+        let (monitor, last_alert) = create_test_monitor();
+        let addr = serve_once(&b"\xff\xfe\n\xc3\x28\n\x80\n"[..]).await;
+
+        let config = decode_errors_config("ingest", StreamType::Tcp, &format!("tcp://{}", addr));
+        assert!(monitor.watch_tcp(&config).await.is_err());
+
+        let (message, context) = last_alert.lock().await.take().unwrap();
+        assert!(message.contains("Stream 'ingest' sent 3"), "unexpected message: {}", message);
+        assert_eq!(context.source.unwrap().kind, AlertSourceKind::Tcp);
+    }
+
+    #[tokio::test]
+    async fn test_tcp_lines_after_invalid_utf8_still_match() {
+        let (monitor, last_alert) = create_test_monitor();
+        let addr = serve_once(&b"\xff\xfe\nERROR after garbage\n"[..]).await;
+
+        let config = stream_config("ingest", StreamType::Tcp, &format!("tcp://{}", addr));
+        assert!(monitor.watch_tcp(&config).await.is_err());
+
+        let (message, _) = last_alert.lock().await.take().unwrap();
+        assert!(message.ends_with("Line: ERROR after garbage"), "unexpected message: {}", message);
+    }
+
+    #[test]
+    fn test_sse_parser_fields() {
+        let mut parser = SseParser::default();
+        let mut events = Vec::new();
+        for line in [
+            ": keep-alive",
+            "event: log",
+            "id: 7",
+            "data: first line",
+            "data:second line",
+            "retry: 5000",
+            "",
+            "",
+            "data",
+            "",
+        ] {
+            events.extend(parser.feed_line(line));
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: Some("log".to_string()),
+                    id: Some("7".to_string()),
+                    data: "first line\nsecond line".to_string(),
+                },
+                // The event type resets after each event; the last event ID is kept
+                SseEvent {
+                    event: None,
+                    id: Some("7".to_string()),
+                    data: String::new(),
+                },
+            ]
+        );
+    }
+
+    /// Alert handler that keeps every message, for streams that deliver several matches
+    struct RecordingAlertHandler(Arc<std::sync::Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl AlertHandler for RecordingAlertHandler {
+        async fn send(&self, _identity: &str, _rule_name: &str, message: &str) -> Result<()> {
+            self.0.lock().unwrap().push(message.to_string());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "test-alert"
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sse_events_are_matched() {
+        let mut rule = test_rule();
+        rule.text = None;
+        rule.pattern = Some("^ERROR".to_string());
+        let sent = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut alert_manager = AlertManager::new("test-server".to_string());
+        alert_manager.register("test-alert".to_string(), Arc::new(RecordingAlertHandler(sent.clone())));
+        let monitor = StreamMonitor::new(vec![rule], Arc::new(alert_manager)).unwrap();
+
+        let addr = serve_once(concat!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n",
+            ": connected\n\n",
+            "event: log\nid: 1\ndata: INFO started\n\n",
+            "id: 2\ndata: ERROR disk full\n\n",
+            "event: log\r\ndata: INFO retrying\r\ndata: ERROR db down\r\n\r\n",
+            "data: ERROR never dispatched\n",
+        ))
+        .await;
+
+        let config = stream_config("events", StreamType::Sse, &format!("http://{}/events", addr));
+        assert!(monitor.watch_sse(&config).await.is_err());
+
+        assert_eq!(monitor.last_event_id.lock().await.as_deref(), Some("2"));
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2, "unexpected alerts: {:?}", *sent);
+        assert!(sent[0].ends_with("Line: ERROR disk full"), "unexpected message: {}", sent[0]);
+        assert!(sent[1].ends_with("Line: ERROR db down"), "unexpected message: {}", sent[1]);
+    }
+
+    #[tokio::test]
+    async fn test_sse_reconnect_sends_headers_and_last_event_id() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let mut requests = Vec::new();
+            for _ in 0..2 {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 4096];
+                let read = socket.read(&mut buf).await.unwrap();
+                requests.push(String::from_utf8_lossy(&buf[..read]).to_lowercase());
+                socket
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\nid: 41\ndata: INFO ok\n\n")
+                    .await
+                    .unwrap();
+            }
+            requests
+        });
+
+        let (monitor, _) = create_test_monitor();
+        let mut config = stream_config("events", StreamType::Sse, &format!("http://{}/events", addr));
+        config.headers = Some([("Authorization".to_string(), "Bearer token".to_string())].into());
+        assert!(monitor.watch_sse(&config).await.is_err());
+        assert!(monitor.watch_sse(&config).await.is_err());
+
+        let requests = server.await.unwrap();
+        assert!(requests[0].contains("accept: text/event-stream"));
+        assert!(requests[0].contains("authorization: bearer token"));
+        assert!(!requests[0].contains("last-event-id"));
+        assert!(requests[1].contains("last-event-id: 41"));
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::Result;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use std::sync::RwLock;
+
+/// Default human-readable format for alert bodies
+pub const DEFAULT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Timezone alert timestamps are rendered in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Zone {
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    /// Parse `utc`, `local` or a fixed offset such as `+02:00` / `-0530`
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "utc" | "z" => return Ok(Zone::Utc),
+            "local" => return Ok(Zone::Local),
+            _ => {}
+        }
+
+        let value = value.trim();
+        let (sign, digits) = match value.as_bytes().first() {
+            Some(b'+') => (1, &value[1..]),
+            Some(b'-') => (-1, &value[1..]),
+            _ => anyhow::bail!(
+                "Invalid timezone '{}': expected 'utc', 'local' or an offset like '+02:00'",
+                value
+            ),
+        };
+        let digits = digits.replace(':', "");
+        if digits.len() != 4 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            anyhow::bail!("Invalid timezone offset '{}': expected +HH:MM", value);
+        }
+        let hours: i32 = digits[..2].parse()?;
+        let minutes: i32 = digits[2..].parse()?;
+        if hours > 23 || minutes > 59 {
+            anyhow::bail!("Invalid timezone offset '{}': out of range", value);
+        }
+
+        let offset = FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .ok_or_else(|| anyhow::anyhow!("Invalid timezone offset '{}'", value))?;
+        Ok(Zone::Fixed(offset))
+    }
+
+    fn offset_at(&self, time: DateTime<Utc>) -> FixedOffset {
+        match self {
+            Zone::Utc => FixedOffset::east_opt(0).unwrap(),
+            Zone::Local => *time.with_timezone(&Local).offset(),
+            Zone::Fixed(offset) => *offset,
+        }
+    }
+}
+
+/// Check a strftime format string, so typos fail at startup rather than in every alert
+pub fn validate_format(format: &str) -> Result<()> {
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        anyhow::bail!("Invalid time_format '{}'", format);
+    }
+    Ok(())
+}
+
+/// How alert handlers render timestamps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeSettings {
+    pub zone: Zone,
+    pub format: String,
+}
+
+impl Default for TimeSettings {
+    fn default() -> Self {
+        Self {
+            zone: Zone::Utc,
+            format: DEFAULT_FORMAT.to_string(),
+        }
+    }
+}
+
+impl TimeSettings {
+    /// Human-readable timestamp for message bodies (stdout, email)
+    pub fn display(&self, time: DateTime<Utc>) -> String {
+        time.with_timezone(&self.zone.offset_at(time))
+            .format(&self.format)
+            .to_string()
+    }
+
+    /// RFC 3339 timestamp for JSON payloads; only the offset follows the configured zone
+    pub fn rfc3339(&self, time: DateTime<Utc>) -> String {
+        time.with_timezone(&self.zone.offset_at(time)).to_rfc3339()
+    }
+}
+
+/// Process-wide settings, set once from the config at startup
+static SETTINGS: RwLock<Option<TimeSettings>> = RwLock::new(None);
+
+pub fn set(settings: TimeSettings) {
+    *SETTINGS.write().unwrap() = Some(settings);
+}
+
+fn with_settings<T>(f: impl FnOnce(&TimeSettings) -> T) -> T {
+    match SETTINGS.read().unwrap().as_ref() {
+        Some(settings) => f(settings),
+        None => f(&TimeSettings::default()),
+    }
+}
+
+/// Current time for alert bodies, in the configured timezone and format
+pub fn now() -> String {
+    with_settings(|settings| settings.display(Utc::now()))
+}
+
+/// Current time as RFC 3339 in the configured timezone, for JSON payloads
+pub fn now_rfc3339() -> String {
+    with_settings(|settings| settings.rfc3339(Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn noon() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_parse_zone() {
+        assert_eq!(Zone::parse("UTC").unwrap(), Zone::Utc);
+        assert_eq!(Zone::parse("local").unwrap(), Zone::Local);
+        assert_eq!(
+            Zone::parse("+05:30").unwrap(),
+            Zone::Fixed(FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap())
+        );
+        assert_eq!(
+            Zone::parse("-0800").unwrap(),
+            Zone::Fixed(FixedOffset::west_opt(8 * 3600).unwrap())
+        );
+        assert!(Zone::parse("Europe/Berlin").is_err());
+        assert!(Zone::parse("+25:00").is_err());
+        assert!(Zone::parse("+2").is_err());
+    }
+
+    #[test]
+    fn test_default_settings_use_utc() {
+        let settings = TimeSettings::default();
+        assert_eq!(settings.display(noon()), "2024-03-15 12:00:00");
+        assert_eq!(settings.rfc3339(noon()), "2024-03-15T12:00:00+00:00");
+    }
+
+    #[test]
+    fn test_display_respects_zone_and_format() {
+        let settings = TimeSettings {
+            zone: Zone::parse("+02:00").unwrap(),
+            format: "%d/%m/%Y %H:%M %z".to_string(),
+        };
+        assert_eq!(settings.display(noon()), "15/03/2024 14:00 +0200");
+        assert_eq!(settings.rfc3339(noon()), "2024-03-15T14:00:00+02:00");
+    }
+
+    #[test]
+    fn test_negative_offset_crosses_midnight() {
+        let settings = TimeSettings {
+            zone: Zone::parse("-08:00").unwrap(),
+            format: DEFAULT_FORMAT.to_string(),
+        };
+        let early = Utc.with_ymd_and_hms(2024, 3, 15, 3, 0, 0).unwrap();
+        assert_eq!(settings.display(early), "2024-03-14 19:00:00");
+    }
+
+    #[test]
+    fn test_validate_format() {
+        assert!(validate_format(DEFAULT_FORMAT).is_ok());
+        assert!(validate_format("%a %b %e %T %Y").is_ok());
+        assert!(validate_format("%Y-%m-%d %Q").is_err());
+    }
+}